@@ -0,0 +1,179 @@
+//! Aligned buffers for direct I/O.
+
+use crate::{Array, ReadAt, WriteAt};
+use std::alloc::{self, Layout};
+use std::fmt;
+use std::io;
+use std::ops::{Deref, DerefMut};
+use std::ptr::NonNull;
+
+/// A zero-initialized byte buffer whose address is aligned to a given
+/// power of two, for use with arrays opened with
+/// [`ArrayEditor::open_direct`].
+///
+/// Direct I/O requires the buffer address, the length, and the offset of
+/// each transfer to be aligned, typically to the logical block size of the
+/// underlying device. [`AlignedBuf::for_array`] picks an alignment from
+/// [`Metadata::blksize`], which is at least as strict as that.
+///
+/// [`ArrayEditor::open_direct`]: crate::ArrayEditor::open_direct
+/// [`Metadata::blksize`]: crate::Metadata::blksize
+pub struct AlignedBuf {
+    ptr: NonNull<u8>,
+    len: usize,
+    align: usize,
+}
+
+// SAFETY: `AlignedBuf` uniquely owns its allocation, like a `Box<[u8]>`.
+unsafe impl Send for AlignedBuf {}
+// SAFETY: Shared access only hands out `&[u8]`.
+unsafe impl Sync for AlignedBuf {}
+
+impl AlignedBuf {
+    /// Allocate a zero-initialized buffer of `len` bytes, aligned to
+    /// `align` bytes.
+    ///
+    /// `align` must be a power of two.
+    pub fn new(len: usize, align: usize) -> io::Result<Self> {
+        let layout = Layout::from_size_align(len, align).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid buffer alignment {align}: must be a power of two"),
+            )
+        })?;
+        let ptr = if len == 0 {
+            // Zero-sized allocations aren't permitted; use a dangling
+            // pointer with the requested alignment instead.
+            NonNull::new(std::ptr::without_provenance_mut(align)).unwrap()
+        } else {
+            // SAFETY: `layout` has a non-zero size.
+            match NonNull::new(unsafe { alloc::alloc_zeroed(layout) }) {
+                Some(ptr) => ptr,
+                None => alloc::handle_alloc_error(layout),
+            }
+        };
+        Ok(Self { ptr, len, align })
+    }
+
+    /// Allocate a zero-initialized buffer of `len` bytes, aligned for
+    /// direct I/O on `array`.
+    ///
+    /// The alignment is [`Metadata::blksize`] of `array`. `len` is not
+    /// rounded; use a multiple of [`AlignedBuf::align`] for transfers.
+    ///
+    /// [`Metadata::blksize`]: crate::Metadata::blksize
+    pub fn for_array<A: Array + ?Sized>(array: &A, len: usize) -> io::Result<Self> {
+        let align = array_alignment(array)?;
+        Self::new(len, align)
+    }
+
+    /// Returns the alignment of the buffer's address.
+    #[inline]
+    #[must_use]
+    pub fn align(&self) -> usize {
+        self.align
+    }
+}
+
+impl Deref for AlignedBuf {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &[u8] {
+        // SAFETY: `ptr` points to `len` initialized bytes owned by `self`.
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl DerefMut for AlignedBuf {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut [u8] {
+        // SAFETY: `ptr` points to `len` initialized bytes owned by `self`.
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl Drop for AlignedBuf {
+    fn drop(&mut self) {
+        if self.len != 0 {
+            // SAFETY: `ptr` was allocated with this layout in `new`.
+            unsafe {
+                alloc::dealloc(
+                    self.ptr.as_ptr(),
+                    Layout::from_size_align_unchecked(self.len, self.align),
+                );
+            }
+        }
+    }
+}
+
+impl fmt::Debug for AlignedBuf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AlignedBuf")
+            .field("len", &self.len)
+            .field("align", &self.align)
+            .finish()
+    }
+}
+
+/// Read from `array` at `offset` into `buf`, checking first that `buf`'s
+/// address and length and `offset` are all aligned as direct I/O requires.
+///
+/// The required alignment is [`Metadata::blksize`] of `array`. Misaligned
+/// arguments fail with an [`io::ErrorKind::InvalidInput`] error describing
+/// which argument is misaligned, rather than the bare `EINVAL` the OS
+/// reports.
+///
+/// [`Metadata::blksize`]: crate::Metadata::blksize
+pub fn read_aligned_at<A: ReadAt + ?Sized>(
+    array: &A,
+    buf: &mut [u8],
+    offset: u64,
+) -> io::Result<usize> {
+    check_alignment(array_alignment(array)?, buf, offset)?;
+    array.read_at(buf, offset)
+}
+
+/// Write `buf` to `array` at `offset`, checking first that `buf`'s address
+/// and length and `offset` are all aligned as direct I/O requires.
+///
+/// See [`read_aligned_at`] for details.
+pub fn write_aligned_at<A: WriteAt + ?Sized>(
+    array: &mut A,
+    buf: &[u8],
+    offset: u64,
+) -> io::Result<usize> {
+    check_alignment(array_alignment(array)?, buf, offset)?;
+    array.write_at(buf, offset)
+}
+
+fn array_alignment<A: Array + ?Sized>(array: &A) -> io::Result<usize> {
+    let blksize = array.metadata()?.blksize().max(1);
+    match usize::try_from(blksize) {
+        Ok(align) if align.is_power_of_two() => Ok(align),
+        _ => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("block size {blksize} isn't usable as a direct I/O alignment"),
+        )),
+    }
+}
+
+fn check_alignment(align: usize, buf: &[u8], offset: u64) -> io::Result<()> {
+    let misaligned = |what: String| {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{what} is not a multiple of the required alignment {align}"),
+        ))
+    };
+    let addr = buf.as_ptr() as usize;
+    if !addr.is_multiple_of(align) {
+        return misaligned(format!("buffer address {addr:#x}"));
+    }
+    if !buf.len().is_multiple_of(align) {
+        return misaligned(format!("buffer length {}", buf.len()));
+    }
+    if !offset.is_multiple_of(align as u64) {
+        return misaligned(format!("offset {offset}"));
+    }
+    Ok(())
+}
@@ -0,0 +1,171 @@
+//! Anonymous file-backed buffers.
+
+use cap_std::fs::{Dir, OpenOptions};
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use io_lifetimes::FromFilelike;
+use std::collections::hash_map::RandomState;
+use std::fs;
+use std::hash::{BuildHasher, Hasher};
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Create a temporary anonymous file, which has no name in any filesystem
+/// and is deleted when the last handle to it is closed.
+///
+/// This is what [`ArrayEditor::anonymous`] and [`ArrayReader::bytes`] use
+/// internally, and is useful for creating an anonymous buffer to hand to
+/// other code as a plain `File`.
+///
+/// On Linux and Android, this is a `memfd_create` file, created with
+/// `MFD_CLOEXEC` and `MFD_ALLOW_SEALING`, so callers may add seals with
+/// `fcntl(F_ADD_SEALS)`. On Windows, this is a file in the system temporary
+/// directory created with `FILE_ATTRIBUTE_TEMPORARY` and
+/// `FILE_FLAG_DELETE_ON_CLOSE`, so that it stays cache-resident where
+/// possible and is deleted once all handles to it are closed. On other
+/// platforms, this is an unlinked file in the system temporary directory, as
+/// created by [`tempfile::tempfile`]. The system temporary directory may be a
+/// small tmpfs or on a different filesystem than other data; see
+/// [`anonymous_in`] to choose the directory. On all platforms, the file is not
+/// inherited by child processes.
+///
+/// # Example
+///
+/// ```
+/// use io_arrays::{ArrayEditor, ReadAt, WriteAt};
+/// use std::io::{Seek, SeekFrom, Write};
+///
+/// # fn main() -> std::io::Result<()> {
+/// let mut file = io_arrays::anonymous()?;
+/// file.write_all(b"hello")?;
+///
+/// let mut editor = ArrayEditor::file(file.try_clone()?);
+/// editor.write_all_at(b"HELLO", 0)?;
+///
+/// let mut buf = [0_u8; 5];
+/// editor.read_exact_at(&mut buf, 0)?;
+/// assert_eq!(&buf, b"HELLO");
+/// assert_eq!(file.seek(SeekFrom::End(0))?, 5);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`ArrayEditor::anonymous`]: crate::ArrayEditor::anonymous
+/// [`ArrayReader::bytes`]: crate::ArrayReader::bytes
+/// [`tempfile::tempfile`]: https://docs.rs/tempfile/latest/tempfile/fn.tempfile.html
+pub fn anonymous() -> io::Result<fs::File> {
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    {
+        let flags = rustix::fs::MemfdFlags::CLOEXEC | rustix::fs::MemfdFlags::ALLOW_SEALING;
+        let name = rustix::cstr!("io_arrays anonymous file");
+        let owned = rustix::fs::memfd_create(name, flags)?;
+        Ok(fs::File::from_into_filelike(owned))
+    }
+
+    #[cfg(windows)]
+    {
+        crate::windows::anonymous_in(&std::env::temp_dir())
+    }
+
+    #[cfg(not(any(target_os = "android", target_os = "linux", windows)))]
+    {
+        tempfile::tempfile()
+    }
+}
+
+/// Create a temporary anonymous file in the directory `dir`.
+///
+/// This is similar to [`anonymous`], but the data lives on the filesystem
+/// containing `dir` rather than in memory or in the system temporary
+/// directory, which is useful for large buffers. The file is unlinked
+/// immediately, so it's not visible by name.
+#[inline]
+pub fn anonymous_in<P: AsRef<Path>>(dir: P) -> io::Result<fs::File> {
+    #[cfg(windows)]
+    {
+        crate::windows::anonymous_in(dir.as_ref())
+    }
+
+    #[cfg(not(windows))]
+    {
+        tempfile::tempfile_in(dir)
+    }
+}
+
+/// Create a temporary anonymous file in the directory `dir`.
+///
+/// This is similar to [`anonymous_in`], but takes a [`cap_std::fs::Dir`]
+/// instead of a path.
+pub fn anonymous_in_dir(dir: &Dir) -> io::Result<fs::File> {
+    // On Linux, try `O_TMPFILE` first, so that the file never has a name.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    {
+        use rustix::fs::{Mode, OFlags};
+
+        if let Ok(owned) = rustix::fs::openat(
+            dir,
+            ".",
+            OFlags::TMPFILE | OFlags::RDWR | OFlags::CLOEXEC,
+            Mode::RUSR | Mode::WUSR,
+        ) {
+            return Ok(fs::File::from_into_filelike(owned));
+        }
+    }
+
+    // Otherwise, create a file with a random name and remove it.
+    let mut options = OpenOptions::new();
+    options.read(true).write(true).create_new(true);
+    loop {
+        let name = temp_name();
+        match dir.open_with(&name, &options) {
+            Ok(file) => {
+                dir.remove_file(&name)?;
+                return Ok(file.into_std());
+            }
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+// On Linux, write the bytes into a memfd and then seal it.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn anonymous_read_only(bytes: &[u8]) -> io::Result<fs::File> {
+    use rustix::fs::SealFlags;
+
+    let mut file = anonymous()?;
+    file.write_all(bytes)?;
+    rustix::fs::fcntl_add_seals(
+        &file,
+        SealFlags::WRITE | SealFlags::SHRINK | SealFlags::GROW | SealFlags::SEAL,
+    )?;
+    Ok(file)
+}
+
+// On Windows, write the bytes into a delete-on-close file and reopen it
+// read-only. The file is deleted once the reopened handle is closed.
+#[cfg(windows)]
+pub(crate) fn anonymous_read_only(bytes: &[u8]) -> io::Result<fs::File> {
+    use cap_fs_ext::{OpenOptions, Reopen};
+
+    let mut file = anonymous()?;
+    file.write_all(bytes)?;
+    file.reopen(OpenOptions::new().read(true))
+}
+
+// Otherwise, write the bytes into a named temporary file, reopen it
+// read-only, and then remove the name.
+#[cfg(not(any(target_os = "android", target_os = "linux", windows)))]
+pub(crate) fn anonymous_read_only(bytes: &[u8]) -> io::Result<fs::File> {
+    let mut named = tempfile::NamedTempFile::new()?;
+    named.write_all(bytes)?;
+    let file = fs::File::open(named.path())?;
+    named.close()?;
+    Ok(file)
+}
+
+/// Generate a random name for a temporary file.
+pub(crate) fn temp_name() -> String {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u8(0);
+    format!(".io_arrays-{:016x}", hasher.finish())
+}
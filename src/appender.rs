@@ -0,0 +1,283 @@
+//! A type for files opened in append mode.
+
+use crate::{filelike, Advice, Array, Metadata};
+#[cfg(not(windows))]
+use io_lifetimes::{AsFd, BorrowedFd, OwnedFd};
+use io_lifetimes::{AsFilelike, BorrowedFilelike, FromFilelike, IntoFilelike};
+use std::fs;
+#[cfg(not(windows))]
+use std::io::Seek;
+use std::io::{self, IoSlice, Write};
+#[cfg(unix)]
+use std::os::unix::io::{AsRawFd, IntoRawFd, RawFd};
+#[cfg(target_os = "wasi")]
+use std::os::wasi::io::{AsRawFd, IntoRawFd, RawFd};
+use std::path::Path;
+#[cfg(windows)]
+use {
+    io_extras::os::windows::{AsRawHandleOrSocket, IntoRawHandleOrSocket, RawHandleOrSocket},
+    io_lifetimes::{AsHandle, BorrowedHandle, OwnedHandle},
+    std::io::{Seek, SeekFrom},
+    std::os::windows::io::{AsRawHandle, IntoRawHandle, RawHandle},
+};
+
+/// An output sink which appends to the end of a file opened in [append mode].
+///
+/// [`ArrayWriter`] and [`ArrayEditor`] reject files in append mode, since
+/// writes to them ignore the offset. `ArrayAppender` is the other side of
+/// that check: it only accepts files in append mode, and instead of writing
+/// at an offset, it writes at the end and reports the offset the data
+/// landed at.
+///
+/// On Posix-ish platforms, each append is a single `write` on the file, which
+/// the OS performs atomically with respect to other appends to the same file
+/// through other open files, so records appended concurrently from different
+/// threads or processes don't overlap. The offset is found from the file's
+/// position afterwards, so it's exact as long as the open file isn't shared,
+/// for example with [`fs::File::try_clone`], with another appender.
+///
+/// On Windows, each append seeks to the end and then writes, so the reported
+/// offset may be stale if another appender writes between the two. The data
+/// is still written at the end.
+///
+/// [append mode]: https://doc.rust-lang.org/stable/std/fs/struct.OpenOptions.html#method.append
+/// [`ArrayWriter`]: crate::ArrayWriter
+/// [`ArrayEditor`]: crate::ArrayEditor
+#[derive(Debug)]
+pub struct ArrayAppender {
+    file: fs::File,
+}
+
+impl ArrayAppender {
+    /// Convert a `File` into an `ArrayAppender`.
+    ///
+    /// The file must be opened in [append mode].
+    ///
+    /// [append mode]: https://doc.rust-lang.org/stable/std/fs/struct.OpenOptions.html#method.append
+    #[inline]
+    pub fn file<Filelike: IntoFilelike + Write>(filelike: Filelike) -> io::Result<Self> {
+        Self::_try_file(fs::File::from_into_filelike(filelike))
+    }
+
+    /// Open the file at `path` in append mode, creating it if it doesn't
+    /// exist.
+    #[inline]
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(Self {
+            file: fs::OpenOptions::new()
+                .append(true)
+                .create(true)
+                .open(path)?,
+        })
+    }
+
+    fn _try_file(file: fs::File) -> io::Result<Self> {
+        #[cfg(not(windows))]
+        {
+            if !rustix::fs::fcntl_getfl(&file)?.contains(rustix::fs::OFlags::APPEND) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "ArrayAppender requires a file opened with O_APPEND",
+                ));
+            }
+        }
+        #[cfg(windows)]
+        {
+            let access = winx::file::query_access_information(file.as_handle())?;
+            if !access.contains(winx::file::AccessMode::FILE_APPEND_DATA)
+                || access.contains(winx::file::AccessMode::FILE_WRITE_DATA)
+            {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "ArrayAppender requires a file opened with FILE_APPEND_DATA",
+                ));
+            }
+        }
+
+        Ok(Self { file })
+    }
+
+    /// Write `buf` at the end of the file, and return the offset it was
+    /// written at.
+    #[inline]
+    pub fn append(&mut self, buf: &[u8]) -> io::Result<u64> {
+        self.append_vectored(&[IoSlice::new(buf)])
+    }
+
+    /// Write the concatenation of `bufs` at the end of the file, and return
+    /// the offset it was written at.
+    ///
+    /// If the OS writes only part of the data, as it may for some kinds of
+    /// files, the rest is appended with further writes, and other appenders'
+    /// data may land between them.
+    pub fn append_vectored(&mut self, bufs: &[IoSlice]) -> io::Result<u64> {
+        let len: usize = bufs.iter().map(|buf| buf.len()).sum();
+
+        #[cfg(windows)]
+        let offset = (&self.file).seek(SeekFrom::End(0))?;
+
+        let nwritten = loop {
+            match (&self.file).write_vectored(bufs) {
+                Ok(0) if len != 0 => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::WriteZero,
+                        "failed to write whole buffer",
+                    ))
+                }
+                Ok(nwritten) => break nwritten,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        };
+
+        // The write leaves the position at the end of the data it appended.
+        #[cfg(not(windows))]
+        let offset = if len == 0 {
+            self.file.metadata()?.len()
+        } else {
+            (&self.file).stream_position()? - nwritten as u64
+        };
+
+        if nwritten < len {
+            let rest = bufs
+                .iter()
+                .flat_map(|buf| buf.iter().copied())
+                .skip(nwritten)
+                .collect::<Vec<u8>>();
+            (&self.file).write_all(&rest)?;
+        }
+
+        Ok(offset)
+    }
+
+    /// Consume `self` and return the underlying `File`.
+    #[inline]
+    #[must_use]
+    pub fn into_file(self) -> fs::File {
+        self.file
+    }
+}
+
+impl Array for ArrayAppender {
+    #[inline]
+    fn metadata(&self) -> io::Result<Metadata> {
+        filelike::metadata(self)
+    }
+
+    #[inline]
+    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        filelike::advise(self, offset, len, advice)
+    }
+
+    #[inline]
+    fn borrowed_filelike(&self) -> Option<BorrowedFilelike<'_>> {
+        Some(self.as_filelike())
+    }
+}
+
+#[cfg(not(windows))]
+impl AsRawFd for ArrayAppender {
+    #[inline]
+    fn as_raw_fd(&self) -> RawFd {
+        self.file.as_raw_fd()
+    }
+}
+
+#[cfg(not(windows))]
+impl AsFd for ArrayAppender {
+    #[inline]
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.file.as_fd()
+    }
+}
+
+#[cfg(windows)]
+impl AsRawHandle for ArrayAppender {
+    #[inline]
+    fn as_raw_handle(&self) -> RawHandle {
+        self.file.as_raw_handle()
+    }
+}
+
+#[cfg(windows)]
+impl AsHandle for ArrayAppender {
+    #[inline]
+    fn as_handle(&self) -> BorrowedHandle<'_> {
+        self.file.as_handle()
+    }
+}
+
+#[cfg(windows)]
+impl AsRawHandleOrSocket for ArrayAppender {
+    #[inline]
+    fn as_raw_handle_or_socket(&self) -> RawHandleOrSocket {
+        self.file.as_raw_handle_or_socket()
+    }
+}
+
+impl From<ArrayAppender> for fs::File {
+    #[inline]
+    fn from(array: ArrayAppender) -> Self {
+        array.file
+    }
+}
+
+#[cfg(not(windows))]
+impl TryFrom<OwnedFd> for ArrayAppender {
+    type Error = io::Error;
+
+    #[inline]
+    fn try_from(fd: OwnedFd) -> io::Result<Self> {
+        Self::_try_file(fs::File::from(fd))
+    }
+}
+
+#[cfg(not(windows))]
+impl From<ArrayAppender> for OwnedFd {
+    #[inline]
+    fn from(array: ArrayAppender) -> Self {
+        array.file.into()
+    }
+}
+
+#[cfg(not(windows))]
+impl IntoRawFd for ArrayAppender {
+    #[inline]
+    fn into_raw_fd(self) -> RawFd {
+        self.file.into_raw_fd()
+    }
+}
+
+#[cfg(windows)]
+impl TryFrom<OwnedHandle> for ArrayAppender {
+    type Error = io::Error;
+
+    #[inline]
+    fn try_from(handle: OwnedHandle) -> io::Result<Self> {
+        Self::_try_file(fs::File::from(handle))
+    }
+}
+
+#[cfg(windows)]
+impl From<ArrayAppender> for OwnedHandle {
+    #[inline]
+    fn from(array: ArrayAppender) -> Self {
+        array.file.into()
+    }
+}
+
+#[cfg(windows)]
+impl IntoRawHandle for ArrayAppender {
+    #[inline]
+    fn into_raw_handle(self) -> RawHandle {
+        self.file.into_raw_handle()
+    }
+}
+
+#[cfg(windows)]
+impl IntoRawHandleOrSocket for ArrayAppender {
+    #[inline]
+    fn into_raw_handle_or_socket(self) -> RawHandleOrSocket {
+        self.file.into_raw_handle_or_socket()
+    }
+}
@@ -0,0 +1,366 @@
+//! Bounded views of arrays.
+
+use crate::{
+    Advice, Array, CloneOutcome, LockKind, Metadata, RangeLockGuard, ReadAt, SyncRangeMode,
+    WriteAt, WriteFlags,
+};
+use std::cmp::min;
+use std::io::{self, IoSlice, IoSliceMut};
+#[cfg(feature = "io-streams")]
+use {crate::arrays::check_stream_range, io_streams::StreamReader};
+
+/// A view of the range of `len` bytes at `offset` in an array, which is
+/// itself an array.
+///
+/// This is created by [`Array::slice`] and [`Array::into_slice`], or by
+/// [`ArraySlice::new`]. `A` may be an array, or a reference to one.
+///
+/// Offsets in the view are relative to the start of the range. Reads stop at
+/// the end of the range, as if it were the end of the array. Writes which
+/// extend past the end of the range fail with
+/// [`io::ErrorKind::InvalidInput`], rather than extending the array, and
+/// views don't implement [`Resize`]. Slices of views compose.
+///
+/// [`Resize`]: crate::Resize
+///
+/// A view doesn't expose the underlying file through
+/// [`Array::borrowed_filelike`], since that would bypass the bounds.
+#[derive(Debug, Clone, Copy)]
+pub struct ArraySlice<A> {
+    inner: A,
+    offset: u64,
+    len: u64,
+}
+
+impl<A> ArraySlice<A> {
+    /// Create a view of the range of `len` bytes at `offset` in `array`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `offset + len` overflows.
+    #[inline]
+    pub fn new(array: A, offset: u64, len: u64) -> Self {
+        assert!(
+            offset.checked_add(len).is_some(),
+            "array slice range overflows"
+        );
+        Self {
+            inner: array,
+            offset,
+            len,
+        }
+    }
+
+    /// Divide the view into two disjoint views, one of the bytes before
+    /// `mid` and one of the bytes at and after it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mid` is past the end of the view.
+    pub fn split_at(self, mid: u64) -> (Self, Self)
+    where
+        A: Clone,
+    {
+        assert!(
+            mid <= self.len,
+            "split point is past the end of the array slice"
+        );
+        let second = Self::new(self.inner.clone(), self.offset + mid, self.len - mid);
+        (Self::new(self.inner, self.offset, mid), second)
+    }
+
+    /// Returns the offset of the start of the view in the underlying array.
+    #[inline]
+    #[must_use]
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Consume the view and return the underlying array.
+    #[inline]
+    pub fn into_inner(self) -> A {
+        self.inner
+    }
+
+    /// Translate the range of `len` bytes at `offset` in the view to an
+    /// offset in the underlying array, failing if it extends past the end
+    /// of the view.
+    fn translate(&self, offset: u64, len: u64) -> io::Result<u64> {
+        match offset.checked_add(len) {
+            Some(end) if end <= self.len => Ok(self.offset + offset),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "range extends past the end of the array slice",
+            )),
+        }
+    }
+
+    /// Returns the number of bytes of a `len`-byte transfer at `offset`
+    /// which are within the view.
+    #[inline]
+    fn available(&self, offset: u64, len: usize) -> usize {
+        min(len as u64, self.len.saturating_sub(offset)) as usize
+    }
+
+    /// Returns whether a transfer of `total` bytes at `offset` is within the
+    /// view.
+    fn fits(&self, offset: u64, total: usize) -> bool {
+        offset
+            .checked_add(total as u64)
+            .is_some_and(|end| end <= self.len)
+    }
+}
+
+#[cfg(feature = "io-streams")]
+impl<A: Array> ArraySlice<A> {
+    /// Returns the length of the view, limited to the end of the
+    /// underlying array.
+    fn readable_len(&self) -> io::Result<u64> {
+        let inner_len = self.inner.metadata()?.len();
+        Ok(min(self.len, inner_len.saturating_sub(self.offset)))
+    }
+}
+
+impl<A: Array> Array for ArraySlice<A> {
+    fn metadata(&self) -> io::Result<Metadata> {
+        let inner = self.inner.metadata()?;
+        let len = min(self.len, inner.len.saturating_sub(self.offset));
+        Ok(Metadata {
+            len,
+            blksize: inner.blksize,
+            allocated_len: min(inner.allocated_len, len),
+            file_type: inner.file_type,
+            modified: inner.modified,
+        })
+    }
+
+    #[inline]
+    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        let len = min(len, self.len.saturating_sub(offset));
+        self.inner
+            .advise(self.offset + min(offset, self.len), len, advice)
+    }
+
+    #[inline]
+    fn readahead(&self, offset: u64, len: u64) -> io::Result<()> {
+        let len = min(len, self.len.saturating_sub(offset));
+        self.inner
+            .readahead(self.offset + min(offset, self.len), len)
+    }
+
+    fn lock_at(&self, offset: u64, len: u64, kind: LockKind) -> io::Result<RangeLockGuard<'_>> {
+        let offset = self.translate(offset, len)?;
+        self.inner.lock_at(offset, len, kind)
+    }
+
+    fn try_lock_at(
+        &self,
+        offset: u64,
+        len: u64,
+        kind: LockKind,
+    ) -> io::Result<Option<RangeLockGuard<'_>>> {
+        let offset = self.translate(offset, len)?;
+        self.inner.try_lock_at(offset, len, kind)
+    }
+}
+
+impl<A: ReadAt> ReadAt for ArraySlice<A> {
+    #[inline]
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        let len = self.available(offset, buf.len());
+        if len == 0 {
+            return Ok(0);
+        }
+        self.inner.read_at(&mut buf[..len], self.offset + offset)
+    }
+
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+        if !self.fits(offset, buf.len()) {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "failed to fill whole buffer",
+            ));
+        }
+        self.inner.read_exact_at(buf, self.offset + offset)
+    }
+
+    fn read_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<usize> {
+        let total = bufs.iter().map(|b| b.len()).sum();
+        if self.fits(offset, total) {
+            return self.inner.read_vectored_at(bufs, self.offset + offset);
+        }
+        // Just read into the first non-empty buffer, rather than trimming
+        // the list to the view.
+        match bufs.iter_mut().find(|b| !b.is_empty()) {
+            Some(buf) => self.read_at(buf, offset),
+            None => Ok(0),
+        }
+    }
+
+    fn read_exact_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<()> {
+        let total = bufs.iter().map(|b| b.len()).sum();
+        if !self.fits(offset, total) {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "failed to fill whole buffer",
+            ));
+        }
+        self.inner
+            .read_exact_vectored_at(bufs, self.offset + offset)
+    }
+
+    #[inline]
+    fn is_read_vectored_at(&self) -> bool {
+        self.inner.is_read_vectored_at()
+    }
+
+    #[inline]
+    fn read_at_nowait(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        let len = self.available(offset, buf.len());
+        if len == 0 {
+            return Ok(0);
+        }
+        self.inner
+            .read_at_nowait(&mut buf[..len], self.offset + offset)
+    }
+
+    #[cfg(feature = "io-streams")]
+    fn read_via_stream_at(&self, offset: u64) -> io::Result<StreamReader> {
+        let len = self.readable_len()?.saturating_sub(offset);
+        if len == 0 {
+            return (&[] as &[u8]).read_via_stream_at(0);
+        }
+        self.inner.read_range_via_stream(self.offset + offset, len)
+    }
+
+    #[cfg(feature = "io-streams")]
+    fn read_range_via_stream(&self, offset: u64, len: u64) -> io::Result<StreamReader> {
+        check_stream_range(self.readable_len()?, offset, len)?;
+        self.inner.read_range_via_stream(self.offset + offset, len)
+    }
+}
+
+impl<A: WriteAt> WriteAt for ArraySlice<A> {
+    #[inline]
+    fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        let len = self.available(offset, buf.len());
+        if len == 0 {
+            return Ok(0);
+        }
+        self.inner.write_at(&buf[..len], self.offset + offset)
+    }
+
+    #[inline]
+    fn write_all_at(&mut self, buf: &[u8], offset: u64) -> io::Result<()> {
+        let offset = self.translate(offset, buf.len() as u64)?;
+        self.inner.write_all_at(buf, offset)
+    }
+
+    fn write_vectored_at(&mut self, bufs: &[IoSlice], offset: u64) -> io::Result<usize> {
+        let total = bufs.iter().map(|b| b.len()).sum();
+        if self.fits(offset, total) {
+            return self.inner.write_vectored_at(bufs, self.offset + offset);
+        }
+        match bufs.iter().find(|b| !b.is_empty()) {
+            Some(buf) => self.write_at(buf, offset),
+            None => Ok(0),
+        }
+    }
+
+    fn write_all_vectored_at(&mut self, bufs: &mut [IoSlice], offset: u64) -> io::Result<()> {
+        let total = bufs.iter().map(|b| b.len() as u64).sum();
+        let offset = self.translate(offset, total)?;
+        self.inner.write_all_vectored_at(bufs, offset)
+    }
+
+    #[inline]
+    fn is_write_vectored_at(&self) -> bool {
+        self.inner.is_write_vectored_at()
+    }
+
+    #[inline]
+    fn write_at_with(&mut self, buf: &[u8], offset: u64, flags: WriteFlags) -> io::Result<usize> {
+        let len = self.available(offset, buf.len());
+        if len == 0 {
+            return Ok(0);
+        }
+        self.inner
+            .write_at_with(&buf[..len], self.offset + offset, flags)
+    }
+
+    fn copy_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<u64> {
+        let offset = self.translate(offset, len)?;
+        self.inner.copy_from(offset, input, input_offset, len)
+    }
+
+    fn clone_range_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<CloneOutcome> {
+        let offset = self.translate(offset, len)?;
+        self.inner
+            .clone_range_from(offset, input, input_offset, len)
+    }
+
+    fn dedupe_range_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<u64> {
+        let offset = self.translate(offset, len)?;
+        self.inner
+            .dedupe_range_from(offset, input, input_offset, len)
+    }
+
+    fn allocate(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        let offset = self.translate(offset, len)?;
+        self.inner.allocate(offset, len)
+    }
+
+    fn reserve_at(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        let offset = self.translate(offset, len)?;
+        self.inner.reserve_at(offset, len)
+    }
+
+    fn punch_hole(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        let offset = self.translate(offset, len)?;
+        self.inner.punch_hole(offset, len)
+    }
+
+    fn zero_range(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        let offset = self.translate(offset, len)?;
+        self.inner.zero_range(offset, len)
+    }
+
+    fn fill_at(&mut self, byte: u8, offset: u64, len: u64) -> io::Result<()> {
+        let offset = self.translate(offset, len)?;
+        self.inner.fill_at(byte, offset, len)
+    }
+
+    #[inline]
+    fn sync_all(&self) -> io::Result<()> {
+        self.inner.sync_all()
+    }
+
+    #[inline]
+    fn sync_data(&self) -> io::Result<()> {
+        self.inner.sync_data()
+    }
+
+    fn sync_range(&self, offset: u64, len: u64, mode: SyncRangeMode) -> io::Result<()> {
+        let len = min(len, self.len.saturating_sub(offset));
+        self.inner
+            .sync_range(self.offset + min(offset, self.len), len, mode)
+    }
+}
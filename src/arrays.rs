@@ -1,34 +1,95 @@
-use crate::{filelike, Advice};
-#[cfg(not(any(target_os = "android", target_os = "linux")))]
-use io_lifetimes::OwnedFilelike;
-#[cfg(not(windows))]
-use io_lifetimes::{AsFd, BorrowedFd};
-use io_lifetimes::{FromFilelike, IntoFilelike};
+use crate::io;
+use alloc::boxed::Box;
+use alloc::rc::Rc;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::min;
+use core::fmt;
+use core::hash::Hasher;
+use core::ops::{BitOr, BitOrAssign};
+#[cfg(all(feature = "std", not(windows)))]
+use io_lifetimes::{AsFd, BorrowedFd, OwnedFd};
 #[cfg(feature = "io-streams")]
 use io_streams::StreamReader;
-use std::fs;
-use std::io::{self, IoSlice, IoSliceMut, Read, Seek, Write};
-#[cfg(unix)]
-use std::os::unix::io::{AsRawFd, RawFd};
-#[cfg(target_os = "wasi")]
-use std::os::wasi::io::{AsRawFd, RawFd};
+#[cfg(all(feature = "std", unix))]
+use std::os::unix::io::{AsRawFd, IntoRawFd, RawFd};
+#[cfg(all(feature = "std", target_os = "wasi"))]
+use std::os::wasi::io::{AsRawFd, IntoRawFd, RawFd};
+#[cfg(feature = "std")]
+use {
+    crate::anonymous::{anonymous, anonymous_in, anonymous_in_dir, anonymous_read_only},
+    crate::array_slice::ArraySlice,
+    crate::backend::{Backend, FileBackend},
+    crate::iter::{BytesAt, ChunksAt, LinesAt},
+    crate::lock::{LockKind, RangeLockGuard},
+    crate::streamer::Streamer,
+    crate::{filelike, Advice},
+    core::ops::Range,
+    io_lifetimes::{AsFilelike, BorrowedFilelike, FromFilelike, IntoFilelike},
+    std::fs,
+    std::io::{IoSlice, IoSliceMut, Read, Seek, Write},
+    std::path::Path,
+    std::time::SystemTime,
+};
 /*
 use system_interface::fs::FileIoExt;
 */
-#[cfg(windows)]
+#[cfg(all(feature = "std", windows))]
 use {
-    io_extras::os::windows::{AsRawHandleOrSocket, RawHandleOrSocket},
-    io_lifetimes::{AsHandle, BorrowedHandle},
-    std::os::windows::io::{AsRawHandle, RawHandle},
+    io_extras::os::windows::{AsRawHandleOrSocket, IntoRawHandleOrSocket, RawHandleOrSocket},
+    io_lifetimes::{AsHandle, BorrowedHandle, OwnedHandle},
+    std::os::windows::io::{AsRawHandle, IntoRawHandle, RawHandle},
 };
 
+/// The size of the buffer used by the default [`WriteAt::copy_from`].
+pub(crate) const COPY_BUF_SIZE: u64 = 64 * 1024;
+
+/// The smallest chunk size used by [`ReadAt::hash_range`].
+const MIN_HASH_CHUNK_SIZE: u64 = 16 * 1024;
+
+/// The chunk size used by [`WriteAt::copy_from_with_progress`].
+const PROGRESS_CHUNK_SIZE: u64 = 1024 * 1024;
+
+/// The block size reported by [`Metadata::blksize`] when there's no better
+/// value to report.
+pub(crate) const DEFAULT_BLKSIZE: u64 = 0x1000;
+
 /// Metadata information about an array.
 ///
 /// This is somewhat analogous to [`std::fs::Metadata`], however it only
 /// includes a few fields, since arrays are more abstract than files.
+#[non_exhaustive]
 pub struct Metadata {
     pub(crate) len: u64,
     pub(crate) blksize: u64,
+    pub(crate) allocated_len: u64,
+    pub(crate) file_type: ArrayType,
+    #[cfg(feature = "std")]
+    pub(crate) modified: Option<SystemTime>,
+}
+
+/// The kind of object backing an array, as reported by
+/// [`Metadata::file_type`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ArrayType {
+    /// A regular file.
+    File,
+
+    /// A block device, such as a disk or partition.
+    BlockDevice,
+
+    /// A character device, such as a terminal or `/dev/urandom`.
+    ///
+    /// Character devices don't have a length; see [`Metadata::is_bounded`].
+    CharDevice,
+
+    /// A buffer in memory, such as a slice or `Vec`.
+    Memory,
+
+    /// Some other kind of object.
+    Other,
 }
 
 #[allow(clippy::len_without_is_empty)]
@@ -41,11 +102,58 @@ impl Metadata {
     }
 
     /// Returns the block size for I/O.
+    ///
+    /// Where the platform doesn't report one, such as for arrays in memory,
+    /// on WASI, and on Windows when the storage can't be queried, this is
+    /// 4096, which is the most common size.
     #[inline]
     #[must_use]
     pub const fn blksize(&self) -> u64 {
         self.blksize
     }
+
+    /// Returns the amount of storage actually allocated for the array, in
+    /// bytes.
+    ///
+    /// For sparse files, this may be much less than [`Metadata::len`]. It
+    /// may also be more, since storage is allocated in whole blocks. For
+    /// in-memory arrays, this is the same as [`Metadata::len`].
+    #[inline]
+    #[must_use]
+    pub const fn allocated_len(&self) -> u64 {
+        self.allocated_len
+    }
+
+    /// Returns the kind of object backing the array.
+    #[inline]
+    #[must_use]
+    pub const fn file_type(&self) -> ArrayType {
+        self.file_type
+    }
+
+    /// Returns the last modification time of the array, if one is
+    /// available.
+    ///
+    /// In-memory arrays don't track modification times, and return `None`.
+    #[cfg(feature = "std")]
+    #[inline]
+    #[must_use]
+    pub const fn modified(&self) -> Option<SystemTime> {
+        self.modified
+    }
+
+    /// Returns whether the array has a meaningful length.
+    ///
+    /// Character devices, such as `/dev/zero` and `/dev/urandom`, report a
+    /// length of 0, but may have any amount of data to read. For them, this
+    /// returns `false`, and [`Metadata::len`] shouldn't be used to size
+    /// reads; read until [`ReadAt::read_at`] returns 0 instead, as
+    /// [`ReadAt::chunks_at`] and [`Streamer`] do.
+    #[inline]
+    #[must_use]
+    pub const fn is_bounded(&self) -> bool {
+        !matches!(self.file_type, ArrayType::CharDevice)
+    }
 }
 
 /// A minimal base trait for array I/O. Defines operations common to all kinds
@@ -63,7 +171,116 @@ pub trait Array {
     /// Announce the expected access pattern of the data at the given offset.
     ///
     /// This is purely a performance hint and has no semantic effect.
+    #[cfg(feature = "std")]
     fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()>;
+
+    /// Start reading the data at the given offset into memory, so that
+    /// subsequent reads of it are fast.
+    ///
+    /// Unlike [`Array::advise`] with [`Advice::WillNeed`], which is only a
+    /// hint, on Linux this reads the data into the page cache before
+    /// returning, which makes it more effective before a burst of random
+    /// reads. It's still best-effort; where the platform doesn't support it,
+    /// it does nothing.
+    ///
+    /// The default implementation calls [`filelike::readahead`] on the file
+    /// from [`Array::borrowed_filelike`], and does nothing if there isn't
+    /// one, as for arrays in memory.
+    #[cfg(feature = "std")]
+    #[inline]
+    fn readahead(&self, offset: u64, len: u64) -> io::Result<()> {
+        match self.borrowed_filelike() {
+            Some(file) => filelike::readahead(&file, offset, len),
+            None => Ok(()),
+        }
+    }
+
+    /// Return a borrowed file descriptor or handle for the array, if it's
+    /// backed by one.
+    ///
+    /// This lets operations such as [`WriteAt::copy_from`] use accelerated
+    /// OS facilities when the input is a file. The default implementation
+    /// returns `None`.
+    #[cfg(feature = "std")]
+    #[inline]
+    fn borrowed_filelike(&self) -> Option<BorrowedFilelike<'_>> {
+        None
+    }
+
+    /// Acquire an advisory lock on the range of `len` bytes at `offset`,
+    /// waiting until it's available. The lock is released when the returned
+    /// guard is dropped.
+    ///
+    /// This uses open file description locks (`F_OFD_SETLKW`) on Linux, so
+    /// locks taken through different handles to the same file exclude each
+    /// other even within a process. Elsewhere on Posix-ish platforms, this
+    /// uses `fcntl(F_SETLKW)`, where locks are owned by the process. On
+    /// Windows, this uses `LockFileEx`.
+    ///
+    /// The default implementation locks the file from
+    /// [`Array::borrowed_filelike`], and fails with
+    /// [`io::ErrorKind::Unsupported`] if there isn't one.
+    #[cfg(feature = "std")]
+    fn lock_at(&self, offset: u64, len: u64, kind: LockKind) -> io::Result<RangeLockGuard<'_>> {
+        match self.borrowed_filelike() {
+            Some(file) => RangeLockGuard::lock(file, offset, len, kind),
+            None => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "locking is not supported",
+            )),
+        }
+    }
+
+    /// Like [`Array::lock_at`], but returns `None` instead of waiting if the
+    /// range is locked by someone else.
+    #[cfg(feature = "std")]
+    fn try_lock_at(
+        &self,
+        offset: u64,
+        len: u64,
+        kind: LockKind,
+    ) -> io::Result<Option<RangeLockGuard<'_>>> {
+        match self.borrowed_filelike() {
+            Some(file) => RangeLockGuard::try_lock(file, offset, len, kind),
+            None => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "locking is not supported",
+            )),
+        }
+    }
+
+    /// Return a view of the given range of the array, which borrows the
+    /// array.
+    ///
+    /// Offsets in the view are relative to the start of the range, and
+    /// reads and writes are confined to it. See [`ArraySlice`] for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the end of `range` is before its start.
+    #[cfg(feature = "std")]
+    #[inline]
+    fn slice(&self, range: Range<u64>) -> ArraySlice<&Self>
+    where
+        Self: Sized,
+    {
+        assert!(range.start <= range.end, "array slice range is inverted");
+        ArraySlice::new(self, range.start, range.end - range.start)
+    }
+
+    /// Like [`Array::slice`], but takes ownership of the array.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `offset + len` overflows.
+    #[cfg(feature = "std")]
+    #[inline]
+    fn into_slice(self, offset: u64, len: u64) -> ArraySlice<Self>
+    where
+        Self: Sized,
+    {
+        ArraySlice::new(self, offset, len)
+    }
 }
 
 /// A trait for reading from arrays.
@@ -73,6 +290,17 @@ pub trait Array {
 ///
 /// Unlike `std::io::Read`, `ReadAt`'s functions take a `&self` rather than a
 /// `&mut self`, since they don't have a current position to mutate.
+///
+/// # Offsets out of range
+///
+/// An offset which can't be addressed is reported as an
+/// [`io::ErrorKind::InvalidInput`] error with the message "offset overflow",
+/// so it can be told apart from a failure of the underlying I/O. This
+/// happens when an offset plus a length overflows `u64`, and for arrays in
+/// memory, when an offset doesn't fit in a `usize`, as on 32-bit platforms.
+/// Files and block devices pass offsets to the OS, which reports offsets
+/// too large for it with `EINVAL` on Posix-ish platforms, and that is also
+/// an `InvalidInput` error.
 pub trait ReadAt: Array {
     /// Reads a number of bytes starting from a given offset.
     ///
@@ -94,281 +322,3854 @@ pub trait ReadAt: Array {
     /// [`std::os::unix::fs::FileExt::read_exact_at`]: https://doc.rust-lang.org/std/os/unix/fs/trait.FileExt.html#tymethod.read_exact_at
     fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()>;
 
+    /// Reads from the given offset until `buf` is full or the array ends,
+    /// and returns the total number of bytes read.
+    ///
+    /// Unlike [`ReadAt::read_at`], this doesn't return early after a short
+    /// read, and it retries reads which fail with
+    /// [`io::ErrorKind::Interrupted`]. It only returns less than `buf.len()`
+    /// when a read returns 0, at the end of the array. And unlike
+    /// [`ReadAt::read_exact_at`], reaching the end isn't an error, so the
+    /// bytes read before it aren't lost.
+    ///
+    /// The default implementation calls [`ReadAt::read_at`] in a loop.
+    fn read_full_at(&self, buf: &mut [u8], mut offset: u64) -> io::Result<usize> {
+        let mut filled = 0;
+        while filled < buf.len() {
+            match self.read_at(&mut buf[filled..], offset) {
+                Ok(0) => break,
+                Ok(n) => {
+                    filled += n;
+                    offset = offset.checked_add(n as u64).ok_or_else(|| {
+                        io::Error::new(io::ErrorKind::InvalidInput, "offset overflow")
+                    })?;
+                }
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => (),
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(filled)
+    }
+
     /// Is to `read_vectored` what `read_at` is to `read`.
+    #[cfg(feature = "std")]
     fn read_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<usize>;
 
     /// Is to `read_exact_vectored` what `read_exact_at` is to `read_exact`.
+    #[cfg(feature = "std")]
     fn read_exact_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<()>;
 
     /// Determines if `Self` has an efficient `read_vectored_at`
     /// implementation.
+    #[cfg(feature = "std")]
     fn is_read_vectored_at(&self) -> bool;
 
-    /// Create a `StreamReader` which reads from the array at the given offset.
+    /// Reads a number of bytes starting from a given offset, only if they
+    /// can be read without blocking, such as when they're in the page cache.
+    ///
+    /// On Linux, this uses `preadv2` with `RWF_NOWAIT`, and fails with
+    /// [`io::ErrorKind::WouldBlock`] if the read would block. Where this
+    /// isn't supported, including on other platforms and on filesystems or
+    /// kernels without `RWF_NOWAIT`, it fails with
+    /// [`io::ErrorKind::Unsupported`], so callers can fall back to
+    /// [`ReadAt::read_at`], for example on another thread. Arrays in memory
+    /// never block, so for them this is the same as [`ReadAt::read_at`].
+    ///
+    /// The default implementation fails with [`io::ErrorKind::Unsupported`].
+    fn read_at_nowait(&self, _buf: &mut [u8], _offset: u64) -> io::Result<usize> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "read_at_nowait is not supported",
+        ))
+    }
+
+    /// Reads exactly `len` bytes starting from a given offset into a new
+    /// `Vec`.
+    ///
+    /// This fails with [`io::ErrorKind::UnexpectedEof`] if the array ends
+    /// before `len` bytes are read.
+    ///
+    /// The default implementation calls [`ReadAt::read_exact_at`].
+    fn read_to_vec_at(&self, offset: u64, len: usize) -> io::Result<Vec<u8>> {
+        let mut buf = vec![0; len];
+        self.read_exact_at(&mut buf, offset)?;
+        Ok(buf)
+    }
+
+    /// Reads up to `len` bytes starting from a given offset into a new
+    /// `Vec`, stopping early if the array ends.
+    ///
+    /// The default implementation calls [`ReadAt::read_full_at`].
+    fn read_up_to_vec_at(&self, offset: u64, len: usize) -> io::Result<Vec<u8>> {
+        let mut buf = vec![0; len];
+        let filled = self.read_full_at(&mut buf, offset)?;
+        buf.truncate(filled);
+        Ok(buf)
+    }
+
+    /// Returns the offset of the first occurrence of `needle` in the array
+    /// at or after the given offset, or `None` if there isn't one.
+    ///
+    /// An empty `needle` matches at `offset`.
+    ///
+    /// The default implementation reads the array in large overlapping
+    /// chunks, so that matches which straddle chunk boundaries are found.
+    fn find_at(&self, needle: &[u8], offset: u64) -> io::Result<Option<u64>> {
+        crate::search::find_at(self, needle, offset)
+    }
+
+    /// Returns the offset of the last occurrence of `needle` in the array
+    /// which ends at or before the given offset, or `None` if there isn't
+    /// one.
+    ///
+    /// `offset` is clamped to the length of the array, so passing
+    /// `u64::MAX` searches backwards from the end of the array. An empty
+    /// `needle` matches at the clamped offset.
+    ///
+    /// The default implementation reads the array in large overlapping
+    /// chunks, so that matches which straddle chunk boundaries are found.
+    fn rfind_at(&self, needle: &[u8], offset: u64) -> io::Result<Option<u64>> {
+        crate::search::rfind_at(self, needle, offset)
+    }
+
+    /// Returns an iterator over the bytes of the array, starting at the
+    /// given offset.
+    ///
+    /// This is to [`Read::bytes`] what `read_at` is to `read`. Since it
+    /// borrows the array immutably, several iterators can run over the same
+    /// array at once.
+    #[cfg(feature = "std")]
+    #[inline]
+    fn bytes_at(&self, offset: u64) -> BytesAt<'_, Self>
+    where
+        Self: Sized,
+    {
+        BytesAt::new(self, offset)
+    }
+
+    /// Returns an iterator over chunks of `chunk_size` bytes of the array,
+    /// starting at the given offset.
+    ///
+    /// The last chunk may be shorter than `chunk_size`. To reuse a buffer
+    /// between chunks, use [`ChunksAt::next_into`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is 0.
+    #[cfg(feature = "std")]
+    #[inline]
+    fn chunks_at(&self, offset: u64, chunk_size: usize) -> ChunksAt<'_, Self>
+    where
+        Self: Sized,
+    {
+        ChunksAt::new(self, offset, chunk_size)
+    }
+
+    /// Returns an iterator over the lines of the array, starting at the
+    /// given offset.
+    ///
+    /// This is to [`BufRead::lines`] what `read_at` is to `read`. Use
+    /// [`LinesAt::with_offsets`] to also get the offset of each line.
+    ///
+    /// [`BufRead::lines`]: std::io::BufRead::lines
+    #[cfg(feature = "std")]
+    #[inline]
+    fn lines_at(&self, offset: u64) -> LinesAt<'_, Self>
+    where
+        Self: Sized,
+    {
+        LinesAt::new(self, offset)
+    }
+
+    /// Feeds the `len` bytes starting from a given offset into `hasher`,
+    /// and returns the number of bytes fed, which is less than `len` if the
+    /// array ends first.
+    ///
+    /// The range is read in chunks of [`Metadata::blksize`] bytes, with a
+    /// floor, and each chunk is passed to [`Hasher::write`], so the whole
+    /// range is never in memory at once. For hashers which don't depend on
+    /// how their input is split, the result is the same as writing the
+    /// range in one call.
+    ///
+    /// [`Hasher::write`]: std::hash::Hasher::write
+    #[inline]
+    fn hash_range<H: Hasher + ?Sized>(
+        &self,
+        offset: u64,
+        len: u64,
+        hasher: &mut H,
+    ) -> io::Result<u64>
+    where
+        Self: Sized,
+    {
+        for_each_chunk(self, offset, len, |chunk| hasher.write(chunk))
+    }
+
+    /// Like [`ReadAt::hash_range`], but feeds the range into a
+    /// [`digest::Update`], such as a SHA-2 or BLAKE3 hasher.
+    #[cfg(feature = "digest")]
+    #[inline]
+    fn hash_range_digest<D: digest::Update + ?Sized>(
+        &self,
+        offset: u64,
+        len: u64,
+        digest: &mut D,
+    ) -> io::Result<u64>
+    where
+        Self: Sized,
+    {
+        for_each_chunk(self, offset, len, |chunk| digest.update(chunk))
+    }
+
+    /// Copy `len` bytes from `self` at `offset` to `output` at
+    /// `output_offset`, returning the number of bytes copied, which is less
+    /// than `len` if `self` ends first.
+    ///
+    /// This is the mirror of [`WriteAt::copy_from`], for code which is
+    /// generic over the source rather than the destination, and it lets the
+    /// source provide the fast path. Arrays in memory, such as `Vec<u8>` and
+    /// [`MemReader`], write the range to `output` directly from their
+    /// buffer.
+    ///
+    /// The default implementation calls [`WriteAt::copy_from`].
+    ///
+    /// [`MemReader`]: crate::MemReader
+    #[inline]
+    fn copy_to<W: WriteAt + ?Sized>(
+        &self,
+        offset: u64,
+        output: &mut W,
+        output_offset: u64,
+        len: u64,
+    ) -> io::Result<u64>
+    where
+        Self: Sized,
+    {
+        output.copy_from(output_offset, self, offset, len)
+    }
+
+    /// Create a `StreamReader` which reads from the array at the given offset.
+    #[cfg(feature = "io-streams")]
+    fn read_via_stream_at(&self, offset: u64) -> io::Result<StreamReader>;
+
+    /// Create a `StreamReader` which reads exactly `len` bytes from the array
+    /// at the given offset, and then reports the end of the stream.
+    ///
+    /// This fails with [`io::ErrorKind::UnexpectedEof`] if the range extends
+    /// past the end of the array.
+    ///
+    /// The default implementation checks the range against
+    /// [`Array::metadata`], unless the array [isn't bounded], and streams
+    /// the output of [`ReadAt::read_via_stream_at`] through a thread which
+    /// stops after `len` bytes.
+    ///
+    /// [isn't bounded]: Metadata::is_bounded
+    #[cfg(feature = "io-streams")]
+    fn read_range_via_stream(&self, offset: u64, len: u64) -> io::Result<StreamReader> {
+        let metadata = self.metadata()?;
+        if metadata.is_bounded() {
+            check_stream_range(metadata.len(), offset, len)?;
+        }
+        let stream = self.read_via_stream_at(offset)?;
+        StreamReader::piped_thread(Box::new(stream.take(len)))
+    }
+}
+
+/// A trait for writing to arrays.
+///
+/// This is similar to [`std::io::Write`] except all of the reading functions
+/// take an `offset` parameter, specifying a position in the array to read at.
+///
+/// Implementations need only provide [`WriteAt::write_at`]; the other
+/// methods have default implementations which can be overridden where a more
+/// efficient version is available. Arrays whose length can be changed also
+/// implement [`Resize`].
+///
+/// Offsets which can't be addressed are reported as they are for
+/// [`ReadAt`].
+pub trait WriteAt: Array {
+    /// Writes a number of bytes starting from a given offset.
+    ///
+    /// This is similar to [`std::os::unix::fs::FileExt::write_at`], except it
+    /// takes `self` by immutable reference since the entire side effect is
+    /// I/O, and it's supported on non-Unix platforms including Windows.
+    ///
+    /// [`std::os::unix::fs::FileExt::write_at`]: https://doc.rust-lang.org/std/os/unix/fs/trait.FileExt.html#tymethod.write_at
+    fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize>;
+
+    /// Attempts to write an entire buffer starting from a given offset.
+    ///
+    /// This is similar to [`std::os::unix::fs::FileExt::write_all_at`], except
+    /// it takes `self` by immutable reference since the entire side effect is
+    /// I/O, and it's supported on non-Unix platforms including Windows.
+    ///
+    /// The default implementation calls [`WriteAt::write_at`] in a loop,
+    /// retrying on [`io::ErrorKind::Interrupted`], and fails with
+    /// [`io::ErrorKind::WriteZero`] if no progress is made.
+    ///
+    /// [`std::os::unix::fs::FileExt::write_all_at`]: https://doc.rust-lang.org/std/os/unix/fs/trait.FileExt.html#tymethod.write_all_at
+    fn write_all_at(&mut self, mut buf: &[u8], mut offset: u64) -> io::Result<()> {
+        while !buf.is_empty() {
+            match self.write_at(buf, offset) {
+                Ok(0) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::WriteZero,
+                        "failed to write whole buffer",
+                    ))
+                }
+                Ok(nwritten) => {
+                    offset = offset.checked_add(nwritten as u64).ok_or_else(|| {
+                        io::Error::new(io::ErrorKind::InvalidInput, "offset overflow")
+                    })?;
+                    buf = &buf[nwritten..];
+                }
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => (),
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(())
+    }
+
+    /// Is to `write_vectored` what `write_at` is to `write`.
+    ///
+    /// The default implementation writes the first non-empty buffer with
+    /// [`WriteAt::write_at`].
+    #[cfg(feature = "std")]
+    fn write_vectored_at(&mut self, bufs: &[IoSlice], offset: u64) -> io::Result<usize> {
+        let buf = bufs
+            .iter()
+            .find(|b| !b.is_empty())
+            .map_or(&[][..], |b| &**b);
+        self.write_at(buf, offset)
+    }
+
+    /// Is to `write_all_vectored` what `write_all_at` is to `write_all`.
+    ///
+    /// The default implementation calls [`WriteAt::write_vectored_at`] in a
+    /// loop, with the same error handling as [`WriteAt::write_all_at`].
+    #[cfg(feature = "std")]
+    fn write_all_vectored_at(
+        &mut self,
+        mut bufs: &mut [IoSlice],
+        mut offset: u64,
+    ) -> io::Result<()> {
+        loop {
+            // Skip over any empty buffers so that an all-empty list doesn't
+            // look like a failure to make progress.
+            while bufs.first().is_some_and(|b| b.is_empty()) {
+                bufs = &mut bufs[1..];
+            }
+            if bufs.is_empty() {
+                return Ok(());
+            }
+            match self.write_vectored_at(bufs, offset) {
+                Ok(0) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::WriteZero,
+                        "failed to write whole buffer",
+                    ))
+                }
+                Ok(mut nwritten) => {
+                    offset = offset.checked_add(nwritten as u64).ok_or_else(|| {
+                        io::Error::new(io::ErrorKind::InvalidInput, "offset overflow")
+                    })?;
+                    while let Some(first) = bufs.first() {
+                        if nwritten < first.len() {
+                            break;
+                        }
+                        nwritten -= first.len();
+                        bufs = &mut bufs[1..];
+                    }
+                    if nwritten != 0 {
+                        // Finish off a partially-written buffer.
+                        let first = bufs[0];
+                        let rest = &first[nwritten..];
+                        self.write_all_at(rest, offset)?;
+                        offset = offset.checked_add(rest.len() as u64).ok_or_else(|| {
+                            io::Error::new(io::ErrorKind::InvalidInput, "offset overflow")
+                        })?;
+                        bufs = &mut bufs[1..];
+                    }
+                }
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => (),
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// Determines if `Self` has an efficient `write_vectored_at`
+    /// implementation.
+    ///
+    /// The default implementation returns `false`.
+    #[cfg(feature = "std")]
+    fn is_write_vectored_at(&self) -> bool {
+        false
+    }
+
+    /// Writes a string starting from a given offset.
+    ///
+    /// The default implementation calls [`WriteAt::write_all_at`].
+    fn write_str_at(&mut self, s: &str, offset: u64) -> io::Result<()> {
+        self.write_all_at(s.as_bytes(), offset)
+    }
+
+    /// Writes formatted data starting from a given offset, and returns the
+    /// number of bytes written.
+    ///
+    /// This is to `write_fmt` what `write_all_at` is to `write_all`. It's
+    /// usually used with [`format_args!`]. The formatted pieces are written
+    /// with [`WriteAt::write_all_at`] as they're produced, rather than being
+    /// collected into a `String` first.
+    fn write_fmt_at(&mut self, fmt: fmt::Arguments, offset: u64) -> io::Result<u64> {
+        if let Some(s) = fmt.as_str() {
+            self.write_str_at(s, offset)?;
+            return Ok(s.len() as u64);
+        }
+        let mut adapter = FmtAdapter {
+            inner: self,
+            start: offset,
+            offset,
+            error: Ok(()),
+        };
+        match fmt::write(&mut adapter, fmt) {
+            Ok(()) => Ok(adapter.offset - adapter.start),
+            Err(_) => Err(match adapter.error {
+                Err(err) => err,
+                Ok(()) => io::Error::new(io::ErrorKind::Other, "formatter error"),
+            }),
+        }
+    }
+
+    /// Writes a number of bytes starting from a given offset, like
+    /// [`WriteAt::write_at`], with per-write `flags`.
+    ///
+    /// On Linux, this uses `pwritev2` with the corresponding `RWF_*` flags.
+    /// Elsewhere, and on kernels without `pwritev2`, [`WriteFlags::DSYNC`] and
+    /// [`WriteFlags::SYNC`] are emulated by calling [`WriteAt::sync_data`] or
+    /// [`WriteAt::sync_all`] after the write, [`WriteFlags::HIPRI`] is
+    /// ignored, and [`WriteFlags::NOWAIT`] fails with
+    /// [`io::ErrorKind::Unsupported`]. With `NOWAIT`, a write which would
+    /// block fails with [`io::ErrorKind::WouldBlock`].
+    ///
+    /// The default implementation uses the emulation.
+    fn write_at_with(&mut self, buf: &[u8], offset: u64, flags: WriteFlags) -> io::Result<usize> {
+        if flags.contains(WriteFlags::NOWAIT) {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "non-blocking writes are not supported",
+            ));
+        }
+        let written = self.write_at(buf, offset)?;
+        if flags.contains(WriteFlags::SYNC) {
+            self.sync_all()?;
+        } else if flags.contains(WriteFlags::DSYNC) {
+            self.sync_data()?;
+        }
+        Ok(written)
+    }
+
+    /// Copy `len` bytes from `input` at `input_offset` to `self` at `offset`.
+    ///
+    /// `input` is taken as a `&dyn ReadAt` so that `WriteAt` can be used as a
+    /// trait object.
+    ///
+    /// If `input` refers to the same file as `self`, the copy has
+    /// `memmove`-like semantics: the result is the same as if the input range
+    /// had been read in full before any of it was written, even if the ranges
+    /// overlap. The file-backed implementations in this crate detect this
+    /// with [`filelike::is_same_file`]. In-memory arrays can't alias in this
+    /// way, since `self` is borrowed mutably.
+    ///
+    /// The default implementation reads from `input` with
+    /// [`ReadAt::read_at`] into a heap buffer and writes it out with
+    /// [`WriteAt::write_all_at`], stopping early if `input` reaches its end.
+    fn copy_from(
+        &mut self,
+        mut offset: u64,
+        input: &dyn ReadAt,
+        mut input_offset: u64,
+        len: u64,
+    ) -> io::Result<u64> {
+        let mut buf = vec![0_u8; min(len, COPY_BUF_SIZE) as usize];
+        let mut copied = 0;
+        while copied < len {
+            let chunk = min(len - copied, buf.len() as u64) as usize;
+            let nread = match input.read_at(&mut buf[..chunk], input_offset) {
+                Ok(0) => break,
+                Ok(nread) => nread,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            };
+            self.write_all_at(&buf[..nread], offset)?;
+            offset = offset
+                .checked_add(nread as u64)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "offset overflow"))?;
+            input_offset = input_offset
+                .checked_add(nread as u64)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "offset overflow"))?;
+            copied += nread as u64;
+        }
+        Ok(copied)
+    }
+
+    /// Copy `len` bytes from `input` at `input_offset` to `self` at `offset`,
+    /// calling `progress` with the cumulative number of bytes copied after
+    /// each chunk.
+    ///
+    /// The copy is performed with [`WriteAt::copy_from`] in chunks of at
+    /// most 1 MiB, so it uses the same accelerated paths, and has the same
+    /// semantics for overlapping ranges. `progress` is called once per chunk,
+    /// with monotonically increasing totals.
+    fn copy_from_with_progress(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+        progress: &mut dyn FnMut(u64),
+    ) -> io::Result<u64> {
+        offset
+            .checked_add(len)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "offset overflow"))?;
+        input_offset
+            .checked_add(len)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "offset overflow"))?;
+        let mut copied = 0;
+        if offset > input_offset {
+            // Copy the chunks from the end, so that if `input` aliases `self`,
+            // each chunk is read before it's overwritten.
+            let mut remaining = len;
+            while remaining > 0 {
+                let chunk = match remaining % PROGRESS_CHUNK_SIZE {
+                    0 => PROGRESS_CHUNK_SIZE,
+                    partial => partial,
+                };
+                remaining -= chunk;
+                copied +=
+                    self.copy_from(offset + remaining, input, input_offset + remaining, chunk)?;
+                progress(copied);
+            }
+        } else {
+            while copied < len {
+                let chunk = min(len - copied, PROGRESS_CHUNK_SIZE);
+                let n = self.copy_from(offset + copied, input, input_offset + copied, chunk)?;
+                copied += n;
+                progress(copied);
+                if n < chunk {
+                    break;
+                }
+            }
+        }
+        Ok(copied)
+    }
+
+    /// Copy `len` bytes from `input` at `input_offset` to `self` at
+    /// `offset`, sharing storage with `input` instead of copying the data
+    /// where possible.
+    ///
+    /// On Linux, when both arrays are files on a filesystem which supports
+    /// reflinks, such as btrfs or XFS, this uses `FICLONERANGE`, which is
+    /// constant-time and shares extents between the files. The filesystem
+    /// requires `offset`, `input_offset`, and `len` to be multiples of the
+    /// filesystem block size, except that `len` may extend to the end of
+    /// `input`; requests which don't meet this fail with
+    /// [`io::ErrorKind::InvalidInput`] rather than silently copying.
+    ///
+    /// If the data can't be shared, for example because the files are on
+    /// different filesystems or the filesystem doesn't support reflinks, this
+    /// falls back to [`WriteAt::copy_from`]. The returned [`CloneOutcome`]
+    /// reports which happened.
+    ///
+    /// The default implementation always calls [`WriteAt::copy_from`].
+    fn clone_range_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<CloneOutcome> {
+        self.copy_from(offset, input, input_offset, len)
+            .map(CloneOutcome::Copied)
+    }
+
+    /// Deduplicate `len` bytes of `self` at `offset` with the identical
+    /// bytes of `input` at `input_offset`, so that the files share storage
+    /// for them, returning the number of bytes deduplicated.
+    ///
+    /// On Linux, when both arrays are files on a filesystem which supports
+    /// it, such as btrfs or XFS, this uses `FIDEDUPERANGE`, which compares
+    /// the ranges in the kernel and only shares them if they're identical.
+    /// If the ranges differ, nothing past the first differing block is
+    /// deduplicated, so the result may be less than `len`. The filesystem
+    /// may require `offset`, `input_offset`, and `len` to be multiples of the
+    /// filesystem block size.
+    ///
+    /// Unlike [`WriteAt::clone_range_from`], this never copies the data, and
+    /// fails with [`io::ErrorKind::Unsupported`] if the filesystem, platform,
+    /// or either array doesn't support deduplication.
+    ///
+    /// The default implementation fails with [`io::ErrorKind::Unsupported`].
+    fn dedupe_range_from(
+        &mut self,
+        _offset: u64,
+        _input: &dyn ReadAt,
+        _input_offset: u64,
+        _len: u64,
+    ) -> io::Result<u64> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "dedupe_range_from is not supported",
+        ))
+    }
+
+    /// Allocates storage for the range of `len` bytes at `offset`, so that
+    /// later writes within it don't fail for lack of space.
+    ///
+    /// If `offset + len` is past the end of the array, the array is extended
+    /// to that length, as with `fallocate` without `FALLOC_FL_KEEP_SIZE`.
+    ///
+    /// This uses `fallocate` on Linux, `F_PREALLOCATE` on macOS and iOS, and
+    /// `posix_fallocate` on other Posix-ish platforms. On Windows, where files
+    /// aren't sparse by default, this extends the file with `SetEndOfFile`.
+    ///
+    /// The default implementation fails with [`io::ErrorKind::Unsupported`].
+    fn allocate(&mut self, _offset: u64, _len: u64) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "allocate is not supported",
+        ))
+    }
+
+    /// Reserves storage for the range of `len` bytes at `offset`, without
+    /// changing the length of the array.
+    ///
+    /// Unlike [`WriteAt::allocate`], this doesn't extend the array, so
+    /// readers which trust its length don't see zeros past the data written
+    /// so far. This is useful for reserving space for future appends. The
+    /// storage shows up in [`Metadata::allocated_len`].
+    ///
+    /// This uses `fallocate` with `FALLOC_FL_KEEP_SIZE` on Linux,
+    /// `F_PREALLOCATE` on macOS and iOS, and `FileAllocationInfo` on
+    /// Windows, where the reservation may be released once the file is
+    /// closed. On other Posix-ish platforms, it uses `posix_fallocate` and
+    /// then truncates the file back to its original length, which isn't
+    /// atomic, so concurrent extensions of the file may be lost, and some
+    /// filesystems release the storage past the end again.
+    ///
+    /// The default implementation fails with [`io::ErrorKind::Unsupported`].
+    fn reserve_at(&mut self, _offset: u64, _len: u64) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "reserve_at is not supported",
+        ))
+    }
+
+    /// Deallocates the storage for the range of `len` bytes at `offset`.
+    ///
+    /// Afterwards, reads of the range return zeros, and the length of the
+    /// array is unchanged; any part of the range past the end of the array
+    /// is ignored.
+    ///
+    /// This uses `fallocate` with `FALLOC_FL_PUNCH_HOLE` on Linux,
+    /// `F_PUNCHHOLE` on macOS and iOS, `fspacectl` on FreeBSD, and
+    /// `FSCTL_SET_ZERO_DATA` on Windows, where storage is only released if
+    /// the file is marked sparse. Where the platform or filesystem can't
+    /// deallocate storage, the range is overwritten with zeros instead. In
+    /// memory, the range is zeroed.
+    ///
+    /// The default implementation fails with [`io::ErrorKind::Unsupported`].
+    fn punch_hole(&mut self, _offset: u64, _len: u64) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "punch_hole is not supported",
+        ))
+    }
+
+    /// Sets the range of `len` bytes at `offset` to zeros, extending the
+    /// array if `offset + len` is past the end.
+    ///
+    /// On Linux, this uses `fallocate` with `FALLOC_FL_ZERO_RANGE`, which
+    /// zeros the range without writing the data. Otherwise, it punches a
+    /// hole with [`WriteAt::punch_hole`] and reallocates the range with
+    /// [`WriteAt::allocate`].
+    ///
+    /// The default implementation writes zeros from a buffer sized from
+    /// [`Metadata::blksize`].
+    fn zero_range(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        fill_chunked(self, 0, offset, len)
+    }
+
+    /// Sets the range of `len` bytes at `offset` to `byte`, extending the
+    /// array if `offset + len` is past the end, as [`WriteAt::write_all_at`]
+    /// does.
+    ///
+    /// This writes from a single buffer sized from [`Metadata::blksize`]
+    /// rather than allocating `len` bytes. When `byte` is zero, this calls
+    /// [`WriteAt::zero_range`].
+    fn fill_at(&mut self, byte: u8, offset: u64, len: u64) -> io::Result<()> {
+        if byte == 0 {
+            self.zero_range(offset, len)
+        } else {
+            fill_chunked(self, byte, offset, len)
+        }
+    }
+
+    /// Flushes all data and metadata written to the array to durable
+    /// storage.
+    ///
+    /// This is similar to [`std::fs::File::sync_all`], which uses `fsync` on
+    /// Posix-ish platforms and `FlushFileBuffers` on Windows.
+    ///
+    /// The default implementation does nothing, which is appropriate for
+    /// arrays which aren't backed by storage, such as arrays in memory.
+    fn sync_all(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Flushes all data written to the array to durable storage, without
+    /// necessarily flushing metadata which isn't needed to read it back.
+    ///
+    /// This is similar to [`std::fs::File::sync_data`], which uses
+    /// `fdatasync` where available.
+    ///
+    /// The default implementation does nothing, which is appropriate for
+    /// arrays which aren't backed by storage, such as arrays in memory.
+    fn sync_data(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Flushes data written to the range of `len` bytes at `offset` to
+    /// storage, or waits for writeback of it, according to `mode`.
+    ///
+    /// On Linux, this uses `sync_file_range`, which doesn't flush metadata
+    /// or the disk's write cache, so it's advisory, and doesn't guarantee
+    /// durability in the way that [`WriteAt::sync_data`] does. Elsewhere,
+    /// and for files `sync_file_range` doesn't support, this calls
+    /// [`WriteAt::sync_data`]. A zero-length range does nothing.
+    ///
+    /// The default implementation calls [`WriteAt::sync_data`].
+    fn sync_range(&self, _offset: u64, _len: u64, _mode: SyncRangeMode) -> io::Result<()> {
+        self.sync_data()
+    }
+}
+
+/// A trait for arrays whose length can be changed.
+///
+/// This is separate from [`WriteAt`] because many writable arrays have a
+/// fixed length, such as slices, memory maps, and views of part of another
+/// array, and don't implement it. Code which resizes an array can require
+/// `W: WriteAt + Resize`, so that passing one of them fails to compile,
+/// rather than failing when it runs:
+///
+/// ```compile_fail,E0277
+/// # use io_arrays::{Resize, WriteAt};
+/// fn truncate<W: WriteAt + Resize>(array: &mut W) -> std::io::Result<()> {
+///     array.set_len(0)
+/// }
+///
+/// let mut buf = [0_u8; 4];
+/// truncate(&mut buf).unwrap();
+/// ```
+pub trait Resize: Array {
+    /// Truncates or extends the array, updating its length to become `size`.
+    ///
+    /// When the array is extended, the new bytes read as zeros.
+    fn set_len(&mut self, size: u64) -> io::Result<()>;
+
+    /// Extends the array to `size` bytes if it's shorter than that, leaving
+    /// it unchanged otherwise.
+    ///
+    /// The default implementation compares `size` with the length from
+    /// [`Array::metadata`], and calls [`Resize::set_len`] if it's greater.
+    fn grow_to(&mut self, size: u64) -> io::Result<()> {
+        if size > self.metadata()?.len() {
+            Resize::set_len(self, size)?;
+        }
+        Ok(())
+    }
+}
+
+/// Check that the range of `len` bytes at `offset` is within an array of
+/// length `array_len`, for [`ReadAt::read_range_via_stream`].
+#[cfg(feature = "io-streams")]
+pub(crate) fn check_stream_range(array_len: u64, offset: u64, len: u64) -> io::Result<()> {
+    let end = offset
+        .checked_add(len)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "offset overflow"))?;
+    if end > array_len {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "stream range extends past the end of the array",
+        ));
+    }
+    Ok(())
+}
+
+/// A [`fmt::Write`] adapter for [`WriteAt::write_fmt_at`], which writes each
+/// piece at a running offset.
+struct FmtAdapter<'a, W: ?Sized> {
+    inner: &'a mut W,
+    start: u64,
+    offset: u64,
+    error: io::Result<()>,
+}
+
+impl<W: WriteAt + ?Sized> fmt::Write for FmtAdapter<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let result = self
+            .offset
+            .checked_add(s.len() as u64)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "offset overflow"))
+            .and_then(|end| {
+                self.inner.write_str_at(s, self.offset)?;
+                Ok(end)
+            });
+        match result {
+            Ok(end) => {
+                self.offset = end;
+                Ok(())
+            }
+            Err(err) => {
+                self.error = Err(err);
+                Err(fmt::Error)
+            }
+        }
+    }
+}
+
+/// Read the `len` bytes of `array` at `offset` in chunks, passing each chunk
+/// to `f`, and return the number of bytes read, stopping early at the end of
+/// the array.
+fn for_each_chunk<A: ReadAt + ?Sized>(
+    array: &A,
+    mut offset: u64,
+    len: u64,
+    mut f: impl FnMut(&[u8]),
+) -> io::Result<u64> {
+    offset
+        .checked_add(len)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "offset overflow"))?;
+    let chunk_size = array.metadata()?.blksize().max(MIN_HASH_CHUNK_SIZE);
+    let mut buf = vec![0_u8; min(len, chunk_size) as usize];
+    let mut total = 0;
+    while total < len {
+        let chunk = min(len - total, chunk_size) as usize;
+        match array.read_at(&mut buf[..chunk], offset) {
+            Ok(0) => break,
+            Ok(n) => {
+                f(&buf[..n]);
+                offset += n as u64;
+                total += n as u64;
+            }
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => (),
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(total)
+}
+
+/// Write `len` copies of `byte` to `array` at `offset`, from a buffer of at
+/// most [`COPY_BUF_SIZE`] rounded up to the block size.
+fn fill_chunked<W: WriteAt + ?Sized>(
+    array: &mut W,
+    byte: u8,
+    mut offset: u64,
+    mut len: u64,
+) -> io::Result<()> {
+    offset
+        .checked_add(len)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "offset overflow"))?;
+    let blksize = array.metadata()?.blksize().max(1);
+    let buf_size = COPY_BUF_SIZE.next_multiple_of(blksize);
+    let buf = vec![byte; min(len, buf_size) as usize];
+    while len != 0 {
+        // Align the ends of the writes to the block size.
+        let chunk = min(len, buf_size - offset % blksize);
+        array.write_all_at(&buf[..chunk as usize], offset)?;
+        offset += chunk;
+        len -= chunk;
+    }
+    Ok(())
+}
+
+/// Flags for [`WriteAt::write_at_with`].
+///
+/// Flags can be combined with `|`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WriteFlags(u32);
+
+impl WriteFlags {
+    /// Make the written data durable before returning, as if by
+    /// [`WriteAt::sync_data`] (`RWF_DSYNC`).
+    pub const DSYNC: Self = Self(1 << 0);
+
+    /// Make the written data and metadata durable before returning, as if by
+    /// [`WriteAt::sync_all`] (`RWF_SYNC`).
+    pub const SYNC: Self = Self(1 << 1);
+
+    /// Use high-priority polled I/O where available (`RWF_HIPRI`).
+    pub const HIPRI: Self = Self(1 << 2);
+
+    /// Fail with [`io::ErrorKind::WouldBlock`] instead of blocking
+    /// (`RWF_NOWAIT`).
+    pub const NOWAIT: Self = Self(1 << 3);
+
+    /// Returns flags with none set.
+    #[inline]
+    #[must_use]
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    /// Returns `true` if no flags are set.
+    #[inline]
+    #[must_use]
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns `true` if all of the flags in `other` are set.
+    #[inline]
+    #[must_use]
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for WriteFlags {
+    type Output = Self;
+
+    #[inline]
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for WriteFlags {
+    #[inline]
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+/// What [`WriteAt::sync_range`] should do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SyncRangeMode {
+    /// Start writeback of any dirty data in the range, without waiting for
+    /// it to complete.
+    InitiateWriteback,
+
+    /// Wait for writeback of the range which is already in progress to
+    /// complete, without starting any more.
+    WaitForCompletion,
+
+    /// Wait for any writeback in progress, start writeback of any dirty data
+    /// in the range, and wait for it to complete.
+    Full,
+}
+
+/// The result of [`WriteAt::clone_range_from`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CloneOutcome {
+    /// The data was shared with the input, and this many bytes were cloned.
+    Cloned(u64),
+
+    /// The data couldn't be shared, so this many bytes were copied.
+    Copied(u64),
+}
+
+#[allow(clippy::len_without_is_empty)]
+impl CloneOutcome {
+    /// Returns the number of bytes cloned or copied.
+    #[inline]
+    #[must_use]
+    pub const fn len(self) -> u64 {
+        match self {
+            Self::Cloned(len) | Self::Copied(len) => len,
+        }
+    }
+
+    /// Returns `true` if the data was shared rather than copied.
+    #[inline]
+    #[must_use]
+    pub const fn is_cloned(self) -> bool {
+        matches!(self, Self::Cloned(_))
+    }
+}
+
+/// A trait for reading and writing to arrays.
+///
+/// This trait simply combines [`ReadAt`] and [`WriteAt`] and has a blanket
+/// implementation for any type that implements both. It can be used as a
+/// trait object, as in `Box<dyn EditAt>`.
+pub trait EditAt: ReadAt + WriteAt {
+    /// Create a [`Streamer`] which reads from and writes to the array,
+    /// starting at the given offset.
+    ///
+    /// Reads and writes share one current position, so data written through
+    /// the streamer is visible to later reads through it. This is useful for
+    /// transforming data in place.
+    ///
+    /// Unlike `read_via_stream_at`, this borrows the array, and doesn't use a
+    /// thread.
+    #[cfg(feature = "std")]
+    #[inline]
+    fn edit_via_stream_at(&mut self, offset: u64) -> io::Result<Streamer<&mut Self>>
+    where
+        Self: Sized,
+    {
+        Ok(Streamer::owned(self, offset))
+    }
+}
+
+impl<T: ReadAt + WriteAt> EditAt for T {}
+
+impl<T: Array + ?Sized> Array for &T {
+    #[inline]
+    fn metadata(&self) -> io::Result<Metadata> {
+        (**self).metadata()
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        (**self).advise(offset, len, advice)
+    }
+    #[cfg(feature = "std")]
+    #[inline]
+    fn readahead(&self, offset: u64, len: u64) -> io::Result<()> {
+        (**self).readahead(offset, len)
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn borrowed_filelike(&self) -> Option<BorrowedFilelike<'_>> {
+        (**self).borrowed_filelike()
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn lock_at(&self, offset: u64, len: u64, kind: LockKind) -> io::Result<RangeLockGuard<'_>> {
+        (**self).lock_at(offset, len, kind)
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn try_lock_at(
+        &self,
+        offset: u64,
+        len: u64,
+        kind: LockKind,
+    ) -> io::Result<Option<RangeLockGuard<'_>>> {
+        (**self).try_lock_at(offset, len, kind)
+    }
+}
+
+impl<T: ReadAt + ?Sized> ReadAt for &T {
+    #[inline]
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        (**self).read_at(buf, offset)
+    }
+
+    #[inline]
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+        (**self).read_exact_at(buf, offset)
+    }
+
+    #[inline]
+    fn read_full_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        (**self).read_full_at(buf, offset)
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn read_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<usize> {
+        (**self).read_vectored_at(bufs, offset)
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn read_exact_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<()> {
+        (**self).read_exact_vectored_at(bufs, offset)
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn is_read_vectored_at(&self) -> bool {
+        (**self).is_read_vectored_at()
+    }
+
+    #[inline]
+    fn read_at_nowait(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        (**self).read_at_nowait(buf, offset)
+    }
+
+    #[inline]
+    fn read_to_vec_at(&self, offset: u64, len: usize) -> io::Result<Vec<u8>> {
+        (**self).read_to_vec_at(offset, len)
+    }
+
+    #[inline]
+    fn read_up_to_vec_at(&self, offset: u64, len: usize) -> io::Result<Vec<u8>> {
+        (**self).read_up_to_vec_at(offset, len)
+    }
+
+    #[inline]
+    fn find_at(&self, needle: &[u8], offset: u64) -> io::Result<Option<u64>> {
+        (**self).find_at(needle, offset)
+    }
+
+    #[inline]
+    fn rfind_at(&self, needle: &[u8], offset: u64) -> io::Result<Option<u64>> {
+        (**self).rfind_at(needle, offset)
+    }
+
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    fn read_via_stream_at(&self, offset: u64) -> io::Result<StreamReader> {
+        (**self).read_via_stream_at(offset)
+    }
+
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    fn read_range_via_stream(&self, offset: u64, len: u64) -> io::Result<StreamReader> {
+        (**self).read_range_via_stream(offset, len)
+    }
+}
+
+impl<T: Array + ?Sized> Array for &mut T {
+    #[inline]
+    fn metadata(&self) -> io::Result<Metadata> {
+        (**self).metadata()
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        (**self).advise(offset, len, advice)
+    }
+    #[cfg(feature = "std")]
+    #[inline]
+    fn readahead(&self, offset: u64, len: u64) -> io::Result<()> {
+        (**self).readahead(offset, len)
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn borrowed_filelike(&self) -> Option<BorrowedFilelike<'_>> {
+        (**self).borrowed_filelike()
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn lock_at(&self, offset: u64, len: u64, kind: LockKind) -> io::Result<RangeLockGuard<'_>> {
+        (**self).lock_at(offset, len, kind)
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn try_lock_at(
+        &self,
+        offset: u64,
+        len: u64,
+        kind: LockKind,
+    ) -> io::Result<Option<RangeLockGuard<'_>>> {
+        (**self).try_lock_at(offset, len, kind)
+    }
+}
+
+impl<T: ReadAt + ?Sized> ReadAt for &mut T {
+    #[inline]
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        (**self).read_at(buf, offset)
+    }
+
+    #[inline]
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+        (**self).read_exact_at(buf, offset)
+    }
+
+    #[inline]
+    fn read_full_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        (**self).read_full_at(buf, offset)
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn read_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<usize> {
+        (**self).read_vectored_at(bufs, offset)
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn read_exact_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<()> {
+        (**self).read_exact_vectored_at(bufs, offset)
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn is_read_vectored_at(&self) -> bool {
+        (**self).is_read_vectored_at()
+    }
+
+    #[inline]
+    fn read_at_nowait(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        (**self).read_at_nowait(buf, offset)
+    }
+
+    #[inline]
+    fn read_to_vec_at(&self, offset: u64, len: usize) -> io::Result<Vec<u8>> {
+        (**self).read_to_vec_at(offset, len)
+    }
+
+    #[inline]
+    fn read_up_to_vec_at(&self, offset: u64, len: usize) -> io::Result<Vec<u8>> {
+        (**self).read_up_to_vec_at(offset, len)
+    }
+
+    #[inline]
+    fn find_at(&self, needle: &[u8], offset: u64) -> io::Result<Option<u64>> {
+        (**self).find_at(needle, offset)
+    }
+
+    #[inline]
+    fn rfind_at(&self, needle: &[u8], offset: u64) -> io::Result<Option<u64>> {
+        (**self).rfind_at(needle, offset)
+    }
+
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    fn read_via_stream_at(&self, offset: u64) -> io::Result<StreamReader> {
+        (**self).read_via_stream_at(offset)
+    }
+
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    fn read_range_via_stream(&self, offset: u64, len: u64) -> io::Result<StreamReader> {
+        (**self).read_range_via_stream(offset, len)
+    }
+}
+
+impl<T: WriteAt + ?Sized> WriteAt for &mut T {
+    #[inline]
+    fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        (**self).write_at(buf, offset)
+    }
+
+    #[inline]
+    fn write_all_at(&mut self, buf: &[u8], offset: u64) -> io::Result<()> {
+        (**self).write_all_at(buf, offset)
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn write_vectored_at(&mut self, bufs: &[IoSlice], offset: u64) -> io::Result<usize> {
+        (**self).write_vectored_at(bufs, offset)
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn write_all_vectored_at(&mut self, bufs: &mut [IoSlice], offset: u64) -> io::Result<()> {
+        (**self).write_all_vectored_at(bufs, offset)
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn is_write_vectored_at(&self) -> bool {
+        (**self).is_write_vectored_at()
+    }
+
+    #[inline]
+    fn write_str_at(&mut self, s: &str, offset: u64) -> io::Result<()> {
+        (**self).write_str_at(s, offset)
+    }
+
+    #[inline]
+    fn write_fmt_at(&mut self, fmt: fmt::Arguments, offset: u64) -> io::Result<u64> {
+        (**self).write_fmt_at(fmt, offset)
+    }
+
+    #[inline]
+    fn write_at_with(&mut self, buf: &[u8], offset: u64, flags: WriteFlags) -> io::Result<usize> {
+        (**self).write_at_with(buf, offset, flags)
+    }
+
+    #[inline]
+    fn copy_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<u64> {
+        (**self).copy_from(offset, input, input_offset, len)
+    }
+
+    #[inline]
+    fn copy_from_with_progress(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+        progress: &mut dyn FnMut(u64),
+    ) -> io::Result<u64> {
+        (**self).copy_from_with_progress(offset, input, input_offset, len, progress)
+    }
+
+    #[inline]
+    fn clone_range_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<CloneOutcome> {
+        (**self).clone_range_from(offset, input, input_offset, len)
+    }
+
+    #[inline]
+    fn dedupe_range_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<u64> {
+        (**self).dedupe_range_from(offset, input, input_offset, len)
+    }
+
+    #[inline]
+    fn allocate(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        (**self).allocate(offset, len)
+    }
+
+    #[inline]
+    fn reserve_at(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        (**self).reserve_at(offset, len)
+    }
+
+    #[inline]
+    fn punch_hole(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        (**self).punch_hole(offset, len)
+    }
+
+    #[inline]
+    fn zero_range(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        (**self).zero_range(offset, len)
+    }
+
+    #[inline]
+    fn fill_at(&mut self, byte: u8, offset: u64, len: u64) -> io::Result<()> {
+        (**self).fill_at(byte, offset, len)
+    }
+
+    #[inline]
+    fn sync_all(&self) -> io::Result<()> {
+        (**self).sync_all()
+    }
+
+    #[inline]
+    fn sync_data(&self) -> io::Result<()> {
+        (**self).sync_data()
+    }
+
+    #[inline]
+    fn sync_range(&self, offset: u64, len: u64, mode: SyncRangeMode) -> io::Result<()> {
+        (**self).sync_range(offset, len, mode)
+    }
+}
+
+impl<T: Resize + ?Sized> Resize for &mut T {
+    #[inline]
+    fn set_len(&mut self, size: u64) -> io::Result<()> {
+        Resize::set_len(&mut **self, size)
+    }
+
+    #[inline]
+    fn grow_to(&mut self, size: u64) -> io::Result<()> {
+        (**self).grow_to(size)
+    }
+}
+
+impl<T: Array + ?Sized> Array for Box<T> {
+    #[inline]
+    fn metadata(&self) -> io::Result<Metadata> {
+        (**self).metadata()
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        (**self).advise(offset, len, advice)
+    }
+    #[cfg(feature = "std")]
+    #[inline]
+    fn readahead(&self, offset: u64, len: u64) -> io::Result<()> {
+        (**self).readahead(offset, len)
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn borrowed_filelike(&self) -> Option<BorrowedFilelike<'_>> {
+        (**self).borrowed_filelike()
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn lock_at(&self, offset: u64, len: u64, kind: LockKind) -> io::Result<RangeLockGuard<'_>> {
+        (**self).lock_at(offset, len, kind)
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn try_lock_at(
+        &self,
+        offset: u64,
+        len: u64,
+        kind: LockKind,
+    ) -> io::Result<Option<RangeLockGuard<'_>>> {
+        (**self).try_lock_at(offset, len, kind)
+    }
+}
+
+impl<T: ReadAt + ?Sized> ReadAt for Box<T> {
+    #[inline]
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        (**self).read_at(buf, offset)
+    }
+
+    #[inline]
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+        (**self).read_exact_at(buf, offset)
+    }
+
+    #[inline]
+    fn read_full_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        (**self).read_full_at(buf, offset)
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn read_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<usize> {
+        (**self).read_vectored_at(bufs, offset)
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn read_exact_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<()> {
+        (**self).read_exact_vectored_at(bufs, offset)
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn is_read_vectored_at(&self) -> bool {
+        (**self).is_read_vectored_at()
+    }
+
+    #[inline]
+    fn read_at_nowait(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        (**self).read_at_nowait(buf, offset)
+    }
+
+    #[inline]
+    fn read_to_vec_at(&self, offset: u64, len: usize) -> io::Result<Vec<u8>> {
+        (**self).read_to_vec_at(offset, len)
+    }
+
+    #[inline]
+    fn read_up_to_vec_at(&self, offset: u64, len: usize) -> io::Result<Vec<u8>> {
+        (**self).read_up_to_vec_at(offset, len)
+    }
+
+    #[inline]
+    fn find_at(&self, needle: &[u8], offset: u64) -> io::Result<Option<u64>> {
+        (**self).find_at(needle, offset)
+    }
+
+    #[inline]
+    fn rfind_at(&self, needle: &[u8], offset: u64) -> io::Result<Option<u64>> {
+        (**self).rfind_at(needle, offset)
+    }
+
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    fn read_via_stream_at(&self, offset: u64) -> io::Result<StreamReader> {
+        (**self).read_via_stream_at(offset)
+    }
+
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    fn read_range_via_stream(&self, offset: u64, len: u64) -> io::Result<StreamReader> {
+        (**self).read_range_via_stream(offset, len)
+    }
+}
+
+impl<T: WriteAt + ?Sized> WriteAt for Box<T> {
+    #[inline]
+    fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        (**self).write_at(buf, offset)
+    }
+
+    #[inline]
+    fn write_all_at(&mut self, buf: &[u8], offset: u64) -> io::Result<()> {
+        (**self).write_all_at(buf, offset)
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn write_vectored_at(&mut self, bufs: &[IoSlice], offset: u64) -> io::Result<usize> {
+        (**self).write_vectored_at(bufs, offset)
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn write_all_vectored_at(&mut self, bufs: &mut [IoSlice], offset: u64) -> io::Result<()> {
+        (**self).write_all_vectored_at(bufs, offset)
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn is_write_vectored_at(&self) -> bool {
+        (**self).is_write_vectored_at()
+    }
+
+    #[inline]
+    fn write_str_at(&mut self, s: &str, offset: u64) -> io::Result<()> {
+        (**self).write_str_at(s, offset)
+    }
+
+    #[inline]
+    fn write_fmt_at(&mut self, fmt: fmt::Arguments, offset: u64) -> io::Result<u64> {
+        (**self).write_fmt_at(fmt, offset)
+    }
+
+    #[inline]
+    fn write_at_with(&mut self, buf: &[u8], offset: u64, flags: WriteFlags) -> io::Result<usize> {
+        (**self).write_at_with(buf, offset, flags)
+    }
+
+    #[inline]
+    fn copy_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<u64> {
+        (**self).copy_from(offset, input, input_offset, len)
+    }
+
+    #[inline]
+    fn copy_from_with_progress(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+        progress: &mut dyn FnMut(u64),
+    ) -> io::Result<u64> {
+        (**self).copy_from_with_progress(offset, input, input_offset, len, progress)
+    }
+
+    #[inline]
+    fn clone_range_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<CloneOutcome> {
+        (**self).clone_range_from(offset, input, input_offset, len)
+    }
+
+    #[inline]
+    fn dedupe_range_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<u64> {
+        (**self).dedupe_range_from(offset, input, input_offset, len)
+    }
+
+    #[inline]
+    fn allocate(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        (**self).allocate(offset, len)
+    }
+
+    #[inline]
+    fn reserve_at(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        (**self).reserve_at(offset, len)
+    }
+
+    #[inline]
+    fn punch_hole(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        (**self).punch_hole(offset, len)
+    }
+
+    #[inline]
+    fn zero_range(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        (**self).zero_range(offset, len)
+    }
+
+    #[inline]
+    fn fill_at(&mut self, byte: u8, offset: u64, len: u64) -> io::Result<()> {
+        (**self).fill_at(byte, offset, len)
+    }
+
+    #[inline]
+    fn sync_all(&self) -> io::Result<()> {
+        (**self).sync_all()
+    }
+
+    #[inline]
+    fn sync_data(&self) -> io::Result<()> {
+        (**self).sync_data()
+    }
+
+    #[inline]
+    fn sync_range(&self, offset: u64, len: u64, mode: SyncRangeMode) -> io::Result<()> {
+        (**self).sync_range(offset, len, mode)
+    }
+}
+
+impl<T: Resize + ?Sized> Resize for Box<T> {
+    #[inline]
+    fn set_len(&mut self, size: u64) -> io::Result<()> {
+        Resize::set_len(&mut **self, size)
+    }
+
+    #[inline]
+    fn grow_to(&mut self, size: u64) -> io::Result<()> {
+        (**self).grow_to(size)
+    }
+}
+
+impl<T: Array + ?Sized> Array for Arc<T> {
+    #[inline]
+    fn metadata(&self) -> io::Result<Metadata> {
+        (**self).metadata()
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        (**self).advise(offset, len, advice)
+    }
+    #[cfg(feature = "std")]
+    #[inline]
+    fn readahead(&self, offset: u64, len: u64) -> io::Result<()> {
+        (**self).readahead(offset, len)
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn borrowed_filelike(&self) -> Option<BorrowedFilelike<'_>> {
+        (**self).borrowed_filelike()
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn lock_at(&self, offset: u64, len: u64, kind: LockKind) -> io::Result<RangeLockGuard<'_>> {
+        (**self).lock_at(offset, len, kind)
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn try_lock_at(
+        &self,
+        offset: u64,
+        len: u64,
+        kind: LockKind,
+    ) -> io::Result<Option<RangeLockGuard<'_>>> {
+        (**self).try_lock_at(offset, len, kind)
+    }
+}
+
+impl<T: ReadAt + ?Sized> ReadAt for Arc<T> {
+    #[inline]
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        (**self).read_at(buf, offset)
+    }
+
+    #[inline]
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+        (**self).read_exact_at(buf, offset)
+    }
+
+    #[inline]
+    fn read_full_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        (**self).read_full_at(buf, offset)
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn read_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<usize> {
+        (**self).read_vectored_at(bufs, offset)
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn read_exact_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<()> {
+        (**self).read_exact_vectored_at(bufs, offset)
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn is_read_vectored_at(&self) -> bool {
+        (**self).is_read_vectored_at()
+    }
+
+    #[inline]
+    fn read_at_nowait(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        (**self).read_at_nowait(buf, offset)
+    }
+
+    #[inline]
+    fn read_to_vec_at(&self, offset: u64, len: usize) -> io::Result<Vec<u8>> {
+        (**self).read_to_vec_at(offset, len)
+    }
+
+    #[inline]
+    fn read_up_to_vec_at(&self, offset: u64, len: usize) -> io::Result<Vec<u8>> {
+        (**self).read_up_to_vec_at(offset, len)
+    }
+
+    #[inline]
+    fn find_at(&self, needle: &[u8], offset: u64) -> io::Result<Option<u64>> {
+        (**self).find_at(needle, offset)
+    }
+
+    #[inline]
+    fn rfind_at(&self, needle: &[u8], offset: u64) -> io::Result<Option<u64>> {
+        (**self).rfind_at(needle, offset)
+    }
+
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    fn read_via_stream_at(&self, offset: u64) -> io::Result<StreamReader> {
+        (**self).read_via_stream_at(offset)
+    }
+
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    fn read_range_via_stream(&self, offset: u64, len: u64) -> io::Result<StreamReader> {
+        (**self).read_range_via_stream(offset, len)
+    }
+}
+
+impl<T: Array + ?Sized> Array for Rc<T> {
+    #[inline]
+    fn metadata(&self) -> io::Result<Metadata> {
+        (**self).metadata()
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        (**self).advise(offset, len, advice)
+    }
+    #[cfg(feature = "std")]
+    #[inline]
+    fn readahead(&self, offset: u64, len: u64) -> io::Result<()> {
+        (**self).readahead(offset, len)
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn borrowed_filelike(&self) -> Option<BorrowedFilelike<'_>> {
+        (**self).borrowed_filelike()
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn lock_at(&self, offset: u64, len: u64, kind: LockKind) -> io::Result<RangeLockGuard<'_>> {
+        (**self).lock_at(offset, len, kind)
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn try_lock_at(
+        &self,
+        offset: u64,
+        len: u64,
+        kind: LockKind,
+    ) -> io::Result<Option<RangeLockGuard<'_>>> {
+        (**self).try_lock_at(offset, len, kind)
+    }
+}
+
+impl<T: ReadAt + ?Sized> ReadAt for Rc<T> {
+    #[inline]
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        (**self).read_at(buf, offset)
+    }
+
+    #[inline]
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+        (**self).read_exact_at(buf, offset)
+    }
+
+    #[inline]
+    fn read_full_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        (**self).read_full_at(buf, offset)
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn read_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<usize> {
+        (**self).read_vectored_at(bufs, offset)
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn read_exact_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<()> {
+        (**self).read_exact_vectored_at(bufs, offset)
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn is_read_vectored_at(&self) -> bool {
+        (**self).is_read_vectored_at()
+    }
+
+    #[inline]
+    fn read_at_nowait(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        (**self).read_at_nowait(buf, offset)
+    }
+
+    #[inline]
+    fn read_to_vec_at(&self, offset: u64, len: usize) -> io::Result<Vec<u8>> {
+        (**self).read_to_vec_at(offset, len)
+    }
+
+    #[inline]
+    fn read_up_to_vec_at(&self, offset: u64, len: usize) -> io::Result<Vec<u8>> {
+        (**self).read_up_to_vec_at(offset, len)
+    }
+
+    #[inline]
+    fn find_at(&self, needle: &[u8], offset: u64) -> io::Result<Option<u64>> {
+        (**self).find_at(needle, offset)
+    }
+
+    #[inline]
+    fn rfind_at(&self, needle: &[u8], offset: u64) -> io::Result<Option<u64>> {
+        (**self).rfind_at(needle, offset)
+    }
+
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    fn read_via_stream_at(&self, offset: u64) -> io::Result<StreamReader> {
+        (**self).read_via_stream_at(offset)
+    }
+
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    fn read_range_via_stream(&self, offset: u64, len: u64) -> io::Result<StreamReader> {
+        (**self).read_range_via_stream(offset, len)
+    }
+}
+
+/// A random-access input source.
+///
+/// By default, an `ArrayReader` reads from a file, with [`FileBackend`], and
+/// implements the traits for accessing its file descriptor or handle. An
+/// `ArrayReader<MemBackend>` reads from a buffer in memory, with
+/// [`MemBackend`]; [`ArrayReader::in_memory`] creates one.
+///
+/// [`MemBackend`]: crate::MemBackend
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct ArrayReader<B: Backend = FileBackend> {
+    pub(crate) backend: B,
+}
+
+/// A random-access output sink.
+///
+/// As with [`ArrayReader`], this writes to a file by default, or to a buffer
+/// in memory with [`MemBackend`].
+///
+/// [`MemBackend`]: crate::MemBackend
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct ArrayWriter<B: Backend = FileBackend> {
+    pub(crate) backend: B,
+}
+
+/// A random-access input source and output sink.
+///
+/// As with [`ArrayReader`], this accesses a file by default, or a buffer in
+/// memory with [`MemBackend`].
+///
+/// [`MemBackend`]: crate::MemBackend
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct ArrayEditor<B: Backend = FileBackend> {
+    pub(crate) backend: B,
+}
+
+/// A read-only view of an [`ArrayEditor`], returned by
+/// [`ArrayEditor::as_reader`].
+///
+/// Like [`ArrayEditor::into_reader`], this only narrows access at the API
+/// level; the underlying file descriptor or handle is still open for
+/// writing.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy)]
+pub struct ReaderRef<'a> {
+    file: &'a fs::File,
+}
+
+#[cfg(feature = "std")]
+impl ArrayReader {
+    /// Convert a `File` into a `ArrayReader`.
+    #[inline]
+    #[must_use]
+    pub fn file<Filelike: IntoFilelike + Read + Seek>(filelike: Filelike) -> Self {
+        Self {
+            backend: FileBackend(fs::File::from_into_filelike(filelike)),
+        }
+    }
+
+    /// Copy a slice of bytes into a memory buffer to allow it to be accessed
+    /// in the manner of an array.
+    ///
+    /// The buffer is an anonymous file, accessed through a file descriptor
+    /// or handle. To access the bytes without a file, use
+    /// [`ArrayReader::in_memory`] instead.
+    ///
+    /// The resulting array is genuinely read-only: on Linux the backing
+    /// memfd is sealed against writes and size changes, and elsewhere the
+    /// backing temporary file is reopened read-only.
+    #[inline]
+    pub fn bytes(bytes: &[u8]) -> io::Result<Self> {
+        Ok(Self {
+            backend: FileBackend(anonymous_read_only(bytes)?),
+        })
+    }
+
+    /// Open the file this reader reads from again, for reading and writing,
+    /// and return an [`ArrayEditor`] for it.
+    ///
+    /// This doesn't reuse the reader's file descriptor or handle, which may
+    /// be open only for reading; it opens the file anew, which checks the
+    /// file's permissions. It fails if the file's permissions don't allow
+    /// writing, if it can't be found again, such as a temporary file which
+    /// has been deleted on some platforms, if it's sealed against writes,
+    /// as the files created by [`ArrayReader::bytes`] are on Linux,
+    /// or with [`io::ErrorKind::Unsupported`] on platforms where files can't
+    /// be reopened.
+    ///
+    /// `self` is still usable afterwards, and sees writes made through the
+    /// editor.
+    pub fn try_reopen_as_editor(&self) -> io::Result<ArrayEditor> {
+        #[cfg(not(windows))]
+        let file = crate::rustix::reopen_read_write(&self.backend.0)?;
+        #[cfg(windows)]
+        let file = crate::windows::reopen_read_write(&self.backend.0)?;
+
+        if !filelike::is_same_file(&self.backend.0, &file)? {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                "the file was replaced before it could be reopened",
+            ));
+        }
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        if rustix::fs::fcntl_get_seals(&file)
+            .is_ok_and(|seals| seals.contains(rustix::fs::SealFlags::WRITE))
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "the file is sealed against writes",
+            ));
+        }
+
+        ArrayEditor::_checked(file)
+    }
+
+    /// Consume `self` and return the underlying `File`.
+    #[inline]
+    #[must_use]
+    pub fn into_file(self) -> fs::File {
+        self.backend.0
+    }
+}
+
+#[cfg(feature = "std")]
+impl ArrayWriter {
+    /// Convert a `File` into a `ArrayWriter`.
+    ///
+    /// The file must not be opened in [append mode].
+    ///
+    /// [append mode]: https://doc.rust-lang.org/stable/std/fs/struct.OpenOptions.html#method.append
+    #[inline]
+    #[must_use]
+    pub fn file<Filelike: IntoFilelike + Write + Seek>(filelike: Filelike) -> Self {
+        Self::_file(fs::File::from_into_filelike(filelike))
+    }
+
+    #[inline]
+    fn _file(file: fs::File) -> Self {
+        match Self::_try_file(file) {
+            Ok(writer) => writer,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    fn _try_file(file: fs::File) -> io::Result<Self> {
+        // On Linux, `pwrite` on a file opened with `O_APPEND` writes to the
+        // end of the file, ignoring the offset.
+        #[cfg(not(windows))]
+        {
+            if rustix::fs::fcntl_getfl(&file)?.contains(rustix::fs::OFlags::APPEND) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "ArrayWriter doesn't support files opened with O_APPEND",
+                ));
+            }
+        }
+        // On Windows, a file opened in append mode has `FILE_APPEND_DATA`
+        // access without `FILE_WRITE_DATA`.
+        #[cfg(windows)]
+        {
+            let access = winx::file::query_access_information(file.as_handle())?;
+            if access.contains(winx::file::AccessMode::FILE_APPEND_DATA)
+                && !access.contains(winx::file::AccessMode::FILE_WRITE_DATA)
+            {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "ArrayWriter doesn't support files opened with FILE_APPEND_DATA",
+                ));
+            }
+        }
+
+        Ok(Self {
+            backend: FileBackend(file),
+        })
+    }
+
+    /// Consume `self` and return the underlying `File`.
+    #[inline]
+    #[must_use]
+    pub fn into_file(self) -> fs::File {
+        self.backend.0
+    }
+}
+
+#[cfg(feature = "std")]
+impl ArrayEditor {
+    /// Convert a `File` into a `ArrayEditor`.
+    #[inline]
+    #[must_use]
+    pub fn file<Filelike: IntoFilelike + Read + Write + Seek>(filelike: Filelike) -> Self {
+        Self {
+            backend: FileBackend(fs::File::from_into_filelike(filelike)),
+        }
+    }
+
+    /// Open the file at `path` for reading and writing, creating it if it
+    /// doesn't exist.
+    ///
+    /// Unlike [`std::fs::File::create`], an existing file is not truncated.
+    #[inline]
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::_checked(
+            fs::OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(false)
+                .open(path)?,
+        )
+    }
+
+    /// Create a new file at `path` and open it for reading and writing,
+    /// failing with [`io::ErrorKind::AlreadyExists`] if it already exists.
+    #[inline]
+    pub fn create_new<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::_checked(
+            fs::OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create_new(true)
+                .open(path)?,
+        )
+    }
+
+    /// Open the existing file at `path` for reading and writing with direct
+    /// I/O, bypassing the OS page cache.
+    ///
+    /// This uses `O_DIRECT` on Linux, Android, and FreeBSD, `F_NOCACHE` on
+    /// Apple platforms, and `FILE_FLAG_NO_BUFFERING` on Windows, and fails
+    /// with [`io::ErrorKind::Unsupported`] elsewhere. Opening may also fail
+    /// on filesystems which don't support direct I/O.
+    ///
+    /// Reads and writes on the resulting array must use buffers, lengths,
+    /// and offsets aligned as the device requires; see [`AlignedBuf`],
+    /// [`read_aligned_at`], and [`write_aligned_at`].
+    ///
+    /// [`AlignedBuf`]: crate::AlignedBuf
+    /// [`read_aligned_at`]: crate::read_aligned_at
+    /// [`write_aligned_at`]: crate::write_aligned_at
+    pub fn open_direct<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut options = fs::OpenOptions::new();
+        options.read(true).write(true);
+
+        #[cfg(any(target_os = "android", target_os = "freebsd", target_os = "linux"))]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.custom_flags(rustix::fs::OFlags::DIRECT.bits() as i32);
+        }
+        #[cfg(windows)]
+        {
+            use std::os::windows::fs::OpenOptionsExt;
+            options.custom_flags(windows_sys::Win32::Storage::FileSystem::FILE_FLAG_NO_BUFFERING);
+        }
+        #[cfg(not(any(
+            target_os = "android",
+            target_os = "freebsd",
+            target_os = "ios",
+            target_os = "linux",
+            target_os = "macos",
+            windows,
+        )))]
+        {
+            let _ = path;
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "direct I/O is not supported on this platform",
+            ));
+        }
+
+        #[allow(unreachable_code)]
+        let file = options.open(path)?;
+
+        #[cfg(any(target_os = "ios", target_os = "macos"))]
+        if unsafe { libc::fcntl(file.as_raw_fd(), libc::F_NOCACHE, 1) } == -1 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Self::_checked(file)
+    }
+
+    /// Create a temporary anonymous resource which can be accessed in the
+    /// manner of an array.
+    #[inline]
+    pub fn anonymous() -> io::Result<Self> {
+        Ok(Self {
+            backend: FileBackend(anonymous()?),
+        })
+    }
+
+    /// Create a temporary anonymous resource in the directory `dir`, which
+    /// can be accessed in the manner of an array.
+    ///
+    /// Unlike [`ArrayEditor::anonymous`], which may keep the data in memory
+    /// or in the system temporary directory, this keeps the data on the
+    /// filesystem containing `dir`.
+    #[inline]
+    pub fn anonymous_in<P: AsRef<Path>>(dir: P) -> io::Result<Self> {
+        Ok(Self {
+            backend: FileBackend(anonymous_in(dir)?),
+        })
+    }
+
+    /// Create a temporary anonymous resource in the directory `dir`, which
+    /// can be accessed in the manner of an array.
+    ///
+    /// This is similar to [`ArrayEditor::anonymous_in`], but takes a
+    /// [`cap_std::fs::Dir`] instead of a path.
+    #[inline]
+    pub fn anonymous_in_dir(dir: &cap_std::fs::Dir) -> io::Result<Self> {
+        Ok(Self {
+            backend: FileBackend(anonymous_in_dir(dir)?),
+        })
+    }
+
+    /// Create a temporary anonymous resource which can be accessed in the
+    /// manner of an array, with an initial length of `len` bytes.
+    ///
+    /// The contents are initially all zeros. The space is not reserved up
+    /// front; on filesystems which support sparse files, it is allocated as
+    /// it's written to.
+    #[inline]
+    pub fn anonymous_with_len(len: u64) -> io::Result<Self> {
+        let file = anonymous()?;
+        file.set_len(len)?;
+        Ok(Self {
+            backend: FileBackend(file),
+        })
+    }
+
+    /// Create a temporary anonymous resource backed by huge pages, which can
+    /// be accessed in the manner of an array.
+    ///
+    /// This uses `memfd_create` with `MFD_HUGETLB`, and fails with
+    /// [`io::ErrorKind::Unsupported`] if the kernel doesn't support it.
+    ///
+    /// The length of the array must always be a multiple of `page_size`;
+    /// `set_len` with any other length fails. Hugetlbfs doesn't support
+    /// `write`, so the contents can be read with the [`ReadAt`] methods, but
+    /// must be populated by memory-mapping the file descriptor; the
+    /// [`WriteAt`] write methods fail.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn anonymous_hugetlb(page_size: HugePageSize) -> io::Result<Self> {
+        let size = match page_size {
+            HugePageSize::Size2MiB => rustix::fs::MemfdFlags::HUGE_2MB,
+            HugePageSize::Size1GiB => rustix::fs::MemfdFlags::HUGE_1GB,
+        };
+        let flags = rustix::fs::MemfdFlags::CLOEXEC
+            | rustix::fs::MemfdFlags::ALLOW_SEALING
+            | rustix::fs::MemfdFlags::HUGETLB
+            | size;
+        let name = rustix::cstr!("io_arrays anonymous hugetlb file");
+        match rustix::fs::memfd_create(name, flags) {
+            Ok(owned) => Ok(Self {
+                backend: FileBackend(fs::File::from_into_filelike(owned)),
+            }),
+            Err(rustix::io::Errno::INVAL | rustix::io::Errno::NOENT) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "huge-page backed memfd is not supported",
+            )),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Divide the array into two disjoint views, one of the bytes before
+    /// `mid` and one of the bytes at and after it.
+    ///
+    /// Positional writes don't share a file position, and `&ArrayEditor`
+    /// implements [`WriteAt`], so the two views can be written to at the
+    /// same time, for example from different threads. Writes through the
+    /// first view can't extend past `mid`; the second view can extend the
+    /// array.
+    #[inline]
+    pub fn split_at(&self, mid: u64) -> (ArraySlice<&Self>, ArraySlice<&Self>) {
+        ArraySlice::new(self, 0, u64::MAX).split_at(mid)
+    }
+
+    /// Consume `self` and return an [`ArrayReader`] for the same file.
+    ///
+    /// This only narrows access at the API level; the underlying file
+    /// descriptor or handle is still open for writing, and can be recovered
+    /// with [`ArrayReader::into_file`].
+    #[inline]
+    #[must_use]
+    pub fn into_reader(self) -> ArrayReader {
+        ArrayReader {
+            backend: FileBackend(self.backend.0),
+        }
+    }
+
+    /// Consume `self` and return an [`ArrayWriter`] for the same file.
+    ///
+    /// This fails if the file isn't open for writing, which is possible for
+    /// an `ArrayEditor` created with [`ArrayEditor::file`] or `From`, or if
+    /// it's in append mode, as [`ArrayWriter`] requires. As with
+    /// [`ArrayEditor::into_reader`], the underlying file descriptor or handle
+    /// is still open for reading.
+    pub fn into_writer(self) -> io::Result<ArrayWriter> {
+        #[cfg(not(windows))]
+        {
+            let flags = rustix::fs::fcntl_getfl(&self.backend.0)?;
+            if flags & rustix::fs::OFlags::RWMODE == rustix::fs::OFlags::RDONLY {
+                return Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    "ArrayWriter requires a file opened for writing",
+                ));
+            }
+        }
+        #[cfg(windows)]
+        {
+            let access = winx::file::query_access_information(self.backend.0.as_handle())?;
+            if !access.contains(winx::file::AccessMode::FILE_WRITE_DATA) {
+                return Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    "ArrayWriter requires a file opened for writing",
+                ));
+            }
+        }
+
+        ArrayWriter::_try_file(self.backend.0)
+    }
+
+    /// Return a read-only view of `self`.
+    ///
+    /// This is a borrowing counterpart of [`ArrayEditor::into_reader`], for
+    /// handing out read access without giving up the editor.
+    #[inline]
+    #[must_use]
+    pub fn as_reader(&self) -> ReaderRef<'_> {
+        ReaderRef { file: &self.backend.0 }
+    }
+
+    /// Consume `self` and return the underlying `File`.
+    #[inline]
+    #[must_use]
+    pub fn into_file(self) -> fs::File {
+        self.backend.0
+    }
+
+    fn _checked(file: fs::File) -> io::Result<Self> {
+        // Check that the file is open for reading and writing, and not in
+        // append mode, where positional writes ignore the offset.
+        #[cfg(not(windows))]
+        {
+            let flags = rustix::fs::fcntl_getfl(&file)?;
+            if flags.contains(rustix::fs::OFlags::APPEND) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "ArrayEditor doesn't support files opened with O_APPEND",
+                ));
+            }
+            if flags & rustix::fs::OFlags::RWMODE != rustix::fs::OFlags::RDWR {
+                return Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    "ArrayEditor requires a file opened for reading and writing",
+                ));
+            }
+        }
+        #[cfg(windows)]
+        {
+            let required =
+                winx::file::AccessMode::FILE_READ_DATA | winx::file::AccessMode::FILE_WRITE_DATA;
+            if !winx::file::query_access_information(file.as_handle())?.contains(required) {
+                return Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    "ArrayEditor requires a file opened for reading and writing",
+                ));
+            }
+        }
+
+        Ok(Self {
+            backend: FileBackend(file),
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl Array for ArrayReader {
+    #[inline]
+    fn metadata(&self) -> io::Result<Metadata> {
+        filelike::metadata(self)
+    }
+
+    #[inline]
+    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        filelike::advise(self, offset, len, advice)
+    }
+    #[inline]
+    fn borrowed_filelike(&self) -> Option<BorrowedFilelike<'_>> {
+        Some(self.as_filelike())
+    }
+}
+
+#[cfg(feature = "std")]
+impl Array for ArrayWriter {
+    #[inline]
+    fn metadata(&self) -> io::Result<Metadata> {
+        filelike::metadata(self)
+    }
+
+    #[inline]
+    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        filelike::advise(self, offset, len, advice)
+    }
+    #[inline]
+    fn borrowed_filelike(&self) -> Option<BorrowedFilelike<'_>> {
+        Some(self.as_filelike())
+    }
+}
+
+#[cfg(feature = "std")]
+impl Array for ArrayEditor {
+    #[inline]
+    fn metadata(&self) -> io::Result<Metadata> {
+        filelike::metadata(self)
+    }
+
+    #[inline]
+    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        filelike::advise(self, offset, len, advice)
+    }
+    #[inline]
+    fn borrowed_filelike(&self) -> Option<BorrowedFilelike<'_>> {
+        Some(self.as_filelike())
+    }
+}
+
+#[cfg(feature = "std")]
+impl Array for ReaderRef<'_> {
+    #[inline]
+    fn metadata(&self) -> io::Result<Metadata> {
+        filelike::metadata(self)
+    }
+
+    #[inline]
+    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        filelike::advise(self, offset, len, advice)
+    }
+    #[inline]
+    fn borrowed_filelike(&self) -> Option<BorrowedFilelike<'_>> {
+        Some(self.as_filelike())
+    }
+}
+
+#[cfg(feature = "std")]
+impl ReadAt for ArrayReader {
+    #[inline]
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        filelike::read_at(self, buf, offset)
+    }
+
+    #[inline]
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+        filelike::read_exact_at(self, buf, offset)
+    }
+
+    #[inline]
+    fn read_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<usize> {
+        filelike::read_vectored_at(self, bufs, offset)
+    }
+
+    #[inline]
+    fn read_exact_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<()> {
+        filelike::read_exact_vectored_at(self, bufs, offset)
+    }
+
+    #[inline]
+    fn is_read_vectored_at(&self) -> bool {
+        filelike::is_read_vectored_at(self)
+    }
+
+    #[inline]
+    fn read_at_nowait(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        filelike::read_at_nowait(self, buf, offset)
+    }
+
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    fn read_via_stream_at(&self, offset: u64) -> io::Result<StreamReader> {
+        filelike::read_via_stream_at(self, offset)
+    }
+
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    fn read_range_via_stream(&self, offset: u64, len: u64) -> io::Result<StreamReader> {
+        filelike::read_range_via_stream(self, offset, len)
+    }
+}
+
+#[cfg(feature = "std")]
+impl ReadAt for ArrayEditor {
+    #[inline]
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        filelike::read_at(self, buf, offset)
+    }
+
+    #[inline]
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+        filelike::read_exact_at(self, buf, offset)
+    }
+
+    #[inline]
+    fn read_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<usize> {
+        filelike::read_vectored_at(self, bufs, offset)
+    }
+
+    #[inline]
+    fn read_exact_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<()> {
+        filelike::read_exact_vectored_at(self, bufs, offset)
+    }
+
+    #[inline]
+    fn is_read_vectored_at(&self) -> bool {
+        filelike::is_read_vectored_at(self)
+    }
+
+    #[inline]
+    fn read_at_nowait(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        filelike::read_at_nowait(self, buf, offset)
+    }
+
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    fn read_via_stream_at(&self, offset: u64) -> io::Result<StreamReader> {
+        filelike::read_via_stream_at(self, offset)
+    }
+
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    fn read_range_via_stream(&self, offset: u64, len: u64) -> io::Result<StreamReader> {
+        filelike::read_range_via_stream(self, offset, len)
+    }
+}
+
+#[cfg(feature = "std")]
+impl ReadAt for ReaderRef<'_> {
+    #[inline]
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        filelike::read_at(self, buf, offset)
+    }
+
+    #[inline]
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+        filelike::read_exact_at(self, buf, offset)
+    }
+
+    #[inline]
+    fn read_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<usize> {
+        filelike::read_vectored_at(self, bufs, offset)
+    }
+
+    #[inline]
+    fn read_exact_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<()> {
+        filelike::read_exact_vectored_at(self, bufs, offset)
+    }
+
+    #[inline]
+    fn is_read_vectored_at(&self) -> bool {
+        filelike::is_read_vectored_at(self)
+    }
+
+    #[inline]
+    fn read_at_nowait(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        filelike::read_at_nowait(self, buf, offset)
+    }
+
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    fn read_via_stream_at(&self, offset: u64) -> io::Result<StreamReader> {
+        filelike::read_via_stream_at(self, offset)
+    }
+
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    fn read_range_via_stream(&self, offset: u64, len: u64) -> io::Result<StreamReader> {
+        filelike::read_range_via_stream(self, offset, len)
+    }
+}
+
+#[cfg(feature = "std")]
+impl WriteAt for ArrayWriter {
+    #[inline]
+    fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        filelike::write_at(&*self, buf, offset)
+    }
+
+    #[inline]
+    fn write_all_at(&mut self, buf: &[u8], offset: u64) -> io::Result<()> {
+        filelike::write_all_at(&*self, buf, offset)
+    }
+
+    #[inline]
+    fn write_vectored_at(&mut self, bufs: &[IoSlice], offset: u64) -> io::Result<usize> {
+        filelike::write_vectored_at(&*self, bufs, offset)
+    }
+
+    #[inline]
+    fn write_all_vectored_at(&mut self, bufs: &mut [IoSlice], offset: u64) -> io::Result<()> {
+        filelike::write_all_vectored_at(&*self, bufs, offset)
+    }
+
+    #[inline]
+    fn is_write_vectored_at(&self) -> bool {
+        filelike::is_write_vectored_at(self)
+    }
+
+    #[inline]
+    fn write_at_with(&mut self, buf: &[u8], offset: u64, flags: WriteFlags) -> io::Result<usize> {
+        filelike::write_at_with(&*self, buf, offset, flags)
+    }
+
+    #[inline]
+    fn copy_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<u64> {
+        filelike::copy_from(&*self, offset, input, input_offset, len)
+    }
+
+    #[inline]
+    fn clone_range_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<CloneOutcome> {
+        filelike::clone_range_from(&*self, offset, input, input_offset, len)
+    }
+
+    #[inline]
+    fn dedupe_range_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<u64> {
+        filelike::dedupe_range_from(&*self, offset, input, input_offset, len)
+    }
+
+    #[inline]
+    fn allocate(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::allocate(&*self, offset, len)
+    }
+
+    #[inline]
+    fn reserve_at(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::reserve_at(&*self, offset, len)
+    }
+
+    #[inline]
+    fn punch_hole(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::punch_hole(&*self, offset, len)
+    }
+
+    #[inline]
+    fn zero_range(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::zero_range(&*self, offset, len)
+    }
+
+    #[inline]
+    fn sync_all(&self) -> io::Result<()> {
+        filelike::sync_all(self)
+    }
+
+    #[inline]
+    fn sync_data(&self) -> io::Result<()> {
+        filelike::sync_data(self)
+    }
+
+    #[inline]
+    fn sync_range(&self, offset: u64, len: u64, mode: SyncRangeMode) -> io::Result<()> {
+        filelike::sync_range(self, offset, len, mode)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Resize for ArrayWriter {
+    #[inline]
+    fn set_len(&mut self, size: u64) -> io::Result<()> {
+        filelike::set_len(&*self, size)
+    }
+}
+
+/// As with `&fs::File`, writes through shared references may run
+/// concurrently, and aren't atomic with respect to each other where they
+/// overlap.
+#[cfg(feature = "std")]
+impl WriteAt for &ArrayWriter {
+    #[inline]
+    fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        filelike::write_at(&*self, buf, offset)
+    }
+
+    #[inline]
+    fn write_all_at(&mut self, buf: &[u8], offset: u64) -> io::Result<()> {
+        filelike::write_all_at(&*self, buf, offset)
+    }
+
+    #[inline]
+    fn write_vectored_at(&mut self, bufs: &[IoSlice], offset: u64) -> io::Result<usize> {
+        filelike::write_vectored_at(&*self, bufs, offset)
+    }
+
+    #[inline]
+    fn write_all_vectored_at(&mut self, bufs: &mut [IoSlice], offset: u64) -> io::Result<()> {
+        filelike::write_all_vectored_at(&*self, bufs, offset)
+    }
+
+    #[inline]
+    fn is_write_vectored_at(&self) -> bool {
+        filelike::is_write_vectored_at(self)
+    }
+
+    #[inline]
+    fn write_at_with(&mut self, buf: &[u8], offset: u64, flags: WriteFlags) -> io::Result<usize> {
+        filelike::write_at_with(&*self, buf, offset, flags)
+    }
+
+    #[inline]
+    fn copy_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<u64> {
+        filelike::copy_from(&*self, offset, input, input_offset, len)
+    }
+
+    #[inline]
+    fn clone_range_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<CloneOutcome> {
+        filelike::clone_range_from(&*self, offset, input, input_offset, len)
+    }
+
+    #[inline]
+    fn dedupe_range_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<u64> {
+        filelike::dedupe_range_from(&*self, offset, input, input_offset, len)
+    }
+
+    #[inline]
+    fn allocate(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::allocate(&*self, offset, len)
+    }
+
+    #[inline]
+    fn reserve_at(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::reserve_at(&*self, offset, len)
+    }
+
+    #[inline]
+    fn punch_hole(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::punch_hole(&*self, offset, len)
+    }
+
+    #[inline]
+    fn zero_range(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::zero_range(&*self, offset, len)
+    }
+
+    #[inline]
+    fn sync_all(&self) -> io::Result<()> {
+        filelike::sync_all(self)
+    }
+
+    #[inline]
+    fn sync_data(&self) -> io::Result<()> {
+        filelike::sync_data(self)
+    }
+
+    #[inline]
+    fn sync_range(&self, offset: u64, len: u64, mode: SyncRangeMode) -> io::Result<()> {
+        filelike::sync_range(self, offset, len, mode)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Resize for &ArrayWriter {
+    #[inline]
+    fn set_len(&mut self, size: u64) -> io::Result<()> {
+        filelike::set_len(&*self, size)
+    }
+}
+
+#[cfg(feature = "std")]
+impl WriteAt for ArrayEditor {
+    #[inline]
+    fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        filelike::write_at(&*self, buf, offset)
+    }
+
+    #[inline]
+    fn write_all_at(&mut self, buf: &[u8], offset: u64) -> io::Result<()> {
+        filelike::write_all_at(&*self, buf, offset)
+    }
+
+    #[inline]
+    fn write_vectored_at(&mut self, bufs: &[IoSlice], offset: u64) -> io::Result<usize> {
+        filelike::write_vectored_at(&*self, bufs, offset)
+    }
+
+    #[inline]
+    fn write_all_vectored_at(&mut self, bufs: &mut [IoSlice], offset: u64) -> io::Result<()> {
+        filelike::write_all_vectored_at(&*self, bufs, offset)
+    }
+
+    #[inline]
+    fn is_write_vectored_at(&self) -> bool {
+        filelike::is_write_vectored_at(self)
+    }
+
+    #[inline]
+    fn write_at_with(&mut self, buf: &[u8], offset: u64, flags: WriteFlags) -> io::Result<usize> {
+        filelike::write_at_with(&*self, buf, offset, flags)
+    }
+
+    #[inline]
+    fn copy_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<u64> {
+        filelike::copy_from(&*self, offset, input, input_offset, len)
+    }
+
+    #[inline]
+    fn clone_range_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<CloneOutcome> {
+        filelike::clone_range_from(&*self, offset, input, input_offset, len)
+    }
+
+    #[inline]
+    fn dedupe_range_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<u64> {
+        filelike::dedupe_range_from(&*self, offset, input, input_offset, len)
+    }
+
+    #[inline]
+    fn allocate(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::allocate(&*self, offset, len)
+    }
+
+    #[inline]
+    fn reserve_at(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::reserve_at(&*self, offset, len)
+    }
+
+    #[inline]
+    fn punch_hole(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::punch_hole(&*self, offset, len)
+    }
+
+    #[inline]
+    fn zero_range(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::zero_range(&*self, offset, len)
+    }
+
+    #[inline]
+    fn sync_all(&self) -> io::Result<()> {
+        filelike::sync_all(self)
+    }
+
+    #[inline]
+    fn sync_data(&self) -> io::Result<()> {
+        filelike::sync_data(self)
+    }
+
+    #[inline]
+    fn sync_range(&self, offset: u64, len: u64, mode: SyncRangeMode) -> io::Result<()> {
+        filelike::sync_range(self, offset, len, mode)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Resize for ArrayEditor {
+    #[inline]
+    fn set_len(&mut self, size: u64) -> io::Result<()> {
+        filelike::set_len(&*self, size)
+    }
+}
+
+/// As with `&fs::File`, writes through shared references may run
+/// concurrently, and aren't atomic with respect to each other where they
+/// overlap.
+#[cfg(feature = "std")]
+impl WriteAt for &ArrayEditor {
+    #[inline]
+    fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        filelike::write_at(&*self, buf, offset)
+    }
+
+    #[inline]
+    fn write_all_at(&mut self, buf: &[u8], offset: u64) -> io::Result<()> {
+        filelike::write_all_at(&*self, buf, offset)
+    }
+
+    #[inline]
+    fn write_vectored_at(&mut self, bufs: &[IoSlice], offset: u64) -> io::Result<usize> {
+        filelike::write_vectored_at(&*self, bufs, offset)
+    }
+
+    #[inline]
+    fn write_all_vectored_at(&mut self, bufs: &mut [IoSlice], offset: u64) -> io::Result<()> {
+        filelike::write_all_vectored_at(&*self, bufs, offset)
+    }
+
+    #[inline]
+    fn is_write_vectored_at(&self) -> bool {
+        filelike::is_write_vectored_at(self)
+    }
+
+    #[inline]
+    fn write_at_with(&mut self, buf: &[u8], offset: u64, flags: WriteFlags) -> io::Result<usize> {
+        filelike::write_at_with(&*self, buf, offset, flags)
+    }
+
+    #[inline]
+    fn copy_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<u64> {
+        filelike::copy_from(&*self, offset, input, input_offset, len)
+    }
+
+    #[inline]
+    fn clone_range_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<CloneOutcome> {
+        filelike::clone_range_from(&*self, offset, input, input_offset, len)
+    }
+
+    #[inline]
+    fn dedupe_range_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<u64> {
+        filelike::dedupe_range_from(&*self, offset, input, input_offset, len)
+    }
+
+    #[inline]
+    fn allocate(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::allocate(&*self, offset, len)
+    }
+
+    #[inline]
+    fn reserve_at(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::reserve_at(&*self, offset, len)
+    }
+
+    #[inline]
+    fn punch_hole(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::punch_hole(&*self, offset, len)
+    }
+
+    #[inline]
+    fn zero_range(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::zero_range(&*self, offset, len)
+    }
+
+    #[inline]
+    fn sync_all(&self) -> io::Result<()> {
+        filelike::sync_all(self)
+    }
+
+    #[inline]
+    fn sync_data(&self) -> io::Result<()> {
+        filelike::sync_data(self)
+    }
+
+    #[inline]
+    fn sync_range(&self, offset: u64, len: u64, mode: SyncRangeMode) -> io::Result<()> {
+        filelike::sync_range(self, offset, len, mode)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Resize for &ArrayEditor {
+    #[inline]
+    fn set_len(&mut self, size: u64) -> io::Result<()> {
+        filelike::set_len(&*self, size)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Array for fs::File {
+    #[inline]
+    fn metadata(&self) -> io::Result<Metadata> {
+        filelike::metadata(self)
+    }
+
+    #[inline]
+    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        filelike::advise(self, offset, len, advice)
+    }
+    #[inline]
+    fn borrowed_filelike(&self) -> Option<BorrowedFilelike<'_>> {
+        Some(self.as_filelike())
+    }
+}
+
+#[cfg(feature = "std")]
+impl ReadAt for fs::File {
+    #[inline]
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        filelike::read_at(self, buf, offset)
+    }
+
+    #[inline]
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+        filelike::read_exact_at(self, buf, offset)
+    }
+
+    #[inline]
+    fn read_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<usize> {
+        filelike::read_vectored_at(self, bufs, offset)
+    }
+
+    #[inline]
+    fn read_exact_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<()> {
+        filelike::read_exact_vectored_at(self, bufs, offset)
+    }
+
+    #[inline]
+    fn is_read_vectored_at(&self) -> bool {
+        filelike::is_read_vectored_at(self)
+    }
+
+    #[inline]
+    fn read_at_nowait(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        filelike::read_at_nowait(self, buf, offset)
+    }
+
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    fn read_via_stream_at(&self, offset: u64) -> io::Result<StreamReader> {
+        filelike::read_via_stream_at(self, offset)
+    }
+
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    fn read_range_via_stream(&self, offset: u64, len: u64) -> io::Result<StreamReader> {
+        filelike::read_range_via_stream(self, offset, len)
+    }
+}
+
+#[cfg(feature = "std")]
+impl WriteAt for fs::File {
+    #[inline]
+    fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        filelike::write_at(&*self, buf, offset)
+    }
+
+    #[inline]
+    fn write_all_at(&mut self, buf: &[u8], offset: u64) -> io::Result<()> {
+        filelike::write_all_at(&*self, buf, offset)
+    }
+
+    #[inline]
+    fn write_vectored_at(&mut self, bufs: &[IoSlice], offset: u64) -> io::Result<usize> {
+        filelike::write_vectored_at(&*self, bufs, offset)
+    }
+
+    #[inline]
+    fn write_all_vectored_at(&mut self, bufs: &mut [IoSlice], offset: u64) -> io::Result<()> {
+        filelike::write_all_vectored_at(&*self, bufs, offset)
+    }
+
+    #[inline]
+    fn is_write_vectored_at(&self) -> bool {
+        filelike::is_write_vectored_at(self)
+    }
+
+    #[inline]
+    fn write_at_with(&mut self, buf: &[u8], offset: u64, flags: WriteFlags) -> io::Result<usize> {
+        filelike::write_at_with(&*self, buf, offset, flags)
+    }
+
+    #[inline]
+    fn copy_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<u64> {
+        filelike::copy_from(&*self, offset, input, input_offset, len)
+    }
+
+    #[inline]
+    fn clone_range_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<CloneOutcome> {
+        filelike::clone_range_from(&*self, offset, input, input_offset, len)
+    }
+
+    #[inline]
+    fn dedupe_range_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<u64> {
+        filelike::dedupe_range_from(&*self, offset, input, input_offset, len)
+    }
+
+    #[inline]
+    fn allocate(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::allocate(&*self, offset, len)
+    }
+
+    #[inline]
+    fn reserve_at(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::reserve_at(&*self, offset, len)
+    }
+
+    #[inline]
+    fn punch_hole(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::punch_hole(&*self, offset, len)
+    }
+
+    #[inline]
+    fn zero_range(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::zero_range(&*self, offset, len)
+    }
+
+    #[inline]
+    fn sync_all(&self) -> io::Result<()> {
+        filelike::sync_all(self)
+    }
+
+    #[inline]
+    fn sync_data(&self) -> io::Result<()> {
+        filelike::sync_data(self)
+    }
+
+    #[inline]
+    fn sync_range(&self, offset: u64, len: u64, mode: SyncRangeMode) -> io::Result<()> {
+        filelike::sync_range(self, offset, len, mode)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Resize for fs::File {
+    #[inline]
+    fn set_len(&mut self, size: u64) -> io::Result<()> {
+        filelike::set_len(&*self, size)
+    }
+}
+
+/// Writing through a shared reference lets several threads write to a file
+/// at once, since each write is a positioned write which doesn't use or
+/// update the file's current position. Writes aren't atomic with respect to
+/// each other, so where concurrent writes overlap, the result may contain
+/// data from any of them, interleaved at any granularity.
+#[cfg(feature = "std")]
+impl WriteAt for &fs::File {
+    #[inline]
+    fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        filelike::write_at(&*self, buf, offset)
+    }
+
+    #[inline]
+    fn write_all_at(&mut self, buf: &[u8], offset: u64) -> io::Result<()> {
+        filelike::write_all_at(&*self, buf, offset)
+    }
+
+    #[inline]
+    fn write_vectored_at(&mut self, bufs: &[IoSlice], offset: u64) -> io::Result<usize> {
+        filelike::write_vectored_at(&*self, bufs, offset)
+    }
+
+    #[inline]
+    fn write_all_vectored_at(&mut self, bufs: &mut [IoSlice], offset: u64) -> io::Result<()> {
+        filelike::write_all_vectored_at(&*self, bufs, offset)
+    }
+
+    #[inline]
+    fn is_write_vectored_at(&self) -> bool {
+        filelike::is_write_vectored_at(self)
+    }
+
+    #[inline]
+    fn write_at_with(&mut self, buf: &[u8], offset: u64, flags: WriteFlags) -> io::Result<usize> {
+        filelike::write_at_with(&*self, buf, offset, flags)
+    }
+
+    #[inline]
+    fn copy_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<u64> {
+        filelike::copy_from(&*self, offset, input, input_offset, len)
+    }
+
+    #[inline]
+    fn clone_range_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<CloneOutcome> {
+        filelike::clone_range_from(&*self, offset, input, input_offset, len)
+    }
+
+    #[inline]
+    fn dedupe_range_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<u64> {
+        filelike::dedupe_range_from(&*self, offset, input, input_offset, len)
+    }
+
+    #[inline]
+    fn allocate(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::allocate(&*self, offset, len)
+    }
+
+    #[inline]
+    fn reserve_at(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::reserve_at(&*self, offset, len)
+    }
+
+    #[inline]
+    fn punch_hole(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::punch_hole(&*self, offset, len)
+    }
+
+    #[inline]
+    fn zero_range(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::zero_range(&*self, offset, len)
+    }
+
+    #[inline]
+    fn sync_all(&self) -> io::Result<()> {
+        filelike::sync_all(self)
+    }
+
+    #[inline]
+    fn sync_data(&self) -> io::Result<()> {
+        filelike::sync_data(self)
+    }
+
+    #[inline]
+    fn sync_range(&self, offset: u64, len: u64, mode: SyncRangeMode) -> io::Result<()> {
+        filelike::sync_range(self, offset, len, mode)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Resize for &fs::File {
+    #[inline]
+    fn set_len(&mut self, size: u64) -> io::Result<()> {
+        filelike::set_len(&*self, size)
+    }
+}
+
+#[cfg(feature = "cap-std")]
+impl Array for cap_std::fs::File {
+    #[inline]
+    fn metadata(&self) -> io::Result<Metadata> {
+        filelike::metadata(self)
+    }
+
+    #[inline]
+    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        filelike::advise(self, offset, len, advice)
+    }
+    #[inline]
+    fn borrowed_filelike(&self) -> Option<BorrowedFilelike<'_>> {
+        Some(self.as_filelike())
+    }
+}
+
+#[cfg(feature = "cap-std")]
+impl ReadAt for cap_std::fs::File {
+    #[inline]
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        filelike::read_at(self, buf, offset)
+    }
+
+    #[inline]
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+        filelike::read_exact_at(self, buf, offset)
+    }
+
+    #[inline]
+    fn read_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<usize> {
+        filelike::read_vectored_at(self, bufs, offset)
+    }
+
+    #[inline]
+    fn read_exact_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<()> {
+        filelike::read_exact_vectored_at(self, bufs, offset)
+    }
+
+    #[inline]
+    fn is_read_vectored_at(&self) -> bool {
+        filelike::is_read_vectored_at(self)
+    }
+
+    #[inline]
+    fn read_at_nowait(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        filelike::read_at_nowait(self, buf, offset)
+    }
+
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    fn read_via_stream_at(&self, offset: u64) -> io::Result<StreamReader> {
+        filelike::read_via_stream_at(self, offset)
+    }
+
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    fn read_range_via_stream(&self, offset: u64, len: u64) -> io::Result<StreamReader> {
+        filelike::read_range_via_stream(self, offset, len)
+    }
+}
+
+#[cfg(feature = "cap-std")]
+impl WriteAt for cap_std::fs::File {
+    #[inline]
+    fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        filelike::write_at(self, buf, offset)
+    }
+
+    #[inline]
+    fn write_all_at(&mut self, buf: &[u8], offset: u64) -> io::Result<()> {
+        filelike::write_all_at(self, buf, offset)
+    }
+
+    #[inline]
+    fn write_vectored_at(&mut self, bufs: &[IoSlice], offset: u64) -> io::Result<usize> {
+        filelike::write_vectored_at(self, bufs, offset)
+    }
+
+    #[inline]
+    fn write_all_vectored_at(&mut self, bufs: &mut [IoSlice], offset: u64) -> io::Result<()> {
+        filelike::write_all_vectored_at(self, bufs, offset)
+    }
+
+    #[inline]
+    fn is_write_vectored_at(&self) -> bool {
+        filelike::is_write_vectored_at(self)
+    }
+
+    #[inline]
+    fn write_at_with(&mut self, buf: &[u8], offset: u64, flags: WriteFlags) -> io::Result<usize> {
+        filelike::write_at_with(&*self, buf, offset, flags)
+    }
+
+    #[inline]
+    fn copy_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<u64> {
+        filelike::copy_from(self, offset, input, input_offset, len)
+    }
+
+    #[inline]
+    fn clone_range_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<CloneOutcome> {
+        filelike::clone_range_from(self, offset, input, input_offset, len)
+    }
+
+    #[inline]
+    fn dedupe_range_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<u64> {
+        filelike::dedupe_range_from(self, offset, input, input_offset, len)
+    }
+
+    #[inline]
+    fn allocate(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::allocate(self, offset, len)
+    }
+
+    #[inline]
+    fn reserve_at(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::reserve_at(self, offset, len)
+    }
+
+    #[inline]
+    fn punch_hole(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::punch_hole(self, offset, len)
+    }
+
+    #[inline]
+    fn zero_range(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::zero_range(self, offset, len)
+    }
+
+    #[inline]
+    fn sync_all(&self) -> io::Result<()> {
+        filelike::sync_all(self)
+    }
+
+    #[inline]
+    fn sync_data(&self) -> io::Result<()> {
+        filelike::sync_data(self)
+    }
+
+    #[inline]
+    fn sync_range(&self, offset: u64, len: u64, mode: SyncRangeMode) -> io::Result<()> {
+        filelike::sync_range(self, offset, len, mode)
+    }
+}
+
+#[cfg(feature = "cap-std")]
+impl Resize for cap_std::fs::File {
+    #[inline]
+    fn set_len(&mut self, size: u64) -> io::Result<()> {
+        filelike::set_len(self, size)
+    }
+}
+
+#[cfg(feature = "cap-std")]
+impl WriteAt for &cap_std::fs::File {
+    #[inline]
+    fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        filelike::write_at(self, buf, offset)
+    }
+
+    #[inline]
+    fn write_all_at(&mut self, buf: &[u8], offset: u64) -> io::Result<()> {
+        filelike::write_all_at(self, buf, offset)
+    }
+
+    #[inline]
+    fn write_vectored_at(&mut self, bufs: &[IoSlice], offset: u64) -> io::Result<usize> {
+        filelike::write_vectored_at(self, bufs, offset)
+    }
+
+    #[inline]
+    fn write_all_vectored_at(&mut self, bufs: &mut [IoSlice], offset: u64) -> io::Result<()> {
+        filelike::write_all_vectored_at(self, bufs, offset)
+    }
+
+    #[inline]
+    fn is_write_vectored_at(&self) -> bool {
+        filelike::is_write_vectored_at(self)
+    }
+
+    #[inline]
+    fn write_at_with(&mut self, buf: &[u8], offset: u64, flags: WriteFlags) -> io::Result<usize> {
+        filelike::write_at_with(&*self, buf, offset, flags)
+    }
+
+    #[inline]
+    fn copy_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<u64> {
+        filelike::copy_from(self, offset, input, input_offset, len)
+    }
+
+    #[inline]
+    fn clone_range_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<CloneOutcome> {
+        filelike::clone_range_from(self, offset, input, input_offset, len)
+    }
+
+    #[inline]
+    fn dedupe_range_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<u64> {
+        filelike::dedupe_range_from(self, offset, input, input_offset, len)
+    }
+
+    #[inline]
+    fn allocate(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::allocate(self, offset, len)
+    }
+
+    #[inline]
+    fn reserve_at(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::reserve_at(self, offset, len)
+    }
+
+    #[inline]
+    fn punch_hole(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::punch_hole(self, offset, len)
+    }
+
+    #[inline]
+    fn zero_range(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::zero_range(self, offset, len)
+    }
+
+    #[inline]
+    fn sync_all(&self) -> io::Result<()> {
+        filelike::sync_all(self)
+    }
+
+    #[inline]
+    fn sync_data(&self) -> io::Result<()> {
+        filelike::sync_data(self)
+    }
+
+    #[inline]
+    fn sync_range(&self, offset: u64, len: u64, mode: SyncRangeMode) -> io::Result<()> {
+        filelike::sync_range(self, offset, len, mode)
+    }
+}
+
+#[cfg(feature = "cap-std")]
+impl Resize for &cap_std::fs::File {
+    #[inline]
+    fn set_len(&mut self, size: u64) -> io::Result<()> {
+        filelike::set_len(self, size)
+    }
+}
+
+#[cfg(feature = "cap-std-utf8")]
+impl Array for cap_std::fs_utf8::File {
+    #[inline]
+    fn metadata(&self) -> io::Result<Metadata> {
+        filelike::metadata(self)
+    }
+
+    #[inline]
+    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        filelike::advise(self, offset, len, advice)
+    }
+    #[inline]
+    fn borrowed_filelike(&self) -> Option<BorrowedFilelike<'_>> {
+        Some(self.as_filelike())
+    }
+}
+
+#[cfg(feature = "cap-std-utf8")]
+impl ReadAt for cap_std::fs_utf8::File {
+    #[inline]
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        filelike::read_at(self, buf, offset)
+    }
+
+    #[inline]
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+        filelike::read_exact_at(self, buf, offset)
+    }
+
+    #[inline]
+    fn read_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<usize> {
+        filelike::read_vectored_at(self, bufs, offset)
+    }
+
+    #[inline]
+    fn read_exact_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<()> {
+        filelike::read_exact_vectored_at(self, bufs, offset)
+    }
+
+    #[inline]
+    fn is_read_vectored_at(&self) -> bool {
+        filelike::is_read_vectored_at(self)
+    }
+
+    #[inline]
+    fn read_at_nowait(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        filelike::read_at_nowait(self, buf, offset)
+    }
+
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    fn read_via_stream_at(&self, offset: u64) -> io::Result<StreamReader> {
+        filelike::read_via_stream_at(self, offset)
+    }
+
     #[cfg(feature = "io-streams")]
-    fn read_via_stream_at(&self, offset: u64) -> io::Result<StreamReader>;
+    #[inline]
+    fn read_range_via_stream(&self, offset: u64, len: u64) -> io::Result<StreamReader> {
+        filelike::read_range_via_stream(self, offset, len)
+    }
 }
 
-/// A trait for writing to arrays.
-///
-/// This is similar to [`std::io::Write`] except all of the reading functions
-/// take an `offset` parameter, specifying a position in the array to read at.
-pub trait WriteAt: Array {
-    /// Writes a number of bytes starting from a given offset.
-    ///
-    /// This is similar to [`std::os::unix::fs::FileExt::write_at`], except it
-    /// takes `self` by immutable reference since the entire side effect is
-    /// I/O, and it's supported on non-Unix platforms including Windows.
-    ///
-    /// [`std::os::unix::fs::FileExt::write_at`]: https://doc.rust-lang.org/std/os/unix/fs/trait.FileExt.html#tymethod.write_at
-    fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize>;
+#[cfg(feature = "cap-std-utf8")]
+impl WriteAt for cap_std::fs_utf8::File {
+    #[inline]
+    fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        filelike::write_at(self, buf, offset)
+    }
 
-    /// Attempts to write an entire buffer starting from a given offset.
-    ///
-    /// This is similar to [`std::os::unix::fs::FileExt::write_all_at`], except
-    /// it takes `self` by immutable reference since the entire side effect is
-    /// I/O, and it's supported on non-Unix platforms including Windows.
-    ///
-    /// [`std::os::unix::fs::FileExt::write_all_at`]: https://doc.rust-lang.org/std/os/unix/fs/trait.FileExt.html#tymethod.write_all_at
-    fn write_all_at(&mut self, buf: &[u8], offset: u64) -> io::Result<()>;
+    #[inline]
+    fn write_all_at(&mut self, buf: &[u8], offset: u64) -> io::Result<()> {
+        filelike::write_all_at(self, buf, offset)
+    }
 
-    /// Is to `write_vectored` what `write_at` is to `write`.
-    fn write_vectored_at(&mut self, bufs: &[IoSlice], offset: u64) -> io::Result<usize>;
+    #[inline]
+    fn write_vectored_at(&mut self, bufs: &[IoSlice], offset: u64) -> io::Result<usize> {
+        filelike::write_vectored_at(self, bufs, offset)
+    }
 
-    /// Is to `write_all_vectored` what `write_all_at` is to `write_all`.
-    fn write_all_vectored_at(&mut self, bufs: &mut [IoSlice], offset: u64) -> io::Result<()>;
+    #[inline]
+    fn write_all_vectored_at(&mut self, bufs: &mut [IoSlice], offset: u64) -> io::Result<()> {
+        filelike::write_all_vectored_at(self, bufs, offset)
+    }
 
-    /// Determines if `Self` has an efficient `write_vectored_at`
-    /// implementation.
-    fn is_write_vectored_at(&self) -> bool;
+    #[inline]
+    fn is_write_vectored_at(&self) -> bool {
+        filelike::is_write_vectored_at(self)
+    }
 
-    /// Copy `len` bytes from `input` at `input_offset` to `self` at `offset`.
-    fn copy_from<R: ReadAt>(
+    #[inline]
+    fn write_at_with(&mut self, buf: &[u8], offset: u64, flags: WriteFlags) -> io::Result<usize> {
+        filelike::write_at_with(&*self, buf, offset, flags)
+    }
+
+    #[inline]
+    fn copy_from(
         &mut self,
         offset: u64,
-        input: &R,
+        input: &dyn ReadAt,
         input_offset: u64,
         len: u64,
-    ) -> io::Result<u64>;
-
-    /// Truncates or extends the underlying array, updating the size of this
-    /// array to become `size`.
-    fn set_len(&mut self, size: u64) -> io::Result<()>;
-}
-
-/// A trait for reading and writing to arrays.
-///
-/// This trait simply combines [`ReadAt`] and [`WriteAt`] and has a blanket
-/// implementation for any type that implements both.
-pub trait EditAt: ReadAt + WriteAt {}
+    ) -> io::Result<u64> {
+        filelike::copy_from(self, offset, input, input_offset, len)
+    }
 
-impl<T: ReadAt + WriteAt> EditAt for T {}
+    #[inline]
+    fn clone_range_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<CloneOutcome> {
+        filelike::clone_range_from(self, offset, input, input_offset, len)
+    }
 
-/// A random-access input source.
-#[derive(Debug)]
-pub struct ArrayReader {
-    file: fs::File,
-}
+    #[inline]
+    fn dedupe_range_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<u64> {
+        filelike::dedupe_range_from(self, offset, input, input_offset, len)
+    }
 
-/// A random-access output sink.
-#[derive(Debug)]
-pub struct ArrayWriter {
-    file: fs::File,
-}
+    #[inline]
+    fn allocate(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::allocate(self, offset, len)
+    }
 
-/// A random-access input source and output sink.
-#[derive(Debug)]
-pub struct ArrayEditor {
-    file: fs::File,
-}
+    #[inline]
+    fn reserve_at(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::reserve_at(self, offset, len)
+    }
 
-impl ArrayReader {
-    /// Convert a `File` into a `ArrayReader`.
     #[inline]
-    #[must_use]
-    pub fn file<Filelike: IntoFilelike + Read + Seek>(filelike: Filelike) -> Self {
-        Self {
-            file: fs::File::from_into_filelike(filelike),
-        }
+    fn punch_hole(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::punch_hole(self, offset, len)
     }
 
-    /// Copy a slice of bytes into a memory buffer to allow it to be accessed
-    /// in the manner of an array.
     #[inline]
-    pub fn bytes(bytes: &[u8]) -> io::Result<Self> {
-        let owned = create_anonymous()?;
-        let mut file = fs::File::from_into_filelike(owned);
-        file.write_all(bytes)?;
-        Ok(Self { file })
+    fn zero_range(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::zero_range(self, offset, len)
     }
-}
 
-impl ArrayWriter {
-    /// Convert a `File` into a `ArrayWriter`.
-    ///
-    /// The file must not be opened in [append mode].
-    ///
-    /// [append mode]: https://doc.rust-lang.org/stable/std/fs/struct.OpenOptions.html#method.append
     #[inline]
-    #[must_use]
-    pub fn file<Filelike: IntoFilelike + Write + Seek>(filelike: Filelike) -> Self {
-        Self::_file(fs::File::from_into_filelike(filelike))
+    fn sync_all(&self) -> io::Result<()> {
+        filelike::sync_all(self)
     }
 
     #[inline]
-    fn _file(file: fs::File) -> Self {
-        // On Linux, `pwrite` on a file opened with `O_APPEND` writes to the
-        // end of the file, ignoring the offset.
-        #[cfg(not(windows))]
-        {
-            assert!(
-                !rustix::fs::fcntl_getfl(&file)
-                    .unwrap()
-                    .contains(rustix::fs::OFlags::APPEND),
-                "ArrayWriter doesn't support files opened with O_APPEND"
-            );
-        }
-        #[cfg(windows)]
-        {
-            assert!(
-                (winx::file::query_access_information(file.as_handle()).unwrap()
-                    & winx::file::AccessMode::FILE_APPEND_DATA)
-                    == winx::file::AccessMode::FILE_APPEND_DATA,
-                "ArrayWriter doesn't support files opened with FILE_APPEND_DATA"
-            );
-        }
+    fn sync_data(&self) -> io::Result<()> {
+        filelike::sync_data(self)
+    }
 
-        Self { file }
+    #[inline]
+    fn sync_range(&self, offset: u64, len: u64, mode: SyncRangeMode) -> io::Result<()> {
+        filelike::sync_range(self, offset, len, mode)
     }
 }
 
-impl ArrayEditor {
-    /// Convert a `File` into a `ArrayEditor`.
+#[cfg(feature = "cap-std-utf8")]
+impl Resize for cap_std::fs_utf8::File {
     #[inline]
-    #[must_use]
-    pub fn file<Filelike: IntoFilelike + Read + Write + Seek>(filelike: Filelike) -> Self {
-        Self {
-            file: fs::File::from_into_filelike(filelike),
-        }
+    fn set_len(&mut self, size: u64) -> io::Result<()> {
+        filelike::set_len(self, size)
     }
+}
 
-    /// Create a temporary anonymous resource which can be accessed in the
-    /// manner of an array.
+#[cfg(feature = "cap-std-utf8")]
+impl WriteAt for &cap_std::fs_utf8::File {
     #[inline]
-    pub fn anonymous() -> io::Result<Self> {
-        let owned = create_anonymous()?;
-        Ok(Self {
-            file: fs::File::from_into_filelike(owned),
-        })
+    fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        filelike::write_at(self, buf, offset)
     }
-}
 
-impl Array for ArrayReader {
     #[inline]
-    fn metadata(&self) -> io::Result<Metadata> {
-        filelike::metadata(self)
+    fn write_all_at(&mut self, buf: &[u8], offset: u64) -> io::Result<()> {
+        filelike::write_all_at(self, buf, offset)
     }
 
     #[inline]
-    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
-        filelike::advise(self, offset, len, advice)
+    fn write_vectored_at(&mut self, bufs: &[IoSlice], offset: u64) -> io::Result<usize> {
+        filelike::write_vectored_at(self, bufs, offset)
     }
-}
 
-impl Array for &ArrayReader {
     #[inline]
-    fn metadata(&self) -> io::Result<Metadata> {
-        filelike::metadata(self)
+    fn write_all_vectored_at(&mut self, bufs: &mut [IoSlice], offset: u64) -> io::Result<()> {
+        filelike::write_all_vectored_at(self, bufs, offset)
     }
 
     #[inline]
-    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
-        filelike::advise(self, offset, len, advice)
+    fn is_write_vectored_at(&self) -> bool {
+        filelike::is_write_vectored_at(self)
     }
-}
 
-impl Array for ArrayWriter {
     #[inline]
-    fn metadata(&self) -> io::Result<Metadata> {
-        filelike::metadata(self)
+    fn write_at_with(&mut self, buf: &[u8], offset: u64, flags: WriteFlags) -> io::Result<usize> {
+        filelike::write_at_with(&*self, buf, offset, flags)
     }
 
     #[inline]
-    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
-        filelike::advise(self, offset, len, advice)
+    fn copy_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<u64> {
+        filelike::copy_from(self, offset, input, input_offset, len)
     }
-}
 
-impl Array for &ArrayWriter {
     #[inline]
-    fn metadata(&self) -> io::Result<Metadata> {
-        filelike::metadata(self)
+    fn clone_range_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<CloneOutcome> {
+        filelike::clone_range_from(self, offset, input, input_offset, len)
     }
 
     #[inline]
-    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
-        filelike::advise(self, offset, len, advice)
+    fn dedupe_range_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<u64> {
+        filelike::dedupe_range_from(self, offset, input, input_offset, len)
     }
-}
 
-impl Array for ArrayEditor {
     #[inline]
-    fn metadata(&self) -> io::Result<Metadata> {
-        filelike::metadata(self)
+    fn allocate(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::allocate(self, offset, len)
     }
 
     #[inline]
-    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
-        filelike::advise(self, offset, len, advice)
+    fn reserve_at(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::reserve_at(self, offset, len)
     }
-}
 
-impl Array for &ArrayEditor {
     #[inline]
-    fn metadata(&self) -> io::Result<Metadata> {
-        filelike::metadata(self)
+    fn punch_hole(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::punch_hole(self, offset, len)
     }
 
     #[inline]
-    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
-        filelike::advise(self, offset, len, advice)
+    fn zero_range(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::zero_range(self, offset, len)
     }
-}
 
-impl ReadAt for ArrayReader {
     #[inline]
-    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
-        filelike::read_at(self, buf, offset)
+    fn sync_all(&self) -> io::Result<()> {
+        filelike::sync_all(self)
     }
 
     #[inline]
-    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
-        filelike::read_exact_at(self, buf, offset)
+    fn sync_data(&self) -> io::Result<()> {
+        filelike::sync_data(self)
     }
 
     #[inline]
-    fn read_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<usize> {
-        filelike::read_vectored_at(self, bufs, offset)
+    fn sync_range(&self, offset: u64, len: u64, mode: SyncRangeMode) -> io::Result<()> {
+        filelike::sync_range(self, offset, len, mode)
     }
+}
 
+#[cfg(feature = "cap-std-utf8")]
+impl Resize for &cap_std::fs_utf8::File {
     #[inline]
-    fn read_exact_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<()> {
-        filelike::read_exact_vectored_at(self, bufs, offset)
+    fn set_len(&mut self, size: u64) -> io::Result<()> {
+        filelike::set_len(self, size)
     }
+}
 
+/// Operations on a `tokio::fs::File` are performed synchronously on the
+/// current thread, rather than on tokio's blocking thread pool, so they block
+/// the thread, the same as [`std::fs::File`].
+///
+/// A `tokio::fs::File` keeps its underlying file open for as long as it
+/// exists, so it's always available here, even while tokio has an operation
+/// in flight on it, unlike with `tokio::fs::File::try_into_std`. However,
+/// these operations don't wait for in-flight operations, so data written
+/// through tokio's `AsyncWrite` may not be visible yet. Await
+/// `tokio::io::AsyncWriteExt::flush` before using a file this way after
+/// writing to it asynchronously.
+#[cfg(feature = "tokio")]
+impl Array for tokio::fs::File {
     #[inline]
-    fn is_read_vectored_at(&self) -> bool {
-        filelike::is_read_vectored_at(self)
+    fn metadata(&self) -> io::Result<Metadata> {
+        filelike::metadata(self)
     }
 
-    #[cfg(feature = "io-streams")]
     #[inline]
-    fn read_via_stream_at(&self, offset: u64) -> io::Result<StreamReader> {
-        filelike::read_via_stream_at(self, offset)
+    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        filelike::advise(self, offset, len, advice)
+    }
+    #[inline]
+    fn borrowed_filelike(&self) -> Option<BorrowedFilelike<'_>> {
+        Some(self.as_filelike())
     }
 }
 
-impl ReadAt for ArrayEditor {
+#[cfg(feature = "tokio")]
+impl ReadAt for tokio::fs::File {
     #[inline]
     fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
         filelike::read_at(self, buf, offset)
@@ -394,14 +4195,26 @@ impl ReadAt for ArrayEditor {
         filelike::is_read_vectored_at(self)
     }
 
+    #[inline]
+    fn read_at_nowait(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        filelike::read_at_nowait(self, buf, offset)
+    }
+
     #[cfg(feature = "io-streams")]
     #[inline]
     fn read_via_stream_at(&self, offset: u64) -> io::Result<StreamReader> {
         filelike::read_via_stream_at(self, offset)
     }
+
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    fn read_range_via_stream(&self, offset: u64, len: u64) -> io::Result<StreamReader> {
+        filelike::read_range_via_stream(self, offset, len)
+    }
 }
 
-impl WriteAt for ArrayWriter {
+#[cfg(feature = "tokio")]
+impl WriteAt for tokio::fs::File {
     #[inline]
     fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
         filelike::write_at(&*self, buf, offset)
@@ -428,10 +4241,15 @@ impl WriteAt for ArrayWriter {
     }
 
     #[inline]
-    fn copy_from<R: ReadAt>(
+    fn write_at_with(&mut self, buf: &[u8], offset: u64, flags: WriteFlags) -> io::Result<usize> {
+        filelike::write_at_with(&*self, buf, offset, flags)
+    }
+
+    #[inline]
+    fn copy_from(
         &mut self,
         offset: u64,
-        input: &R,
+        input: &dyn ReadAt,
         input_offset: u64,
         len: u64,
     ) -> io::Result<u64> {
@@ -439,55 +4257,73 @@ impl WriteAt for ArrayWriter {
     }
 
     #[inline]
-    fn set_len(&mut self, size: u64) -> io::Result<()> {
-        filelike::set_len(&*self, size)
+    fn clone_range_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<CloneOutcome> {
+        filelike::clone_range_from(&*self, offset, input, input_offset, len)
     }
-}
 
-impl WriteAt for &ArrayWriter {
     #[inline]
-    fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
-        filelike::write_at(&*self, buf, offset)
+    fn dedupe_range_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<u64> {
+        filelike::dedupe_range_from(&*self, offset, input, input_offset, len)
     }
 
     #[inline]
-    fn write_all_at(&mut self, buf: &[u8], offset: u64) -> io::Result<()> {
-        filelike::write_all_at(&*self, buf, offset)
+    fn allocate(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::allocate(&*self, offset, len)
     }
 
     #[inline]
-    fn write_vectored_at(&mut self, bufs: &[IoSlice], offset: u64) -> io::Result<usize> {
-        filelike::write_vectored_at(&*self, bufs, offset)
+    fn reserve_at(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::reserve_at(&*self, offset, len)
+    }
+
+    #[inline]
+    fn punch_hole(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::punch_hole(&*self, offset, len)
+    }
+
+    #[inline]
+    fn zero_range(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::zero_range(&*self, offset, len)
     }
 
     #[inline]
-    fn write_all_vectored_at(&mut self, bufs: &mut [IoSlice], offset: u64) -> io::Result<()> {
-        filelike::write_all_vectored_at(&*self, bufs, offset)
+    fn sync_all(&self) -> io::Result<()> {
+        filelike::sync_all(self)
     }
 
     #[inline]
-    fn is_write_vectored_at(&self) -> bool {
-        filelike::is_write_vectored_at(self)
+    fn sync_data(&self) -> io::Result<()> {
+        filelike::sync_data(self)
     }
 
     #[inline]
-    fn copy_from<R: ReadAt>(
-        &mut self,
-        offset: u64,
-        input: &R,
-        input_offset: u64,
-        len: u64,
-    ) -> io::Result<u64> {
-        filelike::copy_from(&*self, offset, input, input_offset, len)
+    fn sync_range(&self, offset: u64, len: u64, mode: SyncRangeMode) -> io::Result<()> {
+        filelike::sync_range(self, offset, len, mode)
     }
+}
 
+#[cfg(feature = "tokio")]
+impl Resize for tokio::fs::File {
     #[inline]
     fn set_len(&mut self, size: u64) -> io::Result<()> {
         filelike::set_len(&*self, size)
     }
 }
 
-impl WriteAt for ArrayEditor {
+#[cfg(feature = "tokio")]
+impl WriteAt for &tokio::fs::File {
     #[inline]
     fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
         filelike::write_at(&*self, buf, offset)
@@ -514,10 +4350,15 @@ impl WriteAt for ArrayEditor {
     }
 
     #[inline]
-    fn copy_from<R: ReadAt>(
+    fn write_at_with(&mut self, buf: &[u8], offset: u64, flags: WriteFlags) -> io::Result<usize> {
+        filelike::write_at_with(&*self, buf, offset, flags)
+    }
+
+    #[inline]
+    fn copy_from(
         &mut self,
         offset: u64,
-        input: &R,
+        input: &dyn ReadAt,
         input_offset: u64,
         len: u64,
     ) -> io::Result<u64> {
@@ -525,67 +4366,74 @@ impl WriteAt for ArrayEditor {
     }
 
     #[inline]
-    fn set_len(&mut self, size: u64) -> io::Result<()> {
-        filelike::set_len(&*self, size)
+    fn clone_range_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<CloneOutcome> {
+        filelike::clone_range_from(&*self, offset, input, input_offset, len)
     }
-}
 
-impl WriteAt for &ArrayEditor {
     #[inline]
-    fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
-        filelike::write_at(&*self, buf, offset)
+    fn dedupe_range_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<u64> {
+        filelike::dedupe_range_from(&*self, offset, input, input_offset, len)
     }
 
     #[inline]
-    fn write_all_at(&mut self, buf: &[u8], offset: u64) -> io::Result<()> {
-        filelike::write_all_at(&*self, buf, offset)
+    fn allocate(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::allocate(&*self, offset, len)
     }
 
     #[inline]
-    fn write_vectored_at(&mut self, bufs: &[IoSlice], offset: u64) -> io::Result<usize> {
-        filelike::write_vectored_at(&*self, bufs, offset)
+    fn reserve_at(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::reserve_at(&*self, offset, len)
     }
 
     #[inline]
-    fn write_all_vectored_at(&mut self, bufs: &mut [IoSlice], offset: u64) -> io::Result<()> {
-        filelike::write_all_vectored_at(&*self, bufs, offset)
+    fn punch_hole(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::punch_hole(&*self, offset, len)
     }
 
     #[inline]
-    fn is_write_vectored_at(&self) -> bool {
-        filelike::is_write_vectored_at(self)
+    fn zero_range(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::zero_range(&*self, offset, len)
     }
 
     #[inline]
-    fn copy_from<R: ReadAt>(
-        &mut self,
-        offset: u64,
-        input: &R,
-        input_offset: u64,
-        len: u64,
-    ) -> io::Result<u64> {
-        filelike::copy_from(&*self, offset, input, input_offset, len)
+    fn sync_all(&self) -> io::Result<()> {
+        filelike::sync_all(self)
     }
 
     #[inline]
-    fn set_len(&mut self, size: u64) -> io::Result<()> {
-        filelike::set_len(&*self, size)
+    fn sync_data(&self) -> io::Result<()> {
+        filelike::sync_data(self)
     }
-}
 
-impl Array for fs::File {
     #[inline]
-    fn metadata(&self) -> io::Result<Metadata> {
-        filelike::metadata(self)
+    fn sync_range(&self, offset: u64, len: u64, mode: SyncRangeMode) -> io::Result<()> {
+        filelike::sync_range(self, offset, len, mode)
     }
+}
 
+#[cfg(feature = "tokio")]
+impl Resize for &tokio::fs::File {
     #[inline]
-    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
-        filelike::advise(self, offset, len, advice)
+    fn set_len(&mut self, size: u64) -> io::Result<()> {
+        filelike::set_len(&*self, size)
     }
 }
 
-impl Array for &fs::File {
+/*
+#[cfg(feature = "cap-async-std")]
+impl Array for cap_async_std::fs::File {
     #[inline]
     fn metadata(&self) -> io::Result<Metadata> {
         filelike::metadata(self)
@@ -597,7 +4445,8 @@ impl Array for &fs::File {
     }
 }
 
-impl ReadAt for fs::File {
+#[cfg(feature = "cap-async-std")]
+impl ReadAt for cap_async_std::fs::File {
     #[inline]
     fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
         filelike::read_at(self, buf, offset)
@@ -623,32 +4472,44 @@ impl ReadAt for fs::File {
         filelike::is_read_vectored_at(self)
     }
 
+    #[inline]
+    fn read_at_nowait(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        filelike::read_at_nowait(self, buf, offset)
+    }
+
     #[cfg(feature = "io-streams")]
     #[inline]
     fn read_via_stream_at(&self, offset: u64) -> io::Result<StreamReader> {
         filelike::read_via_stream_at(self, offset)
     }
+
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    fn read_range_via_stream(&self, offset: u64, len: u64) -> io::Result<StreamReader> {
+        filelike::read_range_via_stream(self, offset, len)
+    }
 }
 
-impl WriteAt for fs::File {
+#[cfg(feature = "cap-async-std")]
+impl WriteAt for cap_async_std::fs::File {
     #[inline]
     fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
-        filelike::write_at(&*self, buf, offset)
+        filelike::write_at(self, buf, offset)
     }
 
     #[inline]
     fn write_all_at(&mut self, buf: &[u8], offset: u64) -> io::Result<()> {
-        filelike::write_all_at(&*self, buf, offset)
+        filelike::write_all_at(self, buf, offset)
     }
 
     #[inline]
     fn write_vectored_at(&mut self, bufs: &[IoSlice], offset: u64) -> io::Result<usize> {
-        filelike::write_vectored_at(&*self, bufs, offset)
+        filelike::write_vectored_at(self, bufs, offset)
     }
 
     #[inline]
     fn write_all_vectored_at(&mut self, bufs: &mut [IoSlice], offset: u64) -> io::Result<()> {
-        filelike::write_all_vectored_at(&*self, bufs, offset)
+        filelike::write_all_vectored_at(self, bufs, offset)
     }
 
     #[inline]
@@ -657,41 +4518,107 @@ impl WriteAt for fs::File {
     }
 
     #[inline]
-    fn copy_from<R: ReadAt>(
+    fn write_at_with(&mut self, buf: &[u8], offset: u64, flags: WriteFlags) -> io::Result<usize> {
+        filelike::write_at_with(&*self, buf, offset, flags)
+    }
+
+    #[inline]
+    fn copy_from(
         &mut self,
         offset: u64,
-        input: &R,
+        input: &dyn ReadAt,
         input_offset: u64,
         len: u64,
     ) -> io::Result<u64> {
-        filelike::copy_from(&*self, offset, input, input_offset, len)
+        filelike::copy_from(self, offset, input, input_offset, len)
+    }
+
+    #[inline]
+    fn clone_range_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<CloneOutcome> {
+        filelike::clone_range_from(self, offset, input, input_offset, len)
+    }
+
+    #[inline]
+    fn dedupe_range_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<u64> {
+        filelike::dedupe_range_from(self, offset, input, input_offset, len)
+    }
+
+    #[inline]
+    fn allocate(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::allocate(self, offset, len)
+    }
+
+    #[inline]
+    fn reserve_at(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::reserve_at(self, offset, len)
+    }
+
+    #[inline]
+    fn punch_hole(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::punch_hole(self, offset, len)
+    }
+
+    #[inline]
+    fn zero_range(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::zero_range(self, offset, len)
     }
 
+    #[inline]
+    fn sync_all(&self) -> io::Result<()> {
+        filelike::sync_all(self)
+    }
+
+    #[inline]
+    fn sync_data(&self) -> io::Result<()> {
+        filelike::sync_data(self)
+    }
+
+    #[inline]
+    fn sync_range(&self, offset: u64, len: u64, mode: SyncRangeMode) -> io::Result<()> {
+        filelike::sync_range(self, offset, len, mode)
+    }
+}
+
+#[cfg(feature = "cap-async-std")]
+impl Resize for cap_async_std::fs::File {
     #[inline]
     fn set_len(&mut self, size: u64) -> io::Result<()> {
-        filelike::set_len(&*self, size)
+        filelike::set_len(self, size)
     }
 }
 
-impl WriteAt for &fs::File {
+#[cfg(feature = "cap-async-std")]
+impl WriteAt for &cap_async_std::fs::File {
     #[inline]
     fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
-        filelike::write_at(&*self, buf, offset)
+        filelike::write_at(self, buf, offset)
     }
 
     #[inline]
     fn write_all_at(&mut self, buf: &[u8], offset: u64) -> io::Result<()> {
-        filelike::write_all_at(&*self, buf, offset)
+        filelike::write_all_at(self, buf, offset)
     }
 
     #[inline]
     fn write_vectored_at(&mut self, bufs: &[IoSlice], offset: u64) -> io::Result<usize> {
-        filelike::write_vectored_at(&*self, bufs, offset)
+        filelike::write_vectored_at(self, bufs, offset)
     }
 
     #[inline]
     fn write_all_vectored_at(&mut self, bufs: &mut [IoSlice], offset: u64) -> io::Result<()> {
-        filelike::write_all_vectored_at(&*self, bufs, offset)
+        filelike::write_all_vectored_at(self, bufs, offset)
     }
 
     #[inline]
@@ -700,128 +4627,154 @@ impl WriteAt for &fs::File {
     }
 
     #[inline]
-    fn copy_from<R: ReadAt>(
+    fn write_at_with(&mut self, buf: &[u8], offset: u64, flags: WriteFlags) -> io::Result<usize> {
+        filelike::write_at_with(&*self, buf, offset, flags)
+    }
+
+    #[inline]
+    fn copy_from(
         &mut self,
         offset: u64,
-        input: &R,
+        input: &dyn ReadAt,
         input_offset: u64,
         len: u64,
     ) -> io::Result<u64> {
-        filelike::copy_from(&*self, offset, input, input_offset, len)
+        filelike::copy_from(self, offset, input, input_offset, len)
     }
 
     #[inline]
-    fn set_len(&mut self, size: u64) -> io::Result<()> {
-        filelike::set_len(&*self, size)
+    fn clone_range_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<CloneOutcome> {
+        filelike::clone_range_from(self, offset, input, input_offset, len)
     }
-}
 
-#[cfg(feature = "cap-std")]
-impl Array for cap_std::fs::File {
     #[inline]
-    fn metadata(&self) -> io::Result<Metadata> {
-        filelike::metadata(self)
+    fn dedupe_range_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<u64> {
+        filelike::dedupe_range_from(self, offset, input, input_offset, len)
     }
 
     #[inline]
-    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
-        filelike::advise(self, offset, len, advice)
+    fn allocate(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::allocate(self, offset, len)
     }
-}
 
-#[cfg(feature = "cap-std")]
-impl Array for &cap_std::fs::File {
     #[inline]
-    fn metadata(&self) -> io::Result<Metadata> {
-        filelike::metadata(self)
+    fn reserve_at(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::reserve_at(self, offset, len)
     }
 
     #[inline]
-    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
-        filelike::advise(self, offset, len, advice)
+    fn punch_hole(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::punch_hole(self, offset, len)
     }
-}
 
-#[cfg(feature = "cap-std")]
-impl ReadAt for cap_std::fs::File {
     #[inline]
-    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
-        filelike::read_at(self, buf, offset)
+    fn zero_range(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::zero_range(self, offset, len)
     }
 
     #[inline]
-    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
-        filelike::read_exact_at(self, buf, offset)
+    fn sync_all(&self) -> io::Result<()> {
+        filelike::sync_all(self)
     }
 
     #[inline]
-    fn read_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<usize> {
-        filelike::read_vectored_at(self, bufs, offset)
+    fn sync_data(&self) -> io::Result<()> {
+        filelike::sync_data(self)
     }
 
     #[inline]
-    fn read_exact_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<()> {
-        filelike::read_exact_vectored_at(self, bufs, offset)
+    fn sync_range(&self, offset: u64, len: u64, mode: SyncRangeMode) -> io::Result<()> {
+        filelike::sync_range(self, offset, len, mode)
     }
+}
 
+#[cfg(feature = "cap-async-std")]
+impl Resize for &cap_async_std::fs::File {
     #[inline]
-    fn is_read_vectored_at(&self) -> bool {
-        filelike::is_read_vectored_at(self)
+    fn set_len(&mut self, size: u64) -> io::Result<()> {
+        filelike::set_len(self, size)
     }
+}
 
-    #[cfg(feature = "io-streams")]
+/// Operations on an `async_std::fs::File` through these traits are performed
+/// synchronously on the current thread, which blocks the executor when done
+/// from an async task. Use its [`AsyncReadAt`] and [`AsyncWriteAt`]
+/// implementations, with the `async-std` feature, from async code instead.
+///
+/// [`AsyncReadAt`]: crate::AsyncReadAt
+/// [`AsyncWriteAt`]: crate::AsyncWriteAt
+#[cfg(feature = "async-std")]
+impl Array for async_std::fs::File {
     #[inline]
-    fn read_via_stream_at(&self, offset: u64) -> io::Result<StreamReader> {
-        filelike::read_via_stream_at(self, offset)
+    fn metadata(&self) -> io::Result<Metadata> {
+        filelike::metadata(self)
+    }
+
+    #[inline]
+    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        filelike::advise(self, offset, len, advice)
     }
 }
 
-#[cfg(feature = "cap-std")]
-impl WriteAt for cap_std::fs::File {
+#[cfg(feature = "async-std")]
+impl ReadAt for async_std::fs::File {
     #[inline]
-    fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
-        filelike::write_at(self, buf, offset)
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        filelike::read_at(self, buf, offset)
     }
 
     #[inline]
-    fn write_all_at(&mut self, buf: &[u8], offset: u64) -> io::Result<()> {
-        filelike::write_all_at(self, buf, offset)
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+        filelike::read_exact_at(self, buf, offset)
     }
 
     #[inline]
-    fn write_vectored_at(&mut self, bufs: &[IoSlice], offset: u64) -> io::Result<usize> {
-        filelike::write_vectored_at(self, bufs, offset)
+    fn read_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<usize> {
+        filelike::read_vectored_at(self, bufs, offset)
     }
 
     #[inline]
-    fn write_all_vectored_at(&mut self, bufs: &mut [IoSlice], offset: u64) -> io::Result<()> {
-        filelike::write_all_vectored_at(self, bufs, offset)
+    fn read_exact_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<()> {
+        filelike::read_exact_vectored_at(self, bufs, offset)
     }
 
     #[inline]
-    fn is_write_vectored_at(&self) -> bool {
-        filelike::is_write_vectored_at(self)
+    fn is_read_vectored_at(&self) -> bool {
+        filelike::is_read_vectored_at(self)
     }
 
     #[inline]
-    fn copy_from<R: ReadAt>(
-        &mut self,
-        offset: u64,
-        input: &R,
-        input_offset: u64,
-        len: u64,
-    ) -> io::Result<u64> {
-        filelike::copy_from(self, offset, input, input_offset, len)
+    fn read_at_nowait(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        filelike::read_at_nowait(self, buf, offset)
     }
 
+    #[cfg(feature = "io-streams")]
     #[inline]
-    fn set_len(&mut self, size: u64) -> io::Result<()> {
-        filelike::set_len(self, size)
+    fn read_via_stream_at(&self, offset: u64) -> io::Result<StreamReader> {
+        filelike::read_via_stream_at(self, offset)
+    }
+
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    fn read_range_via_stream(&self, offset: u64, len: u64) -> io::Result<StreamReader> {
+        filelike::read_range_via_stream(self, offset, len)
     }
 }
 
-#[cfg(feature = "cap-std")]
-impl WriteAt for &cap_std::fs::File {
+#[cfg(feature = "async-std")]
+impl WriteAt for async_std::fs::File {
     #[inline]
     fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
         filelike::write_at(self, buf, offset)
@@ -848,10 +4801,15 @@ impl WriteAt for &cap_std::fs::File {
     }
 
     #[inline]
-    fn copy_from<R: ReadAt>(
+    fn write_at_with(&mut self, buf: &[u8], offset: u64, flags: WriteFlags) -> io::Result<usize> {
+        filelike::write_at_with(&*self, buf, offset, flags)
+    }
+
+    #[inline]
+    fn copy_from(
         &mut self,
         offset: u64,
-        input: &R,
+        input: &dyn ReadAt,
         input_offset: u64,
         len: u64,
     ) -> io::Result<u64> {
@@ -859,74 +4817,73 @@ impl WriteAt for &cap_std::fs::File {
     }
 
     #[inline]
-    fn set_len(&mut self, size: u64) -> io::Result<()> {
-        filelike::set_len(self, size)
-    }
-}
-
-/*
-#[cfg(feature = "cap-async-std")]
-impl Array for cap_async_std::fs::File {
-    #[inline]
-    fn metadata(&self) -> io::Result<Metadata> {
-        filelike::metadata(self)
+    fn clone_range_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<CloneOutcome> {
+        filelike::clone_range_from(self, offset, input, input_offset, len)
     }
 
     #[inline]
-    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
-        filelike::advise(self, offset, len, advice)
+    fn dedupe_range_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<u64> {
+        filelike::dedupe_range_from(self, offset, input, input_offset, len)
     }
-}
 
-#[cfg(feature = "cap-async-std")]
-impl Array for &cap_async_std::fs::File {
     #[inline]
-    fn metadata(&self) -> io::Result<Metadata> {
-        filelike::metadata(self)
+    fn allocate(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::allocate(self, offset, len)
     }
 
     #[inline]
-    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
-        filelike::advise(self, offset, len, advice)
+    fn reserve_at(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::reserve_at(self, offset, len)
     }
-}
 
-#[cfg(feature = "cap-async-std")]
-impl ReadAt for cap_async_std::fs::File {
     #[inline]
-    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
-        filelike::read_at(self, buf, offset)
+    fn punch_hole(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::punch_hole(self, offset, len)
     }
 
     #[inline]
-    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
-        filelike::read_exact_at(self, buf, offset)
+    fn zero_range(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::zero_range(self, offset, len)
     }
 
     #[inline]
-    fn read_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<usize> {
-        filelike::read_vectored_at(self, bufs, offset)
+    fn sync_all(&self) -> io::Result<()> {
+        filelike::sync_all(self)
     }
 
     #[inline]
-    fn read_exact_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<()> {
-        filelike::read_exact_vectored_at(self, bufs, offset)
+    fn sync_data(&self) -> io::Result<()> {
+        filelike::sync_data(self)
     }
 
     #[inline]
-    fn is_read_vectored_at(&self) -> bool {
-        filelike::is_read_vectored_at(self)
+    fn sync_range(&self, offset: u64, len: u64, mode: SyncRangeMode) -> io::Result<()> {
+        filelike::sync_range(self, offset, len, mode)
     }
+}
 
-    #[cfg(feature = "io-streams")]
+#[cfg(feature = "async-std")]
+impl Resize for async_std::fs::File {
     #[inline]
-    fn read_via_stream_at(&self, offset: u64) -> io::Result<StreamReader> {
-        filelike::read_via_stream_at(self, offset)
+    fn set_len(&mut self, size: u64) -> io::Result<()> {
+        filelike::set_len(self, size)
     }
 }
 
-#[cfg(feature = "cap-async-std")]
-impl WriteAt for cap_async_std::fs::File {
+#[cfg(feature = "async-std")]
+impl WriteAt for &async_std::fs::File {
     #[inline]
     fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
         filelike::write_at(self, buf, offset)
@@ -953,10 +4910,15 @@ impl WriteAt for cap_async_std::fs::File {
     }
 
     #[inline]
-    fn copy_from<R: ReadAt>(
+    fn write_at_with(&mut self, buf: &[u8], offset: u64, flags: WriteFlags) -> io::Result<usize> {
+        filelike::write_at_with(&*self, buf, offset, flags)
+    }
+
+    #[inline]
+    fn copy_from(
         &mut self,
         offset: u64,
-        input: &R,
+        input: &dyn ReadAt,
         input_offset: u64,
         len: u64,
     ) -> io::Result<u64> {
@@ -964,335 +4926,456 @@ impl WriteAt for cap_async_std::fs::File {
     }
 
     #[inline]
-    fn set_len(&mut self, size: u64) -> io::Result<()> {
-        filelike::set_len(self, size)
+    fn clone_range_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<CloneOutcome> {
+        filelike::clone_range_from(self, offset, input, input_offset, len)
     }
-}
 
-#[cfg(feature = "cap-async-std")]
-impl WriteAt for &cap_async_std::fs::File {
     #[inline]
-    fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
-        filelike::write_at(self, buf, offset)
+    fn dedupe_range_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<u64> {
+        filelike::dedupe_range_from(self, offset, input, input_offset, len)
     }
 
     #[inline]
-    fn write_all_at(&mut self, buf: &[u8], offset: u64) -> io::Result<()> {
-        filelike::write_all_at(self, buf, offset)
+    fn allocate(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::allocate(self, offset, len)
     }
 
     #[inline]
-    fn write_vectored_at(&mut self, bufs: &[IoSlice], offset: u64) -> io::Result<usize> {
-        filelike::write_vectored_at(self, bufs, offset)
+    fn reserve_at(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::reserve_at(self, offset, len)
     }
 
     #[inline]
-    fn write_all_vectored_at(&mut self, bufs: &mut [IoSlice], offset: u64) -> io::Result<()> {
-        filelike::write_all_vectored_at(self, bufs, offset)
+    fn punch_hole(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::punch_hole(self, offset, len)
     }
 
     #[inline]
-    fn is_write_vectored_at(&self) -> bool {
-        filelike::is_write_vectored_at(self)
+    fn zero_range(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::zero_range(self, offset, len)
     }
 
     #[inline]
-    fn copy_from<R: ReadAt>(
-        &mut self,
-        offset: u64,
-        input: &R,
-        input_offset: u64,
-        len: u64,
-    ) -> io::Result<u64> {
-        filelike::copy_from(self, offset, input, input_offset, len)
+    fn sync_all(&self) -> io::Result<()> {
+        filelike::sync_all(self)
+    }
+
+    #[inline]
+    fn sync_data(&self) -> io::Result<()> {
+        filelike::sync_data(self)
+    }
+
+    #[inline]
+    fn sync_range(&self, offset: u64, len: u64, mode: SyncRangeMode) -> io::Result<()> {
+        filelike::sync_range(self, offset, len, mode)
     }
+}
 
+#[cfg(feature = "async-std")]
+impl Resize for &async_std::fs::File {
     #[inline]
     fn set_len(&mut self, size: u64) -> io::Result<()> {
         filelike::set_len(self, size)
     }
 }
+*/
 
-#[cfg(feature = "async-std")]
-impl Array for async_std::fs::File {
+#[cfg(all(feature = "std", not(windows)))]
+impl AsRawFd for ArrayReader {
     #[inline]
-    fn metadata(&self) -> io::Result<Metadata> {
-        filelike::metadata(self)
+    fn as_raw_fd(&self) -> RawFd {
+        self.backend.0.as_raw_fd()
     }
+}
 
+#[cfg(all(feature = "std", not(windows)))]
+impl AsFd for ArrayReader {
     #[inline]
-    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
-        filelike::advise(self, offset, len, advice)
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.backend.0.as_fd()
     }
 }
 
-#[cfg(feature = "async-std")]
-impl Array for &async_std::fs::File {
+#[cfg(all(feature = "std", windows))]
+impl AsRawHandle for ArrayReader {
     #[inline]
-    fn metadata(&self) -> io::Result<Metadata> {
-        filelike::metadata(self)
+    fn as_raw_handle(&self) -> RawHandle {
+        self.backend.0.as_raw_handle()
     }
+}
 
+#[cfg(all(feature = "std", windows))]
+impl AsHandle for ArrayReader {
     #[inline]
-    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
-        filelike::advise(self, offset, len, advice)
+    fn as_handle(&self) -> BorrowedHandle<'_> {
+        self.backend.0.as_handle()
     }
 }
 
-#[cfg(feature = "async-std")]
-impl ReadAt for async_std::fs::File {
+#[cfg(all(feature = "std", windows))]
+impl AsRawHandleOrSocket for ArrayReader {
     #[inline]
-    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
-        filelike::read_at(self, buf, offset)
+    fn as_raw_handle_or_socket(&self) -> RawHandleOrSocket {
+        self.backend.0.as_raw_handle_or_socket()
     }
+}
 
+#[cfg(feature = "std")]
+impl From<ArrayReader> for fs::File {
     #[inline]
-    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
-        filelike::read_exact_at(self, buf, offset)
+    fn from(array: ArrayReader) -> Self {
+        array.backend.0
     }
+}
 
+#[cfg(all(feature = "std", not(windows)))]
+impl From<OwnedFd> for ArrayReader {
     #[inline]
-    fn read_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<usize> {
-        filelike::read_vectored_at(self, bufs, offset)
+    fn from(fd: OwnedFd) -> Self {
+        Self {
+            backend: FileBackend(fs::File::from(fd)),
+        }
     }
+}
 
+#[cfg(all(feature = "std", not(windows)))]
+impl From<ArrayReader> for OwnedFd {
     #[inline]
-    fn read_exact_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<()> {
-        filelike::read_exact_vectored_at(self, bufs, offset)
+    fn from(array: ArrayReader) -> Self {
+        array.backend.0.into()
     }
+}
 
+#[cfg(all(feature = "std", not(windows)))]
+impl IntoRawFd for ArrayReader {
     #[inline]
-    fn is_read_vectored_at(&self) -> bool {
-        filelike::is_read_vectored_at(self)
+    fn into_raw_fd(self) -> RawFd {
+        self.backend.0.into_raw_fd()
     }
+}
 
-    #[cfg(feature = "io-streams")]
+#[cfg(all(feature = "std", windows))]
+impl From<OwnedHandle> for ArrayReader {
     #[inline]
-    fn read_via_stream_at(&self, offset: u64) -> io::Result<StreamReader> {
-        filelike::read_via_stream_at(self, offset)
+    fn from(handle: OwnedHandle) -> Self {
+        Self {
+            backend: FileBackend(fs::File::from(handle)),
+        }
     }
 }
 
-#[cfg(feature = "async-std")]
-impl WriteAt for async_std::fs::File {
+#[cfg(all(feature = "std", windows))]
+impl From<ArrayReader> for OwnedHandle {
     #[inline]
-    fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
-        filelike::write_at(self, buf, offset)
+    fn from(array: ArrayReader) -> Self {
+        array.backend.0.into()
     }
+}
 
+#[cfg(all(feature = "std", windows))]
+impl IntoRawHandle for ArrayReader {
     #[inline]
-    fn write_all_at(&mut self, buf: &[u8], offset: u64) -> io::Result<()> {
-        filelike::write_all_at(self, buf, offset)
+    fn into_raw_handle(self) -> RawHandle {
+        self.backend.0.into_raw_handle()
     }
+}
 
+#[cfg(all(feature = "std", windows))]
+impl IntoRawHandleOrSocket for ArrayReader {
     #[inline]
-    fn write_vectored_at(&mut self, bufs: &[IoSlice], offset: u64) -> io::Result<usize> {
-        filelike::write_vectored_at(self, bufs, offset)
+    fn into_raw_handle_or_socket(self) -> RawHandleOrSocket {
+        self.backend.0.into_raw_handle_or_socket()
     }
+}
 
+#[cfg(all(feature = "std", not(windows)))]
+impl AsRawFd for ArrayWriter {
     #[inline]
-    fn write_all_vectored_at(&mut self, bufs: &mut [IoSlice], offset: u64) -> io::Result<()> {
-        filelike::write_all_vectored_at(self, bufs, offset)
+    fn as_raw_fd(&self) -> RawFd {
+        self.backend.0.as_raw_fd()
     }
+}
 
+#[cfg(all(feature = "std", not(windows)))]
+impl AsFd for ArrayWriter {
     #[inline]
-    fn is_write_vectored_at(&self) -> bool {
-        filelike::is_write_vectored_at(self)
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.backend.0.as_fd()
     }
+}
 
+#[cfg(all(feature = "std", windows))]
+impl AsRawHandle for ArrayWriter {
     #[inline]
-    fn copy_from<R: ReadAt>(
-        &mut self,
-        offset: u64,
-        input: &R,
-        input_offset: u64,
-        len: u64,
-    ) -> io::Result<u64> {
-        filelike::copy_from(self, offset, input, input_offset, len)
+    fn as_raw_handle(&self) -> RawHandle {
+        self.backend.0.as_raw_handle()
     }
+}
 
+#[cfg(all(feature = "std", windows))]
+impl AsHandle for ArrayWriter {
     #[inline]
-    fn set_len(&mut self, size: u64) -> io::Result<()> {
-        filelike::set_len(self, size)
+    fn as_handle(&self) -> BorrowedHandle<'_> {
+        self.backend.0.as_handle()
     }
 }
 
-#[cfg(feature = "async-std")]
-impl WriteAt for &async_std::fs::File {
+#[cfg(all(feature = "std", windows))]
+impl AsRawHandleOrSocket for ArrayWriter {
     #[inline]
-    fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
-        filelike::write_at(self, buf, offset)
+    fn as_raw_handle_or_socket(&self) -> RawHandleOrSocket {
+        self.backend.0.as_raw_handle_or_socket()
     }
+}
 
+#[cfg(feature = "std")]
+impl From<ArrayWriter> for fs::File {
     #[inline]
-    fn write_all_at(&mut self, buf: &[u8], offset: u64) -> io::Result<()> {
-        filelike::write_all_at(self, buf, offset)
+    fn from(array: ArrayWriter) -> Self {
+        array.backend.0
     }
+}
+
+#[cfg(all(feature = "std", not(windows)))]
+impl TryFrom<OwnedFd> for ArrayWriter {
+    type Error = io::Error;
 
     #[inline]
-    fn write_vectored_at(&mut self, bufs: &[IoSlice], offset: u64) -> io::Result<usize> {
-        filelike::write_vectored_at(self, bufs, offset)
+    fn try_from(fd: OwnedFd) -> io::Result<Self> {
+        Self::_try_file(fs::File::from(fd))
     }
+}
 
+#[cfg(all(feature = "std", not(windows)))]
+impl From<ArrayWriter> for OwnedFd {
     #[inline]
-    fn write_all_vectored_at(&mut self, bufs: &mut [IoSlice], offset: u64) -> io::Result<()> {
-        filelike::write_all_vectored_at(self, bufs, offset)
+    fn from(array: ArrayWriter) -> Self {
+        array.backend.0.into()
     }
+}
 
+#[cfg(all(feature = "std", not(windows)))]
+impl IntoRawFd for ArrayWriter {
     #[inline]
-    fn is_write_vectored_at(&self) -> bool {
-        filelike::is_write_vectored_at(self)
+    fn into_raw_fd(self) -> RawFd {
+        self.backend.0.into_raw_fd()
     }
+}
+
+#[cfg(all(feature = "std", windows))]
+impl TryFrom<OwnedHandle> for ArrayWriter {
+    type Error = io::Error;
 
     #[inline]
-    fn copy_from<R: ReadAt>(
-        &mut self,
-        offset: u64,
-        input: &R,
-        input_offset: u64,
-        len: u64,
-    ) -> io::Result<u64> {
-        filelike::copy_from(self, offset, input, input_offset, len)
+    fn try_from(handle: OwnedHandle) -> io::Result<Self> {
+        Self::_try_file(fs::File::from(handle))
     }
+}
 
+#[cfg(all(feature = "std", windows))]
+impl From<ArrayWriter> for OwnedHandle {
     #[inline]
-    fn set_len(&mut self, size: u64) -> io::Result<()> {
-        filelike::set_len(self, size)
+    fn from(array: ArrayWriter) -> Self {
+        array.backend.0.into()
     }
 }
-*/
 
-#[cfg(not(windows))]
-impl AsRawFd for ArrayReader {
+#[cfg(all(feature = "std", windows))]
+impl IntoRawHandle for ArrayWriter {
+    #[inline]
+    fn into_raw_handle(self) -> RawHandle {
+        self.backend.0.into_raw_handle()
+    }
+}
+
+#[cfg(all(feature = "std", windows))]
+impl IntoRawHandleOrSocket for ArrayWriter {
+    #[inline]
+    fn into_raw_handle_or_socket(self) -> RawHandleOrSocket {
+        self.backend.0.into_raw_handle_or_socket()
+    }
+}
+
+#[cfg(all(feature = "std", not(windows)))]
+impl AsRawFd for ArrayEditor {
     #[inline]
     fn as_raw_fd(&self) -> RawFd {
-        self.file.as_raw_fd()
+        self.backend.0.as_raw_fd()
     }
 }
 
-#[cfg(not(windows))]
-impl AsFd for ArrayReader {
+#[cfg(all(feature = "std", not(windows)))]
+impl AsFd for ArrayEditor {
     #[inline]
     fn as_fd(&self) -> BorrowedFd<'_> {
-        self.file.as_fd()
+        self.backend.0.as_fd()
     }
 }
 
-#[cfg(windows)]
-impl AsRawHandle for ArrayReader {
+#[cfg(all(feature = "std", windows))]
+impl AsRawHandle for ArrayEditor {
     #[inline]
     fn as_raw_handle(&self) -> RawHandle {
-        self.file.as_raw_handle()
+        self.backend.0.as_raw_handle()
     }
 }
 
-#[cfg(windows)]
-impl AsHandle for ArrayReader {
+#[cfg(all(feature = "std", windows))]
+impl AsHandle for ArrayEditor {
     #[inline]
     fn as_handle(&self) -> BorrowedHandle<'_> {
-        self.file.as_handle()
+        self.backend.0.as_handle()
     }
 }
 
-#[cfg(windows)]
-impl AsRawHandleOrSocket for ArrayReader {
+#[cfg(all(feature = "std", windows))]
+impl AsRawHandleOrSocket for ArrayEditor {
     #[inline]
     fn as_raw_handle_or_socket(&self) -> RawHandleOrSocket {
-        self.file.as_raw_handle_or_socket()
+        self.backend.0.as_raw_handle_or_socket()
     }
 }
 
-#[cfg(not(windows))]
-impl AsRawFd for ArrayWriter {
+#[cfg(feature = "std")]
+impl From<ArrayEditor> for fs::File {
     #[inline]
-    fn as_raw_fd(&self) -> RawFd {
-        self.file.as_raw_fd()
+    fn from(array: ArrayEditor) -> Self {
+        array.backend.0
     }
 }
 
-#[cfg(not(windows))]
-impl AsFd for ArrayWriter {
+#[cfg(all(feature = "std", not(windows)))]
+impl From<OwnedFd> for ArrayEditor {
     #[inline]
-    fn as_fd(&self) -> BorrowedFd<'_> {
-        self.file.as_fd()
+    fn from(fd: OwnedFd) -> Self {
+        Self {
+            backend: FileBackend(fs::File::from(fd)),
+        }
     }
 }
 
-#[cfg(windows)]
-impl AsRawHandle for ArrayWriter {
+#[cfg(all(feature = "std", not(windows)))]
+impl From<ArrayEditor> for OwnedFd {
     #[inline]
-    fn as_raw_handle(&self) -> RawHandle {
-        self.file.as_raw_handle()
+    fn from(array: ArrayEditor) -> Self {
+        array.backend.0.into()
     }
 }
 
-#[cfg(windows)]
-impl AsHandle for ArrayWriter {
+#[cfg(all(feature = "std", not(windows)))]
+impl IntoRawFd for ArrayEditor {
     #[inline]
-    fn as_handle(&self) -> BorrowedHandle<'_> {
-        self.file.as_handle()
+    fn into_raw_fd(self) -> RawFd {
+        self.backend.0.into_raw_fd()
     }
 }
 
-#[cfg(windows)]
-impl AsRawHandleOrSocket for ArrayWriter {
+#[cfg(all(feature = "std", windows))]
+impl From<OwnedHandle> for ArrayEditor {
     #[inline]
-    fn as_raw_handle_or_socket(&self) -> RawHandleOrSocket {
-        self.file.as_raw_handle_or_socket()
+    fn from(handle: OwnedHandle) -> Self {
+        Self {
+            backend: FileBackend(fs::File::from(handle)),
+        }
     }
 }
 
-#[cfg(not(windows))]
-impl AsRawFd for ArrayEditor {
+#[cfg(all(feature = "std", windows))]
+impl From<ArrayEditor> for OwnedHandle {
+    #[inline]
+    fn from(array: ArrayEditor) -> Self {
+        array.backend.0.into()
+    }
+}
+
+#[cfg(all(feature = "std", windows))]
+impl IntoRawHandle for ArrayEditor {
+    #[inline]
+    fn into_raw_handle(self) -> RawHandle {
+        self.backend.0.into_raw_handle()
+    }
+}
+
+#[cfg(all(feature = "std", windows))]
+impl IntoRawHandleOrSocket for ArrayEditor {
+    #[inline]
+    fn into_raw_handle_or_socket(self) -> RawHandleOrSocket {
+        self.backend.0.into_raw_handle_or_socket()
+    }
+}
+
+#[cfg(all(feature = "std", not(windows)))]
+impl AsRawFd for ReaderRef<'_> {
     #[inline]
     fn as_raw_fd(&self) -> RawFd {
         self.file.as_raw_fd()
     }
 }
 
-#[cfg(not(windows))]
-impl AsFd for ArrayEditor {
+#[cfg(all(feature = "std", not(windows)))]
+impl AsFd for ReaderRef<'_> {
     #[inline]
     fn as_fd(&self) -> BorrowedFd<'_> {
         self.file.as_fd()
     }
 }
 
-#[cfg(windows)]
-impl AsRawHandle for ArrayEditor {
+#[cfg(all(feature = "std", windows))]
+impl AsRawHandle for ReaderRef<'_> {
     #[inline]
     fn as_raw_handle(&self) -> RawHandle {
         self.file.as_raw_handle()
     }
 }
 
-#[cfg(windows)]
-impl AsHandle for ArrayEditor {
+#[cfg(all(feature = "std", windows))]
+impl AsHandle for ReaderRef<'_> {
     #[inline]
     fn as_handle(&self) -> BorrowedHandle<'_> {
         self.file.as_handle()
     }
 }
 
-#[cfg(windows)]
-impl AsRawHandleOrSocket for ArrayEditor {
+#[cfg(all(feature = "std", windows))]
+impl AsRawHandleOrSocket for ReaderRef<'_> {
     #[inline]
     fn as_raw_handle_or_socket(&self) -> RawHandleOrSocket {
         self.file.as_raw_handle_or_socket()
     }
 }
 
-// On Linux, use `memfd_create`.
-#[cfg(any(target_os = "android", target_os = "linux"))]
-fn create_anonymous() -> io::Result<rustix::fd::OwnedFd> {
-    let flags = rustix::fs::MemfdFlags::CLOEXEC | rustix::fs::MemfdFlags::ALLOW_SEALING;
-    let name = rustix::cstr!("io_arrays anonymous file");
-    Ok(rustix::fs::memfd_create(name, flags)?)
+/// A huge page size, for use with [`ArrayEditor::anonymous_hugetlb`].
+#[cfg(all(feature = "std", any(target_os = "android", target_os = "linux")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HugePageSize {
+    /// 2 MiB pages.
+    Size2MiB,
+
+    /// 1 GiB pages.
+    Size1GiB,
 }
 
-// Otherwise, use a temporary file.
-#[cfg(not(any(target_os = "android", target_os = "linux")))]
-fn create_anonymous() -> io::Result<OwnedFilelike> {
-    let file = tempfile::tempfile()?;
-    Ok(file.into_filelike())
+#[cfg(all(feature = "std", any(target_os = "android", target_os = "linux")))]
+impl HugePageSize {
+    /// Returns the size of a page, in bytes.
+    #[inline]
+    #[must_use]
+    pub const fn bytes(self) -> u64 {
+        match self {
+            Self::Size2MiB => 2 * 1024 * 1024,
+            Self::Size1GiB => 1024 * 1024 * 1024,
+        }
+    }
 }
@@ -0,0 +1,206 @@
+//! An asynchronous stream adapter for arrays.
+
+use crate::arrays::COPY_BUF_SIZE;
+use crate::asynchronous::Offload;
+use crate::{Array, Blocking, ReadAt};
+use futures_io::{AsyncBufRead, AsyncRead, AsyncSeek};
+use std::cmp::min;
+use std::future::Future;
+use std::io::{self, SeekFrom};
+use std::mem::take;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+/// An [`AsyncRead`]/[`AsyncBufRead`]/[`AsyncSeek`] adapter which streams
+/// through an array, tracking its own current position.
+///
+/// This is an asynchronous counterpart of [`Streamer`], which lets an array
+/// be passed to APIs which expect an `AsyncRead` without the threads and
+/// pipes used by `ReadAt::read_via_stream_at`. Reads from the array are
+/// run by the [`Blocking`] adapter, so they're offloaded however it's
+/// configured.
+///
+/// Data is read from the array in chunks of up to the buffer capacity, which
+/// [`AsyncStreamerAt::new`] sets to 64 KiB. Seeking within the buffered data
+/// keeps it.
+///
+/// [`Streamer`]: crate::Streamer
+pub struct AsyncStreamerAt<A> {
+    inner: Blocking<A>,
+    /// The logical position, which is after any consumed buffered data.
+    pos: u64,
+    /// Data read from the array, starting at `pos - consumed`.
+    buf: Vec<u8>,
+    consumed: usize,
+    capacity: usize,
+    pending: Option<Pending>,
+}
+
+/// An operation which is in flight on the array.
+enum Pending {
+    /// A read into the buffer at `offset`.
+    Read {
+        offset: u64,
+        offload: Offload<Vec<u8>>,
+    },
+    /// A query of the length, for seeking relative to the end.
+    Len(Offload<u64>),
+}
+
+impl<A> AsyncStreamerAt<A> {
+    /// Create a streamer which reads from `array`, starting at `offset`.
+    #[inline]
+    pub fn new(array: Blocking<A>, offset: u64) -> Self {
+        Self::with_capacity(COPY_BUF_SIZE as usize, array, offset)
+    }
+
+    /// Create a streamer which reads from `array`, starting at `offset`,
+    /// with a buffer of `capacity` bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    #[inline]
+    pub fn with_capacity(capacity: usize, array: Blocking<A>, offset: u64) -> Self {
+        assert!(capacity != 0, "capacity must be non-zero");
+        Self {
+            inner: array,
+            pos: offset,
+            buf: Vec::new(),
+            consumed: 0,
+            capacity,
+            pending: None,
+        }
+    }
+
+    /// Returns the current position.
+    #[inline]
+    pub fn position(&self) -> u64 {
+        self.pos
+    }
+
+    /// Consume the streamer and return the inner array. Any buffered data
+    /// is discarded.
+    #[inline]
+    pub fn into_inner(self) -> Blocking<A> {
+        self.inner
+    }
+
+    fn seek_to(&mut self, pos: u64) {
+        let start = self.pos - self.consumed as u64;
+        match pos.checked_sub(start) {
+            Some(offset) if offset <= self.buf.len() as u64 => self.consumed = offset as usize,
+            _ => {
+                self.buf.clear();
+                self.consumed = 0;
+            }
+        }
+        self.pos = pos;
+    }
+}
+
+impl<A: ReadAt + Send + Sync + 'static> AsyncBufRead for AsyncStreamerAt<A> {
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        let this = self.get_mut();
+        if this.consumed == this.buf.len() {
+            let pos = this.pos;
+            let offload = match &mut this.pending {
+                Some(Pending::Read { offset, offload }) if *offset == pos => offload,
+                pending => {
+                    let mut data = take(&mut this.buf);
+                    this.consumed = 0;
+                    data.resize(this.capacity, 0);
+                    let offload = this.inner.run_read(move |array| loop {
+                        match array.read_at(&mut data, pos) {
+                            Ok(nread) => {
+                                data.truncate(nread);
+                                return Ok(data);
+                            }
+                            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                            Err(err) => return Err(err),
+                        }
+                    });
+                    match pending.insert(Pending::Read {
+                        offset: pos,
+                        offload,
+                    }) {
+                        Pending::Read { offload, .. } => offload,
+                        Pending::Len(_) => unreachable!(),
+                    }
+                }
+            };
+            let result = ready!(Pin::new(offload).poll(cx));
+            this.pending = None;
+            this.buf = result?;
+            this.consumed = 0;
+        }
+        Poll::Ready(Ok(&this.buf[this.consumed..]))
+    }
+
+    #[inline]
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        let this = self.get_mut();
+        let amt = min(amt, this.buf.len() - this.consumed);
+        this.consumed += amt;
+        this.pos += amt as u64;
+    }
+}
+
+impl<A: ReadAt + Send + Sync + 'static> AsyncRead for AsyncStreamerAt<A> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+        let available = ready!(self.as_mut().poll_fill_buf(cx))?;
+        let n = min(available.len(), buf.len());
+        buf[..n].copy_from_slice(&available[..n]);
+        self.consume(n);
+        Poll::Ready(Ok(n))
+    }
+}
+
+/// Seeking past the end of the array is permitted, as with files; reads
+/// there return 0 bytes.
+impl<A: Array + Send + Sync + 'static> AsyncSeek for AsyncStreamerAt<A> {
+    fn poll_seek(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        pos: SeekFrom,
+    ) -> Poll<io::Result<u64>> {
+        let this = self.get_mut();
+        let (base, delta) = match pos {
+            SeekFrom::Start(offset) => (offset, 0),
+            SeekFrom::Current(delta) => (this.pos, delta),
+            SeekFrom::End(delta) => {
+                let offload = match &mut this.pending {
+                    Some(Pending::Len(offload)) => offload,
+                    pending => {
+                        let offload = this.inner.run_read(|array| Ok(array.metadata()?.len()));
+                        match pending.insert(Pending::Len(offload)) {
+                            Pending::Len(offload) => offload,
+                            Pending::Read { .. } => unreachable!(),
+                        }
+                    }
+                };
+                let len = ready!(Pin::new(offload).poll(cx));
+                this.pending = None;
+                (len?, delta)
+            }
+        };
+        if let Some(Pending::Len(_)) = this.pending {
+            this.pending = None;
+        }
+        let pos = base.checked_add_signed(delta).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )
+        })?;
+        this.seek_to(pos);
+        Poll::Ready(Ok(pos))
+    }
+}
@@ -0,0 +1,500 @@
+//! Asynchronous counterparts of [`Array`], [`ReadAt`], [`WriteAt`], and
+//! [`Resize`], and an adapter which implements them for synchronous arrays.
+
+use crate::arrays::COPY_BUF_SIZE;
+use crate::{Advice, Array, Metadata, ReadAt, Resize, WriteAt};
+#[cfg(feature = "async-std")]
+use io_lifetimes::AsFilelike;
+use std::cmp::min;
+use std::fmt;
+#[cfg(feature = "async-std")]
+use std::fs;
+use std::future::Future;
+use std::io;
+use std::mem::take;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+
+/// An asynchronous counterpart of [`Array`].
+///
+/// The futures returned by this trait and its subtraits are `Send`, so that
+/// they can be spawned on multi-threaded runtimes, which requires arrays to
+/// be `Send` and `Sync`.
+pub trait AsyncArray: Send + Sync {
+    /// Return the [`Metadata`] for the array. This is the asynchronous
+    /// counterpart of [`Array::metadata`].
+    fn metadata(&self) -> impl Future<Output = io::Result<Metadata>> + Send;
+
+    /// Announce the expected access pattern of the data at the given offset.
+    /// This is the asynchronous counterpart of [`Array::advise`].
+    fn advise(
+        &self,
+        offset: u64,
+        len: u64,
+        advice: Advice,
+    ) -> impl Future<Output = io::Result<()>> + Send;
+}
+
+/// An asynchronous counterpart of [`ReadAt`].
+pub trait AsyncReadAt: AsyncArray {
+    /// Reads a number of bytes starting from a given offset. This is the
+    /// asynchronous counterpart of [`ReadAt::read_at`].
+    fn read_at(
+        &self,
+        buf: &mut [u8],
+        offset: u64,
+    ) -> impl Future<Output = io::Result<usize>> + Send;
+
+    /// Reads the exact number of byte required to fill `buf` from the given
+    /// offset. This is the asynchronous counterpart of
+    /// [`ReadAt::read_exact_at`].
+    fn read_exact_at(
+        &self,
+        mut buf: &mut [u8],
+        mut offset: u64,
+    ) -> impl Future<Output = io::Result<()>> + Send {
+        async move {
+            while !buf.is_empty() {
+                match self.read_at(buf, offset).await {
+                    Ok(0) => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::UnexpectedEof,
+                            "failed to fill whole buffer",
+                        ))
+                    }
+                    Ok(nread) => {
+                        buf = &mut take(&mut buf)[nread..];
+                        offset = offset.checked_add(nread as u64).ok_or_else(|| {
+                            io::Error::new(io::ErrorKind::InvalidInput, "offset overflow")
+                        })?;
+                    }
+                    Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                    Err(err) => return Err(err),
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+/// An asynchronous counterpart of [`WriteAt`].
+pub trait AsyncWriteAt: AsyncArray {
+    /// Writes a number of bytes starting from a given offset. This is the
+    /// asynchronous counterpart of [`WriteAt::write_at`].
+    fn write_at(
+        &mut self,
+        buf: &[u8],
+        offset: u64,
+    ) -> impl Future<Output = io::Result<usize>> + Send;
+
+    /// Attempts to write an entire buffer starting from a given offset. This
+    /// is the asynchronous counterpart of [`WriteAt::write_all_at`].
+    fn write_all_at(
+        &mut self,
+        mut buf: &[u8],
+        mut offset: u64,
+    ) -> impl Future<Output = io::Result<()>> + Send {
+        async move {
+            while !buf.is_empty() {
+                match self.write_at(buf, offset).await {
+                    Ok(0) => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::WriteZero,
+                            "failed to write whole buffer",
+                        ))
+                    }
+                    Ok(nwritten) => {
+                        buf = &buf[nwritten..];
+                        offset = offset.checked_add(nwritten as u64).ok_or_else(|| {
+                            io::Error::new(io::ErrorKind::InvalidInput, "offset overflow")
+                        })?;
+                    }
+                    Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                    Err(err) => return Err(err),
+                }
+            }
+            Ok(())
+        }
+    }
+
+    /// Copy `len` bytes from `input` at `input_offset` to `self` at
+    /// `offset`, returning the number of bytes copied, which is less than
+    /// `len` if the end of `input` is reached. This is the asynchronous
+    /// counterpart of [`WriteAt::copy_from`].
+    ///
+    /// The default implementation reads and writes the data in chunks
+    /// through a buffer.
+    fn copy_from<R: AsyncReadAt + ?Sized>(
+        &mut self,
+        mut offset: u64,
+        input: &R,
+        mut input_offset: u64,
+        len: u64,
+    ) -> impl Future<Output = io::Result<u64>> + Send {
+        async move {
+            let mut buf = vec![0_u8; min(len, COPY_BUF_SIZE) as usize];
+            let mut copied = 0;
+            while copied < len {
+                let chunk = min(len - copied, buf.len() as u64) as usize;
+                let nread = match input.read_at(&mut buf[..chunk], input_offset).await {
+                    Ok(0) => break,
+                    Ok(nread) => nread,
+                    Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(err) => return Err(err),
+                };
+                self.write_all_at(&buf[..nread], offset).await?;
+                offset = offset.checked_add(nread as u64).ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidInput, "offset overflow")
+                })?;
+                input_offset = input_offset.checked_add(nread as u64).ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidInput, "offset overflow")
+                })?;
+                copied += nread as u64;
+            }
+            Ok(copied)
+        }
+    }
+
+    /// Flushes all data and metadata written to the array to durable
+    /// storage. This is the asynchronous counterpart of
+    /// [`WriteAt::sync_all`].
+    ///
+    /// The default implementation does nothing.
+    fn sync_all(&self) -> impl Future<Output = io::Result<()>> + Send {
+        async { Ok(()) }
+    }
+
+    /// Flushes all data written to the array to durable storage, without
+    /// necessarily flushing metadata which isn't needed to read it back.
+    /// This is the asynchronous counterpart of [`WriteAt::sync_data`].
+    ///
+    /// The default implementation does nothing.
+    fn sync_data(&self) -> impl Future<Output = io::Result<()>> + Send {
+        async { Ok(()) }
+    }
+}
+
+/// An asynchronous counterpart of [`Resize`].
+pub trait AsyncResize: AsyncArray {
+    /// Truncates or extends the array, updating its length to become `size`.
+    /// This is the asynchronous counterpart of [`Resize::set_len`].
+    fn set_len(&mut self, size: u64) -> impl Future<Output = io::Result<()>> + Send;
+}
+
+/// A task to run in a context where blocking is permitted.
+pub type BlockingTask = Box<dyn FnOnce() + Send>;
+
+/// An adapter which implements [`AsyncReadAt`], [`AsyncWriteAt`], and
+/// [`AsyncResize`] for a synchronous array by running each operation as a
+/// [`BlockingTask`] off of the async task.
+///
+/// By default, each operation runs on a new thread. To use a runtime's pool
+/// for blocking operations instead, use [`Blocking::with_spawner`], with
+/// `tokio::task::spawn_blocking` or `async_std::task::spawn_blocking`, for
+/// example.
+///
+/// The array is shared with the blocking tasks, so reads and writes copy the
+/// data through a buffer owned by the task. Reads may run concurrently, and
+/// writes run exclusively. An operation begins when its future is first
+/// polled, and runs to completion even if the future is dropped.
+pub struct Blocking<A> {
+    inner: Arc<RwLock<A>>,
+    spawn: Arc<dyn Fn(BlockingTask) + Send + Sync>,
+}
+
+impl<A> Blocking<A> {
+    /// Create a `Blocking` which runs each operation on `array` on a new
+    /// thread.
+    #[inline]
+    pub fn new(array: A) -> Self {
+        Self::with_spawner(array, |task| {
+            thread::spawn(task);
+        })
+    }
+
+    /// Create a `Blocking` which runs each operation on `array` by passing a
+    /// [`BlockingTask`] to `spawn`.
+    ///
+    /// `spawn` may run the task on any thread. If it drops the task without
+    /// running it, the operation fails.
+    #[inline]
+    pub fn with_spawner<S>(array: A, spawn: S) -> Self
+    where
+        S: Fn(BlockingTask) + Send + Sync + 'static,
+    {
+        Self {
+            inner: Arc::new(RwLock::new(array)),
+            spawn: Arc::new(spawn),
+        }
+    }
+
+    /// Consume the `Blocking` and return the inner array.
+    ///
+    /// This fails and returns `self` if an operation whose future was
+    /// dropped is still running.
+    pub fn into_inner(self) -> Result<A, Self> {
+        match Arc::try_unwrap(self.inner) {
+            Ok(inner) => Ok(inner.into_inner().unwrap_or_else(PoisonError::into_inner)),
+            Err(inner) => Err(Self {
+                inner,
+                spawn: self.spawn,
+            }),
+        }
+    }
+}
+
+impl<A: Send + Sync + 'static> Blocking<A> {
+    /// Run `f` with shared access to the array, as a blocking task.
+    pub(crate) fn run_read<T, F>(&self, f: F) -> Offload<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&A) -> io::Result<T> + Send + 'static,
+    {
+        let inner = Arc::clone(&self.inner);
+        self.offload(move || f(&inner.read().unwrap_or_else(PoisonError::into_inner)))
+    }
+
+    /// Run `f` with exclusive access to the array, as a blocking task.
+    fn run_write<T, F>(&self, f: F) -> Offload<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut A) -> io::Result<T> + Send + 'static,
+    {
+        let inner = Arc::clone(&self.inner);
+        self.offload(move || f(&mut inner.write().unwrap_or_else(PoisonError::into_inner)))
+    }
+
+    fn offload<T, F>(&self, f: F) -> Offload<T>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> io::Result<T> + Send + 'static,
+    {
+        let shared = Arc::new(Mutex::new(Shared {
+            result: None,
+            waker: None,
+        }));
+        let sender = Sender {
+            shared: Arc::clone(&shared),
+            result: None,
+        };
+        (self.spawn)(Box::new(move || sender.send(f())));
+        Offload { shared }
+    }
+}
+
+impl<A> fmt::Debug for Blocking<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Blocking").finish_non_exhaustive()
+    }
+}
+
+impl<A: Array + Send + Sync + 'static> AsyncArray for Blocking<A> {
+    async fn metadata(&self) -> io::Result<Metadata> {
+        self.run_read(|array| array.metadata()).await
+    }
+
+    async fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        self.run_read(move |array| array.advise(offset, len, advice))
+            .await
+    }
+}
+
+impl<A: ReadAt + Send + Sync + 'static> AsyncReadAt for Blocking<A> {
+    async fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        let len = buf.len();
+        let data = self
+            .run_read(move |array| {
+                let mut data = vec![0_u8; len];
+                let nread = array.read_at(&mut data, offset)?;
+                data.truncate(nread);
+                Ok(data)
+            })
+            .await?;
+        buf[..data.len()].copy_from_slice(&data);
+        Ok(data.len())
+    }
+
+    async fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+        let len = buf.len();
+        let data = self
+            .run_read(move |array| {
+                let mut data = vec![0_u8; len];
+                array.read_exact_at(&mut data, offset)?;
+                Ok(data)
+            })
+            .await?;
+        buf.copy_from_slice(&data);
+        Ok(())
+    }
+}
+
+impl<A: WriteAt + Send + Sync + 'static> AsyncWriteAt for Blocking<A> {
+    async fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        let data = buf.to_vec();
+        self.run_write(move |array| array.write_at(&data, offset))
+            .await
+    }
+
+    async fn write_all_at(&mut self, buf: &[u8], offset: u64) -> io::Result<()> {
+        let data = buf.to_vec();
+        self.run_write(move |array| array.write_all_at(&data, offset))
+            .await
+    }
+
+    async fn sync_all(&self) -> io::Result<()> {
+        self.run_read(|array| array.sync_all()).await
+    }
+
+    async fn sync_data(&self) -> io::Result<()> {
+        self.run_read(|array| array.sync_data()).await
+    }
+}
+
+impl<A: Resize + Send + Sync + 'static> AsyncResize for Blocking<A> {
+    async fn set_len(&mut self, size: u64) -> io::Result<()> {
+        self.run_write(move |array| array.set_len(size)).await
+    }
+}
+
+/// Operations on an `async_std::fs::File` run on async-std's thread pool for
+/// blocking operations, using `async_std::task::spawn_blocking`, on a
+/// duplicate of the file's descriptor or handle.
+///
+/// An `async_std::fs::File` buffers data written through its `AsyncWrite`
+/// implementation, and these operations don't wait for it to be written.
+/// Await `flush` on the file before using it this way after writing to it
+/// asynchronously.
+#[cfg(feature = "async-std")]
+impl AsyncArray for async_std::fs::File {
+    async fn metadata(&self) -> io::Result<Metadata> {
+        offload_file(self, Array::metadata).await
+    }
+
+    async fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        offload_file(self, move |file| file.advise(offset, len, advice)).await
+    }
+}
+
+#[cfg(feature = "async-std")]
+impl AsyncReadAt for async_std::fs::File {
+    async fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        let len = buf.len();
+        let data = offload_file(self, move |file| {
+            let mut data = vec![0_u8; len];
+            let nread = file.read_at(&mut data, offset)?;
+            data.truncate(nread);
+            Ok(data)
+        })
+        .await?;
+        buf[..data.len()].copy_from_slice(&data);
+        Ok(data.len())
+    }
+
+    async fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+        let len = buf.len();
+        let data = offload_file(self, move |file| {
+            let mut data = vec![0_u8; len];
+            file.read_exact_at(&mut data, offset)?;
+            Ok(data)
+        })
+        .await?;
+        buf.copy_from_slice(&data);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "async-std")]
+impl AsyncWriteAt for async_std::fs::File {
+    async fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        let data = buf.to_vec();
+        offload_file(self, move |mut file| file.write_at(&data, offset)).await
+    }
+
+    async fn write_all_at(&mut self, buf: &[u8], offset: u64) -> io::Result<()> {
+        let data = buf.to_vec();
+        offload_file(self, move |mut file| file.write_all_at(&data, offset)).await
+    }
+
+    async fn sync_all(&self) -> io::Result<()> {
+        offload_file(self, WriteAt::sync_all).await
+    }
+
+    async fn sync_data(&self) -> io::Result<()> {
+        offload_file(self, WriteAt::sync_data).await
+    }
+}
+
+#[cfg(feature = "async-std")]
+impl AsyncResize for async_std::fs::File {
+    async fn set_len(&mut self, size: u64) -> io::Result<()> {
+        offload_file(self, move |file| file.set_len(size)).await
+    }
+}
+
+/// Run `f` on a duplicate of `file`, as a blocking task on async-std's pool.
+#[cfg(feature = "async-std")]
+async fn offload_file<T, F>(file: &async_std::fs::File, f: F) -> io::Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce(&fs::File) -> io::Result<T> + Send + 'static,
+{
+    let file = fs::File::from(file.as_filelike().try_clone_to_owned()?);
+    async_std::task::spawn_blocking(move || f(&file)).await
+}
+
+/// The state shared between an [`Offload`] and its [`Sender`].
+struct Shared<T> {
+    result: Option<io::Result<T>>,
+    waker: Option<Waker>,
+}
+
+/// The sending half of an [`Offload`], which delivers its result, or an
+/// error if the task didn't produce one, when it's dropped.
+struct Sender<T> {
+    shared: Arc<Mutex<Shared<T>>>,
+    result: Option<io::Result<T>>,
+}
+
+impl<T> Sender<T> {
+    fn send(mut self, result: io::Result<T>) {
+        self.result = Some(result);
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let result = self.result.take().unwrap_or_else(|| {
+            Err(io::Error::new(
+                io::ErrorKind::Other,
+                "blocking operation did not complete",
+            ))
+        });
+        let mut shared = self.shared.lock().unwrap_or_else(PoisonError::into_inner);
+        shared.result = Some(result);
+        if let Some(waker) = shared.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// A future which resolves to the result of a blocking task.
+pub(crate) struct Offload<T> {
+    shared: Arc<Mutex<Shared<T>>>,
+}
+
+impl<T> Future for Offload<T> {
+    type Output = io::Result<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut shared = self.shared.lock().unwrap_or_else(PoisonError::into_inner);
+        match shared.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                shared.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
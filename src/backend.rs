@@ -0,0 +1,346 @@
+//! Backends for [`ArrayReader`], [`ArrayWriter`], and [`ArrayEditor`].
+
+use crate::{
+    Advice, Array, ArrayEditor, ArrayReader, ArrayWriter, Metadata, ReadAt, Resize, WriteAt,
+    WriteFlags,
+};
+#[cfg(feature = "io-streams")]
+use io_streams::StreamReader;
+use std::fs;
+use std::io::{self, IoSlice, IoSliceMut};
+
+/// The storage an [`ArrayReader`], [`ArrayWriter`], or [`ArrayEditor`]
+/// accesses.
+///
+/// This trait is sealed; it's implemented by [`FileBackend`] and
+/// [`MemBackend`].
+pub trait Backend: private::Sealed {}
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// A backend which holds a file.
+#[derive(Debug)]
+pub struct FileBackend(pub(crate) fs::File);
+
+/// A backend which holds a buffer in memory.
+///
+/// Writes through an [`ArrayWriter`] or [`ArrayEditor`] with this backend
+/// grow the buffer as needed, like writes to a file.
+#[derive(Debug)]
+pub struct MemBackend(Vec<u8>);
+
+impl private::Sealed for FileBackend {}
+impl Backend for FileBackend {}
+
+impl private::Sealed for MemBackend {}
+impl Backend for MemBackend {}
+
+/// A random-access input source which reads from a buffer in memory.
+///
+/// Unlike [`ArrayReader::bytes`], this doesn't copy the data into a file, or
+/// create a file descriptor or handle; reads are served directly out of the
+/// owned `Vec<u8>`.
+pub type MemReader = ArrayReader<MemBackend>;
+
+impl ArrayReader<MemBackend> {
+    /// Copy a slice of bytes into a buffer in memory to allow it to be
+    /// accessed in the manner of an array.
+    ///
+    /// Unlike [`ArrayReader::bytes`], this doesn't create a file descriptor
+    /// or handle. To access a `Vec<u8>` without copying it, use
+    /// [`ArrayReader::from`].
+    #[inline]
+    #[must_use]
+    pub fn in_memory(bytes: &[u8]) -> Self {
+        Self::from(bytes.to_vec())
+    }
+
+    /// Consume `self` and return the underlying `Vec<u8>`.
+    #[inline]
+    #[must_use]
+    pub fn into_vec(self) -> Vec<u8> {
+        self.backend.0
+    }
+}
+
+impl ArrayWriter<MemBackend> {
+    /// Consume `self` and return the underlying `Vec<u8>`.
+    #[inline]
+    #[must_use]
+    pub fn into_vec(self) -> Vec<u8> {
+        self.backend.0
+    }
+}
+
+impl ArrayEditor<MemBackend> {
+    /// Consume `self` and return an [`ArrayReader`] for the same buffer.
+    #[inline]
+    #[must_use]
+    pub fn into_reader(self) -> ArrayReader<MemBackend> {
+        ArrayReader {
+            backend: self.backend,
+        }
+    }
+
+    /// Consume `self` and return an [`ArrayWriter`] for the same buffer.
+    #[inline]
+    #[must_use]
+    pub fn into_writer(self) -> ArrayWriter<MemBackend> {
+        ArrayWriter {
+            backend: self.backend,
+        }
+    }
+
+    /// Consume `self` and return the underlying `Vec<u8>`.
+    #[inline]
+    #[must_use]
+    pub fn into_vec(self) -> Vec<u8> {
+        self.backend.0
+    }
+}
+
+impl From<Vec<u8>> for ArrayReader<MemBackend> {
+    #[inline]
+    fn from(bytes: Vec<u8>) -> Self {
+        Self {
+            backend: MemBackend(bytes),
+        }
+    }
+}
+
+impl From<Vec<u8>> for ArrayWriter<MemBackend> {
+    #[inline]
+    fn from(bytes: Vec<u8>) -> Self {
+        Self {
+            backend: MemBackend(bytes),
+        }
+    }
+}
+
+impl From<Vec<u8>> for ArrayEditor<MemBackend> {
+    #[inline]
+    fn from(bytes: Vec<u8>) -> Self {
+        Self {
+            backend: MemBackend(bytes),
+        }
+    }
+}
+
+impl MemBackend {
+    /// Grow the buffer to hold `len` bytes at `offset`, so that writes past
+    /// the end extend it, as they would a file.
+    #[inline]
+    fn prepare_write(&mut self, offset: u64, len: u64) -> io::Result<&mut Vec<u8>> {
+        self.0.allocate(offset, len)?;
+        Ok(&mut self.0)
+    }
+}
+
+impl Array for ArrayReader<MemBackend> {
+    #[inline]
+    fn metadata(&self) -> io::Result<Metadata> {
+        self.backend.0.metadata()
+    }
+
+    #[inline]
+    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        self.backend.0.advise(offset, len, advice)
+    }
+}
+
+impl Array for ArrayWriter<MemBackend> {
+    #[inline]
+    fn metadata(&self) -> io::Result<Metadata> {
+        self.backend.0.metadata()
+    }
+
+    #[inline]
+    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        self.backend.0.advise(offset, len, advice)
+    }
+}
+
+impl Array for ArrayEditor<MemBackend> {
+    #[inline]
+    fn metadata(&self) -> io::Result<Metadata> {
+        self.backend.0.metadata()
+    }
+
+    #[inline]
+    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        self.backend.0.advise(offset, len, advice)
+    }
+}
+
+// Reads from a buffer in memory are the same whether it's accessed through
+// an `ArrayReader` or an `ArrayEditor`.
+macro_rules! mem_read_at {
+    ($ty:ty) => {
+        impl ReadAt for $ty {
+            #[inline]
+            fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+                self.backend.0.read_at(buf, offset)
+            }
+
+            #[inline]
+            fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+                self.backend.0.read_exact_at(buf, offset)
+            }
+
+            #[inline]
+            fn read_full_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+                self.backend.0.read_full_at(buf, offset)
+            }
+
+            #[inline]
+            fn copy_to<W: WriteAt + ?Sized>(
+                &self,
+                offset: u64,
+                output: &mut W,
+                output_offset: u64,
+                len: u64,
+            ) -> io::Result<u64> {
+                self.backend.0.copy_to(offset, output, output_offset, len)
+            }
+
+            #[inline]
+            fn read_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<usize> {
+                self.backend.0.read_vectored_at(bufs, offset)
+            }
+
+            #[inline]
+            fn read_exact_vectored_at(
+                &self,
+                bufs: &mut [IoSliceMut],
+                offset: u64,
+            ) -> io::Result<()> {
+                self.backend.0.read_exact_vectored_at(bufs, offset)
+            }
+
+            #[inline]
+            fn is_read_vectored_at(&self) -> bool {
+                self.backend.0.is_read_vectored_at()
+            }
+
+            #[inline]
+            fn read_at_nowait(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+                self.backend.0.read_at_nowait(buf, offset)
+            }
+
+            #[cfg(feature = "io-streams")]
+            #[inline]
+            fn read_via_stream_at(&self, offset: u64) -> io::Result<StreamReader> {
+                self.backend.0.read_via_stream_at(offset)
+            }
+
+            #[cfg(feature = "io-streams")]
+            #[inline]
+            fn read_range_via_stream(&self, offset: u64, len: u64) -> io::Result<StreamReader> {
+                self.backend.0.read_range_via_stream(offset, len)
+            }
+        }
+    };
+}
+
+// Writes to a buffer in memory are the same whether it's accessed through
+// an `ArrayWriter` or an `ArrayEditor`.
+macro_rules! mem_write_at {
+    ($ty:ty) => {
+        impl WriteAt for $ty {
+            #[inline]
+            fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
+                self.backend
+                    .prepare_write(offset, buf.len() as u64)?
+                    .write_at(buf, offset)
+            }
+
+            #[inline]
+            fn write_all_at(&mut self, buf: &[u8], offset: u64) -> io::Result<()> {
+                self.backend
+                    .prepare_write(offset, buf.len() as u64)?
+                    .write_all_at(buf, offset)
+            }
+
+            #[inline]
+            fn write_vectored_at(&mut self, bufs: &[IoSlice], offset: u64) -> io::Result<usize> {
+                let len = bufs.iter().map(|buf| buf.len() as u64).sum();
+                self.backend
+                    .prepare_write(offset, len)?
+                    .write_vectored_at(bufs, offset)
+            }
+
+            #[inline]
+            fn write_all_vectored_at(
+                &mut self,
+                bufs: &mut [IoSlice],
+                offset: u64,
+            ) -> io::Result<()> {
+                let len = bufs.iter().map(|buf| buf.len() as u64).sum();
+                self.backend
+                    .prepare_write(offset, len)?
+                    .write_all_vectored_at(bufs, offset)
+            }
+
+            #[inline]
+            fn is_write_vectored_at(&self) -> bool {
+                self.backend.0.is_write_vectored_at()
+            }
+
+            #[inline]
+            fn write_at_with(
+                &mut self,
+                buf: &[u8],
+                offset: u64,
+                flags: WriteFlags,
+            ) -> io::Result<usize> {
+                self.backend
+                    .prepare_write(offset, buf.len() as u64)?
+                    .write_at_with(buf, offset, flags)
+            }
+
+            #[inline]
+            fn punch_hole(&mut self, offset: u64, len: u64) -> io::Result<()> {
+                self.backend.0.punch_hole(offset, len)
+            }
+
+            #[inline]
+            fn zero_range(&mut self, offset: u64, len: u64) -> io::Result<()> {
+                self.backend.0.zero_range(offset, len)
+            }
+
+            #[inline]
+            fn fill_at(&mut self, byte: u8, offset: u64, len: u64) -> io::Result<()> {
+                self.backend.0.fill_at(byte, offset, len)
+            }
+
+            #[inline]
+            fn allocate(&mut self, offset: u64, len: u64) -> io::Result<()> {
+                self.backend.0.allocate(offset, len)
+            }
+
+            #[inline]
+            fn reserve_at(&mut self, offset: u64, len: u64) -> io::Result<()> {
+                self.backend.0.reserve_at(offset, len)
+            }
+        }
+
+        impl Resize for $ty {
+            #[inline]
+            fn set_len(&mut self, size: u64) -> io::Result<()> {
+                Resize::set_len(&mut self.backend.0, size)
+            }
+
+            #[inline]
+            fn grow_to(&mut self, size: u64) -> io::Result<()> {
+                self.backend.0.grow_to(size)
+            }
+        }
+    };
+}
+
+mem_read_at!(ArrayReader<MemBackend>);
+mem_read_at!(ArrayEditor<MemBackend>);
+mem_write_at!(ArrayWriter<MemBackend>);
+mem_write_at!(ArrayEditor<MemBackend>);
@@ -0,0 +1,243 @@
+//! Bulk operations on arrays.
+
+use crate::{Array, ReadAt, Resize, WriteAt};
+use std::cmp::{max, min};
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+/// Copy `len` bytes from `input` at `input_offset` to `output` at `offset`,
+/// splitting the range into chunks of `chunk_size` bytes and copying them on
+/// `threads` threads.
+///
+/// This is useful for very large copies between fast devices, where a single
+/// thread leaves most of the bandwidth unused. `output` must support
+/// positional writes and resizing through a shared reference, as
+/// `&ArrayEditor`, `&ArrayWriter`, and `&File` do. `len` is limited to the
+/// data available in `input`, unless it [isn't bounded], and `output` is
+/// extended to its final length before any chunks are copied, so that the
+/// threads don't race to extend it. The ranges must not overlap if `input`
+/// and `output` refer to the same file.
+///
+/// On failure, outstanding work is abandoned, and the first error is
+/// returned along with the number of bytes from the start of the range which
+/// are known to have been copied.
+///
+/// [isn't bounded]: crate::Metadata::is_bounded
+pub fn copy_from_parallel<O, I>(
+    output: &O,
+    offset: u64,
+    input: &I,
+    input_offset: u64,
+    len: u64,
+    threads: usize,
+    chunk_size: u64,
+) -> Result<u64, ParallelCopyError>
+where
+    O: Sync + ?Sized,
+    for<'a> &'a O: WriteAt + Resize,
+    I: ReadAt + Sync,
+{
+    let fail = |error| ParallelCopyError { copied: 0, error };
+    if chunk_size == 0 {
+        return Err(fail(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "chunk size must be non-zero",
+        )));
+    }
+
+    let input_metadata = input.metadata().map_err(fail)?;
+    let len = if input_metadata.is_bounded() {
+        min(len, input_metadata.len().saturating_sub(input_offset))
+    } else {
+        len
+    };
+    let end = offset.checked_add(len).ok_or_else(|| {
+        fail(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "offset overflow",
+        ))
+    })?;
+    input_offset.checked_add(len).ok_or_else(|| {
+        fail(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "offset overflow",
+        ))
+    })?;
+    let output_len = Array::metadata(&output).map_err(fail)?.len();
+    if len != 0 && end > output_len {
+        let mut output = output;
+        Resize::set_len(&mut output, end).map_err(fail)?;
+    }
+
+    let num_chunks = len.div_ceil(chunk_size);
+    let next = AtomicU64::new(0);
+    let abort = AtomicBool::new(false);
+    let first_error: Mutex<Option<(u64, io::Error)>> = Mutex::new(None);
+
+    thread::scope(|scope| {
+        for _ in 0..max(threads, 1) {
+            scope.spawn(|| {
+                let mut output = output;
+                while !abort.load(Ordering::Relaxed) {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    if index >= num_chunks {
+                        break;
+                    }
+                    let start = index * chunk_size;
+                    let chunk = min(chunk_size, len - start);
+                    let result = output
+                        .copy_from(offset + start, input, input_offset + start, chunk)
+                        .and_then(|copied| {
+                            if copied == chunk {
+                                Ok(())
+                            } else {
+                                Err(io::Error::new(
+                                    io::ErrorKind::UnexpectedEof,
+                                    "input ended during copy",
+                                ))
+                            }
+                        });
+                    if let Err(error) = result {
+                        abort.store(true, Ordering::Relaxed);
+                        let mut first_error = first_error.lock().unwrap();
+                        if first_error.as_ref().is_none_or(|(i, _)| index < *i) {
+                            *first_error = Some((index, error));
+                        }
+                        break;
+                    }
+                }
+            });
+        }
+    });
+
+    // Every chunk claimed before the failing one has finished, since the
+    // threads only stop between chunks.
+    match first_error.into_inner().unwrap() {
+        None => Ok(len),
+        Some((index, error)) => Err(ParallelCopyError {
+            copied: index * chunk_size,
+            error,
+        }),
+    }
+}
+
+/// The error type for [`copy_from_parallel`].
+#[derive(Debug)]
+pub struct ParallelCopyError {
+    copied: u64,
+    error: io::Error,
+}
+
+impl ParallelCopyError {
+    /// Returns the number of bytes from the start of the range which are
+    /// known to have been copied.
+    #[inline]
+    #[must_use]
+    pub const fn copied(&self) -> u64 {
+        self.copied
+    }
+
+    /// Returns the underlying I/O error.
+    #[inline]
+    #[must_use]
+    pub const fn error(&self) -> &io::Error {
+        &self.error
+    }
+
+    /// Consume `self` and return the underlying I/O error.
+    #[inline]
+    #[must_use]
+    pub fn into_error(self) -> io::Error {
+        self.error
+    }
+}
+
+impl fmt::Display for ParallelCopyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "parallel copy failed after {} bytes: {}",
+            self.copied, self.error
+        )
+    }
+}
+
+impl Error for ParallelCopyError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl From<ParallelCopyError> for io::Error {
+    #[inline]
+    fn from(err: ParallelCopyError) -> Self {
+        err.error
+    }
+}
+
+/// The size of the buffers used by [`mismatch_range`].
+const COMPARE_BUF_SIZE: u64 = 64 * 1024;
+
+/// Test whether the `len` bytes of `a` at `a_offset` are equal to the `len`
+/// bytes of `b` at `b_offset`.
+///
+/// See [`mismatch_range`] for details.
+pub fn eq_range<A, B>(a: &A, a_offset: u64, b: &B, b_offset: u64, len: u64) -> io::Result<bool>
+where
+    A: ReadAt + ?Sized,
+    B: ReadAt + ?Sized,
+{
+    Ok(mismatch_range(a, a_offset, b, b_offset, len)?.is_none())
+}
+
+/// Compare the `len` bytes of `a` at `a_offset` with the `len` bytes of `b`
+/// at `b_offset`, and return the position within the range of the first
+/// byte which differs, or `None` if the ranges are equal.
+///
+/// The ranges are read in chunks of at least the larger of the arrays'
+/// [`Metadata::blksize`], stopping at the first chunk which differs, so the
+/// ranges don't need to fit in memory. If either array ends before a
+/// difference is found, this fails with [`io::ErrorKind::UnexpectedEof`],
+/// rather than reporting the ranges as different.
+///
+/// [`Metadata::blksize`]: crate::Metadata::blksize
+pub fn mismatch_range<A, B>(
+    a: &A,
+    a_offset: u64,
+    b: &B,
+    b_offset: u64,
+    len: u64,
+) -> io::Result<Option<u64>>
+where
+    A: ReadAt + ?Sized,
+    B: ReadAt + ?Sized,
+{
+    for offset in [a_offset, b_offset] {
+        offset
+            .checked_add(len)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "offset overflow"))?;
+    }
+    if len == 0 {
+        return Ok(None);
+    }
+    let blksize = max(a.metadata()?.blksize(), b.metadata()?.blksize());
+    let buf_size = min(len, max(COMPARE_BUF_SIZE, blksize)) as usize;
+    let mut a_buf = vec![0_u8; buf_size];
+    let mut b_buf = vec![0_u8; buf_size];
+    let mut pos = 0;
+    while pos < len {
+        let chunk = min(len - pos, buf_size as u64) as usize;
+        let (a_chunk, b_chunk) = (&mut a_buf[..chunk], &mut b_buf[..chunk]);
+        a.read_exact_at(a_chunk, a_offset + pos)?;
+        b.read_exact_at(b_chunk, b_offset + pos)?;
+        if let Some(i) = a_chunk.iter().zip(b_chunk.iter()).position(|(x, y)| x != y) {
+            return Ok(Some(pos + i as u64));
+        }
+        pos += chunk as u64;
+    }
+    Ok(None)
+}
@@ -0,0 +1,248 @@
+//! Implementations for the `bytes` crate's buffer types.
+
+#[cfg(feature = "io-streams")]
+use crate::arrays::check_stream_range;
+#[cfg(feature = "io-streams")]
+use crate::Streamer;
+use crate::{Advice, Array, Metadata, ReadAt, Resize, WriteAt, WriteFlags};
+use bytes::{Bytes, BytesMut};
+#[cfg(feature = "io-streams")]
+use io_streams::StreamReader;
+use std::io::{self, IoSlice, IoSliceMut};
+
+impl Array for Bytes {
+    #[inline]
+    fn metadata(&self) -> io::Result<Metadata> {
+        self.as_ref().metadata()
+    }
+
+    #[inline]
+    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        self.as_ref().advise(offset, len, advice)
+    }
+}
+
+impl ReadAt for Bytes {
+    #[inline]
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        self.as_ref().read_at(buf, offset)
+    }
+
+    #[inline]
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+        self.as_ref().read_exact_at(buf, offset)
+    }
+
+    #[inline]
+    fn read_full_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        self.as_ref().read_full_at(buf, offset)
+    }
+
+    #[inline]
+    fn copy_to<W: WriteAt + ?Sized>(
+        &self,
+        offset: u64,
+        output: &mut W,
+        output_offset: u64,
+        len: u64,
+    ) -> io::Result<u64> {
+        crate::slice::copy_to(self.as_ref(), offset, output, output_offset, len)
+    }
+
+    #[inline]
+    fn read_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<usize> {
+        self.as_ref().read_vectored_at(bufs, offset)
+    }
+
+    #[inline]
+    fn read_exact_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<()> {
+        self.as_ref().read_exact_vectored_at(bufs, offset)
+    }
+
+    #[inline]
+    fn is_read_vectored_at(&self) -> bool {
+        self.as_ref().is_read_vectored_at()
+    }
+
+    #[inline]
+    fn read_at_nowait(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        self.as_ref().read_at_nowait(buf, offset)
+    }
+
+    /// Streams from a clone of the `Bytes`, which shares the buffer rather
+    /// than copying it.
+    #[cfg(feature = "io-streams")]
+    fn read_via_stream_at(&self, offset: u64) -> io::Result<StreamReader> {
+        StreamReader::piped_thread(Box::new(Streamer::owned(self.clone(), offset)))
+    }
+
+    #[cfg(feature = "io-streams")]
+    fn read_range_via_stream(&self, offset: u64, len: u64) -> io::Result<StreamReader> {
+        check_stream_range(self.len() as u64, offset, len)?;
+        StreamReader::piped_thread(Box::new(Streamer::bounded(self.clone(), offset, len)))
+    }
+}
+
+impl Array for BytesMut {
+    #[inline]
+    fn metadata(&self) -> io::Result<Metadata> {
+        self.as_ref().metadata()
+    }
+
+    #[inline]
+    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        self.as_ref().advise(offset, len, advice)
+    }
+}
+
+impl ReadAt for BytesMut {
+    #[inline]
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        self.as_ref().read_at(buf, offset)
+    }
+
+    #[inline]
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+        self.as_ref().read_exact_at(buf, offset)
+    }
+
+    #[inline]
+    fn read_full_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        self.as_ref().read_full_at(buf, offset)
+    }
+
+    #[inline]
+    fn copy_to<W: WriteAt + ?Sized>(
+        &self,
+        offset: u64,
+        output: &mut W,
+        output_offset: u64,
+        len: u64,
+    ) -> io::Result<u64> {
+        crate::slice::copy_to(self.as_ref(), offset, output, output_offset, len)
+    }
+
+    #[inline]
+    fn read_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<usize> {
+        self.as_ref().read_vectored_at(bufs, offset)
+    }
+
+    #[inline]
+    fn read_exact_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<()> {
+        self.as_ref().read_exact_vectored_at(bufs, offset)
+    }
+
+    #[inline]
+    fn is_read_vectored_at(&self) -> bool {
+        self.as_ref().is_read_vectored_at()
+    }
+
+    #[inline]
+    fn read_at_nowait(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        self.as_ref().read_at_nowait(buf, offset)
+    }
+
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    fn read_via_stream_at(&self, offset: u64) -> io::Result<StreamReader> {
+        self.as_ref().read_via_stream_at(offset)
+    }
+
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    fn read_range_via_stream(&self, offset: u64, len: u64) -> io::Result<StreamReader> {
+        self.as_ref().read_range_via_stream(offset, len)
+    }
+}
+
+/// Writes past the end of a `BytesMut` grow it, like writes to a file.
+impl WriteAt for BytesMut {
+    #[inline]
+    fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        self.allocate(offset, buf.len() as u64)?;
+        self.as_mut().write_at(buf, offset)
+    }
+
+    #[inline]
+    fn write_all_at(&mut self, buf: &[u8], offset: u64) -> io::Result<()> {
+        self.allocate(offset, buf.len() as u64)?;
+        self.as_mut().write_all_at(buf, offset)
+    }
+
+    fn write_vectored_at(&mut self, bufs: &[IoSlice], offset: u64) -> io::Result<usize> {
+        let len = bufs.iter().map(|buf| buf.len() as u64).sum();
+        self.allocate(offset, len)?;
+        self.as_mut().write_vectored_at(bufs, offset)
+    }
+
+    fn write_all_vectored_at(&mut self, bufs: &mut [IoSlice], offset: u64) -> io::Result<()> {
+        let len = bufs.iter().map(|buf| buf.len() as u64).sum();
+        self.allocate(offset, len)?;
+        self.as_mut().write_all_vectored_at(bufs, offset)
+    }
+
+    #[inline]
+    fn is_write_vectored_at(&self) -> bool {
+        true
+    }
+
+    #[inline]
+    fn write_at_with(&mut self, buf: &[u8], offset: u64, flags: WriteFlags) -> io::Result<usize> {
+        self.allocate(offset, buf.len() as u64)?;
+        self.as_mut().write_at_with(buf, offset, flags)
+    }
+
+    #[inline]
+    fn punch_hole(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        self.as_mut().punch_hole(offset, len)
+    }
+
+    #[inline]
+    fn zero_range(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        self.fill_at(0, offset, len)
+    }
+
+    #[inline]
+    fn fill_at(&mut self, byte: u8, offset: u64, len: u64) -> io::Result<()> {
+        self.allocate(offset, len)?;
+        self.as_mut().fill_at(byte, offset, len)
+    }
+
+    #[inline]
+    fn allocate(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        let end = offset
+            .checked_add(len)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "offset overflow"))?;
+        self.grow_to(end)
+    }
+
+    /// Reserves capacity in the buffer, without changing its length.
+    fn reserve_at(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        let end = offset
+            .checked_add(len)
+            .and_then(|end| usize::try_from(end).ok())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "offset overflow"))?;
+        BytesMut::reserve(self, end.saturating_sub(self.len()));
+        Ok(())
+    }
+}
+
+impl Resize for BytesMut {
+    #[inline]
+    fn set_len(&mut self, len: u64) -> io::Result<()> {
+        self.resize(
+            len.try_into()
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "offset overflow"))?,
+            0,
+        );
+        Ok(())
+    }
+
+    #[inline]
+    fn grow_to(&mut self, len: u64) -> io::Result<()> {
+        if len > self.len() as u64 {
+            Resize::set_len(self, len)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,491 @@
+//! A block cache in front of an array.
+
+use crate::{
+    Advice, Array, CloneOutcome, LockKind, Metadata, RangeLockGuard, ReadAt, Resize, SyncRangeMode,
+    WriteAt,
+};
+#[cfg(feature = "io-streams")]
+use io_streams::StreamReader;
+use std::cmp::min;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{self, IoSlice, IoSliceMut};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// An array which caches fixed-size blocks of another array in memory,
+/// evicting the least recently used blocks when the cache is full.
+///
+/// Reads are served from cached blocks, and blocks which aren't cached are
+/// read from the inner array in full and added to the cache. The cache is
+/// behind a mutex, so a `CachedArray` can be shared between threads, and
+/// the lock isn't held while reading from the inner array.
+///
+/// When the inner array implements [`WriteAt`], so does the `CachedArray`,
+/// and writes are write-through: they're performed on the inner array and
+/// then applied to any cached blocks they overlap, so the cache stays
+/// coherent. If the inner array is modified by other means, call
+/// [`CachedArray::invalidate`] to discard the affected blocks.
+pub struct CachedArray<A> {
+    inner: A,
+    block_size: usize,
+    capacity: usize,
+    cache: Mutex<Cache>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+/// The cached blocks, and their order of use.
+#[derive(Default)]
+struct Cache {
+    /// The cached blocks, indexed by block number.
+    blocks: BTreeMap<u64, Block>,
+
+    /// The block numbers of the cached blocks, indexed by the time of their
+    /// last use, so that the first entry is the least recently used.
+    lru: BTreeMap<u64, u64>,
+
+    /// The time of the most recent use of a block.
+    clock: u64,
+
+    /// The block number of a cached block which is shorter than the block
+    /// size, because it's at the end of the array.
+    short: Option<u64>,
+}
+
+struct Block {
+    data: Vec<u8>,
+    last_used: u64,
+}
+
+impl<A> CachedArray<A> {
+    /// Create a cache in front of `inner` which holds up to `capacity`
+    /// blocks of `block_size` bytes each.
+    ///
+    /// A `capacity` of zero disables caching.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `block_size` is zero.
+    pub fn new(inner: A, block_size: usize, capacity: usize) -> Self {
+        assert!(block_size != 0, "block size must be non-zero");
+        Self {
+            inner,
+            block_size,
+            capacity,
+            cache: Mutex::new(Cache::default()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Like [`CachedArray::new`], but with a capacity given in bytes, which
+    /// is rounded down to a whole number of blocks.
+    #[inline]
+    pub fn with_capacity_bytes(inner: A, block_size: usize, capacity_bytes: usize) -> Self {
+        Self::new(inner, block_size, capacity_bytes / block_size.max(1))
+    }
+
+    /// Returns the number of block lookups which were served from the
+    /// cache.
+    #[inline]
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of block lookups which read from the inner array.
+    #[inline]
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    /// Discard any cached blocks which overlap the range of `len` bytes at
+    /// `offset`.
+    pub fn invalidate(&self, offset: u64, len: u64) {
+        let block_size = self.block_size as u64;
+        self.cache
+            .lock()
+            .unwrap()
+            .invalidate(offset, len, block_size);
+    }
+
+    /// Consume the cache and return the inner array.
+    #[inline]
+    pub fn into_inner(self) -> A {
+        self.inner
+    }
+
+    /// Apply a write of `buf` at `offset`, which has been performed on the
+    /// inner array, to the cached blocks.
+    fn update(&mut self, buf: &[u8], offset: u64) {
+        let block_size = self.block_size as u64;
+        let cache = self.cache.get_mut().unwrap();
+        cache.remove_short();
+        if buf.is_empty() {
+            return;
+        }
+        let end = offset.saturating_add(buf.len() as u64);
+        let first = offset / block_size;
+        let last = (end - 1) / block_size;
+        for (&index, block) in cache.blocks.range_mut(first..=last) {
+            let block_start = index * block_size;
+            let start = offset.max(block_start);
+            let stop = end.min(block_start + block_size);
+            block.data[(start - block_start) as usize..(stop - block_start) as usize]
+                .copy_from_slice(&buf[(start - offset) as usize..(stop - offset) as usize]);
+        }
+    }
+
+    /// Discard the cached blocks overlapping a range which has been modified
+    /// in the inner array, along with the block at the end of the array.
+    fn invalidate_written(&mut self, offset: u64, len: u64) {
+        let block_size = self.block_size as u64;
+        let cache = self.cache.get_mut().unwrap();
+        cache.remove_short();
+        cache.invalidate(offset, len, block_size);
+    }
+}
+
+impl<A: ReadAt> CachedArray<A> {
+    /// Copy bytes at `within` in block `index` into `buf`, reading the block
+    /// from the inner array if it isn't cached. Returns the number of bytes
+    /// copied, and whether the block is shorter than the block size.
+    fn read_block(&self, index: u64, within: usize, buf: &mut [u8]) -> io::Result<(usize, bool)> {
+        let copy = |data: &[u8], buf: &mut [u8]| {
+            let avail = data.get(within..).unwrap_or(&[]);
+            let n = min(avail.len(), buf.len());
+            buf[..n].copy_from_slice(&avail[..n]);
+            (n, data.len() < self.block_size)
+        };
+
+        if let Some(data) = self.cache.lock().unwrap().get(index) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(copy(data, buf));
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+
+        let offset = index
+            .checked_mul(self.block_size as u64)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "offset overflow"))?;
+        let mut data = vec![0; self.block_size];
+        let filled = self.inner.read_full_at(&mut data, offset)?;
+        data.truncate(filled);
+        let result = copy(&data, buf);
+        if self.capacity != 0 && !data.is_empty() {
+            self.cache
+                .lock()
+                .unwrap()
+                .insert(index, data, self.block_size, self.capacity);
+        }
+        Ok(result)
+    }
+}
+
+impl Cache {
+    /// Look up a block, marking it as the most recently used.
+    fn get(&mut self, index: u64) -> Option<&[u8]> {
+        let block = self.blocks.get_mut(&index)?;
+        self.lru.remove(&block.last_used);
+        self.clock += 1;
+        block.last_used = self.clock;
+        self.lru.insert(self.clock, index);
+        Some(&block.data)
+    }
+
+    fn insert(&mut self, index: u64, data: Vec<u8>, block_size: usize, capacity: usize) {
+        // Another thread may have read the block while we did.
+        self.remove(index);
+        while self.blocks.len() >= capacity {
+            let (_, oldest) = self.lru.pop_first().unwrap();
+            self.remove(oldest);
+        }
+        if data.len() < block_size {
+            if let Some(short) = self.short.replace(index) {
+                self.remove(short);
+            }
+        }
+        self.clock += 1;
+        self.lru.insert(self.clock, index);
+        self.blocks.insert(
+            index,
+            Block {
+                data,
+                last_used: self.clock,
+            },
+        );
+    }
+
+    /// Remove the cached blocks overlapping the range of `len` bytes at
+    /// `offset`.
+    fn invalidate(&mut self, offset: u64, len: u64, block_size: u64) {
+        if len == 0 {
+            return;
+        }
+        let first = offset / block_size;
+        let last = offset.saturating_add(len - 1) / block_size;
+        let indices = self
+            .blocks
+            .range(first..=last)
+            .map(|(&index, _)| index)
+            .collect::<Vec<_>>();
+        for index in indices {
+            self.remove(index);
+        }
+    }
+
+    /// Remove the cached block at the end of the array, if it's shorter
+    /// than the block size, since a write may have extended the array.
+    fn remove_short(&mut self) {
+        if let Some(index) = self.short {
+            self.remove(index);
+        }
+    }
+
+    fn remove(&mut self, index: u64) {
+        if let Some(block) = self.blocks.remove(&index) {
+            self.lru.remove(&block.last_used);
+        }
+        if self.short == Some(index) {
+            self.short = None;
+        }
+    }
+}
+
+impl<A: fmt::Debug> fmt::Debug for CachedArray<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CachedArray")
+            .field("inner", &self.inner)
+            .field("block_size", &self.block_size)
+            .field("capacity", &self.capacity)
+            .field("hits", &self.hits())
+            .field("misses", &self.misses())
+            .finish()
+    }
+}
+
+impl<A: Array> Array for CachedArray<A> {
+    #[inline]
+    fn metadata(&self) -> io::Result<Metadata> {
+        self.inner.metadata()
+    }
+
+    #[inline]
+    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        self.inner.advise(offset, len, advice)
+    }
+
+    #[inline]
+    fn readahead(&self, offset: u64, len: u64) -> io::Result<()> {
+        self.inner.readahead(offset, len)
+    }
+
+    #[inline]
+    fn lock_at(&self, offset: u64, len: u64, kind: LockKind) -> io::Result<RangeLockGuard<'_>> {
+        self.inner.lock_at(offset, len, kind)
+    }
+
+    #[inline]
+    fn try_lock_at(
+        &self,
+        offset: u64,
+        len: u64,
+        kind: LockKind,
+    ) -> io::Result<Option<RangeLockGuard<'_>>> {
+        self.inner.try_lock_at(offset, len, kind)
+    }
+}
+
+impl<A: ReadAt> ReadAt for CachedArray<A> {
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        let block_size = self.block_size as u64;
+        let mut filled = 0;
+        while filled < buf.len() {
+            let pos = offset
+                .checked_add(filled as u64)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "offset overflow"))?;
+            let within = (pos % block_size) as usize;
+            let (n, short) = self.read_block(pos / block_size, within, &mut buf[filled..])?;
+            filled += n;
+            // A block shorter than the block size is at the end of the
+            // array.
+            if short {
+                break;
+            }
+        }
+        Ok(filled)
+    }
+
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+        if self.read_at(buf, offset)? != buf.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "failed to fill whole buffer",
+            ));
+        }
+        Ok(())
+    }
+
+    fn read_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<usize> {
+        let mut total = 0;
+        for buf in bufs {
+            let len = self.read_at(buf, offset + total as u64)?;
+            total += len;
+            if len < buf.len() {
+                break;
+            }
+        }
+        Ok(total)
+    }
+
+    fn read_exact_vectored_at(&self, bufs: &mut [IoSliceMut], mut offset: u64) -> io::Result<()> {
+        for buf in bufs {
+            self.read_exact_at(buf, offset)?;
+            offset += buf.len() as u64;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn is_read_vectored_at(&self) -> bool {
+        false
+    }
+
+    /// Streams from the inner array, bypassing the cache.
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    fn read_via_stream_at(&self, offset: u64) -> io::Result<StreamReader> {
+        self.inner.read_via_stream_at(offset)
+    }
+
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    fn read_range_via_stream(&self, offset: u64, len: u64) -> io::Result<StreamReader> {
+        self.inner.read_range_via_stream(offset, len)
+    }
+}
+
+impl<A: WriteAt> WriteAt for CachedArray<A> {
+    fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        let written = self.inner.write_at(buf, offset)?;
+        self.update(&buf[..written], offset);
+        Ok(written)
+    }
+
+    fn write_all_at(&mut self, buf: &[u8], offset: u64) -> io::Result<()> {
+        match self.inner.write_all_at(buf, offset) {
+            Ok(()) => {
+                self.update(buf, offset);
+                Ok(())
+            }
+            Err(err) => {
+                // Some of the write may have been performed.
+                self.invalidate_written(offset, buf.len() as u64);
+                Err(err)
+            }
+        }
+    }
+
+    fn write_vectored_at(&mut self, bufs: &[IoSlice], offset: u64) -> io::Result<usize> {
+        let written = self.inner.write_vectored_at(bufs, offset)?;
+        self.invalidate_written(offset, written as u64);
+        Ok(written)
+    }
+
+    fn write_all_vectored_at(&mut self, bufs: &mut [IoSlice], offset: u64) -> io::Result<()> {
+        let total = bufs.iter().map(|b| b.len() as u64).sum();
+        let result = self.inner.write_all_vectored_at(bufs, offset);
+        self.invalidate_written(offset, total);
+        result
+    }
+
+    #[inline]
+    fn is_write_vectored_at(&self) -> bool {
+        self.inner.is_write_vectored_at()
+    }
+
+    fn copy_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<u64> {
+        let result = self.inner.copy_from(offset, input, input_offset, len);
+        self.invalidate_written(offset, len);
+        result
+    }
+
+    fn clone_range_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<CloneOutcome> {
+        let result = self
+            .inner
+            .clone_range_from(offset, input, input_offset, len);
+        self.invalidate_written(offset, len);
+        result
+    }
+
+    #[inline]
+    fn dedupe_range_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<u64> {
+        // Deduplication doesn't change the contents of the array.
+        self.inner
+            .dedupe_range_from(offset, input, input_offset, len)
+    }
+
+    fn allocate(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        let result = self.inner.allocate(offset, len);
+        self.invalidate_written(offset, len);
+        result
+    }
+
+    /// Reserving doesn't change the contents or length of the array.
+    #[inline]
+    fn reserve_at(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        self.inner.reserve_at(offset, len)
+    }
+
+    fn punch_hole(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        let result = self.inner.punch_hole(offset, len);
+        self.invalidate_written(offset, len);
+        result
+    }
+
+    fn zero_range(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        let result = self.inner.zero_range(offset, len);
+        self.invalidate_written(offset, len);
+        result
+    }
+
+    #[inline]
+    fn sync_all(&self) -> io::Result<()> {
+        self.inner.sync_all()
+    }
+
+    #[inline]
+    fn sync_data(&self) -> io::Result<()> {
+        self.inner.sync_data()
+    }
+
+    #[inline]
+    fn sync_range(&self, offset: u64, len: u64, mode: SyncRangeMode) -> io::Result<()> {
+        self.inner.sync_range(offset, len, mode)
+    }
+}
+
+impl<A: Resize> Resize for CachedArray<A> {
+    fn set_len(&mut self, size: u64) -> io::Result<()> {
+        let result = Resize::set_len(&mut self.inner, size);
+        self.invalidate_written(size, u64::MAX - size);
+        result
+    }
+}
@@ -0,0 +1,303 @@
+//! Concatenations of arrays.
+
+use crate::{Advice, Array, ArrayType, Metadata, ReadAt, WriteAt};
+#[cfg(feature = "io-streams")]
+use io_streams::StreamReader;
+use std::cmp::min;
+#[cfg(feature = "io-streams")]
+use std::io::Read;
+use std::io::{self, IoSlice, IoSliceMut};
+
+/// An array which presents a sequence of arrays, called segments, as one
+/// concatenated array.
+///
+/// The length of each segment is fixed when the chain is created. Reads
+/// and writes which straddle segment boundaries are split between the
+/// segments. Writes past the end of the last segment fail with
+/// [`io::ErrorKind::InvalidInput`], and chains don't implement
+/// [`Resize`].
+///
+/// [`Resize`]: crate::Resize
+///
+/// To chain arrays of different types, use trait objects, such as
+/// `Box<dyn ReadAt>` or `Box<dyn EditAt>`.
+#[derive(Debug)]
+pub struct ChainArray<A> {
+    segments: Vec<A>,
+    /// The offset of the start of each segment, followed by the total
+    /// length.
+    starts: Vec<u64>,
+}
+
+impl<A: Array> ChainArray<A> {
+    /// Create a chain of `segments`, using their current lengths.
+    pub fn new(segments: Vec<A>) -> io::Result<Self> {
+        let lens = segments
+            .iter()
+            .map(|segment| Ok(segment.metadata()?.len()))
+            .collect::<io::Result<Vec<_>>>()?;
+        Self::with_lens(segments.into_iter().zip(lens))
+    }
+}
+
+impl<A> ChainArray<A> {
+    /// Create a chain of segments with the given lengths.
+    ///
+    /// Reads from a segment which is shorter than its given length end
+    /// early, as they would at the end of an array.
+    pub fn with_lens(segments: impl IntoIterator<Item = (A, u64)>) -> io::Result<Self> {
+        let mut starts = vec![0];
+        let mut total = 0_u64;
+        let segments = segments
+            .into_iter()
+            .map(|(segment, len)| {
+                total = total.checked_add(len).ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidInput, "offset overflow")
+                })?;
+                starts.push(total);
+                Ok(segment)
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+        Ok(Self { segments, starts })
+    }
+
+    /// Returns the segments of the chain.
+    #[inline]
+    pub fn segments(&self) -> &[A] {
+        &self.segments
+    }
+
+    /// Consume the chain and return its segments.
+    #[inline]
+    pub fn into_segments(self) -> Vec<A> {
+        self.segments
+    }
+
+    /// Returns the total length of the chain.
+    #[inline]
+    fn total(&self) -> u64 {
+        *self.starts.last().unwrap()
+    }
+
+    /// Returns the index of the segment containing `offset`, the offset
+    /// within that segment, and the number of bytes of the segment after
+    /// it, or `None` if `offset` is at or past the end of the chain.
+    fn locate(&self, offset: u64) -> Option<(usize, u64, u64)> {
+        if offset >= self.total() {
+            return None;
+        }
+        // Zero-length segments share their start with the next segment, so
+        // this finds the last segment starting at or before `offset`, which
+        // is non-empty.
+        let index = self.starts[..self.segments.len()].partition_point(|&s| s <= offset) - 1;
+        let start = self.starts[index];
+        Some((index, offset - start, self.starts[index + 1] - offset))
+    }
+
+    /// Call `f` with each segment overlapping the range of `len` bytes at
+    /// `offset`, along with the offset and length of the overlap within the
+    /// segment, and the position of the overlap within the range.
+    fn for_each_segment(
+        &self,
+        mut offset: u64,
+        len: u64,
+        mut f: impl FnMut(usize, u64, u64, u64) -> io::Result<()>,
+    ) -> io::Result<()> {
+        let end = offset
+            .checked_add(len)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "offset overflow"))?;
+        let start = offset;
+        while offset < end {
+            let Some((index, segment_offset, available)) = self.locate(offset) else {
+                break;
+            };
+            let chunk = min(available, end - offset);
+            f(index, segment_offset, chunk, offset - start)?;
+            offset += chunk;
+        }
+        Ok(())
+    }
+
+    /// Check that the range of `len` bytes at `offset` is within the chain.
+    fn check_range(&self, offset: u64, len: u64, kind: io::ErrorKind) -> io::Result<()> {
+        match offset.checked_add(len) {
+            Some(end) if end <= self.total() => Ok(()),
+            _ => Err(io::Error::new(
+                kind,
+                "range extends past the end of the chain",
+            )),
+        }
+    }
+}
+
+impl<A: Array> Array for ChainArray<A> {
+    fn metadata(&self) -> io::Result<Metadata> {
+        let mut blksize = 0;
+        let mut allocated_len = 0_u64;
+        let mut file_type = None;
+        let mut modified = None;
+        for segment in &self.segments {
+            let metadata = segment.metadata()?;
+            blksize = blksize.max(metadata.blksize);
+            allocated_len = allocated_len.saturating_add(metadata.allocated_len);
+            file_type = match file_type {
+                None => Some(metadata.file_type),
+                Some(t) if t == metadata.file_type => Some(t),
+                Some(_) => Some(ArrayType::Other),
+            };
+            modified = modified.max(metadata.modified);
+        }
+        Ok(Metadata {
+            len: self.total(),
+            blksize: match blksize {
+                0 => crate::arrays::DEFAULT_BLKSIZE,
+                blksize => blksize,
+            },
+            allocated_len,
+            file_type: file_type.unwrap_or(ArrayType::Other),
+            modified,
+        })
+    }
+
+    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        let len = min(len, self.total().saturating_sub(offset));
+        self.for_each_segment(offset, len, |index, offset, len, _| {
+            self.segments[index].advise(offset, len, copy_advice(&advice))
+        })
+    }
+}
+
+/// Copy an [`Advice`], which doesn't implement `Clone`, so that it can be
+/// passed to each segment.
+fn copy_advice(advice: &Advice) -> Advice {
+    match advice {
+        Advice::Normal => Advice::Normal,
+        Advice::Sequential => Advice::Sequential,
+        Advice::Random => Advice::Random,
+        Advice::WillNeed => Advice::WillNeed,
+        Advice::DontNeed => Advice::DontNeed,
+        Advice::NoReuse => Advice::NoReuse,
+    }
+}
+
+impl<A: ReadAt> ReadAt for ChainArray<A> {
+    /// Reads from the segment containing `offset`, stopping at the end of
+    /// the segment.
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        match self.locate(offset) {
+            Some((index, offset, available)) => {
+                let len = min(buf.len() as u64, available) as usize;
+                self.segments[index].read_at(&mut buf[..len], offset)
+            }
+            None => Ok(0),
+        }
+    }
+
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+        self.check_range(offset, buf.len() as u64, io::ErrorKind::UnexpectedEof)?;
+        self.for_each_segment(offset, buf.len() as u64, |index, offset, len, pos| {
+            let pos = pos as usize;
+            self.segments[index].read_exact_at(&mut buf[pos..pos + len as usize], offset)
+        })
+    }
+
+    fn read_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<usize> {
+        match bufs.iter_mut().find(|b| !b.is_empty()) {
+            Some(buf) => self.read_at(buf, offset),
+            None => Ok(0),
+        }
+    }
+
+    fn read_exact_vectored_at(&self, bufs: &mut [IoSliceMut], mut offset: u64) -> io::Result<()> {
+        for buf in bufs {
+            self.read_exact_at(buf, offset)?;
+            offset += buf.len() as u64;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn is_read_vectored_at(&self) -> bool {
+        false
+    }
+
+    #[cfg(feature = "io-streams")]
+    fn read_via_stream_at(&self, offset: u64) -> io::Result<StreamReader> {
+        let mut streams = Vec::new();
+        self.for_each_segment(
+            offset,
+            self.total().saturating_sub(offset),
+            |index, offset, len, _| {
+                streams.push(self.segments[index].read_range_via_stream(offset, len)?);
+                Ok(())
+            },
+        )?;
+        match streams.len() {
+            0 => (&[] as &[u8]).read_via_stream_at(0),
+            1 => Ok(streams.pop().unwrap()),
+            _ => {
+                let chain = streams.into_iter().fold(
+                    Box::new(io::empty()) as Box<dyn Read + Send>,
+                    |chain, stream| Box::new(chain.chain(stream)),
+                );
+                StreamReader::piped_thread(chain)
+            }
+        }
+    }
+}
+
+impl<A: WriteAt> WriteAt for ChainArray<A> {
+    /// Writes to the segment containing `offset`, stopping at the end of
+    /// the segment.
+    fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        match self.locate(offset) {
+            Some((index, offset, available)) => {
+                let len = min(buf.len() as u64, available) as usize;
+                self.segments[index].write_at(&buf[..len], offset)
+            }
+            None => Ok(0),
+        }
+    }
+
+    fn write_all_at(&mut self, buf: &[u8], offset: u64) -> io::Result<()> {
+        self.check_range(offset, buf.len() as u64, io::ErrorKind::InvalidInput)?;
+        let mut ranges = Vec::new();
+        self.for_each_segment(offset, buf.len() as u64, |index, offset, len, pos| {
+            ranges.push((index, offset, len as usize, pos as usize));
+            Ok(())
+        })?;
+        for (index, offset, len, pos) in ranges {
+            self.segments[index].write_all_at(&buf[pos..pos + len], offset)?;
+        }
+        Ok(())
+    }
+
+    fn write_vectored_at(&mut self, bufs: &[IoSlice], offset: u64) -> io::Result<usize> {
+        match bufs.iter().find(|b| !b.is_empty()) {
+            Some(buf) => self.write_at(buf, offset),
+            None => Ok(0),
+        }
+    }
+
+    fn write_all_vectored_at(&mut self, bufs: &mut [IoSlice], mut offset: u64) -> io::Result<()> {
+        let total = bufs.iter().map(|b| b.len() as u64).sum();
+        self.check_range(offset, total, io::ErrorKind::InvalidInput)?;
+        for buf in bufs.iter() {
+            self.write_all_at(buf, offset)?;
+            offset += buf.len() as u64;
+        }
+        Ok(())
+    }
+
+    fn sync_all(&self) -> io::Result<()> {
+        self.segments
+            .iter()
+            .try_for_each(|segment| segment.sync_all())
+    }
+
+    fn sync_data(&self) -> io::Result<()> {
+        self.segments
+            .iter()
+            .try_for_each(|segment| segment.sync_data())
+    }
+}
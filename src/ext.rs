@@ -0,0 +1,145 @@
+//! Extension traits for reading and writing binary data.
+
+use crate::{ReadAt, WriteAt};
+#[cfg(feature = "bytes")]
+use bytes::Bytes;
+use std::io;
+
+macro_rules! read_methods {
+    ($($ty:ident: $le:ident, $be:ident;)*) => {
+        $(
+            #[doc = concat!("Read a little-endian `", stringify!($ty), "` at `offset`.")]
+            #[inline]
+            fn $le(&self, offset: u64) -> io::Result<$ty> {
+                self.read_array_at(offset).map($ty::from_le_bytes)
+            }
+
+            #[doc = concat!("Read a big-endian `", stringify!($ty), "` at `offset`.")]
+            #[inline]
+            fn $be(&self, offset: u64) -> io::Result<$ty> {
+                self.read_array_at(offset).map($ty::from_be_bytes)
+            }
+        )*
+    };
+}
+
+/// Extension methods for reading fixed-size values from a [`ReadAt`].
+///
+/// Each method for a fixed-size value performs a single
+/// [`ReadAt::read_exact_at`] call, and fails with
+/// [`io::ErrorKind::UnexpectedEof`] if the array ends before the value does.
+///
+/// With the `bytes` feature, there are also methods for reading into
+/// `bytes::Bytes` buffers.
+pub trait ReadAtExt: ReadAt {
+    /// Read `N` bytes at `offset`.
+    #[inline]
+    fn read_array_at<const N: usize>(&self, offset: u64) -> io::Result<[u8; N]> {
+        let mut buf = [0_u8; N];
+        self.read_exact_at(&mut buf, offset)?;
+        Ok(buf)
+    }
+
+    /// Read a `u8` at `offset`.
+    #[inline]
+    fn read_u8_at(&self, offset: u64) -> io::Result<u8> {
+        self.read_array_at(offset).map(u8::from_le_bytes)
+    }
+
+    /// Read an `i8` at `offset`.
+    #[inline]
+    fn read_i8_at(&self, offset: u64) -> io::Result<i8> {
+        self.read_array_at(offset).map(i8::from_le_bytes)
+    }
+
+    read_methods! {
+        u16: read_u16_le_at, read_u16_be_at;
+        i16: read_i16_le_at, read_i16_be_at;
+        u32: read_u32_le_at, read_u32_be_at;
+        i32: read_i32_le_at, read_i32_be_at;
+        u64: read_u64_le_at, read_u64_be_at;
+        i64: read_i64_le_at, read_i64_be_at;
+        f32: read_f32_le_at, read_f32_be_at;
+        f64: read_f64_le_at, read_f64_be_at;
+    }
+
+    /// Read exactly `len` bytes at `offset` into a new [`Bytes`], which can
+    /// be sliced and cloned without copying the data.
+    ///
+    /// This reads with [`ReadAt::read_to_vec_at`], and the resulting buffer
+    /// is converted into a `Bytes` without copying.
+    #[cfg(feature = "bytes")]
+    #[inline]
+    fn read_bytes_at(&self, offset: u64, len: usize) -> io::Result<Bytes> {
+        self.read_to_vec_at(offset, len).map(Bytes::from)
+    }
+
+    /// Read up to `len` bytes at `offset` into a new [`Bytes`], stopping
+    /// early if the array ends.
+    ///
+    /// This reads with [`ReadAt::read_up_to_vec_at`], and the resulting
+    /// buffer is converted into a `Bytes` without copying.
+    #[cfg(feature = "bytes")]
+    #[inline]
+    fn read_up_to_bytes_at(&self, offset: u64, len: usize) -> io::Result<Bytes> {
+        self.read_up_to_vec_at(offset, len).map(Bytes::from)
+    }
+}
+
+impl<T: ReadAt + ?Sized> ReadAtExt for T {}
+
+macro_rules! write_methods {
+    ($($ty:ident: $le:ident, $be:ident;)*) => {
+        $(
+            #[doc = concat!("Write `value` as a little-endian `", stringify!($ty), "` at `offset`.")]
+            #[inline]
+            fn $le(&mut self, value: $ty, offset: u64) -> io::Result<()> {
+                self.write_array_at(value.to_le_bytes(), offset)
+            }
+
+            #[doc = concat!("Write `value` as a big-endian `", stringify!($ty), "` at `offset`.")]
+            #[inline]
+            fn $be(&mut self, value: $ty, offset: u64) -> io::Result<()> {
+                self.write_array_at(value.to_be_bytes(), offset)
+            }
+        )*
+    };
+}
+
+/// Extension methods for writing fixed-size values to a [`WriteAt`].
+///
+/// Each method performs a single [`WriteAt::write_all_at`] call, so writing
+/// past the end of the array extends it, as with any other write. As with
+/// [`WriteAt`] methods, the value comes before the offset.
+pub trait WriteAtExt: WriteAt {
+    /// Write the `N` bytes of `bytes` at `offset`.
+    #[inline]
+    fn write_array_at<const N: usize>(&mut self, bytes: [u8; N], offset: u64) -> io::Result<()> {
+        self.write_all_at(&bytes, offset)
+    }
+
+    /// Write `value` as a `u8` at `offset`.
+    #[inline]
+    fn write_u8_at(&mut self, value: u8, offset: u64) -> io::Result<()> {
+        self.write_array_at([value], offset)
+    }
+
+    /// Write `value` as an `i8` at `offset`.
+    #[inline]
+    fn write_i8_at(&mut self, value: i8, offset: u64) -> io::Result<()> {
+        self.write_array_at(value.to_le_bytes(), offset)
+    }
+
+    write_methods! {
+        u16: write_u16_le_at, write_u16_be_at;
+        i16: write_i16_le_at, write_i16_be_at;
+        u32: write_u32_le_at, write_u32_be_at;
+        i32: write_i32_le_at, write_i32_be_at;
+        u64: write_u64_le_at, write_u64_be_at;
+        i64: write_i64_le_at, write_i64_be_at;
+        f32: write_f32_le_at, write_f32_be_at;
+        f64: write_f64_le_at, write_f64_be_at;
+    }
+}
+
+impl<T: WriteAt + ?Sized> WriteAtExt for T {}
@@ -4,14 +4,24 @@
 //!
 //! [`WriteAt`]: crate::WriteAt
 
-use crate::borrow_streamer::{BorrowStreamer, BorrowStreamerMut};
-use crate::{Advice, ReadAt};
+use crate::streamer::Streamer;
+use crate::{Advice, CloneOutcome, ReadAt, SyncRangeMode, WriteFlags};
 use io_lifetimes::AsFilelike;
+use std::cmp::min;
 use std::fs::File;
 use std::io::{self, copy, Read};
 use system_interface::fs::FileIoExt;
+#[cfg(feature = "io-streams")]
+use {crate::arrays::check_stream_range, io_streams::StreamReader};
+
+/// The size of the buffer used by [`copy_buffered`].
+const FILE_COPY_BUF_SIZE: u64 = 1024 * 1024;
 
 /// Implement [`crate::Array::advise`].
+///
+/// Advice is only a hint, so if the platform doesn't implement it, as some
+/// WASI hosts don't for some kinds of advice, this succeeds without doing
+/// anything.
 #[inline]
 pub fn advise<'f, Filelike: AsFilelike>(
     filelike: &Filelike,
@@ -19,27 +29,432 @@ pub fn advise<'f, Filelike: AsFilelike>(
     len: u64,
     advice: Advice,
 ) -> io::Result<()> {
-    <File as FileIoExt>::advise(&filelike.as_filelike_view::<File>(), offset, len, advice)
+    match <File as FileIoExt>::advise(&filelike.as_filelike_view::<File>(), offset, len, advice) {
+        Err(err) if is_unsupported(&err) => Ok(()),
+        result => result,
+    }
+}
+
+/// Implement [`crate::Array::readahead`].
+///
+/// This uses `readahead` on Linux, which reads the data into the page cache
+/// before returning, and `fcntl(F_RDADVISE)` on Apple platforms. Elsewhere,
+/// and for files which don't support those, it falls back to [`advise`] with
+/// [`Advice::WillNeed`], which does nothing on some platforms, including
+/// Windows.
+pub fn readahead<'f, Filelike: AsFilelike>(
+    filelike: &Filelike,
+    offset: u64,
+    len: u64,
+) -> io::Result<()> {
+    #[cfg(any(target_os = "ios", target_os = "linux", target_os = "macos"))]
+    {
+        use std::os::fd::AsRawFd;
+
+        // Offsets which don't fit in an `off_t` are past the end of any
+        // file, so there's nothing to read.
+        let Ok(offset) = i64::try_from(offset) else {
+            return Ok(());
+        };
+        let fd = filelike.as_filelike_view::<File>().as_raw_fd();
+
+        #[cfg(target_os = "linux")]
+        let result = unsafe {
+            libc::readahead(fd, offset as _, usize::try_from(len).unwrap_or(usize::MAX)) as i32
+        };
+        #[cfg(any(target_os = "ios", target_os = "macos"))]
+        let result = unsafe {
+            let advisory = libc::radvisory {
+                ra_offset: offset,
+                ra_count: i32::try_from(len).unwrap_or(i32::MAX),
+            };
+            libc::fcntl(fd, libc::F_RDADVISE, &advisory)
+        };
+
+        if result != -1 {
+            return Ok(());
+        }
+        let err = io::Error::last_os_error();
+        if !is_unsupported(&err) && err.raw_os_error() != Some(libc::EINVAL) {
+            return Err(err);
+        }
+    }
+
+    advise(filelike, offset, len, Advice::WillNeed)
+}
+
+/// Test whether `err` means that an operation isn't implemented.
+fn is_unsupported(err: &io::Error) -> bool {
+    if err.kind() == io::ErrorKind::Unsupported {
+        return true;
+    }
+    #[cfg(not(windows))]
+    {
+        use rustix::io::Errno;
+
+        // `ENOTSUP` and `EOPNOTSUPP` are the same on some platforms, so
+        // compare rather than match.
+        Errno::from_io_error(err)
+            .is_some_and(|e| e == Errno::NOSYS || e == Errno::NOTSUP || e == Errno::OPNOTSUPP)
+    }
+    #[cfg(windows)]
+    false
+}
+
+/// Implement [`crate::ReadAt::read_range_via_stream`].
+///
+/// `StreamReader` can't limit a file stream, so unlike
+/// [`crate::filelike::read_via_stream_at`], this always streams from a
+/// thread, which reads from a clone of the handle with positional reads and
+/// stops after `len` bytes. That way there's no need to reopen the file.
+#[cfg(feature = "io-streams")]
+pub fn read_range_via_stream<'f, Filelike: AsFilelike>(
+    filelike: &Filelike,
+    offset: u64,
+    len: u64,
+) -> io::Result<StreamReader> {
+    let metadata = crate::filelike::metadata(filelike)?;
+    if metadata.is_bounded() {
+        check_stream_range(metadata.len(), offset, len)?;
+    }
+    let file = filelike.as_filelike_view::<File>().try_clone()?;
+    StreamReader::piped_thread(Box::new(Streamer::bounded(file, offset, len)))
+}
+
+/// Implement [`crate::ReadAt::read_at_nowait`].
+#[inline]
+pub fn read_at_nowait<'f, Filelike: AsFilelike>(
+    filelike: &Filelike,
+    buf: &mut [u8],
+    offset: u64,
+) -> io::Result<usize> {
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    return crate::rustix::read_at_nowait(filelike, buf, offset);
+
+    #[cfg(not(any(target_os = "android", target_os = "linux")))]
+    {
+        let _ = (filelike, buf, offset);
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "read_at_nowait is not supported on this platform",
+        ))
+    }
+}
+
+/// Implement [`crate::WriteAt::write_at_with`].
+pub fn write_at_with<'f, Filelike: AsFilelike>(
+    filelike: &Filelike,
+    buf: &[u8],
+    offset: u64,
+    flags: WriteFlags,
+) -> io::Result<usize> {
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    if let Some(written) = crate::rustix::write_at_with(filelike, buf, offset, flags)? {
+        return Ok(written);
+    }
+
+    if flags.contains(WriteFlags::NOWAIT) {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "non-blocking writes are not supported",
+        ));
+    }
+    let written = crate::filelike::write_at(filelike, buf, offset)?;
+    if flags.contains(WriteFlags::SYNC) {
+        sync_all(filelike)?;
+    } else if flags.contains(WriteFlags::DSYNC) {
+        sync_data(filelike)?;
+    }
+    Ok(written)
 }
 
 /// Implement [`crate::WriteAt::copy_from`].
+///
+/// When `input` is backed by a file, this uses the platform's accelerated
+/// file-to-file copy, such as `copy_file_range` on Linux, or a large buffer
+/// otherwise. When it isn't, the data is streamed through a buffer.
 #[inline]
-pub fn copy_from<'f, Filelike: AsFilelike, R: ReadAt>(
+pub fn copy_from<'f, Filelike: AsFilelike, R: ReadAt + ?Sized>(
     filelike: &Filelike,
     offset: u64,
     input: &R,
     input_offset: u64,
     len: u64,
 ) -> io::Result<u64> {
-    let input_view = filelike.as_filelike_view::<File>();
-    let mut input_tmp = &*input_view;
-    let mut output_streamer = BorrowStreamerMut::new(&mut input_tmp, offset);
-    let input_streamer = BorrowStreamer::new(input, input_offset);
+    if let Some(input_file) = input.borrowed_filelike() {
+        if crate::filelike::is_same_file(filelike, &input_file)? {
+            return copy_within(filelike, offset, &input_file, input_offset, len);
+        }
+
+        #[cfg(not(windows))]
+        return crate::rustix::copy_from_file(filelike, offset, &input_file, input_offset, len);
+        #[cfg(windows)]
+        return crate::windows::copy_from_file(filelike, offset, &input_file, input_offset, len);
+    }
+
+    let output_view = filelike.as_filelike_view::<File>();
+    let mut output_streamer = Streamer::new(&*output_view, offset);
+    let input_streamer = Streamer::new(input, input_offset);
     copy(&mut input_streamer.take(len), &mut output_streamer)
 }
 
-/// Implement [`crate::WriteAt::set_len`].
+/// Copy up to `len` bytes from `input` to `filelike`, where both refer to
+/// the same file, with `memmove`-like semantics for overlapping ranges.
+fn copy_within<Filelike: AsFilelike, Input: AsFilelike>(
+    filelike: &Filelike,
+    offset: u64,
+    input: &Input,
+    input_offset: u64,
+    len: u64,
+) -> io::Result<u64> {
+    let input_len = crate::filelike::metadata(input)?.len();
+    let len = min(len, input_len.saturating_sub(input_offset));
+    if len == 0 || offset == input_offset {
+        return Ok(len);
+    }
+
+    // If the ranges don't overlap, any copy will do.
+    if offset >= input_offset.saturating_add(len) || input_offset >= offset.saturating_add(len) {
+        #[cfg(not(windows))]
+        return crate::rustix::copy_from_file(filelike, offset, input, input_offset, len);
+        #[cfg(windows)]
+        return crate::windows::copy_from_file(filelike, offset, input, input_offset, len);
+    }
+
+    // If the destination is before the source, copying forwards only
+    // overwrites data which has already been read.
+    if offset < input_offset {
+        return copy_buffered(filelike, offset, input, input_offset, len);
+    }
+
+    // Otherwise, copy backwards, so that we only overwrite data which has
+    // already been read.
+    let mut buf = vec![0_u8; min(len, FILE_COPY_BUF_SIZE) as usize];
+    let mut remaining = len;
+    while remaining > 0 {
+        let chunk = min(remaining, buf.len() as u64);
+        remaining -= chunk;
+        let buf = &mut buf[..chunk as usize];
+        crate::filelike::read_exact_at(input, buf, input_offset + remaining)?;
+        crate::filelike::write_all_at(filelike, buf, offset + remaining)?;
+    }
+    Ok(len)
+}
+
+/// Copy up to `len` bytes from `input` to `filelike` by reading into a
+/// large buffer and writing it out, stopping early if `input` reaches its
+/// end.
+///
+/// This is the portable file-to-file copy, for platforms and filesystems
+/// without anything better.
+pub(crate) fn copy_buffered<Filelike: AsFilelike, Input: AsFilelike>(
+    filelike: &Filelike,
+    mut offset: u64,
+    input: &Input,
+    mut input_offset: u64,
+    len: u64,
+) -> io::Result<u64> {
+    let mut buf = vec![0_u8; min(len, FILE_COPY_BUF_SIZE) as usize];
+    let mut copied = 0;
+    while copied < len {
+        let chunk = min(len - copied, buf.len() as u64) as usize;
+        let nread = match crate::filelike::read_at(input, &mut buf[..chunk], input_offset) {
+            Ok(0) => break,
+            Ok(nread) => nread,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        };
+        crate::filelike::write_all_at(filelike, &buf[..nread], offset)?;
+        offset = offset
+            .checked_add(nread as u64)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "offset overflow"))?;
+        input_offset = input_offset
+            .checked_add(nread as u64)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "offset overflow"))?;
+        copied += nread as u64;
+    }
+    Ok(copied)
+}
+
+/// Implement [`crate::WriteAt::allocate`].
+#[inline]
+pub fn allocate<'f, Filelike: AsFilelike>(
+    filelike: &Filelike,
+    offset: u64,
+    len: u64,
+) -> io::Result<()> {
+    #[cfg(not(windows))]
+    return crate::rustix::allocate(filelike, offset, len);
+    #[cfg(windows)]
+    return crate::windows::allocate(filelike, offset, len);
+}
+
+/// Implement [`crate::WriteAt::reserve_at`].
+#[inline]
+pub fn reserve_at<'f, Filelike: AsFilelike>(
+    filelike: &Filelike,
+    offset: u64,
+    len: u64,
+) -> io::Result<()> {
+    #[cfg(not(windows))]
+    return crate::rustix::reserve_at(filelike, offset, len);
+    #[cfg(windows)]
+    return crate::windows::reserve_at(filelike, offset, len);
+}
+
+/// Implement [`crate::WriteAt::punch_hole`].
+pub fn punch_hole<'f, Filelike: AsFilelike>(
+    filelike: &Filelike,
+    offset: u64,
+    len: u64,
+) -> io::Result<()> {
+    let file_len = crate::filelike::metadata(filelike)?.len();
+    let len = min(len, file_len.saturating_sub(offset));
+    if len == 0 {
+        return Ok(());
+    }
+
+    #[cfg(not(windows))]
+    let punched = crate::rustix::punch_hole(filelike, offset, len)?;
+    #[cfg(windows)]
+    let punched = crate::windows::punch_hole(filelike, offset, len)?;
+
+    if !punched {
+        write_zeros(filelike, offset, len)?;
+    }
+    Ok(())
+}
+
+/// Implement [`crate::WriteAt::zero_range`].
+pub fn zero_range<'f, Filelike: AsFilelike>(
+    filelike: &Filelike,
+    offset: u64,
+    len: u64,
+) -> io::Result<()> {
+    let end = offset
+        .checked_add(len)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "offset overflow"))?;
+    if len == 0 {
+        return Ok(());
+    }
+
+    #[cfg(not(windows))]
+    if crate::rustix::zero_range(filelike, offset, len)? {
+        return Ok(());
+    }
+
+    // Otherwise, punch a hole, which zeros the part of the range within the
+    // file, and then reallocate the range, which extends the file if needed.
+    let file_len = crate::filelike::metadata(filelike)?.len();
+    punch_hole(filelike, offset, len)?;
+    match allocate(filelike, offset, len) {
+        Err(err) if err.kind() == io::ErrorKind::Unsupported => {
+            if end > file_len {
+                set_len(filelike, end)?;
+            }
+            Ok(())
+        }
+        result => result,
+    }
+}
+
+/// Overwrite `len` bytes at `offset` in `filelike` with zeros, from a buffer
+/// sized from the file's block size.
+pub(crate) fn write_zeros<Filelike: AsFilelike>(
+    filelike: &Filelike,
+    mut offset: u64,
+    mut len: u64,
+) -> io::Result<()> {
+    let blksize = crate::filelike::metadata(filelike)?.blksize().max(1);
+    let buf_size = FILE_COPY_BUF_SIZE.next_multiple_of(blksize);
+    let zeros = vec![0_u8; min(len, buf_size) as usize];
+    while len != 0 {
+        // Align the ends of the writes to the block size.
+        let chunk = min(len, buf_size - offset % blksize);
+        crate::filelike::write_all_at(filelike, &zeros[..chunk as usize], offset)?;
+        offset += chunk;
+        len -= chunk;
+    }
+    Ok(())
+}
+
+/// Implement [`crate::Resize::set_len`].
 #[inline]
 pub fn set_len<'f, Filelike: AsFilelike>(filelike: &Filelike, size: u64) -> io::Result<()> {
     filelike.as_filelike_view::<File>().set_len(size)
 }
+
+/// Implement [`crate::WriteAt::sync_all`].
+#[inline]
+pub fn sync_all<'f, Filelike: AsFilelike>(filelike: &Filelike) -> io::Result<()> {
+    filelike.as_filelike_view::<File>().sync_all()
+}
+
+/// Implement [`crate::WriteAt::sync_data`].
+#[inline]
+pub fn sync_data<'f, Filelike: AsFilelike>(filelike: &Filelike) -> io::Result<()> {
+    filelike.as_filelike_view::<File>().sync_data()
+}
+
+/// Implement [`crate::WriteAt::sync_range`].
+pub fn sync_range<'f, Filelike: AsFilelike>(
+    filelike: &Filelike,
+    offset: u64,
+    len: u64,
+    mode: SyncRangeMode,
+) -> io::Result<()> {
+    if len == 0 {
+        return Ok(());
+    }
+
+    #[cfg(target_os = "linux")]
+    if crate::rustix::sync_file_range(filelike, offset, len, mode)? {
+        return Ok(());
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = (offset, mode);
+
+    sync_data(filelike)
+}
+
+/// Implement [`crate::WriteAt::clone_range_from`].
+#[inline]
+pub fn clone_range_from<'f, Filelike: AsFilelike, R: ReadAt + ?Sized>(
+    filelike: &Filelike,
+    offset: u64,
+    input: &R,
+    input_offset: u64,
+    len: u64,
+) -> io::Result<CloneOutcome> {
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    if let Some(input_fd) = input.borrowed_filelike() {
+        if let Some(cloned) =
+            crate::rustix::clone_range(filelike, offset, &input_fd, input_offset, len)?
+        {
+            return Ok(CloneOutcome::Cloned(cloned));
+        }
+    }
+
+    copy_from(filelike, offset, input, input_offset, len).map(CloneOutcome::Copied)
+}
+
+/// Implement [`crate::WriteAt::dedupe_range_from`].
+pub fn dedupe_range_from<'f, Filelike: AsFilelike, R: ReadAt + ?Sized>(
+    filelike: &Filelike,
+    offset: u64,
+    input: &R,
+    input_offset: u64,
+    len: u64,
+) -> io::Result<u64> {
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    if let Some(input_fd) = input.borrowed_filelike() {
+        return crate::rustix::dedupe_range(filelike, offset, &input_fd, input_offset, len);
+    }
+    #[cfg(not(any(target_os = "android", target_os = "linux")))]
+    let _ = (filelike, offset, input, input_offset, len);
+
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "dedupe_range_from is not supported",
+    ))
+}
@@ -0,0 +1,127 @@
+//! The error and result types used by the array traits.
+//!
+//! With the `std` feature, which is enabled by default, these are re-exports
+//! of the types in [`std::io`]. Without it, they're minimal replacements,
+//! which carry an [`ErrorKind`] and a static message.
+
+#[cfg(not(feature = "std"))]
+use core::fmt;
+
+#[cfg(feature = "std")]
+pub use std::io::{Error, ErrorKind, Result};
+
+/// A specialized [`Result`](core::result::Result) type for array
+/// operations.
+#[cfg(not(feature = "std"))]
+pub type Result<T> = core::result::Result<T, Error>;
+
+/// The error type for array operations.
+///
+/// This is a minimal version of `std::io::Error`. It only records an
+/// [`ErrorKind`] and a static message, so it doesn't need an allocator.
+#[cfg(not(feature = "std"))]
+#[derive(Debug)]
+pub struct Error {
+    kind: ErrorKind,
+    message: &'static str,
+}
+
+#[cfg(not(feature = "std"))]
+impl Error {
+    /// Creates a new error from a known kind of error and a message.
+    #[inline]
+    #[must_use]
+    pub const fn new(kind: ErrorKind, message: &'static str) -> Self {
+        Self { kind, message }
+    }
+
+    /// Returns the kind of this error.
+    #[inline]
+    #[must_use]
+    pub const fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl From<ErrorKind> for Error {
+    #[inline]
+    fn from(kind: ErrorKind) -> Self {
+        Self::new(kind, kind.as_str())
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message)
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl core::error::Error for Error {}
+
+/// A list specifying general categories of array errors.
+///
+/// This is a subset of `std::io::ErrorKind`, with the kinds which the array
+/// traits and the in-memory arrays report.
+#[cfg(not(feature = "std"))]
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    /// The operation lacked the necessary privileges to complete.
+    PermissionDenied,
+
+    /// An entity already exists.
+    AlreadyExists,
+
+    /// The operation needs to block to complete, but the blocking operation
+    /// was requested to not occur.
+    WouldBlock,
+
+    /// A parameter was incorrect.
+    InvalidInput,
+
+    /// A write returned `Ok(0)`, so the whole buffer couldn't be written.
+    WriteZero,
+
+    /// The operation was interrupted, and can typically be retried.
+    Interrupted,
+
+    /// The operation isn't supported by this array.
+    Unsupported,
+
+    /// An allocation needed to complete the operation failed.
+    OutOfMemory,
+
+    /// The array ended before the operation could be completed.
+    UnexpectedEof,
+
+    /// A custom error that doesn't fall under any other kind.
+    Other,
+}
+
+#[cfg(not(feature = "std"))]
+impl ErrorKind {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::PermissionDenied => "permission denied",
+            Self::AlreadyExists => "entity already exists",
+            Self::WouldBlock => "operation would block",
+            Self::InvalidInput => "invalid input parameter",
+            Self::WriteZero => "write zero",
+            Self::Interrupted => "operation interrupted",
+            Self::Unsupported => "unsupported",
+            Self::OutOfMemory => "out of memory",
+            Self::UnexpectedEof => "unexpected end of file",
+            Self::Other => "other error",
+        }
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
@@ -0,0 +1,45 @@
+//! Utilities for working with lists of [`IoSlice`]s and [`IoSliceMut`]s.
+//!
+//! These help with implementing [`WriteAt::write_all_vectored_at`] and
+//! [`ReadAt::read_exact_vectored_at`] for custom arrays, which need to skip
+//! over the data transferred so far after a partial write or read.
+//!
+//! They're implemented with [`IoSlice::advance_slices`] and
+//! [`IoSliceMut::advance_slices`].
+//!
+//! [`WriteAt::write_all_vectored_at`]: crate::WriteAt::write_all_vectored_at
+//! [`ReadAt::read_exact_vectored_at`]: crate::ReadAt::read_exact_vectored_at
+
+use std::io::{IoSlice, IoSliceMut};
+
+/// Skip over the first `n` bytes of `bufs`, and return the buffers which
+/// remain.
+///
+/// Buffers which are consumed entirely are removed, and the first remaining
+/// buffer is shortened to start at the first byte not skipped over.
+///
+/// # Panics
+///
+/// Panics if `n` is greater than the total length of `bufs`.
+#[inline]
+pub fn advance<'a, 'b>(mut bufs: &'b mut [IoSlice<'a>], n: usize) -> &'b mut [IoSlice<'a>] {
+    IoSlice::advance_slices(&mut bufs, n);
+    bufs
+}
+
+/// Skip over the first `n` bytes of `bufs`, and return the buffers which
+/// remain.
+///
+/// This is [`advance`] for [`IoSliceMut`]s.
+///
+/// # Panics
+///
+/// Panics if `n` is greater than the total length of `bufs`.
+#[inline]
+pub fn advance_mut<'a, 'b>(
+    mut bufs: &'b mut [IoSliceMut<'a>],
+    n: usize,
+) -> &'b mut [IoSliceMut<'a>] {
+    IoSliceMut::advance_slices(&mut bufs, n);
+    bufs
+}
@@ -0,0 +1,247 @@
+//! Iterators over the contents of arrays.
+
+use crate::arrays::DEFAULT_BLKSIZE;
+use crate::{ReadAt, Streamer};
+use std::io::{self, BufRead};
+
+/// An iterator over the bytes of an array, starting at an offset.
+///
+/// This is created by [`ReadAt::bytes_at`]. It reads from the array in
+/// chunks of [`Metadata::blksize`] bytes, so it doesn't perform an I/O
+/// operation per byte. It ends at the end of the array, or after yielding
+/// an error.
+///
+/// [`Metadata::blksize`]: crate::Metadata::blksize
+pub struct BytesAt<'a, A: ?Sized> {
+    array: &'a A,
+    offset: u64,
+    buf: Vec<u8>,
+    pos: usize,
+    done: bool,
+}
+
+impl<'a, A: ReadAt + ?Sized> BytesAt<'a, A> {
+    #[inline]
+    pub(crate) fn new(array: &'a A, offset: u64) -> Self {
+        Self {
+            array,
+            offset,
+            buf: Vec::new(),
+            pos: 0,
+            done: false,
+        }
+    }
+
+    /// Read the next chunk into the buffer, returning `false` at the end of
+    /// the array.
+    fn fill(&mut self) -> io::Result<bool> {
+        if self.buf.capacity() == 0 {
+            let blksize = match self.array.metadata()?.blksize() {
+                0 => DEFAULT_BLKSIZE,
+                blksize => blksize,
+            };
+            self.buf = Vec::with_capacity(usize::try_from(blksize).unwrap_or(usize::MAX));
+        }
+        self.buf.resize(self.buf.capacity(), 0);
+        self.pos = 0;
+        loop {
+            match self.array.read_at(&mut self.buf, self.offset) {
+                Ok(n) => {
+                    self.buf.truncate(n);
+                    self.offset = self.offset.checked_add(n as u64).ok_or_else(|| {
+                        io::Error::new(io::ErrorKind::InvalidInput, "offset overflow")
+                    })?;
+                    return Ok(n != 0);
+                }
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => (),
+                Err(err) => {
+                    self.buf.clear();
+                    return Err(err);
+                }
+            }
+        }
+    }
+}
+
+impl<A: ReadAt + ?Sized> Iterator for BytesAt<'_, A> {
+    type Item = io::Result<u8>;
+
+    fn next(&mut self) -> Option<io::Result<u8>> {
+        if self.pos == self.buf.len() {
+            if self.done {
+                return None;
+            }
+            match self.fill() {
+                Ok(true) => (),
+                Ok(false) => {
+                    self.done = true;
+                    return None;
+                }
+                Err(err) => {
+                    self.done = true;
+                    return Some(Err(err));
+                }
+            }
+        }
+        let byte = self.buf[self.pos];
+        self.pos += 1;
+        Some(Ok(byte))
+    }
+}
+
+/// An iterator over fixed-size chunks of an array, starting at an offset.
+///
+/// This is created by [`ReadAt::chunks_at`]. Each chunk is `chunk_size`
+/// bytes, except that the last chunk before the end of the array may be
+/// shorter. The end of the array is found by reading, rather than from
+/// [`Array::metadata`], so if the array grows during iteration, the new data
+/// is included. The iterator ends after yielding an error.
+///
+/// [`Array::metadata`]: crate::Array::metadata
+pub struct ChunksAt<'a, A: ?Sized> {
+    array: &'a A,
+    offset: u64,
+    chunk_size: usize,
+    done: bool,
+}
+
+impl<'a, A: ReadAt + ?Sized> ChunksAt<'a, A> {
+    #[inline]
+    pub(crate) fn new(array: &'a A, offset: u64, chunk_size: usize) -> Self {
+        assert!(chunk_size != 0, "chunk size must be non-zero");
+        Self {
+            array,
+            offset,
+            chunk_size,
+            done: false,
+        }
+    }
+
+    /// Read the next chunk into `buf`, reusing its allocation, and return
+    /// the length of the chunk.
+    ///
+    /// `buf` is resized to the length of the chunk. This returns `None` at
+    /// the end of the array, like [`Iterator::next`].
+    pub fn next_into(&mut self, buf: &mut Vec<u8>) -> Option<io::Result<usize>> {
+        if self.done {
+            return None;
+        }
+        buf.resize(self.chunk_size, 0);
+        let filled = match self.array.read_full_at(buf, self.offset) {
+            Ok(filled) => filled,
+            Err(err) => {
+                self.done = true;
+                return Some(Err(err));
+            }
+        };
+        // `read_full_at` checks that the end of what it read doesn't
+        // overflow.
+        self.offset += filled as u64;
+        buf.truncate(filled);
+        if filled == 0 {
+            return None;
+        }
+        Some(Ok(filled))
+    }
+}
+
+impl<A: ReadAt + ?Sized> Iterator for ChunksAt<'_, A> {
+    type Item = io::Result<Vec<u8>>;
+
+    #[inline]
+    fn next(&mut self) -> Option<io::Result<Vec<u8>>> {
+        let mut buf = Vec::new();
+        self.next_into(&mut buf).map(|result| result.map(|_| buf))
+    }
+}
+
+/// An iterator over the lines of an array, starting at an offset.
+///
+/// This is created by [`ReadAt::lines_at`]. Lines are split on `\n`, and a
+/// `\r\n` line ending is stripped, as with [`BufRead::lines`]. A line which
+/// isn't valid UTF-8 is reported as an [`io::ErrorKind::InvalidData`] error,
+/// and iteration continues with the next line. The iterator ends after any
+/// other error.
+pub struct LinesAt<'a, A: ?Sized> {
+    stream: Streamer<&'a A>,
+    offset: u64,
+    done: bool,
+}
+
+impl<'a, A: ReadAt + ?Sized> LinesAt<'a, A> {
+    #[inline]
+    pub(crate) fn new(array: &'a A, offset: u64) -> Self {
+        Self {
+            stream: Streamer::new(array, offset),
+            offset,
+            done: false,
+        }
+    }
+
+    /// Return an iterator which also yields the offset of the start of each
+    /// line.
+    #[inline]
+    pub fn with_offsets(self) -> LinesWithOffsetsAt<'a, A> {
+        LinesWithOffsetsAt { lines: self }
+    }
+
+    fn next_line(&mut self) -> Option<(u64, io::Result<String>)> {
+        if self.done {
+            return None;
+        }
+        let start = self.offset;
+        let mut line = Vec::new();
+        match self.stream.read_until(b'\n', &mut line) {
+            Ok(0) => {
+                self.done = true;
+                return None;
+            }
+            Ok(n) => self.offset += n as u64,
+            Err(err) => {
+                self.done = true;
+                return Some((start, Err(err)));
+            }
+        }
+        if line.last() == Some(&b'\n') {
+            line.pop();
+            if line.last() == Some(&b'\r') {
+                line.pop();
+            }
+        }
+        let line = String::from_utf8(line).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("line at offset {start} is not valid UTF-8"),
+            )
+        });
+        Some((start, line))
+    }
+}
+
+impl<A: ReadAt + ?Sized> Iterator for LinesAt<'_, A> {
+    type Item = io::Result<String>;
+
+    #[inline]
+    fn next(&mut self) -> Option<io::Result<String>> {
+        self.next_line().map(|(_, line)| line)
+    }
+}
+
+/// An iterator over the lines of an array and their offsets.
+///
+/// This is created by [`LinesAt::with_offsets`], and yields the offset of
+/// the start of each line along with the line.
+pub struct LinesWithOffsetsAt<'a, A: ?Sized> {
+    lines: LinesAt<'a, A>,
+}
+
+impl<A: ReadAt + ?Sized> Iterator for LinesWithOffsetsAt<'_, A> {
+    type Item = io::Result<(u64, String)>;
+
+    #[inline]
+    fn next(&mut self) -> Option<io::Result<(u64, String)>> {
+        self.lines
+            .next_line()
+            .map(|(offset, line)| line.map(|line| (offset, line)))
+    }
+}
@@ -1,56 +1,191 @@
 //! Random-access I/O
 //!
-//! This crate defines [`ReadAt`], [`WriteAt`], and [`EditAt`] traits which
-//! define interfaces to random-access or seekable devices, such as normal
-//! files, block devices, disk partitions, and memory buffers.
+//! This crate defines [`ReadAt`], [`WriteAt`], [`Resize`], and [`EditAt`]
+//! traits which define interfaces to random-access or seekable devices, such
+//! as normal files, block devices, disk partitions, and memory buffers.
 //!
 //! It also defines [`ArrayReader`], [`ArrayWriter`], and [`ArrayEditor`] types
-//! which implement the above traits and and can be constructed from any
-//! file-like type.  On Posix-ish platforms, including limited support for
-//! WASI, these types just contain a single file descriptor (and implement
+//! which implement the above traits and can be constructed from any file-like
+//! type.  On Posix-ish platforms, including limited support for WASI, these
+//! file-backed types just contain a single file descriptor (and implement
 //! [`AsRawFd`]), plus any resources needed to safely hold the file descriptor
 //! live. On Windows, they contain a single file handle (and implement
 //! [`AsRawHandle`]).
 //!
+//! The array types are generic over a [`Backend`], which defaults to
+//! [`FileBackend`]. With [`MemBackend`], created by [`ArrayReader::in_memory`]
+//! or from a `Vec<u8>`, they hold their contents in memory instead, and have
+//! no file descriptor or handle.
+//!
+//! The file-backed array types also implement io-lifetimes' [`AsFilelike`], so they can
+//! be passed directly to the functions in [`filelike`], and can be converted
+//! to and from owned file descriptors or handles with [`IntoFilelike`] and
+//! [`FromFilelike`] (or `TryFrom`, for [`ArrayWriter`], which validates that
+//! the file isn't in append mode).
+//!
+//! Files opened in append mode can be wrapped in an [`ArrayAppender`]
+//! instead, which writes at the end and reports where each write landed.
+//!
+//! The traits, their implementations for `[u8]`, `Vec<u8>`, and `[u8; N]`,
+//! and the in-memory [`SparseMemArray`], [`ZeroArray`], and
+//! [`PatternArray`] don't need an operating system, and are also available
+//! without the `std` feature, which is enabled by default, in `no_std`
+//! environments with `alloc`. Without `std`, errors are reported with the
+//! minimal types in [`io`], and the methods which use types from `std`, such
+//! as the vectored methods and [`Array::advise`], aren't available.
+//!
+//! [`AsFilelike`]: io_lifetimes::AsFilelike
+//! [`IntoFilelike`]: io_lifetimes::IntoFilelike
+//! [`FromFilelike`]: io_lifetimes::FromFilelike
 //! [`AsRawFd`]: https://doc.rust-lang.org/std/os/unix/io/trait.AsRawFd.html
 //! [`AsRawHandle`]: https://doc.rust-lang.org/std/os/windows/io/trait.AsRawHandle.html
 
 #![deny(missing_docs)]
+#![cfg_attr(not(feature = "std"), no_std)]
 #![cfg_attr(can_vector, feature(can_vector))]
 #![cfg_attr(write_all_vectored, feature(write_all_vectored))]
 
+extern crate alloc;
+
+#[cfg(feature = "std")]
+mod aligned;
+#[cfg(feature = "std")]
+mod anonymous;
+#[cfg(feature = "std")]
+mod appender;
+#[cfg(feature = "std")]
+mod array_slice;
 mod arrays;
-mod borrow_streamer;
+#[cfg(feature = "async")]
+mod async_streamer;
+#[cfg(feature = "async")]
+mod asynchronous;
+#[cfg(feature = "std")]
+mod backend;
+#[cfg(feature = "std")]
+pub mod bulk;
+#[cfg(feature = "bytes")]
+mod bytes;
+#[cfg(feature = "std")]
+mod cache;
+#[cfg(feature = "std")]
+mod chain;
+#[cfg(feature = "std")]
+mod ext;
+#[cfg(feature = "std")]
 mod files;
-#[cfg(feature = "io-streams")]
-mod owned_streamer;
-#[cfg(not(windows))]
+pub mod io;
+#[cfg(feature = "std")]
+pub mod iovec;
+#[cfg(feature = "std")]
+mod iter;
+#[cfg(feature = "std")]
+mod lock;
+#[cfg(feature = "std")]
+mod locked;
+#[cfg(feature = "mmap")]
+mod mmap;
+mod pattern;
+#[cfg(feature = "positioned-io")]
+mod positioned;
+#[cfg(feature = "std")]
+mod readahead;
+#[cfg(all(feature = "std", not(windows)))]
 mod rustix;
+mod search;
 mod slice;
-#[cfg(windows)]
+mod sparse;
+#[cfg(feature = "std")]
+mod stats;
+#[cfg(feature = "std")]
+mod streamer;
+#[cfg(feature = "std")]
+pub mod testing;
+#[cfg(feature = "std")]
+mod trace;
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+mod uring;
+#[cfg(all(feature = "std", windows))]
 mod windows;
 
-pub use arrays::{Array, ArrayEditor, ArrayReader, ArrayWriter, EditAt, Metadata, ReadAt, WriteAt};
+#[cfg(feature = "std")]
+pub use aligned::{read_aligned_at, write_aligned_at, AlignedBuf};
+#[cfg(feature = "std")]
+pub use anonymous::{anonymous, anonymous_in, anonymous_in_dir};
+#[cfg(feature = "std")]
+pub use appender::ArrayAppender;
+#[cfg(feature = "std")]
+pub use array_slice::ArraySlice;
+#[cfg(all(feature = "std", any(target_os = "android", target_os = "linux")))]
+pub use arrays::HugePageSize;
+pub use arrays::{
+    Array, ArrayType, CloneOutcome, EditAt, Metadata, ReadAt, Resize, SyncRangeMode, WriteAt,
+    WriteFlags,
+};
+#[cfg(feature = "std")]
+pub use arrays::{ArrayEditor, ArrayReader, ArrayWriter, ReaderRef};
+#[cfg(feature = "async")]
+pub use async_streamer::AsyncStreamerAt;
+#[cfg(feature = "async")]
+pub use asynchronous::{
+    AsyncArray, AsyncReadAt, AsyncResize, AsyncWriteAt, Blocking, BlockingTask,
+};
+#[cfg(feature = "std")]
+pub use backend::{Backend, FileBackend, MemBackend, MemReader};
+#[cfg(feature = "std")]
+pub use cache::CachedArray;
+#[cfg(feature = "std")]
+pub use chain::ChainArray;
+#[cfg(feature = "std")]
+pub use ext::{ReadAtExt, WriteAtExt};
+#[cfg(feature = "std")]
+pub use iter::{BytesAt, ChunksAt, LinesAt, LinesWithOffsetsAt};
+#[cfg(feature = "std")]
+pub use lock::{LockKind, RangeLockGuard};
+#[cfg(feature = "mmap")]
+pub use mmap::MappedArray;
+pub use pattern::{PatternArray, ZeroArray};
+#[cfg(feature = "positioned-io")]
+pub use positioned::{AsPositionedIo, FromPositionedIo};
+#[cfg(feature = "std")]
+pub use readahead::BufReaderAt;
+pub use sparse::SparseMemArray;
+#[cfg(feature = "std")]
+pub use stats::{ArrayStats, OpStats, StatsArray};
+#[cfg(feature = "std")]
+pub use streamer::Streamer;
+#[cfg(feature = "std")]
+pub use trace::{TraceEvent, TracingArray};
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+pub use uring::UringArray;
 
 /// Advice to pass to [`Array::advise`] to describe an expected access pattern.
 ///
 /// This is a re-export of [`system_interface::fs::Advice`].
+#[cfg(feature = "std")]
 pub use system_interface::fs::Advice;
 
 /// Functions for custom implementations of [`ReadAt`] and [`WriteAt`] for
 /// file-like types.
+#[cfg(feature = "std")]
 pub mod filelike {
     // We can't use Windows' `read_at` or `write_at` here because it isn't able to
     // extend the length of a file we can't `reopen` (such as temporary files).
     // However, while `FileIoExt` can't use `seek_write` because it mutates the
     // current position, here we *can* use plain `seek_write` because `ArrayEditor`
     // doesn't expose the current position.
-    pub use crate::files::{advise, copy_from, set_len};
+    #[cfg(feature = "io-streams")]
+    pub use crate::files::read_range_via_stream;
+    pub use crate::files::{
+        advise, allocate, clone_range_from, copy_from, dedupe_range_from, punch_hole,
+        read_at_nowait, readahead, reserve_at, set_len, sync_all, sync_data, sync_range,
+        write_at_with, zero_range,
+    };
     #[cfg(all(not(windows), feature = "io-streams"))]
     pub use crate::rustix::read_via_stream_at;
     #[cfg(not(windows))]
     pub use crate::rustix::{
-        is_read_vectored_at, is_write_vectored_at, metadata, read_at, read_exact_at,
+        is_read_vectored_at, is_same_file, is_write_vectored_at, metadata, read_at, read_exact_at,
         read_exact_vectored_at, read_vectored_at, write_all_at, write_all_vectored_at, write_at,
         write_vectored_at,
     };
@@ -58,7 +193,7 @@ pub mod filelike {
     pub use crate::windows::read_via_stream_at;
     #[cfg(windows)]
     pub use crate::windows::{
-        is_read_vectored_at, is_write_vectored_at, metadata, read_at, read_exact_at,
+        is_read_vectored_at, is_same_file, is_write_vectored_at, metadata, read_at, read_exact_at,
         read_exact_vectored_at, read_vectored_at, write_all_at, write_all_vectored_at, write_at,
         write_vectored_at,
     };
@@ -0,0 +1,128 @@
+//! Advisory byte-range locks.
+
+use io_lifetimes::BorrowedFilelike;
+use std::fmt;
+use std::io;
+
+/// The kind of lock to acquire with [`Array::lock_at`].
+///
+/// [`Array::lock_at`]: crate::Array::lock_at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LockKind {
+    /// A shared lock, which any number of holders may hold at once, as long
+    /// as nobody holds an exclusive lock on an overlapping range.
+    Shared,
+
+    /// An exclusive lock, which excludes all other locks on overlapping
+    /// ranges.
+    Exclusive,
+}
+
+/// A lock on a range of an array, acquired with [`Array::lock_at`] or
+/// [`Array::try_lock_at`]. The lock is released when this is dropped.
+///
+/// [`Array::lock_at`]: crate::Array::lock_at
+/// [`Array::try_lock_at`]: crate::Array::try_lock_at
+pub struct RangeLockGuard<'a> {
+    file: BorrowedFilelike<'a>,
+    offset: u64,
+    len: u64,
+}
+
+impl<'a> RangeLockGuard<'a> {
+    /// Lock the range of `len` bytes at `offset` in `file`, waiting until
+    /// it's available.
+    pub(crate) fn lock(
+        file: BorrowedFilelike<'a>,
+        offset: u64,
+        len: u64,
+        kind: LockKind,
+    ) -> io::Result<Self> {
+        check_range(offset, len)?;
+
+        #[cfg(not(windows))]
+        crate::rustix::lock_range(&file, offset, len, kind, true)?;
+        #[cfg(windows)]
+        crate::windows::lock_range(&file, offset, len, kind, true)?;
+
+        Ok(Self { file, offset, len })
+    }
+
+    /// Lock the range of `len` bytes at `offset` in `file`, returning `None`
+    /// if it's locked by someone else.
+    pub(crate) fn try_lock(
+        file: BorrowedFilelike<'a>,
+        offset: u64,
+        len: u64,
+        kind: LockKind,
+    ) -> io::Result<Option<Self>> {
+        check_range(offset, len)?;
+
+        #[cfg(not(windows))]
+        let locked = crate::rustix::lock_range(&file, offset, len, kind, false)?;
+        #[cfg(windows)]
+        let locked = crate::windows::lock_range(&file, offset, len, kind, false)?;
+
+        Ok(locked.then_some(Self { file, offset, len }))
+    }
+
+    /// Returns the offset of the locked range.
+    #[inline]
+    #[must_use]
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Returns the length of the locked range.
+    #[allow(clippy::len_without_is_empty)]
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Release the lock, reporting any error. Dropping the guard also
+    /// releases the lock, but ignores errors.
+    pub fn unlock(self) -> io::Result<()> {
+        let result = self.unlock_range();
+        std::mem::forget(self);
+        result
+    }
+
+    fn unlock_range(&self) -> io::Result<()> {
+        #[cfg(not(windows))]
+        return crate::rustix::unlock_range(&self.file, self.offset, self.len);
+        #[cfg(windows)]
+        return crate::windows::unlock_range(&self.file, self.offset, self.len);
+    }
+}
+
+impl Drop for RangeLockGuard<'_> {
+    fn drop(&mut self) {
+        let _ = self.unlock_range();
+    }
+}
+
+impl fmt::Debug for RangeLockGuard<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RangeLockGuard")
+            .field("offset", &self.offset)
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+/// Locks on empty ranges aren't meaningful, and Posix-ish platforms would
+/// interpret a zero length as the whole rest of the file.
+fn check_range(offset: u64, len: u64) -> io::Result<()> {
+    if len == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "cannot lock an empty range",
+        ));
+    }
+    offset
+        .checked_add(len)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "offset overflow"))?;
+    Ok(())
+}
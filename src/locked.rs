@@ -0,0 +1,341 @@
+//! Implementations for arrays behind a `Mutex` or an `RwLock`.
+//!
+//! These let in-memory arrays, which need `&mut self` to be written to, be
+//! shared between threads and written to through `&Mutex<T>` or
+//! `&RwLock<T>`. A poisoned lock is reported as an [`io::Error`].
+
+use crate::{Advice, Array, Metadata, ReadAt, Resize, SyncRangeMode, WriteAt, WriteFlags};
+#[cfg(feature = "io-streams")]
+use io_streams::StreamReader;
+use std::io::{self, IoSlice, IoSliceMut};
+use std::sync::{Mutex, PoisonError, RwLock};
+
+fn poisoned<T>(_err: PoisonError<T>) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, "lock poisoned")
+}
+
+/// Implement the array traits for a lock type, where `$read` and `$write`
+/// are the methods which acquire it for reading and for writing.
+macro_rules! locked {
+    ($lock:ident, $read:ident, $write:ident) => {
+        impl<T: Array + ?Sized> Array for $lock<T> {
+            #[inline]
+            fn metadata(&self) -> io::Result<Metadata> {
+                self.$read().map_err(poisoned)?.metadata()
+            }
+
+            #[inline]
+            fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+                self.$read().map_err(poisoned)?.advise(offset, len, advice)
+            }
+
+            #[inline]
+            fn readahead(&self, offset: u64, len: u64) -> io::Result<()> {
+                self.$read().map_err(poisoned)?.readahead(offset, len)
+            }
+        }
+
+        /// Each read holds the lock only for its duration, so streamers and
+        /// other adapters which read through a shared reference may be used
+        /// alongside writers.
+        impl<T: ReadAt + ?Sized> ReadAt for $lock<T> {
+            #[inline]
+            fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+                self.$read().map_err(poisoned)?.read_at(buf, offset)
+            }
+
+            #[inline]
+            fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+                self.$read().map_err(poisoned)?.read_exact_at(buf, offset)
+            }
+
+            #[inline]
+            fn read_full_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+                self.$read().map_err(poisoned)?.read_full_at(buf, offset)
+            }
+
+            #[inline]
+            fn read_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<usize> {
+                self.$read()
+                    .map_err(poisoned)?
+                    .read_vectored_at(bufs, offset)
+            }
+
+            #[inline]
+            fn read_exact_vectored_at(
+                &self,
+                bufs: &mut [IoSliceMut],
+                offset: u64,
+            ) -> io::Result<()> {
+                self.$read()
+                    .map_err(poisoned)?
+                    .read_exact_vectored_at(bufs, offset)
+            }
+
+            #[inline]
+            fn is_read_vectored_at(&self) -> bool {
+                self.$read()
+                    .map_or(false, |inner| inner.is_read_vectored_at())
+            }
+
+            #[inline]
+            fn read_at_nowait(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+                self.$read().map_err(poisoned)?.read_at_nowait(buf, offset)
+            }
+
+            #[inline]
+            fn read_to_vec_at(&self, offset: u64, len: usize) -> io::Result<Vec<u8>> {
+                self.$read().map_err(poisoned)?.read_to_vec_at(offset, len)
+            }
+
+            #[inline]
+            fn read_up_to_vec_at(&self, offset: u64, len: usize) -> io::Result<Vec<u8>> {
+                self.$read()
+                    .map_err(poisoned)?
+                    .read_up_to_vec_at(offset, len)
+            }
+
+            #[cfg(feature = "io-streams")]
+            #[inline]
+            fn read_via_stream_at(&self, offset: u64) -> io::Result<StreamReader> {
+                self.$read().map_err(poisoned)?.read_via_stream_at(offset)
+            }
+
+            #[cfg(feature = "io-streams")]
+            #[inline]
+            fn read_range_via_stream(&self, offset: u64, len: u64) -> io::Result<StreamReader> {
+                self.$read()
+                    .map_err(poisoned)?
+                    .read_range_via_stream(offset, len)
+            }
+        }
+
+        /// Writing through an owned lock doesn't need to lock it, since it's
+        /// borrowed mutably.
+        impl<T: WriteAt + ?Sized> WriteAt for $lock<T> {
+            #[inline]
+            fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
+                self.get_mut().map_err(poisoned)?.write_at(buf, offset)
+            }
+
+            #[inline]
+            fn write_all_at(&mut self, buf: &[u8], offset: u64) -> io::Result<()> {
+                self.get_mut().map_err(poisoned)?.write_all_at(buf, offset)
+            }
+
+            #[inline]
+            fn write_vectored_at(&mut self, bufs: &[IoSlice], offset: u64) -> io::Result<usize> {
+                self.get_mut()
+                    .map_err(poisoned)?
+                    .write_vectored_at(bufs, offset)
+            }
+
+            #[inline]
+            fn write_all_vectored_at(
+                &mut self,
+                bufs: &mut [IoSlice],
+                offset: u64,
+            ) -> io::Result<()> {
+                self.get_mut()
+                    .map_err(poisoned)?
+                    .write_all_vectored_at(bufs, offset)
+            }
+
+            #[inline]
+            fn is_write_vectored_at(&self) -> bool {
+                self.$read()
+                    .map_or(false, |inner| inner.is_write_vectored_at())
+            }
+
+            #[inline]
+            fn write_at_with(
+                &mut self,
+                buf: &[u8],
+                offset: u64,
+                flags: WriteFlags,
+            ) -> io::Result<usize> {
+                self.get_mut()
+                    .map_err(poisoned)?
+                    .write_at_with(buf, offset, flags)
+            }
+
+            #[inline]
+            fn copy_from(
+                &mut self,
+                offset: u64,
+                input: &dyn ReadAt,
+                input_offset: u64,
+                len: u64,
+            ) -> io::Result<u64> {
+                self.get_mut()
+                    .map_err(poisoned)?
+                    .copy_from(offset, input, input_offset, len)
+            }
+
+            #[inline]
+            fn allocate(&mut self, offset: u64, len: u64) -> io::Result<()> {
+                self.get_mut().map_err(poisoned)?.allocate(offset, len)
+            }
+
+            #[inline]
+            fn reserve_at(&mut self, offset: u64, len: u64) -> io::Result<()> {
+                self.get_mut().map_err(poisoned)?.reserve_at(offset, len)
+            }
+
+            #[inline]
+            fn punch_hole(&mut self, offset: u64, len: u64) -> io::Result<()> {
+                self.get_mut().map_err(poisoned)?.punch_hole(offset, len)
+            }
+
+            #[inline]
+            fn zero_range(&mut self, offset: u64, len: u64) -> io::Result<()> {
+                self.get_mut().map_err(poisoned)?.zero_range(offset, len)
+            }
+
+            #[inline]
+            fn fill_at(&mut self, byte: u8, offset: u64, len: u64) -> io::Result<()> {
+                self.get_mut().map_err(poisoned)?.fill_at(byte, offset, len)
+            }
+
+            #[inline]
+            fn sync_all(&self) -> io::Result<()> {
+                self.$read().map_err(poisoned)?.sync_all()
+            }
+
+            #[inline]
+            fn sync_data(&self) -> io::Result<()> {
+                self.$read().map_err(poisoned)?.sync_data()
+            }
+
+            #[inline]
+            fn sync_range(&self, offset: u64, len: u64, mode: SyncRangeMode) -> io::Result<()> {
+                self.$read()
+                    .map_err(poisoned)?
+                    .sync_range(offset, len, mode)
+            }
+        }
+
+        /// Each write holds the lock only for its duration. `copy_from` and
+        /// the methods based on it use the default implementations, which
+        /// lock the array for each chunk, so `input` may read from the same
+        /// lock without deadlocking.
+        impl<T: WriteAt + ?Sized> WriteAt for &$lock<T> {
+            #[inline]
+            fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
+                self.$write().map_err(poisoned)?.write_at(buf, offset)
+            }
+
+            #[inline]
+            fn write_all_at(&mut self, buf: &[u8], offset: u64) -> io::Result<()> {
+                self.$write().map_err(poisoned)?.write_all_at(buf, offset)
+            }
+
+            #[inline]
+            fn write_vectored_at(&mut self, bufs: &[IoSlice], offset: u64) -> io::Result<usize> {
+                self.$write()
+                    .map_err(poisoned)?
+                    .write_vectored_at(bufs, offset)
+            }
+
+            #[inline]
+            fn write_all_vectored_at(
+                &mut self,
+                bufs: &mut [IoSlice],
+                offset: u64,
+            ) -> io::Result<()> {
+                self.$write()
+                    .map_err(poisoned)?
+                    .write_all_vectored_at(bufs, offset)
+            }
+
+            #[inline]
+            fn is_write_vectored_at(&self) -> bool {
+                self.$read()
+                    .map_or(false, |inner| inner.is_write_vectored_at())
+            }
+
+            #[inline]
+            fn write_at_with(
+                &mut self,
+                buf: &[u8],
+                offset: u64,
+                flags: WriteFlags,
+            ) -> io::Result<usize> {
+                self.$write()
+                    .map_err(poisoned)?
+                    .write_at_with(buf, offset, flags)
+            }
+
+            #[inline]
+            fn allocate(&mut self, offset: u64, len: u64) -> io::Result<()> {
+                self.$write().map_err(poisoned)?.allocate(offset, len)
+            }
+
+            #[inline]
+            fn reserve_at(&mut self, offset: u64, len: u64) -> io::Result<()> {
+                self.$write().map_err(poisoned)?.reserve_at(offset, len)
+            }
+
+            #[inline]
+            fn punch_hole(&mut self, offset: u64, len: u64) -> io::Result<()> {
+                self.$write().map_err(poisoned)?.punch_hole(offset, len)
+            }
+
+            #[inline]
+            fn zero_range(&mut self, offset: u64, len: u64) -> io::Result<()> {
+                self.$write().map_err(poisoned)?.zero_range(offset, len)
+            }
+
+            #[inline]
+            fn fill_at(&mut self, byte: u8, offset: u64, len: u64) -> io::Result<()> {
+                self.$write().map_err(poisoned)?.fill_at(byte, offset, len)
+            }
+
+            #[inline]
+            fn sync_all(&self) -> io::Result<()> {
+                self.$read().map_err(poisoned)?.sync_all()
+            }
+
+            #[inline]
+            fn sync_data(&self) -> io::Result<()> {
+                self.$read().map_err(poisoned)?.sync_data()
+            }
+
+            #[inline]
+            fn sync_range(&self, offset: u64, len: u64, mode: SyncRangeMode) -> io::Result<()> {
+                self.$read()
+                    .map_err(poisoned)?
+                    .sync_range(offset, len, mode)
+            }
+        }
+
+        impl<T: Resize + ?Sized> Resize for $lock<T> {
+            #[inline]
+            fn set_len(&mut self, size: u64) -> io::Result<()> {
+                Resize::set_len(self.get_mut().map_err(poisoned)?, size)
+            }
+
+            #[inline]
+            fn grow_to(&mut self, size: u64) -> io::Result<()> {
+                self.get_mut().map_err(poisoned)?.grow_to(size)
+            }
+        }
+
+        /// `grow_to` holds the lock while it checks the length and extends
+        /// the array, so concurrent calls don't shrink it.
+        impl<T: Resize + ?Sized> Resize for &$lock<T> {
+            #[inline]
+            fn set_len(&mut self, size: u64) -> io::Result<()> {
+                Resize::set_len(&mut *self.$write().map_err(poisoned)?, size)
+            }
+
+            #[inline]
+            fn grow_to(&mut self, size: u64) -> io::Result<()> {
+                self.$write().map_err(poisoned)?.grow_to(size)
+            }
+        }
+    };
+}
+
+locked!(Mutex, lock, lock);
+locked!(RwLock, read, write);
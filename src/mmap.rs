@@ -0,0 +1,576 @@
+//! Memory-mapped arrays, and implementations for `memmap2`'s memory maps.
+
+use crate::{anonymous, Advice, Array, Metadata, ReadAt, Resize, WriteAt, WriteFlags};
+use io_lifetimes::{AsFilelike, BorrowedFilelike, FromFilelike, IntoFilelike};
+#[cfg(feature = "io-streams")]
+use io_streams::StreamReader;
+use memmap2::{Mmap, MmapMut};
+use std::cmp::min;
+use std::fs;
+use std::io::{self, IoSlice, IoSliceMut};
+
+/// A random-access input source and output sink backed by a memory map of a
+/// file.
+///
+/// This owns both the file and a shared, writable map of it, and reads and
+/// writes within the map are plain memory copies. [`Resize::set_len`]
+/// unmaps the file, resizes it, and maps it again.
+///
+/// Ranges past the end of the map, which can exist if the file is extended
+/// other than through [`Resize::set_len`], such as by writes past the end
+/// or by another process, are read and written with the file's own I/O
+/// functions, which see the same data as the map. [`MappedArray::remap`]
+/// maps the file's current length.
+///
+/// As with [`Mmap`], mapping a file is unsafe, because other handles to it,
+/// including in other processes, could modify or truncate it while it's
+/// mapped; [`MappedArray::file`] and [`MappedArray::remap`] are `unsafe` for
+/// that reason. [`MappedArray::anonymous`] owns the only handle to its file,
+/// so it's safe.
+#[derive(Debug)]
+pub struct MappedArray {
+    file: fs::File,
+    /// The map of the file, or `None` if the file was empty when mapped.
+    map: Option<MmapMut>,
+}
+
+impl MappedArray {
+    /// Map a file, which must be open for reading and writing, into a
+    /// `MappedArray`.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated, other than through the
+    /// returned `MappedArray`, for as long as it's mapped. This includes
+    /// other handles to the same file, in this process or others. If it's
+    /// modified, reads through the map may observe the data changing under
+    /// them, and if it's truncated, accessing the pages past the new end
+    /// raises `SIGBUS` on Posix-ish platforms.
+    pub unsafe fn file<Filelike: IntoFilelike>(filelike: Filelike) -> io::Result<Self> {
+        let mut array = Self {
+            file: fs::File::from_into_filelike(filelike),
+            map: None,
+        };
+        array.map_file()?;
+        Ok(array)
+    }
+
+    /// Create a temporary anonymous file of `len` zero bytes, and map it into
+    /// a `MappedArray`.
+    pub fn anonymous(len: u64) -> io::Result<Self> {
+        let file = anonymous()?;
+        file.set_len(len)?;
+        // SAFETY: The file is anonymous, and `self` owns the only handle to
+        // it, so it can only be modified or truncated through `self`.
+        unsafe { Self::file(file) }
+    }
+
+    /// Map the current length of the file, replacing the existing map.
+    ///
+    /// # Safety
+    ///
+    /// As with [`MappedArray::file`], the file must not be modified or
+    /// truncated, other than through `self`, for as long as it's mapped.
+    pub unsafe fn remap(&mut self) -> io::Result<()> {
+        self.map_file()
+    }
+
+    /// Flush the writes made through the map to the file with `msync` or
+    /// `FlushViewOfFile`.
+    pub fn flush(&self) -> io::Result<()> {
+        match &self.map {
+            Some(map) => map.flush(),
+            None => Ok(()),
+        }
+    }
+
+    /// Flush the writes made through the map to the range of `len` bytes at
+    /// `offset`, clamped to the map, to the file.
+    pub fn flush_range(&self, offset: u64, len: u64) -> io::Result<()> {
+        match &self.map {
+            Some(map) if len != 0 => match clamp_range(map.len(), offset, len) {
+                (_, 0) => Ok(()),
+                (start, len) => map.flush_range(start, len),
+            },
+            _ => Ok(()),
+        }
+    }
+
+    /// Consume `self`, unmapping the file, and return the underlying `File`.
+    #[inline]
+    pub fn into_file(self) -> fs::File {
+        self.file
+    }
+
+    /// Returns the mapped bytes.
+    #[inline]
+    fn mapped(&self) -> &[u8] {
+        self.map.as_deref().unwrap_or(&[])
+    }
+
+    /// Returns the number of bytes of a `len`-byte access at `offset` which
+    /// are within the map.
+    #[inline]
+    fn within_map(&self, offset: u64, len: usize) -> usize {
+        min(
+            len as u64,
+            (self.mapped().len() as u64).saturating_sub(offset),
+        ) as usize
+    }
+
+    /// Map the current length of the file, replacing the existing map.
+    fn map_file(&mut self) -> io::Result<()> {
+        self.map = None;
+        let len = self.file.metadata()?.len();
+        if len != 0 {
+            // SAFETY: The map is only accessed through `self`, and the
+            // caller of `MappedArray::file` or `MappedArray::remap` promised
+            // that the file is only modified or truncated through `self`.
+            self.map = Some(unsafe { MmapMut::map_mut(&self.file)? });
+        }
+        Ok(())
+    }
+
+    /// Map the file again if an operation on the file changed its length.
+    fn remap_if_resized(&mut self) -> io::Result<()> {
+        if self.file.metadata()?.len() != self.mapped().len() as u64 {
+            self.map_file()?;
+        }
+        Ok(())
+    }
+}
+
+/// Clamp the range of `len` bytes at `offset` to a map of `map_len` bytes,
+/// where a `len` of zero extends to the end, as with `posix_fadvise`.
+fn clamp_range(map_len: usize, offset: u64, len: u64) -> (usize, usize) {
+    let start = min(offset, map_len as u64) as usize;
+    let avail = map_len - start;
+    let len = match len {
+        0 => avail,
+        len => min(len, avail as u64) as usize,
+    };
+    (start, len)
+}
+
+/// Pass `advice` for the range of `len` bytes at `offset` to `madvise`.
+///
+/// `Advice::DontNeed` and `Advice::NoReuse` are ignored, since
+/// `MADV_DONTNEED` can discard the contents of private maps and there is no
+/// `madvise` equivalent of `POSIX_FADV_NOREUSE`.
+#[cfg(unix)]
+fn madvise(
+    map_len: usize,
+    offset: u64,
+    len: u64,
+    advice: Advice,
+    advise_range: impl FnOnce(memmap2::Advice, usize, usize) -> io::Result<()>,
+) -> io::Result<()> {
+    let advice = match advice {
+        Advice::Normal => memmap2::Advice::Normal,
+        Advice::Sequential => memmap2::Advice::Sequential,
+        Advice::Random => memmap2::Advice::Random,
+        Advice::WillNeed => memmap2::Advice::WillNeed,
+        Advice::DontNeed | Advice::NoReuse => return Ok(()),
+    };
+    match clamp_range(map_len, offset, len) {
+        (_, 0) => Ok(()),
+        (start, len) => advise_range(advice, start, len),
+    }
+}
+
+/// Reads and writes access the mapped bytes directly.
+///
+/// If the mapped file is truncated while the map is in use, for example by
+/// another process, accessing the pages past the new end of the file raises
+/// `SIGBUS` on Posix-ish platforms, which these implementations can't detect
+/// or recover from. As with any use of `memmap2`, the file must not be
+/// truncated while it's mapped.
+impl Array for Mmap {
+    #[inline]
+    fn metadata(&self) -> io::Result<Metadata> {
+        self[..].metadata()
+    }
+
+    /// Advises with `madvise` on Posix-ish platforms, and does nothing
+    /// elsewhere.
+    #[cfg(unix)]
+    #[inline]
+    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        madvise(self.len(), offset, len, advice, |advice, start, len| {
+            self.advise_range(advice, start, len)
+        })
+    }
+
+    #[cfg(not(unix))]
+    #[inline]
+    fn advise(&self, _offset: u64, _len: u64, _advice: Advice) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl ReadAt for Mmap {
+    #[inline]
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        self[..].read_at(buf, offset)
+    }
+
+    #[inline]
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+        self[..].read_exact_at(buf, offset)
+    }
+
+    #[inline]
+    fn copy_to<W: WriteAt + ?Sized>(
+        &self,
+        offset: u64,
+        output: &mut W,
+        output_offset: u64,
+        len: u64,
+    ) -> io::Result<u64> {
+        crate::slice::copy_to(&self[..], offset, output, output_offset, len)
+    }
+
+    #[inline]
+    fn read_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<usize> {
+        self[..].read_vectored_at(bufs, offset)
+    }
+
+    #[inline]
+    fn read_exact_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<()> {
+        self[..].read_exact_vectored_at(bufs, offset)
+    }
+
+    #[inline]
+    fn is_read_vectored_at(&self) -> bool {
+        self[..].is_read_vectored_at()
+    }
+
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    fn read_via_stream_at(&self, offset: u64) -> io::Result<StreamReader> {
+        self[..].read_via_stream_at(offset)
+    }
+
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    fn read_range_via_stream(&self, offset: u64, len: u64) -> io::Result<StreamReader> {
+        self[..].read_range_via_stream(offset, len)
+    }
+}
+
+/// See the `Array` implementation for [`Mmap`] for the hazard of truncating a
+/// mapped file.
+impl Array for MmapMut {
+    #[inline]
+    fn metadata(&self) -> io::Result<Metadata> {
+        self[..].metadata()
+    }
+
+    /// Advises with `madvise` on Posix-ish platforms, and does nothing
+    /// elsewhere.
+    #[cfg(unix)]
+    #[inline]
+    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        madvise(self.len(), offset, len, advice, |advice, start, len| {
+            self.advise_range(advice, start, len)
+        })
+    }
+
+    #[cfg(not(unix))]
+    #[inline]
+    fn advise(&self, _offset: u64, _len: u64, _advice: Advice) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl ReadAt for MmapMut {
+    #[inline]
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        self[..].read_at(buf, offset)
+    }
+
+    #[inline]
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+        self[..].read_exact_at(buf, offset)
+    }
+
+    #[inline]
+    fn copy_to<W: WriteAt + ?Sized>(
+        &self,
+        offset: u64,
+        output: &mut W,
+        output_offset: u64,
+        len: u64,
+    ) -> io::Result<u64> {
+        crate::slice::copy_to(&self[..], offset, output, output_offset, len)
+    }
+
+    #[inline]
+    fn read_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<usize> {
+        self[..].read_vectored_at(bufs, offset)
+    }
+
+    #[inline]
+    fn read_exact_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<()> {
+        self[..].read_exact_vectored_at(bufs, offset)
+    }
+
+    #[inline]
+    fn is_read_vectored_at(&self) -> bool {
+        self[..].is_read_vectored_at()
+    }
+
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    fn read_via_stream_at(&self, offset: u64) -> io::Result<StreamReader> {
+        self[..].read_via_stream_at(offset)
+    }
+
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    fn read_range_via_stream(&self, offset: u64, len: u64) -> io::Result<StreamReader> {
+        self[..].read_range_via_stream(offset, len)
+    }
+}
+
+/// Writes are within the map, which can't grow, as with slices. Syncing
+/// flushes the map with `msync` or `FlushViewOfFile`.
+impl WriteAt for MmapMut {
+    #[inline]
+    fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        self[..].write_at(buf, offset)
+    }
+
+    #[inline]
+    fn write_all_at(&mut self, buf: &[u8], offset: u64) -> io::Result<()> {
+        self[..].write_all_at(buf, offset)
+    }
+
+    #[inline]
+    fn write_vectored_at(&mut self, bufs: &[IoSlice], offset: u64) -> io::Result<usize> {
+        self[..].write_vectored_at(bufs, offset)
+    }
+
+    #[inline]
+    fn write_all_vectored_at(&mut self, bufs: &mut [IoSlice], offset: u64) -> io::Result<()> {
+        self[..].write_all_vectored_at(bufs, offset)
+    }
+
+    #[inline]
+    fn is_write_vectored_at(&self) -> bool {
+        self[..].is_write_vectored_at()
+    }
+
+    /// Writes within the map, and then, if `flags` contains
+    /// [`WriteFlags::DSYNC`] or [`WriteFlags::SYNC`], flushes the range
+    /// written.
+    fn write_at_with(&mut self, buf: &[u8], offset: u64, flags: WriteFlags) -> io::Result<usize> {
+        let written = self[..].write_at(buf, offset)?;
+        if written != 0 && (flags.contains(WriteFlags::DSYNC) || flags.contains(WriteFlags::SYNC)) {
+            // Writes past the end of the map are cut short.
+            let (start, len) = clamp_range(self.len(), offset, written as u64);
+            if len != 0 {
+                self.flush_range(start, len)?;
+            }
+        }
+        Ok(written)
+    }
+
+    #[inline]
+    fn punch_hole(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        self[..].punch_hole(offset, len)
+    }
+
+    #[inline]
+    fn zero_range(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        self[..].zero_range(offset, len)
+    }
+
+    #[inline]
+    fn fill_at(&mut self, byte: u8, offset: u64, len: u64) -> io::Result<()> {
+        self[..].fill_at(byte, offset, len)
+    }
+
+    #[inline]
+    fn sync_all(&self) -> io::Result<()> {
+        self.flush()
+    }
+
+    #[inline]
+    fn sync_data(&self) -> io::Result<()> {
+        self.flush()
+    }
+}
+
+impl Array for MappedArray {
+    #[inline]
+    fn metadata(&self) -> io::Result<Metadata> {
+        Array::metadata(&self.file)
+    }
+
+    /// Advises the map with `madvise` on Posix-ish platforms, and advises the
+    /// file elsewhere.
+    #[cfg(unix)]
+    #[inline]
+    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        match &self.map {
+            Some(map) => Array::advise(map, offset, len, advice),
+            None => Ok(()),
+        }
+    }
+
+    #[cfg(not(unix))]
+    #[inline]
+    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        Array::advise(&self.file, offset, len, advice)
+    }
+
+    #[inline]
+    fn borrowed_filelike(&self) -> Option<BorrowedFilelike<'_>> {
+        Some(self.file.as_filelike())
+    }
+}
+
+impl ReadAt for MappedArray {
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        if offset < self.mapped().len() as u64 {
+            self.mapped().read_at(buf, offset)
+        } else {
+            self.file.read_at(buf, offset)
+        }
+    }
+
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+        let n = self.within_map(offset, buf.len());
+        self.mapped().read_exact_at(&mut buf[..n], offset)?;
+        if n < buf.len() {
+            self.file.read_exact_at(&mut buf[n..], offset + n as u64)?;
+        }
+        Ok(())
+    }
+
+    fn read_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<usize> {
+        match bufs.iter_mut().find(|b| !b.is_empty()) {
+            Some(buf) => self.read_at(buf, offset),
+            None => Ok(0),
+        }
+    }
+
+    fn read_exact_vectored_at(&self, bufs: &mut [IoSliceMut], mut offset: u64) -> io::Result<()> {
+        for buf in bufs {
+            self.read_exact_at(buf, offset)?;
+            offset += buf.len() as u64;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn is_read_vectored_at(&self) -> bool {
+        false
+    }
+
+    /// Streams from the file, which sees the same data as the map.
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    fn read_via_stream_at(&self, offset: u64) -> io::Result<StreamReader> {
+        self.file.read_via_stream_at(offset)
+    }
+
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    fn read_range_via_stream(&self, offset: u64, len: u64) -> io::Result<StreamReader> {
+        self.file.read_range_via_stream(offset, len)
+    }
+}
+
+impl WriteAt for MappedArray {
+    fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        match self.within_map(offset, buf.len()) {
+            0 => self.file.write_at(buf, offset),
+            n => {
+                let map = self.map.as_mut().unwrap();
+                map[offset as usize..][..n].copy_from_slice(&buf[..n]);
+                Ok(n)
+            }
+        }
+    }
+
+    fn write_all_at(&mut self, buf: &[u8], offset: u64) -> io::Result<()> {
+        let n = self.within_map(offset, buf.len());
+        if n != 0 {
+            let map = self.map.as_mut().unwrap();
+            map[offset as usize..][..n].copy_from_slice(&buf[..n]);
+        }
+        if n < buf.len() {
+            self.file.write_all_at(&buf[n..], offset + n as u64)?;
+        }
+        Ok(())
+    }
+
+    fn write_vectored_at(&mut self, bufs: &[IoSlice], offset: u64) -> io::Result<usize> {
+        match bufs.iter().find(|b| !b.is_empty()) {
+            Some(buf) => self.write_at(buf, offset),
+            None => Ok(0),
+        }
+    }
+
+    fn write_all_vectored_at(&mut self, bufs: &mut [IoSlice], mut offset: u64) -> io::Result<()> {
+        for buf in bufs.iter() {
+            self.write_all_at(buf, offset)?;
+            offset += buf.len() as u64;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn is_write_vectored_at(&self) -> bool {
+        false
+    }
+
+    /// Allocates with the file, and maps the file again if it's extended.
+    fn allocate(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        self.file.allocate(offset, len)?;
+        self.remap_if_resized()
+    }
+
+    /// Reserves with the file, which doesn't change its length, so the map
+    /// is unaffected.
+    #[inline]
+    fn reserve_at(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        self.file.reserve_at(offset, len)
+    }
+
+    /// Punches a hole in the file, which the map sees as zeros.
+    #[inline]
+    fn punch_hole(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        self.file.punch_hole(offset, len)
+    }
+
+    /// Zeros the range with the file, and maps the file again if it's
+    /// extended.
+    fn zero_range(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        self.file.zero_range(offset, len)?;
+        self.remap_if_resized()
+    }
+
+    /// Flushes the map, and then syncs the file.
+    fn sync_all(&self) -> io::Result<()> {
+        self.flush()?;
+        self.file.sync_all()
+    }
+
+    /// Flushes the map, and then syncs the file's data.
+    fn sync_data(&self) -> io::Result<()> {
+        self.flush()?;
+        self.file.sync_data()
+    }
+}
+
+impl Resize for MappedArray {
+    /// Unmaps the file, resizes it, and maps it again.
+    fn set_len(&mut self, size: u64) -> io::Result<()> {
+        self.map = None;
+        self.file.set_len(size)?;
+        self.map_file()
+    }
+}
@@ -0,0 +1,392 @@
+//! Arrays with generated contents and no backing storage.
+
+#[cfg(feature = "io-streams")]
+use crate::arrays::check_stream_range;
+use crate::arrays::DEFAULT_BLKSIZE;
+#[cfg(feature = "io-streams")]
+use crate::Streamer;
+use crate::{io, Array, ArrayType, Metadata, ReadAt, Resize, WriteAt};
+use alloc::vec::Vec;
+use core::cmp::min;
+use core::fmt;
+#[cfg(feature = "io-streams")]
+use io_streams::StreamReader;
+#[cfg(feature = "std")]
+use {
+    crate::Advice,
+    std::io::{IoSlice, IoSliceMut},
+};
+
+/// An array of zeros, with no backing storage.
+///
+/// An array created with [`ZeroArray::new`] is read-only, and writing to it
+/// fails with [`io::ErrorKind::PermissionDenied`]. An array created with
+/// [`ZeroArray::sink`] accepts and discards writes, only extending its length
+/// to the end of the furthest write, which makes it a destination for
+/// [`WriteAt::copy_from`] which doesn't keep the data.
+#[derive(Clone, Debug)]
+pub struct ZeroArray {
+    len: u64,
+    blksize: u64,
+    sink: bool,
+}
+
+/// An array of a repeating pattern of bytes, with no backing storage.
+///
+/// The byte at offset `i` is `pattern[i % pattern.len()]`. A `PatternArray`
+/// is read-only, and writing to it fails with
+/// [`io::ErrorKind::PermissionDenied`].
+#[derive(Clone)]
+pub struct PatternArray {
+    pattern: Vec<u8>,
+    len: u64,
+    blksize: u64,
+}
+
+impl ZeroArray {
+    /// Create a read-only array of `len` zeros.
+    #[inline]
+    #[must_use]
+    pub const fn new(len: u64) -> Self {
+        Self {
+            len,
+            blksize: DEFAULT_BLKSIZE,
+            sink: false,
+        }
+    }
+
+    /// Create an empty array which discards the data written to it.
+    ///
+    /// Writes succeed without storing anything, and extend the length of the
+    /// array to the end of the write, so [`Metadata::len`] reports the
+    /// furthest offset written to. The array always reads as zeros.
+    ///
+    /// [`Metadata::len`]: crate::Metadata::len
+    #[inline]
+    #[must_use]
+    pub const fn sink() -> Self {
+        Self {
+            len: 0,
+            blksize: DEFAULT_BLKSIZE,
+            sink: true,
+        }
+    }
+
+    /// Set the block size reported by [`Array::metadata`].
+    #[inline]
+    #[must_use]
+    pub const fn with_blksize(mut self, blksize: u64) -> Self {
+        self.blksize = blksize;
+        self
+    }
+
+    /// Record a write of `len` bytes at `offset`, failing if this isn't a
+    /// sink.
+    fn discard(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        if !self.sink {
+            return Err(read_only("cannot write to a ZeroArray"));
+        }
+        let end = offset
+            .checked_add(len)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "offset overflow"))?;
+        self.len = self.len.max(end);
+        Ok(())
+    }
+}
+
+impl PatternArray {
+    /// Create a read-only array of `len` bytes of `pattern` repeated.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pattern` is empty.
+    #[inline]
+    #[must_use]
+    pub fn new(pattern: Vec<u8>, len: u64) -> Self {
+        assert!(!pattern.is_empty(), "pattern must be non-empty");
+        Self {
+            pattern,
+            len,
+            blksize: DEFAULT_BLKSIZE,
+        }
+    }
+
+    /// Set the block size reported by [`Array::metadata`].
+    #[inline]
+    #[must_use]
+    pub fn with_blksize(mut self, blksize: u64) -> Self {
+        self.blksize = blksize;
+        self
+    }
+
+    /// Returns the pattern.
+    #[inline]
+    #[must_use]
+    pub fn pattern(&self) -> &[u8] {
+        &self.pattern
+    }
+
+    /// Fill `buf` with the pattern, starting at the phase for `offset`.
+    fn fill(&self, buf: &mut [u8], offset: u64) {
+        let phase = (offset % self.pattern.len() as u64) as usize;
+        let (first, rest) = buf.split_at_mut(min(buf.len(), self.pattern.len() - phase));
+        first.copy_from_slice(&self.pattern[phase..][..first.len()]);
+        for chunk in rest.chunks_mut(self.pattern.len()) {
+            chunk.copy_from_slice(&self.pattern[..chunk.len()]);
+        }
+    }
+}
+
+impl fmt::Debug for PatternArray {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PatternArray")
+            .field("pattern_len", &self.pattern.len())
+            .field("len", &self.len)
+            .field("blksize", &self.blksize)
+            .finish()
+    }
+}
+
+/// Returns the number of bytes of a `len`-byte read at `offset` which are
+/// within an array of length `array_len`.
+#[inline]
+fn available(array_len: u64, offset: u64, len: usize) -> usize {
+    min(len as u64, array_len.saturating_sub(offset)) as usize
+}
+
+fn read_only(message: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::PermissionDenied, message)
+}
+
+fn unexpected_eof() -> io::Error {
+    io::Error::new(io::ErrorKind::UnexpectedEof, "failed to fill whole buffer")
+}
+
+impl Array for ZeroArray {
+    fn metadata(&self) -> io::Result<Metadata> {
+        Ok(Metadata {
+            len: self.len,
+            blksize: self.blksize,
+            allocated_len: 0,
+            file_type: ArrayType::Memory,
+            #[cfg(feature = "std")]
+            modified: None,
+        })
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn advise(&self, _offset: u64, _len: u64, _advice: Advice) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Array for PatternArray {
+    fn metadata(&self) -> io::Result<Metadata> {
+        Ok(Metadata {
+            len: self.len,
+            blksize: self.blksize,
+            allocated_len: 0,
+            file_type: ArrayType::Memory,
+            #[cfg(feature = "std")]
+            modified: None,
+        })
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn advise(&self, _offset: u64, _len: u64, _advice: Advice) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl ReadAt for ZeroArray {
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        let len = available(self.len, offset, buf.len());
+        buf[..len].fill(0);
+        Ok(len)
+    }
+
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+        if available(self.len, offset, buf.len()) != buf.len() {
+            return Err(unexpected_eof());
+        }
+        buf.fill(0);
+        Ok(())
+    }
+
+    #[cfg(feature = "std")]
+    fn read_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<usize> {
+        let mut total = 0;
+        for buf in bufs {
+            let len = self.read_at(buf, offset + total as u64)?;
+            total += len;
+            if len < buf.len() {
+                break;
+            }
+        }
+        Ok(total)
+    }
+
+    #[cfg(feature = "std")]
+    fn read_exact_vectored_at(&self, bufs: &mut [IoSliceMut], mut offset: u64) -> io::Result<()> {
+        for buf in bufs {
+            self.read_exact_at(buf, offset)?;
+            offset += buf.len() as u64;
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn is_read_vectored_at(&self) -> bool {
+        true
+    }
+
+    #[inline]
+    fn read_at_nowait(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        self.read_at(buf, offset)
+    }
+
+    #[cfg(feature = "io-streams")]
+    fn read_via_stream_at(&self, offset: u64) -> io::Result<StreamReader> {
+        StreamReader::piped_thread(Box::new(Streamer::owned(self.clone(), offset)))
+    }
+
+    #[cfg(feature = "io-streams")]
+    fn read_range_via_stream(&self, offset: u64, len: u64) -> io::Result<StreamReader> {
+        check_stream_range(self.len, offset, len)?;
+        StreamReader::piped_thread(Box::new(Streamer::bounded(self.clone(), offset, len)))
+    }
+}
+
+impl ReadAt for PatternArray {
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        let len = available(self.len, offset, buf.len());
+        self.fill(&mut buf[..len], offset);
+        Ok(len)
+    }
+
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+        if available(self.len, offset, buf.len()) != buf.len() {
+            return Err(unexpected_eof());
+        }
+        self.fill(buf, offset);
+        Ok(())
+    }
+
+    #[cfg(feature = "std")]
+    fn read_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<usize> {
+        let mut total = 0;
+        for buf in bufs {
+            let len = self.read_at(buf, offset + total as u64)?;
+            total += len;
+            if len < buf.len() {
+                break;
+            }
+        }
+        Ok(total)
+    }
+
+    #[cfg(feature = "std")]
+    fn read_exact_vectored_at(&self, bufs: &mut [IoSliceMut], mut offset: u64) -> io::Result<()> {
+        for buf in bufs {
+            self.read_exact_at(buf, offset)?;
+            offset += buf.len() as u64;
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn is_read_vectored_at(&self) -> bool {
+        true
+    }
+
+    #[inline]
+    fn read_at_nowait(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        self.read_at(buf, offset)
+    }
+
+    #[cfg(feature = "io-streams")]
+    fn read_via_stream_at(&self, offset: u64) -> io::Result<StreamReader> {
+        StreamReader::piped_thread(Box::new(Streamer::owned(self.clone(), offset)))
+    }
+
+    #[cfg(feature = "io-streams")]
+    fn read_range_via_stream(&self, offset: u64, len: u64) -> io::Result<StreamReader> {
+        check_stream_range(self.len, offset, len)?;
+        StreamReader::piped_thread(Box::new(Streamer::bounded(self.clone(), offset, len)))
+    }
+}
+
+impl WriteAt for ZeroArray {
+    #[inline]
+    fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        self.discard(offset, buf.len() as u64)?;
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn write_all_at(&mut self, buf: &[u8], offset: u64) -> io::Result<()> {
+        self.discard(offset, buf.len() as u64)
+    }
+
+    #[cfg(feature = "std")]
+    fn write_vectored_at(&mut self, bufs: &[IoSlice], offset: u64) -> io::Result<usize> {
+        let len = bufs.iter().map(|buf| buf.len()).sum::<usize>();
+        self.discard(offset, len as u64)?;
+        Ok(len)
+    }
+
+    #[cfg(feature = "std")]
+    fn write_all_vectored_at(&mut self, bufs: &mut [IoSlice], offset: u64) -> io::Result<()> {
+        let len = bufs.iter().map(|buf| buf.len() as u64).sum();
+        self.discard(offset, len)
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn is_write_vectored_at(&self) -> bool {
+        true
+    }
+
+    #[inline]
+    fn allocate(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        self.discard(offset, len)
+    }
+
+    /// Does nothing on a sink, since the array is all zeros.
+    #[inline]
+    fn punch_hole(&mut self, _offset: u64, _len: u64) -> io::Result<()> {
+        self.discard(0, 0)
+    }
+
+    #[inline]
+    fn zero_range(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        self.discard(offset, len)
+    }
+
+    #[inline]
+    fn fill_at(&mut self, _byte: u8, offset: u64, len: u64) -> io::Result<()> {
+        self.discard(offset, len)
+    }
+}
+
+/// A sink can be resized; otherwise, resizing fails, as writes do.
+impl Resize for ZeroArray {
+    fn set_len(&mut self, size: u64) -> io::Result<()> {
+        if !self.sink {
+            return Err(read_only("cannot write to a ZeroArray"));
+        }
+        self.len = size;
+        Ok(())
+    }
+}
+
+impl WriteAt for PatternArray {
+    #[inline]
+    fn write_at(&mut self, _buf: &[u8], _offset: u64) -> io::Result<usize> {
+        Err(read_only("cannot write to a PatternArray"))
+    }
+}
@@ -0,0 +1,205 @@
+//! Adapters between this crate's traits and `positioned_io`'s.
+
+use crate::arrays::DEFAULT_BLKSIZE;
+#[cfg(feature = "io-streams")]
+use crate::Streamer;
+use crate::{Advice, Array, ArrayType, Metadata, ReadAt, WriteAt};
+#[cfg(feature = "io-streams")]
+use io_streams::StreamReader;
+use std::fmt;
+#[cfg(feature = "io-streams")]
+use std::io::Read;
+use std::io::{self, IoSliceMut};
+
+/// An adapter which implements `positioned_io`'s `ReadAt`, `WriteAt`, and
+/// `Size` traits for an array.
+///
+/// `positioned_io::WriteAt::flush` does nothing, since arrays don't buffer
+/// writes.
+#[derive(Debug)]
+pub struct AsPositionedIo<A> {
+    inner: A,
+}
+
+/// An adapter which implements this crate's traits for a type implementing
+/// `positioned_io::ReadAt` or `positioned_io::WriteAt`.
+///
+/// `positioned_io` has no way to query the length of a `ReadAt` in general,
+/// so [`Array::metadata`] fails with [`io::ErrorKind::Unsupported`] unless
+/// the adapter was created with [`FromPositionedIo::sized`], which uses
+/// `positioned_io::Size`. `positioned_io` can't resize arrays, so the
+/// adapter doesn't implement [`Resize`].
+///
+/// [`Resize`]: crate::Resize
+pub struct FromPositionedIo<T> {
+    inner: T,
+    size: Option<SizeFn<T>>,
+}
+
+/// `positioned_io::Size::size` for a type.
+type SizeFn<T> = fn(&T) -> io::Result<Option<u64>>;
+
+impl<A> AsPositionedIo<A> {
+    /// Create an `AsPositionedIo` which implements `positioned_io`'s traits
+    /// for `inner`.
+    #[inline]
+    pub fn new(inner: A) -> Self {
+        Self { inner }
+    }
+
+    /// Consume the `AsPositionedIo` and return the inner array.
+    #[inline]
+    pub fn into_inner(self) -> A {
+        self.inner
+    }
+}
+
+impl<T> FromPositionedIo<T> {
+    /// Create a `FromPositionedIo` which implements this crate's traits for
+    /// `inner`, without a known length.
+    #[inline]
+    pub fn new(inner: T) -> Self {
+        Self { inner, size: None }
+    }
+
+    /// Create a `FromPositionedIo` which implements this crate's traits for
+    /// `inner`, with [`Array::metadata`] reporting the length from
+    /// `positioned_io::Size`.
+    #[inline]
+    pub fn sized(inner: T) -> Self
+    where
+        T: positioned_io::Size,
+    {
+        Self {
+            inner,
+            size: Some(T::size),
+        }
+    }
+
+    /// Consume the `FromPositionedIo` and return the inner value.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for FromPositionedIo<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FromPositionedIo")
+            .field("inner", &self.inner)
+            .field("sized", &self.size.is_some())
+            .finish()
+    }
+}
+
+impl<A: ReadAt> positioned_io::ReadAt for AsPositionedIo<A> {
+    #[inline]
+    fn read_at(&self, pos: u64, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.read_at(buf, pos)
+    }
+
+    #[inline]
+    fn read_exact_at(&self, pos: u64, buf: &mut [u8]) -> io::Result<()> {
+        self.inner.read_exact_at(buf, pos)
+    }
+}
+
+impl<A: WriteAt> positioned_io::WriteAt for AsPositionedIo<A> {
+    #[inline]
+    fn write_at(&mut self, pos: u64, buf: &[u8]) -> io::Result<usize> {
+        self.inner.write_at(buf, pos)
+    }
+
+    #[inline]
+    fn write_all_at(&mut self, pos: u64, buf: &[u8]) -> io::Result<()> {
+        self.inner.write_all_at(buf, pos)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// The size is unknown for character devices and other arrays which aren't
+/// files, block devices, or memory.
+impl<A: Array> positioned_io::Size for AsPositionedIo<A> {
+    fn size(&self) -> io::Result<Option<u64>> {
+        let metadata = self.inner.metadata()?;
+        Ok(match metadata.file_type() {
+            ArrayType::File | ArrayType::BlockDevice | ArrayType::Memory => Some(metadata.len()),
+            _ => None,
+        })
+    }
+}
+
+impl<T> Array for FromPositionedIo<T> {
+    fn metadata(&self) -> io::Result<Metadata> {
+        let size = match self.size {
+            Some(size) => size(&self.inner)?,
+            None => None,
+        };
+        let len = size
+            .ok_or_else(|| io::Error::new(io::ErrorKind::Unsupported, "the length is not known"))?;
+        Ok(Metadata {
+            len,
+            blksize: DEFAULT_BLKSIZE,
+            allocated_len: len,
+            file_type: ArrayType::Other,
+            modified: None,
+        })
+    }
+
+    #[inline]
+    fn advise(&self, _offset: u64, _len: u64, _advice: Advice) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<T: positioned_io::ReadAt> ReadAt for FromPositionedIo<T> {
+    #[inline]
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        self.inner.read_at(offset, buf)
+    }
+
+    #[inline]
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+        self.inner.read_exact_at(offset, buf)
+    }
+
+    fn read_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<usize> {
+        match bufs.iter_mut().find(|b| !b.is_empty()) {
+            Some(buf) => self.read_at(buf, offset),
+            None => Ok(0),
+        }
+    }
+
+    fn read_exact_vectored_at(&self, bufs: &mut [IoSliceMut], mut offset: u64) -> io::Result<()> {
+        for buf in bufs {
+            self.read_exact_at(buf, offset)?;
+            offset += buf.len() as u64;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn is_read_vectored_at(&self) -> bool {
+        false
+    }
+
+    /// There's no stream to forward to, so this reads the data from `offset`
+    /// to the end into memory, and streams it from a thread.
+    #[cfg(feature = "io-streams")]
+    fn read_via_stream_at(&self, offset: u64) -> io::Result<StreamReader> {
+        let mut data = Vec::new();
+        Streamer::new(self, offset).read_to_end(&mut data)?;
+        StreamReader::piped_thread(Box::new(io::Cursor::new(data)))
+    }
+}
+
+impl<T: positioned_io::WriteAt> WriteAt for FromPositionedIo<T> {
+    #[inline]
+    fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        self.inner.write_at(offset, buf)
+    }
+}
@@ -0,0 +1,226 @@
+//! Readahead for sequential reads at offsets.
+
+use crate::arrays::DEFAULT_BLKSIZE;
+use crate::{Advice, Array, Metadata, ReadAt};
+#[cfg(feature = "io-streams")]
+use io_streams::StreamReader;
+use std::cmp::min;
+use std::fmt;
+use std::io::{self, IoSliceMut};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// The number of blocks in a window, by default.
+const DEFAULT_WINDOW_BLOCKS: u64 = 16;
+
+/// The number of windows, so that a few readers scanning different parts of
+/// the array don't evict each other's windows.
+const WINDOWS: usize = 4;
+
+/// An array which reads ahead of small reads, so that scanning forward from
+/// an offset with many small reads performs few reads of the inner array.
+///
+/// A read which falls inside one of a few windows of recently read data is
+/// served from memory. Otherwise, a window starting at the block containing
+/// the offset is read from the inner array, usually with a single
+/// [`ReadAt::read_at`] call, and replaces the oldest window. Reads at least as large as a
+/// window go directly to the inner array.
+///
+/// Each window is behind its own mutex, which isn't held while reading from
+/// the inner array, so a `BufReaderAt` can be shared between threads.
+/// Unlike [`CachedArray`], this isn't a general-purpose cache; the windows
+/// aren't updated if the inner array is modified.
+///
+/// [`CachedArray`]: crate::CachedArray
+pub struct BufReaderAt<A> {
+    inner: A,
+    window_blocks: u64,
+    /// The size of a window, or zero if it hasn't been computed yet.
+    window_size: AtomicU64,
+    windows: [Mutex<Window>; WINDOWS],
+    /// The index of the window to replace next.
+    next: AtomicUsize,
+}
+
+#[derive(Default)]
+struct Window {
+    offset: u64,
+    data: Vec<u8>,
+    /// Whether the window ends at the end of the array.
+    eof: bool,
+}
+
+impl<A> BufReaderAt<A> {
+    /// Create a `BufReaderAt` with windows of 16 times the
+    /// [`Metadata::blksize`] of `inner`.
+    ///
+    /// [`Metadata::blksize`]: crate::Metadata::blksize
+    #[inline]
+    pub fn new(inner: A) -> Self {
+        Self::with_window_blocks(inner, DEFAULT_WINDOW_BLOCKS)
+    }
+
+    /// Create a `BufReaderAt` with windows of `window_blocks` times the
+    /// [`Metadata::blksize`] of `inner`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `window_blocks` is zero.
+    ///
+    /// [`Metadata::blksize`]: crate::Metadata::blksize
+    pub fn with_window_blocks(inner: A, window_blocks: u64) -> Self {
+        assert!(window_blocks != 0, "window size must be non-zero");
+        Self {
+            inner,
+            window_blocks,
+            window_size: AtomicU64::new(0),
+            windows: Default::default(),
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    /// Discard the windows, so that later reads see any changes made to the
+    /// inner array.
+    pub fn discard(&self) {
+        for window in &self.windows {
+            *window.lock().unwrap() = Window::default();
+        }
+    }
+
+    /// Consume the `BufReaderAt` and return the inner array.
+    #[inline]
+    pub fn into_inner(self) -> A {
+        self.inner
+    }
+}
+
+impl<A: ReadAt> BufReaderAt<A> {
+    /// Returns the block size of the inner array and the size of a window.
+    fn sizes(&self) -> io::Result<(u64, u64)> {
+        let window_size = self.window_size.load(Ordering::Relaxed);
+        if window_size != 0 {
+            return Ok((window_size / self.window_blocks, window_size));
+        }
+        let blksize = match self.inner.metadata()?.blksize() {
+            0 => DEFAULT_BLKSIZE,
+            blksize => blksize,
+        };
+        let window_size = blksize.saturating_mul(self.window_blocks);
+        self.window_size.store(window_size, Ordering::Relaxed);
+        Ok((blksize, window_size))
+    }
+
+    /// Copy the bytes at `offset` from a window into `buf`, if a window
+    /// contains `offset`.
+    fn read_window(&self, buf: &mut [u8], offset: u64) -> Option<usize> {
+        self.windows.iter().find_map(|window| {
+            let window = window.lock().unwrap();
+            let within = usize::try_from(offset.checked_sub(window.offset)?).ok()?;
+            let avail = window.data.get(within..)?;
+            // The end of a window is only the end of the array if the window
+            // was cut short by it.
+            if avail.is_empty() && !window.eof {
+                return None;
+            }
+            let n = min(avail.len(), buf.len());
+            buf[..n].copy_from_slice(&avail[..n]);
+            Some(n)
+        })
+    }
+
+    /// Read a window starting at `start`, replacing the oldest window.
+    fn fill_window(&self, start: u64, window_size: u64) -> io::Result<()> {
+        let mut data = vec![0; usize::try_from(window_size).unwrap_or(usize::MAX)];
+        let filled = self.inner.read_full_at(&mut data, start)?;
+        let eof = filled < data.len();
+        data.truncate(filled);
+        let index = self.next.fetch_add(1, Ordering::Relaxed) % WINDOWS;
+        *self.windows[index].lock().unwrap() = Window {
+            offset: start,
+            data,
+            eof,
+        };
+        Ok(())
+    }
+}
+
+impl<A: fmt::Debug> fmt::Debug for BufReaderAt<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BufReaderAt")
+            .field("inner", &self.inner)
+            .field("window_blocks", &self.window_blocks)
+            .finish()
+    }
+}
+
+impl<A: Array> Array for BufReaderAt<A> {
+    #[inline]
+    fn metadata(&self) -> io::Result<Metadata> {
+        self.inner.metadata()
+    }
+
+    #[inline]
+    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        self.inner.advise(offset, len, advice)
+    }
+}
+
+impl<A: ReadAt> ReadAt for BufReaderAt<A> {
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        if let Some(n) = self.read_window(buf, offset) {
+            return Ok(n);
+        }
+        let (blksize, window_size) = self.sizes()?;
+        if buf.len() as u64 >= window_size {
+            return self.inner.read_at(buf, offset);
+        }
+        self.fill_window(offset - offset % blksize, window_size)?;
+        Ok(self.read_window(buf, offset).unwrap_or(0))
+    }
+
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+        if self.read_full_at(buf, offset)? != buf.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "failed to fill whole buffer",
+            ));
+        }
+        Ok(())
+    }
+
+    fn read_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<usize> {
+        match bufs.iter_mut().find(|b| !b.is_empty()) {
+            Some(buf) => self.read_at(buf, offset),
+            None => Ok(0),
+        }
+    }
+
+    fn read_exact_vectored_at(&self, bufs: &mut [IoSliceMut], mut offset: u64) -> io::Result<()> {
+        for buf in bufs {
+            self.read_exact_at(buf, offset)?;
+            offset += buf.len() as u64;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn is_read_vectored_at(&self) -> bool {
+        false
+    }
+
+    /// Streams from the inner array, bypassing the windows.
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    fn read_via_stream_at(&self, offset: u64) -> io::Result<StreamReader> {
+        self.inner.read_via_stream_at(offset)
+    }
+
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    fn read_range_via_stream(&self, offset: u64, len: u64) -> io::Result<StreamReader> {
+        self.inner.read_range_via_stream(offset, len)
+    }
+}
@@ -4,73 +4,310 @@
 //! [`ReadAt`]: crate::ReadAt
 //! [`WriteAt`]: crate::WriteAt
 
-use crate::Metadata;
+#[cfg(target_os = "linux")]
+use crate::SyncRangeMode;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use crate::WriteFlags;
+use crate::{ArrayType, LockKind, Metadata};
 use io_lifetimes::AsFilelike;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use std::cmp::min;
 use std::fs::File;
-use std::io::{self, IoSlice, IoSliceMut};
+use std::io::{self, IoSlice, IoSliceMut, Read};
+#[cfg(any(
+    target_os = "android",
+    target_os = "linux",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "macos"
+))]
+use std::os::fd::AsRawFd;
 #[cfg(unix)]
 use std::os::unix::fs::MetadataExt;
-#[cfg(target_os = "wasi")]
-use std::os::unix::fs::MetadataExt;
 use system_interface::fs::FileIoExt;
 #[cfg(feature = "io-streams")]
 use {
-    crate::owned_streamer::OwnedStreamer,
+    crate::streamer::Streamer,
     cap_fs_ext::{OpenOptions, Reopen},
     io_streams::StreamReader,
     std::io::SeekFrom,
 };
 
 /// Implement [`crate::Array::metadata`].
+///
+/// For block devices, `fstat` doesn't report a size, so this queries the
+/// device for its size and logical sector size.
 #[inline]
 pub fn metadata<'a, Filelike: AsFilelike>(filelike: &Filelike) -> io::Result<Metadata> {
-    filelike
-        .as_filelike_view::<File>()
-        .metadata()
-        .map(|meta| Metadata {
-            len: meta.len(),
-            blksize: meta.blksize(),
-        })
+    let file = filelike.as_filelike_view::<File>();
+    let meta = file.metadata()?;
+    let file_type = file_type(&meta);
+    let modified = meta.modified().ok();
+
+    if file_type == ArrayType::BlockDevice {
+        if let Some((len, blksize)) = block_device_size(&file)? {
+            return Ok(Metadata {
+                len,
+                blksize,
+                allocated_len: len,
+                file_type,
+                modified,
+            });
+        }
+    }
+
+    Ok(Metadata {
+        len: meta.len(),
+        #[cfg(unix)]
+        blksize: meta.blksize(),
+        // WASI's `filestat` has no block size.
+        #[cfg(target_os = "wasi")]
+        blksize: crate::arrays::DEFAULT_BLKSIZE,
+
+        // `st_blocks` is always in units of 512 bytes.
+        #[cfg(unix)]
+        allocated_len: meta.blocks().saturating_mul(512),
+        // WASI's `filestat` has no allocation size either.
+        #[cfg(target_os = "wasi")]
+        allocated_len: meta.len(),
+
+        file_type,
+        modified,
+    })
+}
+
+fn file_type(meta: &std::fs::Metadata) -> ArrayType {
+    let file_type = meta.file_type();
+    if file_type.is_file() {
+        return ArrayType::File;
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::FileTypeExt;
+
+        if file_type.is_block_device() {
+            return ArrayType::BlockDevice;
+        }
+        if file_type.is_char_device() {
+            return ArrayType::CharDevice;
+        }
+    }
+
+    ArrayType::Other
+}
+
+/// Query the size and logical sector size of a block device.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn block_device_size(file: &File) -> io::Result<Option<(u64, u64)>> {
+    use rustix::ioctl::{ioctl, Getter, ReadOpcode};
+
+    // SAFETY: `BLKGETSIZE64` is a getter opcode that gets a u64. Its opcode
+    // is declared with `size_t`, but the kernel always writes a u64.
+    let len = unsafe { ioctl(file, Getter::<ReadOpcode<0x12, 114, usize>, u64>::new())? };
+    let blksize = rustix::fs::ioctl_blksszget(file)?;
+    Ok(Some((len, blksize.into())))
+}
+
+/// Query the size and logical sector size of a block device.
+#[cfg(target_os = "freebsd")]
+fn block_device_size(file: &File) -> io::Result<Option<(u64, u64)>> {
+    use rustix::ioctl::{ioctl, Getter, ReadOpcode};
+
+    // SAFETY: `DIOCGMEDIASIZE` is a getter opcode that gets an `off_t`, and
+    // `DIOCGSECTORSIZE` is a getter opcode that gets a `u_int`.
+    let (len, blksize) = unsafe {
+        (
+            ioctl(file, Getter::<ReadOpcode<b'd', 129, i64>, i64>::new())?,
+            ioctl(file, Getter::<ReadOpcode<b'd', 128, u32>, u32>::new())?,
+        )
+    };
+    Ok(Some((len.try_into().unwrap_or(0), blksize.into())))
+}
+
+/// Query the size and logical sector size of a block device.
+#[cfg(any(target_os = "ios", target_os = "macos"))]
+fn block_device_size(file: &File) -> io::Result<Option<(u64, u64)>> {
+    use rustix::ioctl::{ioctl, Getter, ReadOpcode};
+
+    // SAFETY: `DKIOCGETBLOCKSIZE` is a getter opcode that gets a u32, and
+    // `DKIOCGETBLOCKCOUNT` is a getter opcode that gets a u64.
+    let (blksize, count) = unsafe {
+        (
+            ioctl(file, Getter::<ReadOpcode<b'd', 24, u32>, u32>::new())?,
+            ioctl(file, Getter::<ReadOpcode<b'd', 25, u64>, u64>::new())?,
+        )
+    };
+    let blksize = u64::from(blksize);
+    Ok(Some((count.saturating_mul(blksize), blksize)))
+}
+
+/// On other platforms, we don't know how to query block devices, so use
+/// whatever `fstat` reports.
+#[cfg(not(any(
+    target_os = "android",
+    target_os = "linux",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "macos"
+)))]
+fn block_device_size(_file: &File) -> io::Result<Option<(u64, u64)>> {
+    Ok(None)
+}
+
+/// Test whether `a` and `b` refer to the same underlying file, by comparing
+/// their device and inode numbers.
+///
+/// This is useful for detecting aliasing, such as before a
+/// [`crate::WriteAt::copy_from`] between two arrays.
+pub fn is_same_file<A: AsFilelike, B: AsFilelike>(a: &A, b: &B) -> io::Result<bool> {
+    let a = rustix::fs::fstat(a.as_filelike())?;
+    let b = rustix::fs::fstat(b.as_filelike())?;
+    Ok(a.st_dev == b.st_dev && a.st_ino == b.st_ino)
+}
+
+/// Open the file `file` refers to again, for reading and writing.
+///
+/// This finds the file through `/proc/self/fd` on Linux and Android, and
+/// with `F_GETPATH` on Apple platforms, and fails with
+/// [`io::ErrorKind::Unsupported`] elsewhere. Opening it checks the file's
+/// permissions like any other open, so this can't gain access that opening
+/// the file by name wouldn't.
+pub(crate) fn reopen_read_write(file: &File) -> io::Result<File> {
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    let path = std::path::PathBuf::from(format!("/proc/self/fd/{}", file.as_raw_fd()));
+    #[cfg(any(target_os = "ios", target_os = "macos"))]
+    let path = {
+        use std::os::unix::ffi::OsStringExt;
+
+        let mut buf = vec![0_u8; libc::PATH_MAX as usize];
+        if unsafe { libc::fcntl(file.as_raw_fd(), libc::F_GETPATH, buf.as_mut_ptr()) } == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        buf.truncate(buf.iter().position(|b| *b == 0).unwrap_or(buf.len()));
+        std::path::PathBuf::from(std::ffi::OsString::from_vec(buf))
+    };
+    #[cfg(not(any(
+        target_os = "android",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos"
+    )))]
+    {
+        let _ = file;
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "reopening a file is not supported on this platform",
+        ));
+    }
+
+    #[allow(unreachable_code)]
+    File::options().read(true).write(true).open(path)
+}
+
+/// Test whether `err` means that the file doesn't support positional I/O,
+/// as for pipes, sockets, and some character devices.
+fn is_unseekable(err: &io::Error) -> bool {
+    rustix::io::Errno::from_io_error(err) == Some(rustix::io::Errno::SPIPE)
 }
 
 /// Implement [`crate::ReadAt::read_at`].
+///
+/// Files which don't support positional reads, such as pipes, sockets, and
+/// some character devices, are read from their current position instead,
+/// ignoring `offset`. Streams like these have no positions to read at, so
+/// this is only useful for reading them sequentially, or for devices such
+/// as `/dev/urandom` where every position is alike.
 #[inline]
 pub fn read_at<'a, Filelike: AsFilelike>(
     filelike: &Filelike,
     buf: &mut [u8],
     offset: u64,
 ) -> io::Result<usize> {
-    <File as FileIoExt>::read_at(&filelike.as_filelike_view::<File>(), buf, offset)
+    let file = filelike.as_filelike_view::<File>();
+    match <File as FileIoExt>::read_at(&file, buf, offset) {
+        Err(err) if is_unseekable(&err) => (&*file).read(buf),
+        result => result,
+    }
 }
 
 /// Implement [`crate::ReadAt::read_exact_at`].
+///
+/// As with [`read_at`], files which don't support positional reads are read
+/// from their current position.
 #[inline]
 pub fn read_exact_at<'a, Filelike: AsFilelike>(
     filelike: &Filelike,
     buf: &mut [u8],
     offset: u64,
 ) -> io::Result<()> {
-    <File as FileIoExt>::read_exact_at(&filelike.as_filelike_view::<File>(), buf, offset)
+    let file = filelike.as_filelike_view::<File>();
+    match <File as FileIoExt>::read_exact_at(&file, buf, offset) {
+        Err(err) if is_unseekable(&err) => (&*file).read_exact(buf),
+        result => result,
+    }
+}
+
+/// Read from `filelike` with `preadv2` and `RWF_NOWAIT`, for
+/// [`crate::ReadAt::read_at_nowait`].
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn read_at_nowait<Filelike: AsFilelike>(
+    filelike: &Filelike,
+    buf: &mut [u8],
+    offset: u64,
+) -> io::Result<usize> {
+    use rustix::io::{preadv2, Errno, ReadWriteFlags};
+
+    match preadv2(
+        filelike.as_filelike(),
+        &mut [IoSliceMut::new(buf)],
+        offset,
+        ReadWriteFlags::NOWAIT,
+    ) {
+        Ok(n) => Ok(n),
+        // `RWF_NOWAIT` isn't supported by this kernel or filesystem.
+        Err(Errno::OPNOTSUPP | Errno::NOSYS | Errno::INVAL) => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "read_at_nowait is not supported",
+        )),
+        Err(err) => Err(err.into()),
+    }
 }
 
 /// Implement [`crate::ReadAt::read_vectored_at`].
+///
+/// As with [`read_at`], files which don't support positional reads are read
+/// from their current position.
 #[inline]
 pub fn read_vectored_at<'a, Filelike: AsFilelike>(
     filelike: &Filelike,
     bufs: &mut [IoSliceMut],
     offset: u64,
 ) -> io::Result<usize> {
-    <File as FileIoExt>::read_vectored_at(&filelike.as_filelike_view::<File>(), bufs, offset)
+    let file = filelike.as_filelike_view::<File>();
+    match <File as FileIoExt>::read_vectored_at(&file, bufs, offset) {
+        Err(err) if is_unseekable(&err) => (&*file).read_vectored(bufs),
+        result => result,
+    }
 }
 
 /// Implement [`crate::ReadAt::read_exact_vectored_at`].
+///
+/// As with [`read_at`], files which don't support positional reads are read
+/// from their current position.
 #[inline]
 pub fn read_exact_vectored_at<'a, Filelike: AsFilelike>(
     filelike: &Filelike,
     bufs: &mut [IoSliceMut],
     offset: u64,
 ) -> io::Result<()> {
-    <File as FileIoExt>::read_exact_vectored_at(&filelike.as_filelike_view::<File>(), bufs, offset)
+    let file = filelike.as_filelike_view::<File>();
+    match <File as FileIoExt>::read_exact_vectored_at(&file, bufs, offset) {
+        Err(err) if is_unseekable(&err) => {
+            bufs.iter_mut().try_for_each(|buf| (&*file).read_exact(buf))
+        }
+        result => result,
+    }
 }
 
 /// Implement [`crate::ReadAt::is_read_vectored_at`].
@@ -96,7 +333,7 @@ pub fn read_via_stream_at<'a, Filelike: AsFilelike>(
     }
 
     // Otherwise, manually stream the file.
-    StreamReader::piped_thread(Box::new(OwnedStreamer::new(view.try_clone()?, offset)))
+    StreamReader::piped_thread(Box::new(Streamer::owned(view.try_clone()?, offset)))
 }
 
 /// Implement [`crate::WriteAt::write_at`].
@@ -119,6 +356,41 @@ pub fn write_all_at<'a, Filelike: AsFilelike>(
     <File as FileIoExt>::write_all_at(&filelike.as_filelike_view::<File>(), buf, offset)
 }
 
+/// Write to `filelike` with `pwritev2`, for
+/// [`crate::WriteAt::write_at_with`], returning `None` if the kernel doesn't
+/// support `pwritev2`.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn write_at_with<Filelike: AsFilelike>(
+    filelike: &Filelike,
+    buf: &[u8],
+    offset: u64,
+    flags: WriteFlags,
+) -> io::Result<Option<usize>> {
+    use rustix::io::{pwritev2, Errno, ReadWriteFlags};
+
+    let mut rwf = ReadWriteFlags::empty();
+    for (flag, rw_flag) in [
+        (WriteFlags::DSYNC, ReadWriteFlags::DSYNC),
+        (WriteFlags::SYNC, ReadWriteFlags::SYNC),
+        (WriteFlags::HIPRI, ReadWriteFlags::HIPRI),
+        (WriteFlags::NOWAIT, ReadWriteFlags::NOWAIT),
+    ] {
+        if flags.contains(flag) {
+            rwf |= rw_flag;
+        }
+    }
+
+    match pwritev2(filelike.as_filelike(), &[IoSlice::new(buf)], offset, rwf) {
+        Ok(written) => Ok(Some(written)),
+        Err(Errno::NOSYS) => Ok(None),
+        Err(Errno::OPNOTSUPP) if flags.contains(WriteFlags::NOWAIT) => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "non-blocking writes are not supported",
+        )),
+        Err(err) => Err(err.into()),
+    }
+}
+
 /// Implement [`crate::WriteAt::write_vectored_at`].
 #[inline]
 pub fn write_vectored_at<'a, Filelike: AsFilelike>(
@@ -144,3 +416,696 @@ pub fn write_all_vectored_at<'a, Filelike: AsFilelike>(
 pub fn is_write_vectored_at<'a, Filelike: AsFilelike>(filelike: &Filelike) -> bool {
     <File as FileIoExt>::is_write_vectored_at(&filelike.as_filelike_view::<File>())
 }
+
+/// Allocate storage for the range of `len` bytes at `offset` in `filelike`,
+/// extending it if needed.
+///
+/// rustix's `fallocate` uses `fallocate` on Linux, `F_PREALLOCATE` and
+/// `ftruncate` on macOS and iOS, and `posix_fallocate` elsewhere.
+#[cfg(not(any(
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "redox"
+)))]
+#[inline]
+pub(crate) fn allocate<Filelike: AsFilelike>(
+    filelike: &Filelike,
+    offset: u64,
+    len: u64,
+) -> io::Result<()> {
+    let file = filelike.as_filelike_view::<File>();
+    Ok(rustix::fs::fallocate(
+        &*file,
+        rustix::fs::FallocateFlags::empty(),
+        offset,
+        len,
+    )?)
+}
+
+/// These platforms have no `posix_fallocate`, and writing zeros wouldn't
+/// reliably reserve space, so report that allocation isn't supported.
+#[cfg(any(
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "redox"
+))]
+#[inline]
+pub(crate) fn allocate<Filelike: AsFilelike>(
+    _filelike: &Filelike,
+    _offset: u64,
+    _len: u64,
+) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "allocate is not supported on this platform",
+    ))
+}
+
+/// Reserve storage for the range of `len` bytes at `offset` in `filelike`,
+/// without changing its length.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[inline]
+pub(crate) fn reserve_at<Filelike: AsFilelike>(
+    filelike: &Filelike,
+    offset: u64,
+    len: u64,
+) -> io::Result<()> {
+    let file = filelike.as_filelike_view::<File>();
+    Ok(rustix::fs::fallocate(
+        &*file,
+        rustix::fs::FallocateFlags::KEEP_SIZE,
+        offset,
+        len,
+    )?)
+}
+
+/// Reserve storage for the range of `len` bytes at `offset` in `filelike`,
+/// without changing its length.
+///
+/// `F_PREALLOCATE` allocates space past the physical end of the file, so
+/// this may reserve more than needed, but it doesn't change the length.
+#[cfg(any(target_os = "ios", target_os = "macos"))]
+pub(crate) fn reserve_at<Filelike: AsFilelike>(
+    filelike: &Filelike,
+    offset: u64,
+    len: u64,
+) -> io::Result<()> {
+    let end = offset
+        .checked_add(len)
+        .and_then(|end| i64::try_from(end).ok())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "offset overflow"))?;
+    let file = filelike.as_filelike_view::<File>();
+    let mut store = libc::fstore_t {
+        fst_flags: libc::F_ALLOCATECONTIG,
+        fst_posmode: libc::F_PEOFPOSMODE,
+        fst_offset: 0,
+        fst_length: end,
+        fst_bytesalloc: 0,
+    };
+    // Try to allocate contiguous space first, and then any space.
+    if unsafe { libc::fcntl(file.as_raw_fd(), libc::F_PREALLOCATE, &store) } == -1 {
+        store.fst_flags = libc::F_ALLOCATEALL;
+        if unsafe { libc::fcntl(file.as_raw_fd(), libc::F_PREALLOCATE, &store) } == -1 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+/// Reserve storage for the range of `len` bytes at `offset` in `filelike`,
+/// by allocating it and then truncating the file back to its original
+/// length. This isn't atomic.
+#[cfg(not(any(
+    target_os = "android",
+    target_os = "dragonfly",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "redox"
+)))]
+pub(crate) fn reserve_at<Filelike: AsFilelike>(
+    filelike: &Filelike,
+    offset: u64,
+    len: u64,
+) -> io::Result<()> {
+    let file = filelike.as_filelike_view::<File>();
+    let original_len = file.metadata()?.len();
+    allocate(filelike, offset, len)?;
+    if file.metadata()?.len() != original_len {
+        file.set_len(original_len)?;
+    }
+    Ok(())
+}
+
+/// These platforms have no `posix_fallocate`, so report that reserving
+/// isn't supported, as for [`allocate`].
+#[cfg(any(
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "redox"
+))]
+#[inline]
+pub(crate) fn reserve_at<Filelike: AsFilelike>(
+    _filelike: &Filelike,
+    _offset: u64,
+    _len: u64,
+) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "reserve_at is not supported on this platform",
+    ))
+}
+
+/// Deallocate the storage for `len` bytes at `offset` in `filelike`, which
+/// must be within the file, returning `false` if the filesystem doesn't
+/// support it.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn punch_hole<Filelike: AsFilelike>(
+    filelike: &Filelike,
+    offset: u64,
+    len: u64,
+) -> io::Result<bool> {
+    use rustix::fs::FallocateFlags;
+
+    let file = filelike.as_filelike_view::<File>();
+    let flags = FallocateFlags::PUNCH_HOLE | FallocateFlags::KEEP_SIZE;
+    match rustix::fs::fallocate(&*file, flags, offset, len) {
+        Ok(()) => Ok(true),
+        Err(rustix::io::Errno::OPNOTSUPP | rustix::io::Errno::NOSYS) => Ok(false),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Deallocate the storage for `len` bytes at `offset` in `filelike`, which
+/// must be within the file, returning `false` if the filesystem doesn't
+/// support it.
+///
+/// `F_PUNCHHOLE` requires the range to be aligned to the filesystem block
+/// size, so this punches the aligned part of the range and zeros the
+/// unaligned ends.
+#[cfg(any(target_os = "ios", target_os = "macos"))]
+pub(crate) fn punch_hole<Filelike: AsFilelike>(
+    filelike: &Filelike,
+    offset: u64,
+    len: u64,
+) -> io::Result<bool> {
+    let file = filelike.as_filelike_view::<File>();
+    let blksize = file.metadata()?.blksize().max(1);
+    let start = offset.div_ceil(blksize) * blksize;
+    let end = (offset + len) / blksize * blksize;
+    if start >= end {
+        return Ok(false);
+    }
+
+    let args = libc::fpunchhole_t {
+        fp_flags: 0,
+        reserved: 0,
+        fp_offset: start as libc::off_t,
+        fp_length: (end - start) as libc::off_t,
+    };
+    // SAFETY: `F_PUNCHHOLE` takes a pointer to an `fpunchhole_t`, which
+    // lives for the duration of the call.
+    if unsafe {
+        libc::fcntl(
+            file.as_raw_fd(),
+            libc::F_PUNCHHOLE,
+            &args as *const libc::fpunchhole_t,
+        )
+    } == -1
+    {
+        let err = io::Error::last_os_error();
+        return match err.raw_os_error() {
+            Some(libc::ENOTSUP) | Some(libc::EINVAL) => Ok(false),
+            _ => Err(err),
+        };
+    }
+
+    crate::files::write_zeros(filelike, offset, start - offset)?;
+    crate::files::write_zeros(filelike, end, offset + len - end)?;
+    Ok(true)
+}
+
+/// Deallocate the storage for `len` bytes at `offset` in `filelike`, which
+/// must be within the file, returning `false` if the filesystem doesn't
+/// support it.
+#[cfg(target_os = "freebsd")]
+pub(crate) fn punch_hole<Filelike: AsFilelike>(
+    filelike: &Filelike,
+    offset: u64,
+    len: u64,
+) -> io::Result<bool> {
+    let file = filelike.as_filelike_view::<File>();
+    let range = libc::spacectl_range {
+        r_offset: offset as libc::off_t,
+        r_len: len as libc::off_t,
+    };
+    // SAFETY: `fspacectl` reads the range, which lives for the duration of
+    // the call, and a null `rmsr` is permitted.
+    let ret = unsafe {
+        libc::fspacectl(
+            file.as_raw_fd(),
+            libc::SPACECTL_DEALLOC,
+            &range,
+            0,
+            std::ptr::null_mut(),
+        )
+    };
+    if ret == -1 {
+        let err = io::Error::last_os_error();
+        return match err.raw_os_error() {
+            Some(libc::EOPNOTSUPP) | Some(libc::ENODEV) => Ok(false),
+            _ => Err(err),
+        };
+    }
+    Ok(true)
+}
+
+/// On other platforms, there's no way to deallocate storage.
+#[cfg(not(any(
+    target_os = "android",
+    target_os = "linux",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "macos"
+)))]
+pub(crate) fn punch_hole<Filelike: AsFilelike>(
+    _filelike: &Filelike,
+    _offset: u64,
+    _len: u64,
+) -> io::Result<bool> {
+    Ok(false)
+}
+
+/// Zero `len` bytes at `offset` in `filelike` with `FALLOC_FL_ZERO_RANGE`,
+/// extending it if needed, returning `false` if the filesystem doesn't
+/// support it.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn zero_range<Filelike: AsFilelike>(
+    filelike: &Filelike,
+    offset: u64,
+    len: u64,
+) -> io::Result<bool> {
+    let file = filelike.as_filelike_view::<File>();
+    let flags = rustix::fs::FallocateFlags::ZERO_RANGE;
+    match rustix::fs::fallocate(&*file, flags, offset, len) {
+        Ok(()) => Ok(true),
+        Err(rustix::io::Errno::OPNOTSUPP | rustix::io::Errno::NOSYS) => Ok(false),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// On other platforms, there's no way to zero a range without writing it.
+#[cfg(not(any(target_os = "android", target_os = "linux")))]
+pub(crate) fn zero_range<Filelike: AsFilelike>(
+    _filelike: &Filelike,
+    _offset: u64,
+    _len: u64,
+) -> io::Result<bool> {
+    Ok(false)
+}
+
+/// Flush or wait for writeback of `len` bytes at `offset` in `filelike`
+/// with `sync_file_range`, returning `false` if it isn't supported for this
+/// file.
+#[cfg(target_os = "linux")]
+pub(crate) fn sync_file_range<Filelike: AsFilelike>(
+    filelike: &Filelike,
+    offset: u64,
+    len: u64,
+    mode: SyncRangeMode,
+) -> io::Result<bool> {
+    let flags = match mode {
+        SyncRangeMode::InitiateWriteback => libc::SYNC_FILE_RANGE_WRITE,
+        SyncRangeMode::WaitForCompletion => libc::SYNC_FILE_RANGE_WAIT_BEFORE,
+        SyncRangeMode::Full => {
+            libc::SYNC_FILE_RANGE_WAIT_BEFORE
+                | libc::SYNC_FILE_RANGE_WRITE
+                | libc::SYNC_FILE_RANGE_WAIT_AFTER
+        }
+    };
+
+    // Offsets past `i64::MAX` can't hold any data, and a length of zero
+    // means "through the end of the file".
+    let Ok(offset) = i64::try_from(offset) else {
+        return Ok(true);
+    };
+    let nbytes = i64::try_from(len)
+        .ok()
+        .filter(|len| offset.checked_add(*len).is_some())
+        .unwrap_or(0);
+
+    let file = filelike.as_filelike_view::<File>();
+    // SAFETY: `sync_file_range` has no memory-safety preconditions.
+    if unsafe { libc::sync_file_range(file.as_raw_fd(), offset, nbytes, flags) } == -1 {
+        let err = io::Error::last_os_error();
+        return match err.raw_os_error() {
+            Some(libc::ENOSYS) | Some(libc::ESPIPE) | Some(libc::EINVAL) => Ok(false),
+            _ => Err(err),
+        };
+    }
+    Ok(true)
+}
+
+/// Lock `len` bytes at `offset` in `filelike` with `fcntl`, returning
+/// `false` if `wait` is false and the range is locked by someone else.
+///
+/// On Linux, this uses open file description locks, falling back to
+/// process-associated locks on kernels which don't support them.
+#[cfg(unix)]
+pub(crate) fn lock_range<Filelike: AsFilelike>(
+    filelike: &Filelike,
+    offset: u64,
+    len: u64,
+    kind: LockKind,
+    wait: bool,
+) -> io::Result<bool> {
+    let l_type = match kind {
+        LockKind::Shared => libc::F_RDLCK,
+        LockKind::Exclusive => libc::F_WRLCK,
+    };
+    match fcntl_lock(filelike, l_type, offset, len, wait) {
+        Ok(()) => Ok(true),
+        Err(err)
+            if !wait && matches!(err.raw_os_error(), Some(libc::EAGAIN) | Some(libc::EACCES)) =>
+        {
+            Ok(false)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// Unlock `len` bytes at `offset` in `filelike`.
+#[cfg(unix)]
+pub(crate) fn unlock_range<Filelike: AsFilelike>(
+    filelike: &Filelike,
+    offset: u64,
+    len: u64,
+) -> io::Result<()> {
+    fcntl_lock(filelike, libc::F_UNLCK, offset, len, false)
+}
+
+#[cfg(unix)]
+fn fcntl_lock<Filelike: AsFilelike>(
+    filelike: &Filelike,
+    l_type: libc::c_int,
+    offset: u64,
+    len: u64,
+    wait: bool,
+) -> io::Result<()> {
+    use std::os::fd::AsRawFd;
+
+    let l_start = libc::off_t::try_from(offset)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "offset overflow"))?;
+    // A length of zero means "through the end of the file, however far it
+    // grows", which is the closest we can get to ranges past `off_t::MAX`.
+    let l_len = libc::off_t::try_from(len)
+        .ok()
+        .filter(|len| l_start.checked_add(*len).is_some())
+        .unwrap_or(0);
+
+    // SAFETY: `flock` is a plain-old-data struct, and zero is a valid value
+    // for all of its fields, including any platform-specific ones.
+    let mut lock: libc::flock = unsafe { std::mem::zeroed() };
+    lock.l_type = l_type as _;
+    lock.l_whence = libc::SEEK_SET as _;
+    lock.l_start = l_start;
+    lock.l_len = l_len;
+
+    let file = filelike.as_filelike_view::<File>();
+    let fcntl = |cmd| {
+        // SAFETY: The `F_*SETLK*` commands take a pointer to a `flock`, which
+        // lives for the duration of the call.
+        if unsafe { libc::fcntl(file.as_raw_fd(), cmd, &lock as *const libc::flock) } == -1 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(())
+        }
+    };
+
+    #[cfg(target_os = "linux")]
+    match fcntl(if wait {
+        libc::F_OFD_SETLKW
+    } else {
+        libc::F_OFD_SETLK
+    }) {
+        Err(err) if err.raw_os_error() == Some(libc::EINVAL) => {}
+        result => return result,
+    }
+
+    fcntl(if wait { libc::F_SETLKW } else { libc::F_SETLK })
+}
+
+/// WASI doesn't have byte-range locks.
+#[cfg(not(unix))]
+pub(crate) fn lock_range<Filelike: AsFilelike>(
+    _filelike: &Filelike,
+    _offset: u64,
+    _len: u64,
+    _kind: LockKind,
+    _wait: bool,
+) -> io::Result<bool> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "locking is not supported on this platform",
+    ))
+}
+
+/// WASI doesn't have byte-range locks.
+#[cfg(not(unix))]
+pub(crate) fn unlock_range<Filelike: AsFilelike>(
+    _filelike: &Filelike,
+    _offset: u64,
+    _len: u64,
+) -> io::Result<()> {
+    Ok(())
+}
+
+/// Copy up to `len` bytes from the file `input` to `filelike`.
+///
+/// On Linux, this uses `copy_file_range`. Elsewhere, including on macOS,
+/// where `fcopyfile` and `fclonefileat` only operate on whole files, this
+/// copies through a large buffer.
+pub(crate) fn copy_from_file<Filelike: AsFilelike, Input: AsFilelike>(
+    filelike: &Filelike,
+    offset: u64,
+    input: &Input,
+    input_offset: u64,
+    len: u64,
+) -> io::Result<u64> {
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    let (copied, finished) = copy_file_range(filelike, offset, input, input_offset, len)?;
+    #[cfg(not(any(target_os = "android", target_os = "linux")))]
+    let (copied, finished) = (0, false);
+
+    if finished {
+        return Ok(copied);
+    }
+
+    Ok(copied
+        + crate::files::copy_buffered(
+            filelike,
+            offset + copied,
+            input,
+            input_offset + copied,
+            len - copied,
+        )?)
+}
+
+/// Copy up to `len` bytes from `input` to `filelike` with `copy_file_range`.
+///
+/// Returns the number of bytes copied, and whether the copy is finished,
+/// either because `len` bytes were copied or the end of `input` was reached.
+/// If it's not finished, the kernel couldn't perform the rest of the copy,
+/// for example because the files are on different filesystems, and the
+/// caller should copy the remainder some other way.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn copy_file_range<Filelike: AsFilelike, Input: AsFilelike>(
+    filelike: &Filelike,
+    mut offset: u64,
+    input: &Input,
+    mut input_offset: u64,
+    len: u64,
+) -> io::Result<(u64, bool)> {
+    use rustix::io::Errno;
+
+    let mut copied = 0;
+    while copied < len {
+        let chunk = (len - copied).try_into().unwrap_or(usize::MAX);
+        match rustix::fs::copy_file_range(
+            input.as_filelike(),
+            Some(&mut input_offset),
+            filelike.as_filelike(),
+            Some(&mut offset),
+            chunk,
+        ) {
+            Ok(0) => return Ok((copied, true)),
+            Ok(n) => copied += n as u64,
+            Err(Errno::INTR) => continue,
+            Err(
+                Errno::XDEV
+                | Errno::NOSYS
+                | Errno::OPNOTSUPP
+                | Errno::INVAL
+                | Errno::BADF
+                | Errno::PERM
+                | Errno::OVERFLOW,
+            ) => return Ok((copied, false)),
+            Err(err) => return Err(err.into()),
+        }
+    }
+    Ok((copied, true))
+}
+
+/// Clone up to `len` bytes from `input` to `filelike` with `FICLONERANGE`.
+///
+/// Returns the number of bytes cloned, or `None` if the filesystem can't
+/// clone this range, in which case the caller should copy it instead.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn clone_range<Filelike: AsFilelike, Input: AsFilelike>(
+    filelike: &Filelike,
+    offset: u64,
+    input: &Input,
+    input_offset: u64,
+    len: u64,
+) -> io::Result<Option<u64>> {
+    use rustix::io::Errno;
+    use rustix::ioctl::{ioctl, Setter, WriteOpcode};
+
+    /// `struct file_clone_range` from <linux/fs.h>.
+    #[repr(C)]
+    struct FileCloneRange {
+        src_fd: i64,
+        src_offset: u64,
+        src_length: u64,
+        dest_offset: u64,
+    }
+
+    let input_len = rustix::fs::fstat(input.as_filelike())?.st_size as u64;
+    let len = min(len, input_len.saturating_sub(input_offset));
+    if len == 0 {
+        return Ok(Some(0));
+    }
+
+    // The range must be block-aligned, except that it may extend to the end
+    // of the input.
+    let blksize = rustix::fs::fstat(filelike.as_filelike())?.st_blksize as u64;
+    if blksize != 0
+        && (!offset.is_multiple_of(blksize)
+            || !input_offset.is_multiple_of(blksize)
+            || (!len.is_multiple_of(blksize) && input_offset + len != input_len))
+    {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "clone range is not aligned to the filesystem block size",
+        ));
+    }
+
+    let range = FileCloneRange {
+        src_fd: input.as_filelike().as_raw_fd().into(),
+        src_offset: input_offset,
+        src_length: len,
+        dest_offset: offset,
+    };
+    // SAFETY: `FICLONERANGE` is a setter opcode that takes a
+    // `struct file_clone_range`, and `src_fd` is a valid open file
+    // descriptor for the duration of the call.
+    let result = unsafe {
+        ioctl(
+            filelike.as_filelike(),
+            Setter::<WriteOpcode<0x94, 13, FileCloneRange>, FileCloneRange>::new(range),
+        )
+    };
+    match result {
+        Ok(()) => Ok(Some(len)),
+        Err(
+            Errno::OPNOTSUPP
+            | Errno::XDEV
+            | Errno::INVAL
+            | Errno::NOTTY
+            | Errno::BADF
+            | Errno::PERM,
+        ) => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Deduplicate up to `len` bytes of `filelike` with `input` with
+/// `FIDEDUPERANGE`, returning the number of bytes deduplicated.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn dedupe_range<Filelike: AsFilelike, Input: AsFilelike>(
+    filelike: &Filelike,
+    mut offset: u64,
+    input: &Input,
+    mut input_offset: u64,
+    len: u64,
+) -> io::Result<u64> {
+    use rustix::io::Errno;
+    use rustix::ioctl::{ioctl, ReadWriteOpcode, Updater};
+
+    /// `struct file_dedupe_range` from <linux/fs.h>, without the trailing
+    /// array, which determines the opcode.
+    #[repr(C)]
+    struct FileDedupeRangeHeader {
+        src_offset: u64,
+        src_length: u64,
+        dest_count: u16,
+        reserved1: u16,
+        reserved2: u32,
+    }
+
+    /// `struct file_dedupe_range` with one `struct file_dedupe_range_info`.
+    #[repr(C)]
+    struct FileDedupeRange {
+        header: FileDedupeRangeHeader,
+        dest_fd: i64,
+        dest_offset: u64,
+        bytes_deduped: u64,
+        status: i32,
+        reserved: u32,
+    }
+
+    /// `FILE_DEDUPE_RANGE_DIFFERS` from <linux/fs.h>.
+    const DIFFERS: i32 = 1;
+
+    let unsupported = || io::Error::new(io::ErrorKind::Unsupported, "dedupe is not supported");
+
+    let input_len = rustix::fs::fstat(input.as_filelike())?.st_size as u64;
+    let len = min(len, input_len.saturating_sub(input_offset));
+    let mut deduped = 0;
+    while deduped < len {
+        let mut range = FileDedupeRange {
+            header: FileDedupeRangeHeader {
+                src_offset: input_offset,
+                src_length: len - deduped,
+                dest_count: 1,
+                reserved1: 0,
+                reserved2: 0,
+            },
+            dest_fd: filelike.as_filelike().as_raw_fd().into(),
+            dest_offset: offset,
+            bytes_deduped: 0,
+            status: 0,
+            reserved: 0,
+        };
+        // SAFETY: `FIDEDUPERANGE` is an updater opcode that takes a
+        // `struct file_dedupe_range` followed by `dest_count` entries, and
+        // `dest_fd` is a valid open file descriptor for the duration of the
+        // call.
+        let result = unsafe {
+            ioctl(
+                input.as_filelike(),
+                Updater::<ReadWriteOpcode<0x94, 54, FileDedupeRangeHeader>, FileDedupeRange>::new(
+                    &mut range,
+                ),
+            )
+        };
+        match result {
+            Ok(()) => {}
+            Err(Errno::OPNOTSUPP | Errno::NOTTY | Errno::XDEV) => return Err(unsupported()),
+            Err(err) => return Err(err.into()),
+        }
+
+        match range.status {
+            0 => {}
+            DIFFERS => break,
+            status => {
+                return match Errno::from_raw_os_error(-status) {
+                    Errno::OPNOTSUPP | Errno::NOTTY | Errno::XDEV => Err(unsupported()),
+                    err => Err(err.into()),
+                }
+            }
+        }
+        if range.bytes_deduped == 0 {
+            break;
+        }
+        deduped += range.bytes_deduped;
+        offset += range.bytes_deduped;
+        input_offset += range.bytes_deduped;
+    }
+    Ok(deduped)
+}
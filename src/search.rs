@@ -0,0 +1,81 @@
+//! Searching for byte patterns within arrays.
+
+use crate::{io, ReadAt};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::{max, min};
+use memchr::memmem;
+
+/// The size of the buffer used by [`find_at`] and [`rfind_at`].
+const SEARCH_BUF_SIZE: usize = 64 * 1024;
+
+/// Allocate a search buffer large enough that consecutive chunks, which
+/// overlap by `needle.len() - 1` bytes, always make progress.
+fn search_buf(needle: &[u8]) -> Vec<u8> {
+    vec![0; max(SEARCH_BUF_SIZE, needle.len() * 2)]
+}
+
+/// Implement [`ReadAt::find_at`].
+pub(crate) fn find_at<A: ReadAt + ?Sized>(
+    array: &A,
+    needle: &[u8],
+    offset: u64,
+) -> io::Result<Option<u64>> {
+    if needle.is_empty() {
+        return Ok(Some(offset));
+    }
+    let overlap = needle.len() - 1;
+    let mut buf = search_buf(needle);
+    // The offset in the array of `buf[0]`.
+    let mut pos = offset;
+    let mut filled = 0;
+    loop {
+        // Fill the buffer, so that we search in large chunks.
+        let at = pos
+            .checked_add(filled as u64)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "offset overflow"))?;
+        filled += array.read_full_at(&mut buf[filled..], at)?;
+        let eof = filled < buf.len();
+        if let Some(i) = memmem::find(&buf[..filled], needle) {
+            return Ok(Some(pos + i as u64));
+        }
+        if eof {
+            return Ok(None);
+        }
+        // Keep the end of the buffer, in case a match straddles the chunks.
+        buf.copy_within(filled - overlap..filled, 0);
+        pos += (filled - overlap) as u64;
+        filled = overlap;
+    }
+}
+
+/// Implement [`ReadAt::rfind_at`].
+pub(crate) fn rfind_at<A: ReadAt + ?Sized>(
+    array: &A,
+    needle: &[u8],
+    offset: u64,
+) -> io::Result<Option<u64>> {
+    let mut end = min(offset, array.metadata()?.len());
+    if needle.is_empty() {
+        return Ok(Some(end));
+    }
+    let overlap = needle.len() as u64 - 1;
+    let mut buf = search_buf(needle);
+    loop {
+        if end < needle.len() as u64 {
+            return Ok(None);
+        }
+        let start = end.saturating_sub(buf.len() as u64);
+        let chunk = &mut buf[..(end - start) as usize];
+        array.read_exact_at(chunk, start)?;
+        if let Some(i) = memmem::rfind(chunk, needle) {
+            return Ok(Some(start + i as u64));
+        }
+        if start == 0 {
+            return Ok(None);
+        }
+        // Include the start of this chunk in the next one, in case a match
+        // straddles the chunks.
+        end = start + overlap;
+    }
+}
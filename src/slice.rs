@@ -1,42 +1,41 @@
-use crate::borrow_streamer::BorrowStreamer;
-use crate::{Advice, Array, Metadata, ReadAt, WriteAt};
+#[cfg(feature = "io-streams")]
+use crate::arrays::check_stream_range;
+use crate::arrays::DEFAULT_BLKSIZE;
+use crate::{io, Array, ArrayType, Metadata, ReadAt, Resize, WriteAt, WriteFlags};
+use alloc::vec::Vec;
+use core::cmp::min;
 #[cfg(feature = "io-streams")]
 use io_streams::StreamReader;
-use std::cmp::min;
-use std::io::{self, IoSlice, IoSliceMut, Read};
-
-impl Array for [u8] {
-    #[inline]
-    fn metadata(&self) -> io::Result<Metadata> {
-        Ok(Metadata {
-            len: self.len().try_into().unwrap(),
-            // An array in memory doesn't have a natural "block size" in the
-            // way that filesystems do, so currently this is an arbitrarily
-            // chosen value. In the future this could be guided by performance
-            // measurements.
-            blksize: 4096,
-        })
-    }
-
-    #[inline]
-    fn advise(&self, _offset: u64, _len: u64, _advice: Advice) -> io::Result<()> {
-        Ok(())
-    }
+#[cfg(feature = "std")]
+use {
+    crate::Advice,
+    std::io::{Cursor, IoSlice, IoSliceMut},
+};
+
+/// The error for resizing a slice, which has a fixed length.
+fn cannot_resize() -> io::Error {
+    io::Error::new(io::ErrorKind::PermissionDenied, "cannot set_len on a slice")
 }
 
-impl Array for &mut [u8] {
+impl Array for [u8] {
     #[inline]
     fn metadata(&self) -> io::Result<Metadata> {
+        let len = self.len().try_into().unwrap();
         Ok(Metadata {
-            len: self.len().try_into().unwrap(),
+            len,
             // An array in memory doesn't have a natural "block size" in the
             // way that filesystems do, so currently this is an arbitrarily
             // chosen value. In the future this could be guided by performance
             // measurements.
-            blksize: 4096,
+            blksize: DEFAULT_BLKSIZE,
+            allocated_len: len,
+            file_type: ArrayType::Memory,
+            #[cfg(feature = "std")]
+            modified: None,
         })
     }
 
+    #[cfg(feature = "std")]
     #[inline]
     fn advise(&self, _offset: u64, _len: u64, _advice: Advice) -> io::Result<()> {
         Ok(())
@@ -46,38 +45,55 @@ impl Array for &mut [u8] {
 impl ReadAt for [u8] {
     #[inline]
     fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
-        self.read_exact_at(buf, offset)?;
-        Ok(buf.len())
-    }
-
-    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
         let offset = offset
             .try_into()
-            .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "offset overflow"))?;
         let at: &[u8] = self.get(offset..).unwrap_or(&[]);
         let len = min(at.len(), buf.len());
         buf[..len].copy_from_slice(&at[..len]);
+        Ok(len)
+    }
+
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+        if self.read_at(buf, offset)? != buf.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "failed to fill whole buffer",
+            ));
+        }
         Ok(())
     }
 
+    /// A slice is read in one step, so this is the same as
+    /// [`ReadAt::read_at`].
+    #[inline]
+    fn read_full_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        self.read_at(buf, offset)
+    }
+
+    #[cfg(feature = "std")]
     fn read_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<usize> {
         let initial_offset = offset
             .try_into()
-            .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "offset overflow"))?;
         let mut running_offset = initial_offset;
         for buf in bufs {
             let at = self.get(running_offset..).unwrap_or(&[]);
             let len = min(at.len(), buf.len());
-            buf.copy_from_slice(&at[..len]);
+            buf[..len].copy_from_slice(&at[..len]);
             running_offset += len;
+            if len < buf.len() {
+                break;
+            }
         }
         Ok(running_offset - initial_offset)
     }
 
+    #[cfg(feature = "std")]
     fn read_exact_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<()> {
         let mut running_offset = offset
             .try_into()
-            .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "offset overflow"))?;
         for buf in bufs {
             let at = self.get(running_offset..).unwrap_or(&[]);
             if at.len() < buf.len() {
@@ -93,14 +109,25 @@ impl ReadAt for [u8] {
         Ok(())
     }
 
+    #[cfg(feature = "std")]
     #[inline]
     fn is_read_vectored_at(&self) -> bool {
         true
     }
 
+    #[inline]
+    fn read_at_nowait(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        self.read_at(buf, offset)
+    }
+
+    #[cfg(feature = "io-streams")]
+    fn read_via_stream_at(&self, offset: u64) -> io::Result<StreamReader> {
+        read_via_stream_at(self, offset)
+    }
+
     #[cfg(feature = "io-streams")]
-    fn read_via_stream_at(&self, _offset: u64) -> io::Result<StreamReader> {
-        todo!("slice::read_via_stream_at")
+    fn read_range_via_stream(&self, offset: u64, len: u64) -> io::Result<StreamReader> {
+        read_range_via_stream(self, offset, len)
     }
 }
 
@@ -114,17 +141,18 @@ impl WriteAt for [u8] {
     fn write_all_at(&mut self, buf: &[u8], offset: u64) -> io::Result<()> {
         let offset = offset
             .try_into()
-            .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "offset overflow"))?;
         let at = self.get_mut(offset..).unwrap_or(&mut []);
         let len = min(at.len(), buf.len());
         at[..len].copy_from_slice(&buf[..len]);
         Ok(())
     }
 
+    #[cfg(feature = "std")]
     fn write_vectored_at(&mut self, bufs: &[IoSlice], offset: u64) -> io::Result<usize> {
         let initial_offset = offset
             .try_into()
-            .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "offset overflow"))?;
         let mut running_offset = initial_offset;
         for buf in bufs {
             let at = self.get_mut(running_offset..).unwrap_or(&mut []);
@@ -135,10 +163,11 @@ impl WriteAt for [u8] {
         Ok(running_offset - initial_offset)
     }
 
+    #[cfg(feature = "std")]
     fn write_all_vectored_at(&mut self, bufs: &mut [IoSlice], offset: u64) -> io::Result<()> {
         let mut running_offset = offset
             .try_into()
-            .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "offset overflow"))?;
         for buf in bufs {
             let at = self.get_mut(running_offset..).unwrap_or(&mut []);
             if at.len() < buf.len() {
@@ -154,133 +183,233 @@ impl WriteAt for [u8] {
         Ok(())
     }
 
+    #[cfg(feature = "std")]
     #[inline]
     fn is_write_vectored_at(&self) -> bool {
         true
     }
 
     #[inline]
-    fn copy_from<R: ReadAt>(
-        &mut self,
-        _offset: u64,
-        input: &R,
-        input_offset: u64,
+    fn write_at_with(&mut self, buf: &[u8], offset: u64, _flags: WriteFlags) -> io::Result<usize> {
+        self.write_at(buf, offset)
+    }
+
+    #[inline]
+    fn zero_range(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        self.fill_at(0, offset, len)
+    }
+
+    fn fill_at(&mut self, byte: u8, offset: u64, len: u64) -> io::Result<()> {
+        let end = offset
+            .checked_add(len)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "offset overflow"))?;
+        let self_len = self.len() as u64;
+        self[min(offset, self_len) as usize..min(end, self_len) as usize].fill(byte);
+        if end > self_len {
+            return Err(cannot_resize());
+        }
+        Ok(())
+    }
+
+    fn punch_hole(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        let start = min(offset, self.len() as u64) as usize;
+        let end = min(offset.saturating_add(len), self.len() as u64) as usize;
+        self[start..end].fill(0);
+        Ok(())
+    }
+}
+
+impl Array for Vec<u8> {
+    #[inline]
+    fn metadata(&self) -> io::Result<Metadata> {
+        self.as_slice().metadata()
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        self.as_slice().advise(offset, len, advice)
+    }
+}
+
+impl ReadAt for Vec<u8> {
+    #[inline]
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        self.as_slice().read_at(buf, offset)
+    }
+
+    #[inline]
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+        self.as_slice().read_exact_at(buf, offset)
+    }
+
+    #[inline]
+    fn read_full_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        self.as_slice().read_full_at(buf, offset)
+    }
+
+    #[inline]
+    fn copy_to<W: WriteAt + ?Sized>(
+        &self,
+        offset: u64,
+        output: &mut W,
+        output_offset: u64,
         len: u64,
     ) -> io::Result<u64> {
-        let _todo = BorrowStreamer::new(input, input_offset).take(len);
-        todo!("slice::copy_from")
+        copy_to(self.as_slice(), offset, output, output_offset, len)
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn read_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<usize> {
+        self.as_slice().read_vectored_at(bufs, offset)
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn read_exact_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<()> {
+        self.as_slice().read_exact_vectored_at(bufs, offset)
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn is_read_vectored_at(&self) -> bool {
+        self.as_slice().is_read_vectored_at()
     }
 
     #[inline]
-    fn set_len(&mut self, _len: u64) -> io::Result<()> {
-        Err(io::Error::new(
-            io::ErrorKind::PermissionDenied,
-            "cannot set_len on a slice",
-        ))
+    fn read_at_nowait(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        self.as_slice().read_at_nowait(buf, offset)
+    }
+
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    fn read_via_stream_at(&self, offset: u64) -> io::Result<StreamReader> {
+        self.as_slice().read_via_stream_at(offset)
+    }
+
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    fn read_range_via_stream(&self, offset: u64, len: u64) -> io::Result<StreamReader> {
+        self.as_slice().read_range_via_stream(offset, len)
     }
 }
 
-impl WriteAt for &mut [u8] {
+impl WriteAt for Vec<u8> {
     #[inline]
     fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
-        self.write_all_at(buf, offset)?;
-        Ok(buf.len())
+        self.as_mut_slice().write_at(buf, offset)
     }
 
+    #[inline]
     fn write_all_at(&mut self, buf: &[u8], offset: u64) -> io::Result<()> {
-        let offset = offset
-            .try_into()
-            .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
-        let at = self.get_mut(offset..).unwrap_or(&mut []);
-        let len = min(at.len(), buf.len());
-        at[..len].copy_from_slice(&buf[..len]);
-        Ok(())
+        self.as_mut_slice().write_all_at(buf, offset)
     }
 
+    #[cfg(feature = "std")]
+    #[inline]
     fn write_vectored_at(&mut self, bufs: &[IoSlice], offset: u64) -> io::Result<usize> {
-        let initial_offset = offset
-            .try_into()
-            .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
-        let mut running_offset = initial_offset;
-        for buf in bufs {
-            let at = self.get_mut(running_offset..).unwrap_or(&mut []);
-            let len = min(at.len(), buf.len());
-            at[..len].copy_from_slice(buf);
-            running_offset += len;
-        }
-        Ok(running_offset - initial_offset)
+        self.as_mut_slice().write_vectored_at(bufs, offset)
     }
 
+    #[cfg(feature = "std")]
+    #[inline]
     fn write_all_vectored_at(&mut self, bufs: &mut [IoSlice], offset: u64) -> io::Result<()> {
-        let mut running_offset = offset
-            .try_into()
-            .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
-        for buf in bufs {
-            let at = self.get_mut(running_offset..).unwrap_or(&mut []);
-            if at.len() < buf.len() {
-                return Err(io::Error::new(
-                    io::ErrorKind::UnexpectedEof,
-                    "failed to fill whole buffer",
-                ));
-            }
-            let len = buf.len();
-            at[..len].copy_from_slice(buf);
-            running_offset += len;
-        }
-        Ok(())
+        self.as_mut_slice().write_all_vectored_at(bufs, offset)
     }
 
+    #[cfg(feature = "std")]
     #[inline]
     fn is_write_vectored_at(&self) -> bool {
-        true
+        self.as_slice().is_write_vectored_at()
+    }
+
+    #[inline]
+    fn write_at_with(&mut self, buf: &[u8], offset: u64, flags: WriteFlags) -> io::Result<usize> {
+        self.as_mut_slice().write_at_with(buf, offset, flags)
     }
 
     #[inline]
-    fn copy_from<R: ReadAt>(
+    fn copy_from(
         &mut self,
-        _offset: u64,
-        input: &R,
+        offset: u64,
+        input: &dyn ReadAt,
         input_offset: u64,
         len: u64,
     ) -> io::Result<u64> {
-        let _todo = BorrowStreamer::new(input, input_offset).take(len);
-        todo!("slice::copy_from")
+        self.as_mut_slice()
+            .copy_from(offset, input, input_offset, len)
+    }
+
+    #[inline]
+    fn punch_hole(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        self.as_mut_slice().punch_hole(offset, len)
+    }
+
+    #[inline]
+    fn zero_range(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        self.fill_at(0, offset, len)
     }
 
     #[inline]
-    fn set_len(&mut self, _len: u64) -> io::Result<()> {
-        Err(io::Error::new(
-            io::ErrorKind::PermissionDenied,
-            "cannot set_len on a slice",
-        ))
+    fn fill_at(&mut self, byte: u8, offset: u64, len: u64) -> io::Result<()> {
+        self.allocate(offset, len)?;
+        self.as_mut_slice().fill_at(byte, offset, len)
+    }
+
+    #[inline]
+    fn allocate(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        let end = offset
+            .checked_add(len)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "offset overflow"))?;
+        self.grow_to(end)
+    }
+
+    /// Reserves capacity in the vector, without changing its length.
+    fn reserve_at(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        let end = offset
+            .checked_add(len)
+            .and_then(|end| usize::try_from(end).ok())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "offset overflow"))?;
+        self.try_reserve(end.saturating_sub(self.len()))
+            .map_err(|_| io::ErrorKind::OutOfMemory.into())
     }
 }
 
-impl Array for Vec<u8> {
+impl Resize for Vec<u8> {
     #[inline]
-    fn metadata(&self) -> io::Result<Metadata> {
-        self.as_slice().metadata()
+    fn set_len(&mut self, len: u64) -> io::Result<()> {
+        self.resize(
+            len.try_into()
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "offset overflow"))?,
+            0,
+        );
+        Ok(())
     }
 
     #[inline]
-    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
-        self.as_slice().advise(offset, len, advice)
+    fn grow_to(&mut self, len: u64) -> io::Result<()> {
+        if len > self.len() as u64 {
+            Resize::set_len(self, len)?;
+        }
+        Ok(())
     }
 }
 
-impl Array for &mut Vec<u8> {
+impl<const N: usize> Array for [u8; N] {
     #[inline]
     fn metadata(&self) -> io::Result<Metadata> {
         self.as_slice().metadata()
     }
 
+    #[cfg(feature = "std")]
     #[inline]
     fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
         self.as_slice().advise(offset, len, advice)
     }
 }
 
-impl ReadAt for Vec<u8> {
+impl<const N: usize> ReadAt for [u8; N] {
     #[inline]
     fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
         self.as_slice().read_at(buf, offset)
@@ -291,28 +420,59 @@ impl ReadAt for Vec<u8> {
         self.as_slice().read_exact_at(buf, offset)
     }
 
+    #[inline]
+    fn read_full_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        self.as_slice().read_full_at(buf, offset)
+    }
+
+    #[inline]
+    fn copy_to<W: WriteAt + ?Sized>(
+        &self,
+        offset: u64,
+        output: &mut W,
+        output_offset: u64,
+        len: u64,
+    ) -> io::Result<u64> {
+        copy_to(self.as_slice(), offset, output, output_offset, len)
+    }
+
+    #[cfg(feature = "std")]
     #[inline]
     fn read_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<usize> {
         self.as_slice().read_vectored_at(bufs, offset)
     }
 
+    #[cfg(feature = "std")]
     #[inline]
     fn read_exact_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<()> {
         self.as_slice().read_exact_vectored_at(bufs, offset)
     }
 
+    #[cfg(feature = "std")]
     #[inline]
     fn is_read_vectored_at(&self) -> bool {
         self.as_slice().is_read_vectored_at()
     }
 
+    #[inline]
+    fn read_at_nowait(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        self.as_slice().read_at_nowait(buf, offset)
+    }
+
     #[cfg(feature = "io-streams")]
-    fn read_via_stream_at(&self, _offset: u64) -> io::Result<StreamReader> {
-        todo!("slice::read_via_stream_at")
+    #[inline]
+    fn read_via_stream_at(&self, offset: u64) -> io::Result<StreamReader> {
+        self.as_slice().read_via_stream_at(offset)
+    }
+
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    fn read_range_via_stream(&self, offset: u64, len: u64) -> io::Result<StreamReader> {
+        self.as_slice().read_range_via_stream(offset, len)
     }
 }
 
-impl WriteAt for Vec<u8> {
+impl<const N: usize> WriteAt for [u8; N] {
     #[inline]
     fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
         self.as_mut_slice().write_at(buf, offset)
@@ -323,89 +483,294 @@ impl WriteAt for Vec<u8> {
         self.as_mut_slice().write_all_at(buf, offset)
     }
 
+    #[cfg(feature = "std")]
     #[inline]
     fn write_vectored_at(&mut self, bufs: &[IoSlice], offset: u64) -> io::Result<usize> {
         self.as_mut_slice().write_vectored_at(bufs, offset)
     }
 
+    #[cfg(feature = "std")]
     #[inline]
     fn write_all_vectored_at(&mut self, bufs: &mut [IoSlice], offset: u64) -> io::Result<()> {
         self.as_mut_slice().write_all_vectored_at(bufs, offset)
     }
 
+    #[cfg(feature = "std")]
     #[inline]
     fn is_write_vectored_at(&self) -> bool {
         self.as_slice().is_write_vectored_at()
     }
 
     #[inline]
-    fn copy_from<R: ReadAt>(
-        &mut self,
+    fn write_at_with(&mut self, buf: &[u8], offset: u64, flags: WriteFlags) -> io::Result<usize> {
+        self.as_mut_slice().write_at_with(buf, offset, flags)
+    }
+
+    #[inline]
+    fn punch_hole(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        self.as_mut_slice().punch_hole(offset, len)
+    }
+
+    #[inline]
+    fn zero_range(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        self.as_mut_slice().zero_range(offset, len)
+    }
+
+    #[inline]
+    fn fill_at(&mut self, byte: u8, offset: u64, len: u64) -> io::Result<()> {
+        self.as_mut_slice().fill_at(byte, offset, len)
+    }
+}
+
+/// Positional reads of a cursor read from the start of its underlying bytes,
+/// and don't consult or modify the cursor's position.
+#[cfg(feature = "std")]
+impl<T: AsRef<[u8]>> Array for Cursor<T> {
+    #[inline]
+    fn metadata(&self) -> io::Result<Metadata> {
+        self.get_ref().as_ref().metadata()
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        self.get_ref().as_ref().advise(offset, len, advice)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: AsRef<[u8]>> ReadAt for Cursor<T> {
+    #[inline]
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        self.get_ref().as_ref().read_at(buf, offset)
+    }
+
+    #[inline]
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+        self.get_ref().as_ref().read_exact_at(buf, offset)
+    }
+
+    #[inline]
+    fn read_full_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        self.get_ref().as_ref().read_full_at(buf, offset)
+    }
+
+    #[inline]
+    fn copy_to<W: WriteAt + ?Sized>(
+        &self,
         offset: u64,
-        input: &R,
-        input_offset: u64,
+        output: &mut W,
+        output_offset: u64,
         len: u64,
     ) -> io::Result<u64> {
-        self.as_mut_slice()
-            .copy_from(offset, input, input_offset, len)
+        copy_to(self.get_ref().as_ref(), offset, output, output_offset, len)
     }
 
+    #[cfg(feature = "std")]
     #[inline]
-    fn set_len(&mut self, len: u64) -> io::Result<()> {
-        self.resize(
-            len.try_into()
-                .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?,
-            0,
-        );
-        Ok(())
+    fn read_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<usize> {
+        self.get_ref().as_ref().read_vectored_at(bufs, offset)
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn read_exact_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<()> {
+        self.get_ref().as_ref().read_exact_vectored_at(bufs, offset)
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn is_read_vectored_at(&self) -> bool {
+        self.get_ref().as_ref().is_read_vectored_at()
+    }
+
+    #[inline]
+    fn read_at_nowait(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        self.get_ref().as_ref().read_at_nowait(buf, offset)
+    }
+
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    fn read_via_stream_at(&self, offset: u64) -> io::Result<StreamReader> {
+        self.get_ref().as_ref().read_via_stream_at(offset)
+    }
+
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    fn read_range_via_stream(&self, offset: u64, len: u64) -> io::Result<StreamReader> {
+        self.get_ref().as_ref().read_range_via_stream(offset, len)
     }
 }
 
-impl WriteAt for &mut Vec<u8> {
+/// Positional writes to a cursor grow the vector as needed, like writes to
+/// a file, and don't consult or modify the cursor's position.
+#[cfg(feature = "std")]
+impl WriteAt for Cursor<Vec<u8>> {
     #[inline]
     fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
-        self.as_mut_slice().write_at(buf, offset)
+        self.get_mut().allocate(offset, buf.len() as u64)?;
+        self.get_mut().write_at(buf, offset)
     }
 
     #[inline]
     fn write_all_at(&mut self, buf: &[u8], offset: u64) -> io::Result<()> {
-        self.as_mut_slice().write_all_at(buf, offset)
+        self.get_mut().allocate(offset, buf.len() as u64)?;
+        self.get_mut().write_all_at(buf, offset)
     }
 
+    #[cfg(feature = "std")]
     #[inline]
     fn write_vectored_at(&mut self, bufs: &[IoSlice], offset: u64) -> io::Result<usize> {
-        self.as_mut_slice().write_vectored_at(bufs, offset)
+        let len = bufs.iter().map(|buf| buf.len() as u64).sum();
+        self.get_mut().allocate(offset, len)?;
+        self.get_mut().write_vectored_at(bufs, offset)
     }
 
+    #[cfg(feature = "std")]
     #[inline]
     fn write_all_vectored_at(&mut self, bufs: &mut [IoSlice], offset: u64) -> io::Result<()> {
-        self.as_mut_slice().write_all_vectored_at(bufs, offset)
+        let len = bufs.iter().map(|buf| buf.len() as u64).sum();
+        self.get_mut().allocate(offset, len)?;
+        self.get_mut().write_all_vectored_at(bufs, offset)
     }
 
+    #[cfg(feature = "std")]
     #[inline]
     fn is_write_vectored_at(&self) -> bool {
-        self.as_slice().is_write_vectored_at()
+        self.get_ref().is_write_vectored_at()
     }
 
     #[inline]
-    fn copy_from<R: ReadAt>(
-        &mut self,
-        offset: u64,
-        input: &R,
-        input_offset: u64,
-        len: u64,
-    ) -> io::Result<u64> {
-        self.as_mut_slice()
-            .copy_from(offset, input, input_offset, len)
+    fn write_at_with(&mut self, buf: &[u8], offset: u64, flags: WriteFlags) -> io::Result<usize> {
+        self.get_mut().allocate(offset, buf.len() as u64)?;
+        self.get_mut().write_at_with(buf, offset, flags)
+    }
+
+    #[inline]
+    fn punch_hole(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        self.get_mut().punch_hole(offset, len)
     }
 
+    #[inline]
+    fn zero_range(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        self.get_mut().zero_range(offset, len)
+    }
+
+    #[inline]
+    fn fill_at(&mut self, byte: u8, offset: u64, len: u64) -> io::Result<()> {
+        self.get_mut().fill_at(byte, offset, len)
+    }
+
+    #[inline]
+    fn allocate(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        self.get_mut().allocate(offset, len)
+    }
+
+    #[inline]
+    fn reserve_at(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        self.get_mut().reserve_at(offset, len)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Resize for Cursor<Vec<u8>> {
     #[inline]
     fn set_len(&mut self, len: u64) -> io::Result<()> {
-        self.resize(
-            len.try_into()
-                .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?,
-            0,
-        );
-        Ok(())
+        Resize::set_len(self.get_mut(), len)
+    }
+
+    #[inline]
+    fn grow_to(&mut self, len: u64) -> io::Result<()> {
+        self.get_mut().grow_to(len)
     }
 }
+
+/// Positional writes to a cursor write within the slice, which can't grow,
+/// and don't consult or modify the cursor's position.
+#[cfg(feature = "std")]
+impl WriteAt for Cursor<&mut [u8]> {
+    #[inline]
+    fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        self.get_mut().write_at(buf, offset)
+    }
+
+    #[inline]
+    fn write_all_at(&mut self, buf: &[u8], offset: u64) -> io::Result<()> {
+        self.get_mut().write_all_at(buf, offset)
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn write_vectored_at(&mut self, bufs: &[IoSlice], offset: u64) -> io::Result<usize> {
+        self.get_mut().write_vectored_at(bufs, offset)
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn write_all_vectored_at(&mut self, bufs: &mut [IoSlice], offset: u64) -> io::Result<()> {
+        self.get_mut().write_all_vectored_at(bufs, offset)
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn is_write_vectored_at(&self) -> bool {
+        self.get_ref().is_write_vectored_at()
+    }
+
+    #[inline]
+    fn write_at_with(&mut self, buf: &[u8], offset: u64, flags: WriteFlags) -> io::Result<usize> {
+        self.get_mut().write_at_with(buf, offset, flags)
+    }
+
+    #[inline]
+    fn punch_hole(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        self.get_mut().punch_hole(offset, len)
+    }
+
+    #[inline]
+    fn zero_range(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        self.get_mut().zero_range(offset, len)
+    }
+
+    #[inline]
+    fn fill_at(&mut self, byte: u8, offset: u64, len: u64) -> io::Result<()> {
+        self.get_mut().fill_at(byte, offset, len)
+    }
+}
+
+/// Implement [`ReadAt::copy_to`] for an in-memory buffer, by writing the
+/// range directly from it.
+pub(crate) fn copy_to<W: WriteAt + ?Sized>(
+    bytes: &[u8],
+    offset: u64,
+    output: &mut W,
+    output_offset: u64,
+    len: u64,
+) -> io::Result<u64> {
+    let start = min(offset, bytes.len() as u64) as usize;
+    let end = min(offset.saturating_add(len), bytes.len() as u64) as usize;
+    output.write_all_at(&bytes[start..end], output_offset)?;
+    Ok((end - start) as u64)
+}
+
+/// Implement [`ReadAt::read_via_stream_at`] for an in-memory buffer.
+///
+/// There's no file to reopen, so this copies the bytes after `offset` and
+/// streams them from a thread.
+#[cfg(feature = "io-streams")]
+fn read_via_stream_at(bytes: &[u8], offset: u64) -> io::Result<StreamReader> {
+    let offset = usize::try_from(offset)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "offset overflow"))?;
+    let tail = bytes.get(offset..).unwrap_or(&[]).to_vec();
+    StreamReader::piped_thread(Box::new(Cursor::new(tail)))
+}
+
+/// Implement [`ReadAt::read_range_via_stream`] for an in-memory buffer.
+#[cfg(feature = "io-streams")]
+fn read_range_via_stream(bytes: &[u8], offset: u64, len: u64) -> io::Result<StreamReader> {
+    check_stream_range(bytes.len() as u64, offset, len)?;
+    // The range is within `bytes`, so these conversions can't fail.
+    let start = offset as usize;
+    let range = bytes[start..start + len as usize].to_vec();
+    StreamReader::piped_thread(Box::new(Cursor::new(range)))
+}
@@ -0,0 +1,308 @@
+//! A sparse in-memory array type.
+
+#[cfg(feature = "io-streams")]
+use crate::arrays::check_stream_range;
+use crate::arrays::DEFAULT_BLKSIZE;
+#[cfg(feature = "io-streams")]
+use crate::Streamer;
+use crate::{io, Array, ArrayType, Metadata, ReadAt, Resize, WriteAt};
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::cmp::min;
+use core::fmt;
+#[cfg(feature = "io-streams")]
+use io_streams::StreamReader;
+#[cfg(feature = "std")]
+use {
+    crate::Advice,
+    std::io::{IoSlice, IoSliceMut},
+};
+
+/// The size of the pages which a [`SparseMemArray`] allocates.
+const PAGE_SIZE: usize = DEFAULT_BLKSIZE as usize;
+
+/// An in-memory array which only allocates storage for the pages which have
+/// been written to, like a sparse file.
+///
+/// Unwritten ranges, or holes, read as zeros. [`Resize::set_len`] only
+/// updates the length, so an array with a length of a terabyte or more can
+/// be created cheaply, and the memory used is proportional to the data
+/// written. [`WriteAt::punch_hole`] deallocates whole pages, and
+/// [`Metadata::allocated_len`] reports the storage in use.
+///
+/// [`Metadata::allocated_len`]: crate::Metadata::allocated_len
+#[derive(Clone, Default)]
+pub struct SparseMemArray {
+    /// The populated pages, indexed by page number.
+    pages: BTreeMap<u64, Box<[u8; PAGE_SIZE]>>,
+    len: u64,
+}
+
+impl SparseMemArray {
+    /// Create a new empty array.
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Copy the bytes at `offset` into `buf`, which must be within the
+    /// array.
+    fn copy_out(&self, buf: &mut [u8], offset: u64) {
+        buf.fill(0);
+        if buf.is_empty() {
+            return;
+        }
+        let end = offset + buf.len() as u64;
+        let first = offset / PAGE_SIZE as u64;
+        let last = (end - 1) / PAGE_SIZE as u64;
+        for (&index, page) in self.pages.range(first..=last) {
+            let page_start = index * PAGE_SIZE as u64;
+            let start = offset.max(page_start);
+            let stop = end.min(page_start + PAGE_SIZE as u64);
+            buf[(start - offset) as usize..(stop - offset) as usize].copy_from_slice(
+                &page[(start - page_start) as usize..(stop - page_start) as usize],
+            );
+        }
+    }
+
+    /// Copy `buf` into the array at `offset`, allocating pages as needed and
+    /// extending the array if the write ends past the end.
+    fn copy_in(&mut self, buf: &[u8], offset: u64) -> io::Result<()> {
+        let end = offset
+            .checked_add(buf.len() as u64)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "offset overflow"))?;
+        let mut pos = offset;
+        while pos < end {
+            let index = pos / PAGE_SIZE as u64;
+            let within = (pos % PAGE_SIZE as u64) as usize;
+            let n = min(PAGE_SIZE - within, (end - pos) as usize);
+            let chunk = &buf[(pos - offset) as usize..][..n];
+            // Writing zeros into a hole doesn't change its contents, so
+            // leave it unallocated.
+            if let Some(page) = self.pages.get_mut(&index) {
+                page[within..within + n].copy_from_slice(chunk);
+            } else if chunk.iter().any(|&b| b != 0) {
+                let mut page = Box::new([0; PAGE_SIZE]);
+                page[within..within + n].copy_from_slice(chunk);
+                self.pages.insert(index, page);
+            }
+            pos += n as u64;
+        }
+        self.len = self.len.max(end);
+        Ok(())
+    }
+
+    /// Zero the range from `start` to `end`, deallocating the pages it
+    /// covers entirely, including a last page which extends past the end
+    /// of the array.
+    fn zero_pages(&mut self, start: u64, end: u64) {
+        if start >= end {
+            return;
+        }
+        let first = start / PAGE_SIZE as u64;
+        let last = (end - 1) / PAGE_SIZE as u64;
+        let indices = self
+            .pages
+            .range(first..=last)
+            .map(|(&index, _)| index)
+            .collect::<Vec<_>>();
+        for index in indices {
+            let page_start = index * PAGE_SIZE as u64;
+            let from = start.max(page_start);
+            let to = end.min(page_start + PAGE_SIZE as u64);
+            let page_end = min(page_start + PAGE_SIZE as u64, self.len);
+            if from == page_start && to >= page_end {
+                self.pages.remove(&index);
+            } else {
+                let page = self.pages.get_mut(&index).unwrap();
+                page[(from - page_start) as usize..(to - page_start) as usize].fill(0);
+            }
+        }
+    }
+
+    /// Returns the number of bytes of a `len`-byte read at `offset` which
+    /// are within the array.
+    #[inline]
+    fn available(&self, offset: u64, len: usize) -> usize {
+        min(len as u64, self.len.saturating_sub(offset)) as usize
+    }
+}
+
+impl fmt::Debug for SparseMemArray {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SparseMemArray")
+            .field("len", &self.len)
+            .field("allocated_pages", &self.pages.len())
+            .finish()
+    }
+}
+
+impl Array for SparseMemArray {
+    fn metadata(&self) -> io::Result<Metadata> {
+        Ok(Metadata {
+            len: self.len,
+            blksize: PAGE_SIZE as u64,
+            allocated_len: self.pages.len() as u64 * PAGE_SIZE as u64,
+            file_type: ArrayType::Memory,
+            #[cfg(feature = "std")]
+            modified: None,
+        })
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn advise(&self, _offset: u64, _len: u64, _advice: Advice) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl ReadAt for SparseMemArray {
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        let len = self.available(offset, buf.len());
+        self.copy_out(&mut buf[..len], offset);
+        Ok(len)
+    }
+
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+        if self.available(offset, buf.len()) != buf.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "failed to fill whole buffer",
+            ));
+        }
+        self.copy_out(buf, offset);
+        Ok(())
+    }
+
+    #[cfg(feature = "std")]
+    fn read_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<usize> {
+        let mut total = 0;
+        for buf in bufs {
+            let len = self.read_at(buf, offset + total as u64)?;
+            total += len;
+            if len < buf.len() {
+                break;
+            }
+        }
+        Ok(total)
+    }
+
+    #[cfg(feature = "std")]
+    fn read_exact_vectored_at(&self, bufs: &mut [IoSliceMut], mut offset: u64) -> io::Result<()> {
+        for buf in bufs {
+            self.read_exact_at(buf, offset)?;
+            offset += buf.len() as u64;
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn is_read_vectored_at(&self) -> bool {
+        true
+    }
+
+    #[inline]
+    fn read_at_nowait(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        self.read_at(buf, offset)
+    }
+
+    /// Streams from a copy of the array, which shares no storage with it, so
+    /// later writes to the array aren't seen by the stream.
+    #[cfg(feature = "io-streams")]
+    fn read_via_stream_at(&self, offset: u64) -> io::Result<StreamReader> {
+        StreamReader::piped_thread(Box::new(Streamer::owned(self.clone(), offset)))
+    }
+
+    #[cfg(feature = "io-streams")]
+    fn read_range_via_stream(&self, offset: u64, len: u64) -> io::Result<StreamReader> {
+        check_stream_range(self.len, offset, len)?;
+        StreamReader::piped_thread(Box::new(Streamer::bounded(self.clone(), offset, len)))
+    }
+}
+
+impl WriteAt for SparseMemArray {
+    #[inline]
+    fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        self.copy_in(buf, offset)?;
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn write_all_at(&mut self, buf: &[u8], offset: u64) -> io::Result<()> {
+        self.copy_in(buf, offset)
+    }
+
+    #[cfg(feature = "std")]
+    fn write_vectored_at(&mut self, bufs: &[IoSlice], offset: u64) -> io::Result<usize> {
+        let mut total = 0;
+        for buf in bufs {
+            self.copy_in(buf, offset + total as u64)?;
+            total += buf.len();
+        }
+        Ok(total)
+    }
+
+    #[cfg(feature = "std")]
+    fn write_all_vectored_at(&mut self, bufs: &mut [IoSlice], mut offset: u64) -> io::Result<()> {
+        for buf in bufs.iter() {
+            self.copy_in(buf, offset)?;
+            offset += buf.len() as u64;
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn is_write_vectored_at(&self) -> bool {
+        true
+    }
+
+    /// Allocates zeroed pages for any holes in the range.
+    fn allocate(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        let end = offset
+            .checked_add(len)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "offset overflow"))?;
+        if len == 0 {
+            return Ok(());
+        }
+        for index in offset / PAGE_SIZE as u64..=(end - 1) / PAGE_SIZE as u64 {
+            self.pages
+                .entry(index)
+                .or_insert_with(|| Box::new([0; PAGE_SIZE]));
+        }
+        self.len = self.len.max(end);
+        Ok(())
+    }
+
+    /// Deallocates the pages entirely within the range, and zeros the parts
+    /// of pages at its ends.
+    #[inline]
+    fn punch_hole(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        self.zero_pages(offset, min(offset.saturating_add(len), self.len));
+        Ok(())
+    }
+
+    fn zero_range(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        let end = offset
+            .checked_add(len)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "offset overflow"))?;
+        self.zero_pages(offset, min(end, self.len));
+        self.len = self.len.max(end);
+        Ok(())
+    }
+}
+
+impl Resize for SparseMemArray {
+    /// Updates the length, deallocating any pages past the new end. This
+    /// doesn't allocate any memory when extending the array.
+    fn set_len(&mut self, size: u64) -> io::Result<()> {
+        // Zero the data past the new end, so that it reads as zeros if the
+        // array is extended again.
+        self.zero_pages(size, self.len);
+        self.len = size;
+        Ok(())
+    }
+}
@@ -0,0 +1,483 @@
+//! Instrumentation of array I/O.
+
+use crate::{
+    Advice, Array, CloneOutcome, LockKind, Metadata, RangeLockGuard, ReadAt, Resize, SyncRangeMode,
+    WriteAt, WriteFlags,
+};
+#[cfg(feature = "io-streams")]
+use io_streams::StreamReader;
+use std::io::{self, IoSlice, IoSliceMut};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// An array which counts the reads and writes performed through it, and
+/// the bytes they transfer.
+///
+/// Every method is delegated to the inner array. Each read or write call
+/// counts as one request, whose size is the number of bytes requested;
+/// bytes are counted as they're transferred. [`WriteAt::copy_from`],
+/// [`WriteAt::clone_range_from`], [`WriteAt::zero_range`], and
+/// [`WriteAt::fill_at`] count as writes, and other operations, such as
+/// [`Resize::set_len`] and reads via streams, aren't counted.
+///
+/// The counters are atomic, so reads from multiple threads through `&self`
+/// are all counted.
+#[derive(Debug)]
+pub struct StatsArray<A> {
+    inner: A,
+    reads: Counters,
+    writes: Counters,
+    max_end: AtomicU64,
+}
+
+/// A snapshot of the counters of a [`StatsArray`], returned by
+/// [`StatsArray::stats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArrayStats {
+    reads: OpStats,
+    writes: OpStats,
+    max_end: u64,
+}
+
+/// Counts of one kind of request, in an [`ArrayStats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct OpStats {
+    count: u64,
+    bytes: u64,
+    total_len: u64,
+    min_len: u64,
+    max_len: u64,
+}
+
+#[derive(Debug)]
+struct Counters {
+    count: AtomicU64,
+    bytes: AtomicU64,
+    total_len: AtomicU64,
+    min_len: AtomicU64,
+    max_len: AtomicU64,
+}
+
+impl<A> StatsArray<A> {
+    /// Create a `StatsArray` which counts the I/O performed on `inner`,
+    /// starting from zero.
+    #[inline]
+    pub fn new(inner: A) -> Self {
+        Self {
+            inner,
+            reads: Counters::new(),
+            writes: Counters::new(),
+            max_end: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns a snapshot of the counters.
+    ///
+    /// The counters are updated independently, so if I/O is performed
+    /// concurrently, the snapshot may include part of a request.
+    pub fn stats(&self) -> ArrayStats {
+        ArrayStats {
+            reads: self.reads.snapshot(),
+            writes: self.writes.snapshot(),
+            max_end: self.max_end.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Reset the counters to zero.
+    pub fn reset(&self) {
+        self.reads.reset();
+        self.writes.reset();
+        self.max_end.store(0, Ordering::Relaxed);
+    }
+
+    /// Consume the `StatsArray` and return the inner array.
+    #[inline]
+    pub fn into_inner(self) -> A {
+        self.inner
+    }
+
+    /// Record a read of `len` bytes at `offset`, returning `result`.
+    fn read<T>(&self, offset: u64, len: usize, result: io::Result<T>) -> io::Result<T>
+    where
+        T: Transferred,
+    {
+        self.record(&self.reads, offset, len as u64, result)
+    }
+
+    /// Record a write of `len` bytes at `offset`, returning `result`.
+    fn write<T>(&self, offset: u64, len: u64, result: io::Result<T>) -> io::Result<T>
+    where
+        T: Transferred,
+    {
+        self.record(&self.writes, offset, len, result)
+    }
+
+    fn record<T: Transferred>(
+        &self,
+        counters: &Counters,
+        offset: u64,
+        len: u64,
+        result: io::Result<T>,
+    ) -> io::Result<T> {
+        counters.count.fetch_add(1, Ordering::Relaxed);
+        counters.total_len.fetch_add(len, Ordering::Relaxed);
+        counters.min_len.fetch_min(len, Ordering::Relaxed);
+        counters.max_len.fetch_max(len, Ordering::Relaxed);
+        if let Ok(value) = &result {
+            let bytes = value.transferred(len);
+            counters.bytes.fetch_add(bytes, Ordering::Relaxed);
+            self.max_end
+                .fetch_max(offset.saturating_add(bytes), Ordering::Relaxed);
+        }
+        result
+    }
+}
+
+/// The number of bytes transferred by a successful request, given the
+/// number requested.
+pub(crate) trait Transferred {
+    fn transferred(&self, len: u64) -> u64;
+}
+
+impl Transferred for () {
+    #[inline]
+    fn transferred(&self, len: u64) -> u64 {
+        len
+    }
+}
+
+impl Transferred for usize {
+    #[inline]
+    fn transferred(&self, _len: u64) -> u64 {
+        *self as u64
+    }
+}
+
+impl Transferred for u64 {
+    #[inline]
+    fn transferred(&self, _len: u64) -> u64 {
+        *self
+    }
+}
+
+impl Transferred for CloneOutcome {
+    #[inline]
+    fn transferred(&self, _len: u64) -> u64 {
+        self.len()
+    }
+}
+
+impl Counters {
+    fn new() -> Self {
+        Self {
+            count: AtomicU64::new(0),
+            bytes: AtomicU64::new(0),
+            total_len: AtomicU64::new(0),
+            min_len: AtomicU64::new(u64::MAX),
+            max_len: AtomicU64::new(0),
+        }
+    }
+
+    fn snapshot(&self) -> OpStats {
+        let count = self.count.load(Ordering::Relaxed);
+        OpStats {
+            count,
+            bytes: self.bytes.load(Ordering::Relaxed),
+            total_len: self.total_len.load(Ordering::Relaxed),
+            min_len: if count == 0 {
+                0
+            } else {
+                self.min_len.load(Ordering::Relaxed)
+            },
+            max_len: self.max_len.load(Ordering::Relaxed),
+        }
+    }
+
+    fn reset(&self) {
+        self.count.store(0, Ordering::Relaxed);
+        self.bytes.store(0, Ordering::Relaxed);
+        self.total_len.store(0, Ordering::Relaxed);
+        self.min_len.store(u64::MAX, Ordering::Relaxed);
+        self.max_len.store(0, Ordering::Relaxed);
+    }
+}
+
+impl ArrayStats {
+    /// Returns the counts of read requests.
+    #[inline]
+    #[must_use]
+    pub const fn reads(&self) -> OpStats {
+        self.reads
+    }
+
+    /// Returns the counts of write requests.
+    #[inline]
+    #[must_use]
+    pub const fn writes(&self) -> OpStats {
+        self.writes
+    }
+
+    /// Returns the offset just past the last byte read or written, which is
+    /// the highest offset touched. A whole-file scan shows up as this being
+    /// the length of the array.
+    #[inline]
+    #[must_use]
+    pub const fn max_end(&self) -> u64 {
+        self.max_end
+    }
+}
+
+impl OpStats {
+    /// Returns the number of requests.
+    #[inline]
+    #[must_use]
+    pub const fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Returns the number of bytes transferred.
+    #[inline]
+    #[must_use]
+    pub const fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    /// Returns the size of the smallest request, or zero if there were
+    /// none.
+    #[inline]
+    #[must_use]
+    pub const fn min_len(&self) -> u64 {
+        self.min_len
+    }
+
+    /// Returns the size of the largest request, or zero if there were none.
+    #[inline]
+    #[must_use]
+    pub const fn max_len(&self) -> u64 {
+        self.max_len
+    }
+
+    /// Returns the mean size of the requests, rounded down, or zero if there
+    /// were none.
+    #[inline]
+    #[must_use]
+    pub const fn mean_len(&self) -> u64 {
+        match self.count {
+            0 => 0,
+            count => self.total_len / count,
+        }
+    }
+}
+
+impl<A: Array> Array for StatsArray<A> {
+    #[inline]
+    fn metadata(&self) -> io::Result<Metadata> {
+        self.inner.metadata()
+    }
+
+    #[inline]
+    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        self.inner.advise(offset, len, advice)
+    }
+
+    #[inline]
+    fn readahead(&self, offset: u64, len: u64) -> io::Result<()> {
+        self.inner.readahead(offset, len)
+    }
+
+    #[inline]
+    fn lock_at(&self, offset: u64, len: u64, kind: LockKind) -> io::Result<RangeLockGuard<'_>> {
+        self.inner.lock_at(offset, len, kind)
+    }
+
+    #[inline]
+    fn try_lock_at(
+        &self,
+        offset: u64,
+        len: u64,
+        kind: LockKind,
+    ) -> io::Result<Option<RangeLockGuard<'_>>> {
+        self.inner.try_lock_at(offset, len, kind)
+    }
+}
+
+impl<A: ReadAt> ReadAt for StatsArray<A> {
+    #[inline]
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        let result = self.inner.read_at(buf, offset);
+        self.read(offset, buf.len(), result)
+    }
+
+    #[inline]
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+        let result = self.inner.read_exact_at(buf, offset);
+        self.read(offset, buf.len(), result)
+    }
+
+    #[inline]
+    fn read_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<usize> {
+        let len = bufs.iter().map(|b| b.len()).sum();
+        let result = self.inner.read_vectored_at(bufs, offset);
+        self.read(offset, len, result)
+    }
+
+    #[inline]
+    fn read_exact_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<()> {
+        let len = bufs.iter().map(|b| b.len()).sum();
+        let result = self.inner.read_exact_vectored_at(bufs, offset);
+        self.read(offset, len, result)
+    }
+
+    #[inline]
+    fn is_read_vectored_at(&self) -> bool {
+        self.inner.is_read_vectored_at()
+    }
+
+    #[inline]
+    fn read_at_nowait(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        let result = self.inner.read_at_nowait(buf, offset);
+        self.read(offset, buf.len(), result)
+    }
+
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    fn read_via_stream_at(&self, offset: u64) -> io::Result<StreamReader> {
+        self.inner.read_via_stream_at(offset)
+    }
+
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    fn read_range_via_stream(&self, offset: u64, len: u64) -> io::Result<StreamReader> {
+        self.inner.read_range_via_stream(offset, len)
+    }
+}
+
+impl<A: WriteAt> WriteAt for StatsArray<A> {
+    #[inline]
+    fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        let result = self.inner.write_at(buf, offset);
+        self.write(offset, buf.len() as u64, result)
+    }
+
+    #[inline]
+    fn write_all_at(&mut self, buf: &[u8], offset: u64) -> io::Result<()> {
+        let result = self.inner.write_all_at(buf, offset);
+        self.write(offset, buf.len() as u64, result)
+    }
+
+    #[inline]
+    fn write_vectored_at(&mut self, bufs: &[IoSlice], offset: u64) -> io::Result<usize> {
+        let len = bufs.iter().map(|b| b.len() as u64).sum();
+        let result = self.inner.write_vectored_at(bufs, offset);
+        self.write(offset, len, result)
+    }
+
+    #[inline]
+    fn write_all_vectored_at(&mut self, bufs: &mut [IoSlice], offset: u64) -> io::Result<()> {
+        let len = bufs.iter().map(|b| b.len() as u64).sum();
+        let result = self.inner.write_all_vectored_at(bufs, offset);
+        self.write(offset, len, result)
+    }
+
+    #[inline]
+    fn is_write_vectored_at(&self) -> bool {
+        self.inner.is_write_vectored_at()
+    }
+
+    #[inline]
+    fn write_at_with(&mut self, buf: &[u8], offset: u64, flags: WriteFlags) -> io::Result<usize> {
+        let result = self.inner.write_at_with(buf, offset, flags);
+        self.write(offset, buf.len() as u64, result)
+    }
+
+    #[inline]
+    fn copy_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<u64> {
+        let result = self.inner.copy_from(offset, input, input_offset, len);
+        self.write(offset, len, result)
+    }
+
+    #[inline]
+    fn clone_range_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<CloneOutcome> {
+        let result = self
+            .inner
+            .clone_range_from(offset, input, input_offset, len);
+        self.write(offset, len, result)
+    }
+
+    #[inline]
+    fn dedupe_range_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<u64> {
+        self.inner
+            .dedupe_range_from(offset, input, input_offset, len)
+    }
+
+    #[inline]
+    fn allocate(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        self.inner.allocate(offset, len)
+    }
+
+    #[inline]
+    fn reserve_at(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        self.inner.reserve_at(offset, len)
+    }
+
+    #[inline]
+    fn punch_hole(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        self.inner.punch_hole(offset, len)
+    }
+
+    #[inline]
+    fn zero_range(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        let result = self.inner.zero_range(offset, len);
+        self.write(offset, len, result)
+    }
+
+    #[inline]
+    fn fill_at(&mut self, byte: u8, offset: u64, len: u64) -> io::Result<()> {
+        let result = self.inner.fill_at(byte, offset, len);
+        self.write(offset, len, result)
+    }
+
+    #[inline]
+    fn sync_all(&self) -> io::Result<()> {
+        self.inner.sync_all()
+    }
+
+    #[inline]
+    fn sync_data(&self) -> io::Result<()> {
+        self.inner.sync_data()
+    }
+
+    #[inline]
+    fn sync_range(&self, offset: u64, len: u64, mode: SyncRangeMode) -> io::Result<()> {
+        self.inner.sync_range(offset, len, mode)
+    }
+}
+
+impl<A: Resize> Resize for StatsArray<A> {
+    #[inline]
+    fn set_len(&mut self, size: u64) -> io::Result<()> {
+        Resize::set_len(&mut self.inner, size)
+    }
+
+    #[inline]
+    fn grow_to(&mut self, size: u64) -> io::Result<()> {
+        self.inner.grow_to(size)
+    }
+}
@@ -0,0 +1,375 @@
+//! A stream adapter for arrays.
+
+use crate::arrays::DEFAULT_BLKSIZE;
+use crate::{Array, ReadAt, WriteAt};
+use std::cmp::min;
+use std::fmt::Arguments;
+use std::io::{self, BufRead, IoSlice, IoSliceMut, Read, Seek, SeekFrom, Write};
+use system_interface::io::Peek;
+
+/// A [`Read`]/[`BufRead`]/[`Write`]/[`Seek`]/[`Peek`] adapter which streams
+/// through an array, tracking its own current position.
+///
+/// This allows any [`ReadAt`] to be passed to APIs which expect a
+/// [`Read`], without a thread. `Write` is implemented when the array is
+/// [`WriteAt`].
+///
+/// `A` may be an array, or a reference to one, such as the `&mut` reference
+/// held by the streamer returned from [`EditAt::edit_via_stream_at`]. Reads
+/// and writes share the position, so data written through the streamer is
+/// visible to later reads through it.
+///
+/// In POSIX, `dup` produces a new file descriptor which shares a file
+/// description with the original file descriptor, and the file
+/// description includes the current position. In order to have independent
+/// streams through a file, we track our own current position.
+///
+/// The buffer used by [`BufRead`] is allocated on the first call to
+/// `fill_buf`, sized from [`Metadata::blksize`]. Other reads consume
+/// buffered data first, and otherwise read from the array directly, so
+/// `BufRead` and `Read` methods may be mixed freely. Writes discard the
+/// buffer.
+///
+/// A bounded streamer reads up to an end position, and reports an
+/// `UnexpectedEof` error if the array ends before that.
+///
+/// A read or write which would move the position past `u64::MAX` fails with
+/// an `InvalidInput` error with the message "position overflow", without
+/// transferring any data.
+///
+/// [`EditAt::edit_via_stream_at`]: crate::EditAt::edit_via_stream_at
+/// [`Metadata::blksize`]: crate::Metadata::blksize
+pub struct Streamer<A> {
+    inner: A,
+    /// The logical position, which is after any consumed buffered data.
+    pos: u64,
+    end: Option<u64>,
+    /// Data read from the array, starting at `pos - consumed`.
+    buf: Vec<u8>,
+    consumed: usize,
+}
+
+impl<'array, T: ?Sized> Streamer<&'array T> {
+    /// Create a streamer which borrows `array`, starting at `offset`.
+    #[inline]
+    pub fn new(array: &'array T, offset: u64) -> Self {
+        Self::owned(array, offset)
+    }
+}
+
+impl<A> Streamer<A> {
+    /// Create a streamer which takes ownership of `array`, starting at
+    /// `offset`.
+    ///
+    /// `array` may also be a `&mut` reference.
+    #[inline]
+    pub fn owned(array: A, offset: u64) -> Self {
+        Self {
+            inner: array,
+            pos: offset,
+            end: None,
+            buf: Vec::new(),
+            consumed: 0,
+        }
+    }
+
+    /// Consume the streamer and return the underlying array.
+    #[inline]
+    pub fn into_inner(self) -> A {
+        self.inner
+    }
+
+    /// Create a streamer which reads the `len` bytes at `pos`. The range
+    /// must not overflow.
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    pub(crate) fn bounded(inner: A, pos: u64, len: u64) -> Self {
+        Self {
+            inner,
+            pos,
+            end: Some(pos + len),
+            buf: Vec::new(),
+            consumed: 0,
+        }
+    }
+
+    /// Copy buffered data into `buf`, and return the number of bytes
+    /// copied.
+    #[inline]
+    fn read_buffered(&mut self, buf: &mut [u8]) -> usize {
+        let buffered = &self.buf[self.consumed..];
+        let n = min(buffered.len(), buf.len());
+        buf[..n].copy_from_slice(&buffered[..n]);
+        self.consumed += n;
+        self.pos += n as u64;
+        n
+    }
+
+    #[inline]
+    fn has_buffered(&self) -> bool {
+        self.consumed < self.buf.len()
+    }
+
+    #[inline]
+    fn discard_buffer(&mut self) {
+        self.buf.clear();
+        self.consumed = 0;
+    }
+
+    /// Limit the length of a read into a buffer of `len` bytes to the end
+    /// of the range.
+    #[inline]
+    fn limit(&self, len: usize) -> usize {
+        match self.end {
+            Some(end) => min(len as u64, end.saturating_sub(self.pos)) as usize,
+            None => len,
+        }
+    }
+
+    /// Check the result of a non-empty read which returned `n` bytes.
+    #[inline]
+    fn check_eof(&self, n: usize) -> io::Result<usize> {
+        match self.end {
+            Some(end) if n == 0 && self.pos < end => Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "array ended before the end of the stream range",
+            )),
+            _ => Ok(n),
+        }
+    }
+}
+
+impl<A: ReadAt> Read for Streamer<A> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.has_buffered() {
+            return Ok(self.read_buffered(buf));
+        }
+        let _new_pos = self
+            .pos
+            .checked_add(buf.len() as u64)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "position overflow"))?;
+        let limit = self.limit(buf.len());
+        if limit == 0 {
+            return Ok(0);
+        }
+        let n = self.check_eof(self.inner.read_at(&mut buf[..limit], self.pos)?)?;
+        self.pos += n as u64;
+        Ok(n)
+    }
+
+    #[inline]
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut]) -> io::Result<usize> {
+        if self.end.is_some() || self.has_buffered() {
+            // Just read into the first non-empty buffer, rather than
+            // trimming the list to the range or the buffered data.
+            return match bufs.iter_mut().find(|b| !b.is_empty()) {
+                Some(buf) => self.read(buf),
+                None => Ok(0),
+            };
+        }
+
+        let mut new_pos = self.pos;
+        for buf in bufs.iter() {
+            new_pos = new_pos
+                .checked_add(buf.len() as u64)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "position overflow"))?;
+        }
+        let n = self.inner.read_vectored_at(bufs, self.pos)?;
+        self.pos += n as u64;
+        Ok(n)
+    }
+
+    #[cfg(can_vector)]
+    #[inline]
+    fn is_read_vectored(&self) -> bool {
+        self.inner.is_read_vectored_at()
+    }
+
+    #[inline]
+    fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        let n = self.read_buffered(buf);
+        let buf = &mut buf[n..];
+        if buf.is_empty() {
+            return Ok(());
+        }
+        let new_pos = self
+            .pos
+            .checked_add(buf.len() as u64)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "position overflow"))?;
+        if self.end.is_some_and(|end| new_pos > end) {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "failed to fill whole buffer",
+            ));
+        }
+        let _: () = self.inner.read_exact_at(buf, self.pos)?;
+        self.pos = new_pos;
+        Ok(())
+    }
+}
+
+impl<A: ReadAt> Peek for Streamer<A> {
+    #[inline]
+    fn peek(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.has_buffered() {
+            let buffered = &self.buf[self.consumed..];
+            let n = min(buffered.len(), buf.len());
+            buf[..n].copy_from_slice(&buffered[..n]);
+            return Ok(n);
+        }
+        let limit = self.limit(buf.len());
+        self.inner.read_at(&mut buf[..limit], self.pos)
+    }
+}
+
+impl<A: WriteAt> Write for Streamer<A> {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.discard_buffer();
+        let _new_pos = self
+            .pos
+            .checked_add(buf.len() as u64)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "position overflow"))?;
+        let n = self.inner.write_at(buf, self.pos)?;
+        self.pos += n as u64;
+        Ok(n)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    #[inline]
+    fn write_vectored(&mut self, bufs: &[IoSlice]) -> io::Result<usize> {
+        self.discard_buffer();
+        let mut new_pos = self.pos;
+        for buf in bufs.iter() {
+            new_pos = new_pos
+                .checked_add(buf.len() as u64)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "position overflow"))?;
+        }
+        let n = self.inner.write_vectored_at(bufs, self.pos)?;
+        self.pos += n as u64;
+        Ok(n)
+    }
+
+    #[cfg(can_vector)]
+    #[inline]
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored_at()
+    }
+
+    #[inline]
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.discard_buffer();
+        let new_pos = self
+            .pos
+            .checked_add(buf.len() as u64)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "position overflow"))?;
+        let _: () = self.inner.write_all_at(buf, self.pos)?;
+        self.pos = new_pos;
+        Ok(())
+    }
+
+    #[cfg(write_all_vectored)]
+    #[inline]
+    fn write_all_vectored(&mut self, bufs: &mut [IoSlice]) -> io::Result<()> {
+        self.discard_buffer();
+        let mut new_pos = self.pos;
+        for buf in bufs.iter() {
+            new_pos = new_pos
+                .checked_add(buf.len() as u64)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "position overflow"))?;
+        }
+        let _: () = self.inner.write_all_vectored_at(bufs, self.pos)?;
+        for buf in bufs {
+            self.pos += buf.len() as u64;
+        }
+        Ok(())
+    }
+
+    fn write_fmt(&mut self, fmt: Arguments) -> io::Result<()> {
+        // TODO: Use `to_str` when it's stabilized: https://github.com/rust-lang/rust/issues/74442
+        self.write_all(fmt.to_string().as_bytes())
+    }
+}
+
+/// Seeking past the end of the array is permitted, as with files; reads
+/// there return 0 bytes, and writes extend the array.
+impl<A: Array> Seek for Streamer<A> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let (base, delta) = match pos {
+            SeekFrom::Start(offset) => {
+                self.seek_to(offset);
+                return Ok(offset);
+            }
+            SeekFrom::Current(delta) => (self.pos, delta),
+            SeekFrom::End(delta) => (self.inner.metadata()?.len(), delta),
+        };
+        let pos = base.checked_add_signed(delta).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )
+        })?;
+        self.seek_to(pos);
+        Ok(pos)
+    }
+
+    #[inline]
+    fn stream_position(&mut self) -> io::Result<u64> {
+        Ok(self.pos)
+    }
+}
+
+impl<A> Streamer<A> {
+    /// Set the position to `pos`, keeping the buffer if `pos` is within it.
+    fn seek_to(&mut self, pos: u64) {
+        let start = self.pos - self.consumed as u64;
+        match pos.checked_sub(start) {
+            Some(offset) if offset <= self.buf.len() as u64 => self.consumed = offset as usize,
+            _ => self.discard_buffer(),
+        }
+        self.pos = pos;
+    }
+}
+
+impl<A: ReadAt> BufRead for Streamer<A> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if !self.has_buffered() {
+            self.discard_buffer();
+            if self.buf.capacity() == 0 {
+                let blksize = match self.inner.metadata()?.blksize() {
+                    0 => DEFAULT_BLKSIZE,
+                    blksize => blksize,
+                };
+                self.buf = Vec::with_capacity(usize::try_from(blksize).unwrap_or(usize::MAX));
+            }
+            let limit = self.limit(self.buf.capacity());
+            if limit != 0 {
+                self.buf.resize(limit, 0);
+                match self
+                    .inner
+                    .read_at(&mut self.buf, self.pos)
+                    .and_then(|n| self.check_eof(n))
+                {
+                    Ok(n) => self.buf.truncate(n),
+                    Err(err) => {
+                        self.buf.clear();
+                        return Err(err);
+                    }
+                }
+            }
+        }
+        Ok(&self.buf[self.consumed..])
+    }
+
+    #[inline]
+    fn consume(&mut self, amt: usize) {
+        let amt = min(amt, self.buf.len() - self.consumed);
+        self.consumed += amt;
+        self.pos += amt as u64;
+    }
+}
@@ -0,0 +1,441 @@
+//! Support for testing code which uses arrays.
+//!
+//! [`FaultInjectionArray`] wraps an array, and makes reads and writes
+//! performed through it fail or misbehave according to a schedule of
+//! [`Rule`]s, so that error handling can be tested without mocking the OS.
+//!
+//! ```
+//! use io_arrays::testing::{Fault, FaultInjectionArray, Op, Rule};
+//! use io_arrays::{ReadAt, WriteAt};
+//! use std::io;
+//!
+//! # fn main() -> io::Result<()> {
+//! let mut array = FaultInjectionArray::new(vec![0_u8; 16])
+//!     // The first two writes are interrupted.
+//!     .inject(Rule::new(Op::Write, Fault::Error(io::ErrorKind::Interrupted)).times(2))
+//!     // After that, writes write at most 3 bytes.
+//!     .inject(Rule::new(Op::Write, Fault::Short(3)));
+//!
+//! // `write_all_at` retries until all the data is written.
+//! array.write_all_at(b"hello", 4)?;
+//! assert_eq!(array.read_to_vec_at(4, 5)?, b"hello");
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    Advice, Array, LockKind, Metadata, RangeLockGuard, ReadAt, Resize, SyncRangeMode, WriteAt,
+};
+#[cfg(feature = "io-streams")]
+use io_streams::StreamReader;
+use std::cmp::min;
+use std::io::{self, IoSlice, IoSliceMut};
+use std::mem::take;
+use std::ops::Range;
+use std::sync::{Mutex, PoisonError};
+
+/// A kind of operation which faults can be injected into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    /// A read, by `read_at`, `read_vectored_at`, or `read_at_nowait`,
+    /// including each of the reads `read_exact_at` is made of.
+    Read,
+
+    /// A write, by `write_at` or `write_vectored_at`, including each of the
+    /// writes `write_all_at` and other default methods are made of.
+    Write,
+}
+
+/// A fault to inject into an operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    /// Fail with an error of the given kind, without performing the
+    /// operation.
+    Error(io::ErrorKind),
+
+    /// Transfer at most the given number of bytes, as a short read or
+    /// write. Vectored operations transfer them from the first non-empty
+    /// buffer.
+    Short(usize),
+
+    /// Invert the bits of the bytes transferred, so that reads return, or
+    /// writes store, corrupted data.
+    Corrupt,
+}
+
+/// A rule in the schedule of a [`FaultInjectionArray`], which injects a
+/// [`Fault`] into some of the operations of one kind.
+///
+/// A rule matches the operations of its kind which touch its range of
+/// offsets, if it has one. By default it applies to all of them; it can
+/// skip some first with [`Rule::after`], and stop after some with
+/// [`Rule::times`]. So, for example, to fail the third write:
+///
+/// ```
+/// # use io_arrays::testing::{Fault, Op, Rule};
+/// # use std::io;
+/// let rule = Rule::new(Op::Write, Fault::Error(io::ErrorKind::StorageFull))
+///     .after(2)
+///     .times(1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Rule {
+    op: Op,
+    fault: Fault,
+    range: Option<Range<u64>>,
+    after: u64,
+    times: Option<u64>,
+}
+
+impl Rule {
+    /// Create a rule which injects `fault` into every operation of kind
+    /// `op`.
+    #[inline]
+    #[must_use]
+    pub const fn new(op: Op, fault: Fault) -> Self {
+        Self {
+            op,
+            fault,
+            range: None,
+            after: 0,
+            times: None,
+        }
+    }
+
+    /// Only match operations which touch `range`.
+    #[inline]
+    #[must_use]
+    pub fn at(mut self, range: Range<u64>) -> Self {
+        self.range = Some(range);
+        self
+    }
+
+    /// Let the first `n` matching operations through before applying the
+    /// fault.
+    #[inline]
+    #[must_use]
+    pub const fn after(mut self, n: u64) -> Self {
+        self.after = n;
+        self
+    }
+
+    /// Apply the fault to `n` operations, and then stop.
+    #[inline]
+    #[must_use]
+    pub const fn times(mut self, n: u64) -> Self {
+        self.times = Some(n);
+        self
+    }
+
+    fn matches(&self, op: Op, offset: u64, len: usize) -> bool {
+        self.op == op
+            && self.range.as_ref().is_none_or(|range| {
+                offset < range.end && offset.saturating_add(len.max(1) as u64) > range.start
+            })
+    }
+}
+
+#[derive(Debug)]
+struct Scheduled {
+    rule: Rule,
+    seen: u64,
+    applied: u64,
+}
+
+/// An array which injects faults into the reads and writes performed
+/// through it, for testing.
+///
+/// Each read or write is checked against the [`Rule`]s added with
+/// [`FaultInjectionArray::inject`], in the order they were added, and the
+/// fault of the first one which applies is injected. Operations with no
+/// fault are delegated to the inner array.
+///
+/// Only the primitive reads and writes are delegated directly; methods such
+/// as [`ReadAt::read_exact_at`], [`WriteAt::write_all_at`], and
+/// [`WriteAt::copy_from`] are made of them, so they see the injected faults
+/// and retry interrupted and short operations as they would for a file.
+/// Other methods, such as [`Array::metadata`] and [`Resize::set_len`],
+/// are delegated without faults. Streams, such as those from
+/// `read_via_stream_at`, read from the inner array, so they don't see faults
+/// either.
+#[derive(Debug)]
+pub struct FaultInjectionArray<A> {
+    inner: A,
+    rules: Mutex<Vec<Scheduled>>,
+}
+
+impl<A> FaultInjectionArray<A> {
+    /// Create a `FaultInjectionArray` which delegates to `inner`, with no
+    /// faults scheduled.
+    #[inline]
+    pub fn new(inner: A) -> Self {
+        Self {
+            inner,
+            rules: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Add `rule` to the end of the schedule.
+    #[must_use]
+    pub fn inject(self, rule: Rule) -> Self {
+        self.rules
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(Scheduled {
+                rule,
+                seen: 0,
+                applied: 0,
+            });
+        self
+    }
+
+    /// Consume the `FaultInjectionArray` and return the inner array.
+    #[inline]
+    pub fn into_inner(self) -> A {
+        self.inner
+    }
+
+    /// Find the fault to inject into an operation, and count it against the
+    /// rules it matches.
+    fn fault(&self, op: Op, offset: u64, len: usize) -> Option<Fault> {
+        let mut rules = self.rules.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut fault = None;
+        for scheduled in rules.iter_mut() {
+            if !scheduled.rule.matches(op, offset, len) {
+                continue;
+            }
+            scheduled.seen += 1;
+            if fault.is_none()
+                && scheduled.seen > scheduled.rule.after
+                && scheduled
+                    .rule
+                    .times
+                    .is_none_or(|times| scheduled.applied < times)
+            {
+                scheduled.applied += 1;
+                fault = Some(scheduled.rule.fault);
+            }
+        }
+        fault
+    }
+
+    /// Perform a read into `buf` with `read`, injecting any fault.
+    fn read_with(
+        &self,
+        buf: &mut [u8],
+        offset: u64,
+        read: impl FnOnce(&mut [u8]) -> io::Result<usize>,
+    ) -> io::Result<usize> {
+        match self.fault(Op::Read, offset, buf.len()) {
+            None => read(buf),
+            Some(Fault::Error(kind)) => Err(injected(kind)),
+            Some(Fault::Short(n)) => {
+                let n = min(n, buf.len());
+                read(&mut buf[..n])
+            }
+            Some(Fault::Corrupt) => {
+                let n = read(buf)?;
+                corrupt(&mut buf[..n]);
+                Ok(n)
+            }
+        }
+    }
+}
+
+/// The error for an injected [`Fault::Error`].
+fn injected(kind: io::ErrorKind) -> io::Error {
+    io::Error::new(kind, "injected fault")
+}
+
+fn corrupt(bytes: &mut [u8]) {
+    for byte in bytes {
+        *byte = !*byte;
+    }
+}
+
+impl<A: Array> Array for FaultInjectionArray<A> {
+    #[inline]
+    fn metadata(&self) -> io::Result<Metadata> {
+        self.inner.metadata()
+    }
+
+    #[inline]
+    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        self.inner.advise(offset, len, advice)
+    }
+
+    #[inline]
+    fn lock_at(&self, offset: u64, len: u64, kind: LockKind) -> io::Result<RangeLockGuard<'_>> {
+        self.inner.lock_at(offset, len, kind)
+    }
+
+    #[inline]
+    fn try_lock_at(
+        &self,
+        offset: u64,
+        len: u64,
+        kind: LockKind,
+    ) -> io::Result<Option<RangeLockGuard<'_>>> {
+        self.inner.try_lock_at(offset, len, kind)
+    }
+}
+
+impl<A: ReadAt> ReadAt for FaultInjectionArray<A> {
+    #[inline]
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        self.read_with(buf, offset, |buf| self.inner.read_at(buf, offset))
+    }
+
+    /// Reads with [`ReadAt::read_at`] until `buf` is full, retrying
+    /// interrupted and short reads, as files do.
+    fn read_exact_at(&self, mut buf: &mut [u8], mut offset: u64) -> io::Result<()> {
+        while !buf.is_empty() {
+            match self.read_at(buf, offset) {
+                Ok(0) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "failed to fill whole buffer",
+                    ))
+                }
+                Ok(nread) => {
+                    offset = offset.checked_add(nread as u64).ok_or_else(|| {
+                        io::Error::new(io::ErrorKind::InvalidInput, "offset overflow")
+                    })?;
+                    buf = &mut take(&mut buf)[nread..];
+                }
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => (),
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(())
+    }
+
+    fn read_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<usize> {
+        let len = bufs.iter().map(|b| b.len()).sum();
+        match self.fault(Op::Read, offset, len) {
+            None => self.inner.read_vectored_at(bufs, offset),
+            Some(Fault::Error(kind)) => Err(injected(kind)),
+            Some(Fault::Short(n)) => match bufs.iter_mut().find(|b| !b.is_empty()) {
+                Some(buf) => {
+                    let n = min(n, buf.len());
+                    self.inner.read_at(&mut buf[..n], offset)
+                }
+                None => Ok(0),
+            },
+            Some(Fault::Corrupt) => {
+                let nread = self.inner.read_vectored_at(bufs, offset)?;
+                let mut remaining = nread;
+                for buf in bufs.iter_mut() {
+                    let n = min(remaining, buf.len());
+                    corrupt(&mut buf[..n]);
+                    remaining -= n;
+                }
+                Ok(nread)
+            }
+        }
+    }
+
+    /// Reads into each buffer in turn with [`ReadAt::read_exact_at`].
+    fn read_exact_vectored_at(&self, bufs: &mut [IoSliceMut], mut offset: u64) -> io::Result<()> {
+        for buf in bufs {
+            self.read_exact_at(buf, offset)?;
+            offset = offset
+                .checked_add(buf.len() as u64)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "offset overflow"))?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn is_read_vectored_at(&self) -> bool {
+        self.inner.is_read_vectored_at()
+    }
+
+    #[inline]
+    fn read_at_nowait(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        self.read_with(buf, offset, |buf| self.inner.read_at_nowait(buf, offset))
+    }
+
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    fn read_via_stream_at(&self, offset: u64) -> io::Result<StreamReader> {
+        self.inner.read_via_stream_at(offset)
+    }
+}
+
+impl<A: WriteAt> WriteAt for FaultInjectionArray<A> {
+    fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        match self.fault(Op::Write, offset, buf.len()) {
+            None => self.inner.write_at(buf, offset),
+            Some(Fault::Error(kind)) => Err(injected(kind)),
+            Some(Fault::Short(n)) => self.inner.write_at(&buf[..min(n, buf.len())], offset),
+            Some(Fault::Corrupt) => {
+                let mut data = buf.to_vec();
+                corrupt(&mut data);
+                self.inner.write_at(&data, offset)
+            }
+        }
+    }
+
+    fn write_vectored_at(&mut self, bufs: &[IoSlice], offset: u64) -> io::Result<usize> {
+        let len = bufs.iter().map(|b| b.len()).sum();
+        match self.fault(Op::Write, offset, len) {
+            None => self.inner.write_vectored_at(bufs, offset),
+            Some(Fault::Error(kind)) => Err(injected(kind)),
+            Some(Fault::Short(n)) => {
+                let buf = bufs
+                    .iter()
+                    .find(|b| !b.is_empty())
+                    .map_or(&[][..], |b| &**b);
+                self.inner.write_at(&buf[..min(n, buf.len())], offset)
+            }
+            Some(Fault::Corrupt) => {
+                let mut data: Vec<u8> = bufs.iter().flat_map(|b| b.iter().copied()).collect();
+                corrupt(&mut data);
+                self.inner.write_at(&data, offset)
+            }
+        }
+    }
+
+    #[inline]
+    fn is_write_vectored_at(&self) -> bool {
+        self.inner.is_write_vectored_at()
+    }
+
+    #[inline]
+    fn allocate(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        self.inner.allocate(offset, len)
+    }
+
+    #[inline]
+    fn punch_hole(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        self.inner.punch_hole(offset, len)
+    }
+
+    #[inline]
+    fn sync_all(&self) -> io::Result<()> {
+        self.inner.sync_all()
+    }
+
+    #[inline]
+    fn sync_data(&self) -> io::Result<()> {
+        self.inner.sync_data()
+    }
+
+    #[inline]
+    fn sync_range(&self, offset: u64, len: u64, mode: SyncRangeMode) -> io::Result<()> {
+        self.inner.sync_range(offset, len, mode)
+    }
+}
+
+impl<A: Resize> Resize for FaultInjectionArray<A> {
+    #[inline]
+    fn set_len(&mut self, size: u64) -> io::Result<()> {
+        Resize::set_len(&mut self.inner, size)
+    }
+
+    #[inline]
+    fn grow_to(&mut self, size: u64) -> io::Result<()> {
+        self.inner.grow_to(size)
+    }
+}
@@ -0,0 +1,428 @@
+//! Tracing of array I/O.
+
+use crate::stats::Transferred;
+use crate::{
+    Advice, Array, CloneOutcome, LockKind, Metadata, RangeLockGuard, ReadAt, Resize, SyncRangeMode,
+    WriteAt, WriteFlags,
+};
+#[cfg(feature = "io-streams")]
+use io_streams::StreamReader;
+use std::fmt;
+use std::io::{self, IoSlice, IoSliceMut};
+use std::time::{Duration, Instant};
+
+/// Expand to a `tracing` macro invocation at a level known only at runtime.
+#[cfg(feature = "tracing")]
+macro_rules! at_level {
+    ($mac:ident, $level:expr, $($args:tt)*) => {
+        match $level {
+            tracing::Level::ERROR => tracing::$mac!(tracing::Level::ERROR, $($args)*),
+            tracing::Level::WARN => tracing::$mac!(tracing::Level::WARN, $($args)*),
+            tracing::Level::INFO => tracing::$mac!(tracing::Level::INFO, $($args)*),
+            tracing::Level::DEBUG => tracing::$mac!(tracing::Level::DEBUG, $($args)*),
+            tracing::Level::TRACE => tracing::$mac!(tracing::Level::TRACE, $($args)*),
+        }
+    };
+}
+
+/// An array which reports each operation performed through it, for
+/// debugging.
+///
+/// Every method is delegated to the inner array, and its results, including
+/// errors and short reads and writes, are passed through unchanged. Each
+/// operation is reported to the hook set with [`TracingArray::with_hook`],
+/// if any, as a [`TraceEvent`].
+///
+/// With the `tracing` feature, each operation is also run in a `tracing`
+/// span named `array_op`, with `op`, `offset`, and `len` fields, and an
+/// event is emitted in the span when it completes, with a `ret` field
+/// holding the number of bytes transferred or an `error` field, and an
+/// `elapsed_us` field. These are at `tracing::Level::TRACE` by default,
+/// which can be changed with `TracingArray::with_level`, and can be
+/// compiled out with `tracing`'s `max_level_*` features.
+pub struct TracingArray<A> {
+    inner: A,
+    tracer: Tracer,
+}
+
+/// A report of an operation performed through a [`TracingArray`].
+#[derive(Debug)]
+pub struct TraceEvent<'a> {
+    op: &'static str,
+    offset: u64,
+    len: u64,
+    result: Result<u64, &'a io::Error>,
+    elapsed: Duration,
+}
+
+type Hook = Box<dyn Fn(&TraceEvent<'_>) + Send + Sync>;
+
+struct Tracer {
+    #[cfg(feature = "tracing")]
+    level: tracing::Level,
+    hook: Option<Hook>,
+}
+
+impl<A> TracingArray<A> {
+    /// Create a `TracingArray` which reports the operations performed on
+    /// `inner`.
+    #[inline]
+    pub fn new(inner: A) -> Self {
+        Self {
+            inner,
+            tracer: Tracer {
+                #[cfg(feature = "tracing")]
+                level: tracing::Level::TRACE,
+                hook: None,
+            },
+        }
+    }
+
+    /// Set the level of the spans and events emitted for each operation.
+    #[cfg(feature = "tracing")]
+    #[inline]
+    #[must_use]
+    pub fn with_level(mut self, level: tracing::Level) -> Self {
+        self.tracer.level = level;
+        self
+    }
+
+    /// Set a function to call with a [`TraceEvent`] after each operation.
+    #[inline]
+    #[must_use]
+    pub fn with_hook(mut self, hook: impl Fn(&TraceEvent<'_>) + Send + Sync + 'static) -> Self {
+        self.tracer.hook = Some(Box::new(hook));
+        self
+    }
+
+    /// Consume the `TracingArray` and return the inner array.
+    #[inline]
+    pub fn into_inner(self) -> A {
+        self.inner
+    }
+}
+
+impl Tracer {
+    /// Perform the operation `f`, named `op`, on the range of `len` bytes at
+    /// `offset`, and report it.
+    fn trace<T: Transferred>(
+        &self,
+        op: &'static str,
+        offset: u64,
+        len: u64,
+        f: impl FnOnce() -> io::Result<T>,
+    ) -> io::Result<T> {
+        #[cfg(feature = "tracing")]
+        let span = at_level!(span, self.level, "array_op", op, offset, len);
+        #[cfg(feature = "tracing")]
+        let _entered = span.enter();
+
+        let start = Instant::now();
+        let result = f();
+        let elapsed = start.elapsed();
+
+        #[cfg(feature = "tracing")]
+        {
+            let elapsed_us = elapsed.as_micros() as u64;
+            match &result {
+                Ok(value) => {
+                    let ret = value.transferred(len);
+                    at_level!(event, self.level, ret, elapsed_us)
+                }
+                Err(error) => at_level!(event, self.level, error = %error, elapsed_us),
+            }
+        }
+
+        if let Some(hook) = &self.hook {
+            hook(&TraceEvent {
+                op,
+                offset,
+                len,
+                result: result.as_ref().map(|value| value.transferred(len)),
+                elapsed,
+            });
+        }
+        result
+    }
+}
+
+#[allow(clippy::len_without_is_empty)]
+impl TraceEvent<'_> {
+    /// Returns the name of the operation, which is the name of the method,
+    /// such as `"read_at"`.
+    #[inline]
+    #[must_use]
+    pub const fn op(&self) -> &'static str {
+        self.op
+    }
+
+    /// Returns the offset of the operation, or the new length for
+    /// `set_len` and `grow_to`.
+    #[inline]
+    #[must_use]
+    pub const fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Returns the number of bytes requested.
+    #[inline]
+    #[must_use]
+    pub const fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns the number of bytes transferred, or the error the operation
+    /// failed with.
+    #[inline]
+    pub fn result(&self) -> Result<u64, &io::Error> {
+        self.result
+    }
+
+    /// Returns how long the operation took.
+    #[inline]
+    #[must_use]
+    pub const fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+}
+
+impl<A: fmt::Debug> fmt::Debug for TracingArray<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut s = f.debug_struct("TracingArray");
+        s.field("inner", &self.inner);
+        #[cfg(feature = "tracing")]
+        s.field("level", &self.tracer.level);
+        s.field("hook", &self.tracer.hook.is_some()).finish()
+    }
+}
+
+impl<A: Array> Array for TracingArray<A> {
+    #[inline]
+    fn metadata(&self) -> io::Result<Metadata> {
+        self.inner.metadata()
+    }
+
+    #[inline]
+    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        self.inner.advise(offset, len, advice)
+    }
+
+    #[inline]
+    fn readahead(&self, offset: u64, len: u64) -> io::Result<()> {
+        self.inner.readahead(offset, len)
+    }
+
+    #[inline]
+    fn lock_at(&self, offset: u64, len: u64, kind: LockKind) -> io::Result<RangeLockGuard<'_>> {
+        self.inner.lock_at(offset, len, kind)
+    }
+
+    #[inline]
+    fn try_lock_at(
+        &self,
+        offset: u64,
+        len: u64,
+        kind: LockKind,
+    ) -> io::Result<Option<RangeLockGuard<'_>>> {
+        self.inner.try_lock_at(offset, len, kind)
+    }
+}
+
+impl<A: ReadAt> ReadAt for TracingArray<A> {
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        self.tracer.trace("read_at", offset, buf.len() as u64, || {
+            self.inner.read_at(buf, offset)
+        })
+    }
+
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+        self.tracer
+            .trace("read_exact_at", offset, buf.len() as u64, || {
+                self.inner.read_exact_at(buf, offset)
+            })
+    }
+
+    fn read_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<usize> {
+        let len = bufs.iter().map(|b| b.len() as u64).sum();
+        self.tracer.trace("read_vectored_at", offset, len, || {
+            self.inner.read_vectored_at(bufs, offset)
+        })
+    }
+
+    fn read_exact_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<()> {
+        let len = bufs.iter().map(|b| b.len() as u64).sum();
+        self.tracer
+            .trace("read_exact_vectored_at", offset, len, || {
+                self.inner.read_exact_vectored_at(bufs, offset)
+            })
+    }
+
+    #[inline]
+    fn is_read_vectored_at(&self) -> bool {
+        self.inner.is_read_vectored_at()
+    }
+
+    fn read_at_nowait(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        self.tracer
+            .trace("read_at_nowait", offset, buf.len() as u64, || {
+                self.inner.read_at_nowait(buf, offset)
+            })
+    }
+
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    fn read_via_stream_at(&self, offset: u64) -> io::Result<StreamReader> {
+        self.inner.read_via_stream_at(offset)
+    }
+
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    fn read_range_via_stream(&self, offset: u64, len: u64) -> io::Result<StreamReader> {
+        self.inner.read_range_via_stream(offset, len)
+    }
+}
+
+impl<A: WriteAt> WriteAt for TracingArray<A> {
+    fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        let inner = &mut self.inner;
+        self.tracer.trace("write_at", offset, buf.len() as u64, || {
+            inner.write_at(buf, offset)
+        })
+    }
+
+    fn write_all_at(&mut self, buf: &[u8], offset: u64) -> io::Result<()> {
+        let inner = &mut self.inner;
+        self.tracer
+            .trace("write_all_at", offset, buf.len() as u64, || {
+                inner.write_all_at(buf, offset)
+            })
+    }
+
+    fn write_vectored_at(&mut self, bufs: &[IoSlice], offset: u64) -> io::Result<usize> {
+        let len = bufs.iter().map(|b| b.len() as u64).sum();
+        let inner = &mut self.inner;
+        self.tracer.trace("write_vectored_at", offset, len, || {
+            inner.write_vectored_at(bufs, offset)
+        })
+    }
+
+    fn write_all_vectored_at(&mut self, bufs: &mut [IoSlice], offset: u64) -> io::Result<()> {
+        let len = bufs.iter().map(|b| b.len() as u64).sum();
+        let inner = &mut self.inner;
+        self.tracer.trace("write_all_vectored_at", offset, len, || {
+            inner.write_all_vectored_at(bufs, offset)
+        })
+    }
+
+    #[inline]
+    fn is_write_vectored_at(&self) -> bool {
+        self.inner.is_write_vectored_at()
+    }
+
+    fn write_at_with(&mut self, buf: &[u8], offset: u64, flags: WriteFlags) -> io::Result<usize> {
+        let inner = &mut self.inner;
+        self.tracer
+            .trace("write_at_with", offset, buf.len() as u64, || {
+                inner.write_at_with(buf, offset, flags)
+            })
+    }
+
+    fn copy_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<u64> {
+        let inner = &mut self.inner;
+        self.tracer.trace("copy_from", offset, len, || {
+            inner.copy_from(offset, input, input_offset, len)
+        })
+    }
+
+    fn clone_range_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<CloneOutcome> {
+        let inner = &mut self.inner;
+        self.tracer.trace("clone_range_from", offset, len, || {
+            inner.clone_range_from(offset, input, input_offset, len)
+        })
+    }
+
+    fn dedupe_range_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<u64> {
+        let inner = &mut self.inner;
+        self.tracer.trace("dedupe_range_from", offset, len, || {
+            inner.dedupe_range_from(offset, input, input_offset, len)
+        })
+    }
+
+    fn allocate(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        let inner = &mut self.inner;
+        self.tracer
+            .trace("allocate", offset, len, || inner.allocate(offset, len))
+    }
+
+    fn reserve_at(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        let inner = &mut self.inner;
+        self.tracer
+            .trace("reserve_at", offset, len, || inner.reserve_at(offset, len))
+    }
+
+    fn punch_hole(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        let inner = &mut self.inner;
+        self.tracer
+            .trace("punch_hole", offset, len, || inner.punch_hole(offset, len))
+    }
+
+    fn zero_range(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        let inner = &mut self.inner;
+        self.tracer
+            .trace("zero_range", offset, len, || inner.zero_range(offset, len))
+    }
+
+    fn fill_at(&mut self, byte: u8, offset: u64, len: u64) -> io::Result<()> {
+        let inner = &mut self.inner;
+        self.tracer
+            .trace("fill_at", offset, len, || inner.fill_at(byte, offset, len))
+    }
+
+    fn sync_all(&self) -> io::Result<()> {
+        self.tracer
+            .trace("sync_all", 0, 0, || self.inner.sync_all())
+    }
+
+    fn sync_data(&self) -> io::Result<()> {
+        self.tracer
+            .trace("sync_data", 0, 0, || self.inner.sync_data())
+    }
+
+    fn sync_range(&self, offset: u64, len: u64, mode: SyncRangeMode) -> io::Result<()> {
+        self.tracer.trace("sync_range", offset, len, || {
+            self.inner.sync_range(offset, len, mode)
+        })
+    }
+}
+
+impl<A: Resize> Resize for TracingArray<A> {
+    fn set_len(&mut self, size: u64) -> io::Result<()> {
+        let inner = &mut self.inner;
+        self.tracer
+            .trace("set_len", size, 0, || Resize::set_len(inner, size))
+    }
+
+    fn grow_to(&mut self, size: u64) -> io::Result<()> {
+        let inner = &mut self.inner;
+        self.tracer
+            .trace("grow_to", size, 0, || inner.grow_to(size))
+    }
+}
@@ -0,0 +1,553 @@
+//! An array backend which performs I/O with `io_uring`.
+
+use crate::{
+    filelike, Advice, Array, CloneOutcome, Metadata, ReadAt, Resize, SyncRangeMode, WriteAt,
+    WriteFlags,
+};
+use io_lifetimes::{AsFd, AsFilelike, BorrowedFd, BorrowedFilelike, FromFilelike, IntoFilelike};
+#[cfg(feature = "io-streams")]
+use io_streams::StreamReader;
+use io_uring::{opcode, squeue, types, IoUring};
+use std::cmp::min;
+use std::fmt;
+use std::fs;
+use std::io::{self, IoSlice, IoSliceMut};
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::sync::Mutex;
+
+/// The default number of submission queue entries.
+const DEFAULT_ENTRIES: u32 = 64;
+
+/// The size of the buffers used by [`UringArray`]'s `copy_from`.
+const COPY_CHUNK_SIZE: usize = 64 * 1024;
+
+/// The largest transfer Linux performs in a single read or write.
+const MAX_RW_LEN: usize = 0x7fff_f000;
+
+/// The largest number of buffers Linux accepts in a single vectored read or
+/// write.
+const MAX_IOVECS: usize = 1024;
+
+/// The index of the array's file in the ring's registered file table.
+const FIXED_FILE: types::Fixed = types::Fixed(0);
+
+/// A random-access input source and output sink which performs its I/O by
+/// submitting requests to an `io_uring` instance.
+///
+/// The [`ReadAt`] and [`WriteAt`] methods are synchronous, like those of
+/// [`ArrayEditor`], but operations made up of many transfers, such as
+/// [`UringArray::read_exact_ranges_at`], [`UringArray::write_all_ranges_at`],
+/// and `copy_from` from an in-memory array, submit a whole batch of requests
+/// before waiting for any of them to complete, which avoids a system call
+/// per transfer.
+///
+/// The file is registered with the ring when the `UringArray` is created,
+/// and unregistered when it's dropped. Operations on the ring are
+/// serialized, so a `UringArray` may be shared between threads, but
+/// transfers from different threads aren't overlapped.
+///
+/// [`ArrayEditor`]: crate::ArrayEditor
+pub struct UringArray {
+    file: fs::File,
+    ring: Mutex<Ring>,
+}
+
+struct Ring {
+    uring: IoUring,
+
+    /// Set if submission failed in a way that may have left entries in the
+    /// submission queue, which refer to buffers that are no longer live.
+    broken: bool,
+}
+
+impl UringArray {
+    /// Create an `io_uring` instance and register the file-like object
+    /// `filelike` with it.
+    ///
+    /// This fails with [`io::ErrorKind::Unsupported`] if the kernel doesn't
+    /// support `io_uring`, or if it has been disabled.
+    #[inline]
+    pub fn new<Filelike: IntoFilelike>(filelike: Filelike) -> io::Result<Self> {
+        Self::with_entries(filelike, DEFAULT_ENTRIES)
+    }
+
+    /// Like [`UringArray::new`], but with a submission queue of `entries`
+    /// entries, which bounds the number of requests submitted at once.
+    pub fn with_entries<Filelike: IntoFilelike>(
+        filelike: Filelike,
+        entries: u32,
+    ) -> io::Result<Self> {
+        let file = fs::File::from_into_filelike(filelike);
+        let uring = IoUring::new(entries).map_err(|err| {
+            match err.raw_os_error() {
+                // `ENOSYS` if the kernel is too old or built without
+                // `io_uring`, and `EPERM` if it's disabled by a sysctl or
+                // a seccomp filter.
+                Some(libc::ENOSYS | libc::EPERM) => {
+                    io::Error::new(io::ErrorKind::Unsupported, "io_uring is not supported")
+                }
+                _ => err,
+            }
+        })?;
+        uring.submitter().register_files(&[file.as_raw_fd()])?;
+        Ok(Self {
+            file,
+            ring: Mutex::new(Ring {
+                uring,
+                broken: false,
+            }),
+        })
+    }
+
+    /// Read into each buffer in `ranges` from its paired offset, filling
+    /// every buffer completely.
+    ///
+    /// Reads for all the ranges are submitted together, so this is much
+    /// cheaper than calling [`ReadAt::read_exact_at`] for each range in
+    /// turn. Fails with [`io::ErrorKind::UnexpectedEof`] if any range
+    /// extends past the end of the array, in which case the contents of the
+    /// buffers are unspecified.
+    pub fn read_exact_ranges_at(&self, ranges: &mut [(&mut [u8], u64)]) -> io::Result<()> {
+        let mut pending = ranges
+            .iter_mut()
+            .map(|(buf, offset)| (buf.as_mut_ptr(), buf.len(), *offset))
+            .collect::<Vec<_>>();
+        // SAFETY: The buffers are borrowed from `ranges` until we return.
+        unsafe { self.transfer_exact(&mut pending, false) }
+    }
+
+    /// Write each buffer in `ranges` to its paired offset, writing every
+    /// buffer completely.
+    ///
+    /// Writes for all the ranges are submitted together, and may complete in
+    /// any order, so the ranges shouldn't overlap.
+    pub fn write_all_ranges_at(&self, ranges: &[(&[u8], u64)]) -> io::Result<()> {
+        let mut pending = ranges
+            .iter()
+            .map(|(buf, offset)| (buf.as_ptr().cast_mut(), buf.len(), *offset))
+            .collect::<Vec<_>>();
+        // SAFETY: The buffers are borrowed from `ranges` until we return, and
+        // are only read from.
+        unsafe { self.transfer_exact(&mut pending, true) }
+    }
+
+    /// Perform the transfers in `pending`, each a pointer, a length, and an
+    /// offset, resubmitting short transfers until they're complete.
+    ///
+    /// # Safety
+    ///
+    /// Each pointer must be valid for reads, and for writes if `write` is
+    /// false, of its paired length.
+    unsafe fn transfer_exact(
+        &self,
+        pending: &mut Vec<(*mut u8, usize, u64)>,
+        write: bool,
+    ) -> io::Result<()> {
+        pending.retain(|(_, len, _)| *len != 0);
+        while !pending.is_empty() {
+            let entries = pending
+                .iter()
+                .map(|&(ptr, len, offset)| {
+                    let len = min(len, MAX_RW_LEN) as u32;
+                    if write {
+                        opcode::Write::new(FIXED_FILE, ptr, len)
+                            .offset(offset)
+                            .build()
+                    } else {
+                        opcode::Read::new(FIXED_FILE, ptr, len)
+                            .offset(offset)
+                            .build()
+                    }
+                })
+                .collect::<Vec<_>>();
+            let results = self.run(&entries)?;
+
+            for ((ptr, len, offset), result) in pending.iter_mut().zip(results) {
+                match result {
+                    Ok(0) if write => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::WriteZero,
+                            "failed to write whole buffer",
+                        ))
+                    }
+                    Ok(0) => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::UnexpectedEof,
+                            "failed to fill whole buffer",
+                        ))
+                    }
+                    Ok(n) => {
+                        *ptr = ptr.add(n);
+                        *len -= n;
+                        *offset = offset.checked_add(n as u64).ok_or_else(|| {
+                            io::Error::new(io::ErrorKind::InvalidInput, "offset overflow")
+                        })?;
+                    }
+                    Err(err)
+                        if matches!(
+                            err.kind(),
+                            io::ErrorKind::Interrupted | io::ErrorKind::WouldBlock
+                        ) => {}
+                    Err(err) => return Err(err),
+                }
+            }
+            pending.retain(|(_, len, _)| *len != 0);
+        }
+        Ok(())
+    }
+
+    /// Submit `entries` and wait for all of them to complete, returning
+    /// their results in order.
+    ///
+    /// # Safety
+    ///
+    /// The buffers referenced by `entries` must be valid until this
+    /// returns.
+    unsafe fn run(&self, entries: &[squeue::Entry]) -> io::Result<Vec<io::Result<usize>>> {
+        let mut ring = self.ring.lock().unwrap();
+        if ring.broken {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                "io_uring instance is unusable after an earlier submission error",
+            ));
+        }
+
+        let mut results = Vec::with_capacity(entries.len());
+        results.resize_with(entries.len(), || Ok(0));
+        let capacity = ring.uring.submission().capacity();
+        for (batch_index, batch) in entries.chunks(capacity).enumerate() {
+            let base = batch_index * capacity;
+            {
+                let mut sq = ring.uring.submission();
+                for (i, entry) in batch.iter().enumerate() {
+                    // The queue is empty between batches, so this can't fail.
+                    sq.push(&entry.clone().user_data((base + i) as u64))
+                        .expect("submission queue is full");
+                }
+            }
+
+            let mut completed = 0;
+            while completed < batch.len() {
+                if let Err(err) = ring.uring.submit_and_wait(batch.len() - completed) {
+                    match err.raw_os_error() {
+                        Some(libc::EINTR | libc::EAGAIN | libc::EBUSY) => {}
+                        _ => {
+                            let submitted = batch.len() - ring.uring.submission().len();
+                            if submitted != completed {
+                                // The kernel may still be using buffers which
+                                // we'd release by returning.
+                                std::process::abort();
+                            }
+                            ring.broken = true;
+                            return Err(err);
+                        }
+                    }
+                }
+                for cqe in ring.uring.completion() {
+                    let result = cqe.result();
+                    results[cqe.user_data() as usize] = if result < 0 {
+                        Err(io::Error::from_raw_os_error(-result))
+                    } else {
+                        Ok(result as usize)
+                    };
+                    completed += 1;
+                }
+            }
+        }
+        Ok(results)
+    }
+
+    /// Submit a single entry and wait for it to complete.
+    ///
+    /// # Safety
+    ///
+    /// The buffers referenced by `entry` must be valid until this returns.
+    unsafe fn run_one(&self, entry: squeue::Entry) -> io::Result<usize> {
+        self.run(&[entry])?.pop().unwrap()
+    }
+
+    /// Copy from `input` to `self` by reading chunks of `input` into a batch
+    /// of buffers, and then submitting writes for all of them at once.
+    fn copy_batched(
+        &self,
+        mut offset: u64,
+        input: &dyn ReadAt,
+        mut input_offset: u64,
+        len: u64,
+    ) -> io::Result<u64> {
+        let depth = self.ring.lock().unwrap().uring.submission().capacity();
+        let chunk_size = min(len, COPY_CHUNK_SIZE as u64) as usize;
+        let mut bufs = Vec::new();
+        let mut copied = 0;
+        let mut eof = false;
+        while copied < len && !eof {
+            let mut batch = Vec::new();
+            while batch.len() < depth && copied < len {
+                if bufs.len() == batch.len() {
+                    bufs.push(vec![0_u8; chunk_size]);
+                }
+                let chunk = min(len - copied, chunk_size as u64) as usize;
+                let buf = &mut bufs[batch.len()][..chunk];
+                let nread = match input.read_at(buf, input_offset) {
+                    Ok(0) => {
+                        eof = true;
+                        break;
+                    }
+                    Ok(nread) => nread,
+                    Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(err) => return Err(err),
+                };
+                batch.push((nread, offset));
+                offset = offset.checked_add(nread as u64).ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidInput, "offset overflow")
+                })?;
+                input_offset = input_offset.checked_add(nread as u64).ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidInput, "offset overflow")
+                })?;
+                copied += nread as u64;
+            }
+            let ranges = batch
+                .iter()
+                .zip(&bufs)
+                .map(|(&(nread, offset), buf)| (&buf[..nread], offset))
+                .collect::<Vec<_>>();
+            self.write_all_ranges_at(&ranges)?;
+        }
+        Ok(copied)
+    }
+}
+
+impl Drop for UringArray {
+    fn drop(&mut self) {
+        let ring = self.ring.get_mut().unwrap();
+        let _ = ring.uring.submitter().unregister_files();
+    }
+}
+
+impl fmt::Debug for UringArray {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UringArray")
+            .field("file", &self.file)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Array for UringArray {
+    #[inline]
+    fn metadata(&self) -> io::Result<Metadata> {
+        filelike::metadata(self)
+    }
+
+    #[inline]
+    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        filelike::advise(self, offset, len, advice)
+    }
+
+    #[inline]
+    fn borrowed_filelike(&self) -> Option<BorrowedFilelike<'_>> {
+        Some(self.as_filelike())
+    }
+}
+
+impl ReadAt for UringArray {
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        let len = min(buf.len(), MAX_RW_LEN) as u32;
+        let entry = opcode::Read::new(FIXED_FILE, buf.as_mut_ptr(), len)
+            .offset(offset)
+            .build();
+        // SAFETY: `buf` is borrowed until we return.
+        unsafe { self.run_one(entry) }
+    }
+
+    #[inline]
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+        self.read_exact_ranges_at(&mut [(buf, offset)])
+    }
+
+    fn read_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<usize> {
+        let len = min(bufs.len(), MAX_IOVECS) as u32;
+        let entry = opcode::Readv::new(FIXED_FILE, bufs.as_mut_ptr().cast::<libc::iovec>(), len)
+            .offset(offset)
+            .build();
+        // SAFETY: `bufs` is borrowed until we return, and `IoSliceMut` is
+        // ABI-compatible with `iovec`.
+        unsafe { self.run_one(entry) }
+    }
+
+    fn read_exact_vectored_at(&self, bufs: &mut [IoSliceMut], mut offset: u64) -> io::Result<()> {
+        let mut ranges = Vec::with_capacity(bufs.len());
+        for buf in bufs.iter_mut() {
+            let len = buf.len() as u64;
+            ranges.push((&mut **buf, offset));
+            offset = offset
+                .checked_add(len)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "offset overflow"))?;
+        }
+        self.read_exact_ranges_at(&mut ranges)
+    }
+
+    #[inline]
+    fn is_read_vectored_at(&self) -> bool {
+        true
+    }
+
+    #[inline]
+    fn read_at_nowait(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        filelike::read_at_nowait(self, buf, offset)
+    }
+
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    fn read_via_stream_at(&self, offset: u64) -> io::Result<StreamReader> {
+        filelike::read_via_stream_at(self, offset)
+    }
+
+    #[cfg(feature = "io-streams")]
+    #[inline]
+    fn read_range_via_stream(&self, offset: u64, len: u64) -> io::Result<StreamReader> {
+        filelike::read_range_via_stream(self, offset, len)
+    }
+}
+
+impl WriteAt for UringArray {
+    fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        let len = min(buf.len(), MAX_RW_LEN) as u32;
+        let entry = opcode::Write::new(FIXED_FILE, buf.as_ptr(), len)
+            .offset(offset)
+            .build();
+        // SAFETY: `buf` is borrowed until we return.
+        unsafe { self.run_one(entry) }
+    }
+
+    #[inline]
+    fn write_all_at(&mut self, buf: &[u8], offset: u64) -> io::Result<()> {
+        self.write_all_ranges_at(&[(buf, offset)])
+    }
+
+    fn write_vectored_at(&mut self, bufs: &[IoSlice], offset: u64) -> io::Result<usize> {
+        let len = min(bufs.len(), MAX_IOVECS) as u32;
+        let entry = opcode::Writev::new(FIXED_FILE, bufs.as_ptr().cast::<libc::iovec>(), len)
+            .offset(offset)
+            .build();
+        // SAFETY: `bufs` is borrowed until we return, and `IoSlice` is
+        // ABI-compatible with `iovec`.
+        unsafe { self.run_one(entry) }
+    }
+
+    fn write_all_vectored_at(&mut self, bufs: &mut [IoSlice], mut offset: u64) -> io::Result<()> {
+        let mut ranges = Vec::with_capacity(bufs.len());
+        for buf in bufs.iter() {
+            ranges.push((&**buf, offset));
+            offset = offset
+                .checked_add(buf.len() as u64)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "offset overflow"))?;
+        }
+        self.write_all_ranges_at(&ranges)
+    }
+
+    #[inline]
+    fn is_write_vectored_at(&self) -> bool {
+        true
+    }
+
+    #[inline]
+    fn write_at_with(&mut self, buf: &[u8], offset: u64, flags: WriteFlags) -> io::Result<usize> {
+        filelike::write_at_with(&*self, buf, offset, flags)
+    }
+
+    /// Copy from `input`. If `input` is backed by a file, this uses
+    /// [`filelike::copy_from`], which can copy within the kernel. Otherwise,
+    /// it reads a batch of chunks from `input` and submits writes for all of
+    /// them together.
+    fn copy_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<u64> {
+        if input.borrowed_filelike().is_some() {
+            filelike::copy_from(&*self, offset, input, input_offset, len)
+        } else {
+            self.copy_batched(offset, input, input_offset, len)
+        }
+    }
+
+    #[inline]
+    fn clone_range_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<CloneOutcome> {
+        filelike::clone_range_from(&*self, offset, input, input_offset, len)
+    }
+
+    #[inline]
+    fn dedupe_range_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<u64> {
+        filelike::dedupe_range_from(&*self, offset, input, input_offset, len)
+    }
+
+    #[inline]
+    fn allocate(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::allocate(&*self, offset, len)
+    }
+
+    #[inline]
+    fn reserve_at(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::reserve_at(&*self, offset, len)
+    }
+
+    #[inline]
+    fn punch_hole(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::punch_hole(&*self, offset, len)
+    }
+
+    #[inline]
+    fn zero_range(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        filelike::zero_range(&*self, offset, len)
+    }
+
+    #[inline]
+    fn sync_all(&self) -> io::Result<()> {
+        filelike::sync_all(self)
+    }
+
+    #[inline]
+    fn sync_data(&self) -> io::Result<()> {
+        filelike::sync_data(self)
+    }
+
+    #[inline]
+    fn sync_range(&self, offset: u64, len: u64, mode: SyncRangeMode) -> io::Result<()> {
+        filelike::sync_range(self, offset, len, mode)
+    }
+}
+
+impl Resize for UringArray {
+    #[inline]
+    fn set_len(&mut self, size: u64) -> io::Result<()> {
+        filelike::set_len(&*self, size)
+    }
+}
+
+impl AsRawFd for UringArray {
+    #[inline]
+    fn as_raw_fd(&self) -> RawFd {
+        self.file.as_raw_fd()
+    }
+}
+
+impl AsFd for UringArray {
+    #[inline]
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.file.as_fd()
+    }
+}
@@ -4,18 +4,28 @@
 //! These can use `seek_read`/`seek_write` because the file's current position
 //! is not exposed.
 //!
+//! `seek_read` and `seek_write` are `ReadFile` and `WriteFile` with the
+//! offset passed in an `OVERLAPPED`, so each positional operation is atomic
+//! with respect to its offset, and concurrent operations on the same handle
+//! don't race. However, for handles not opened with `FILE_FLAG_OVERLAPPED`,
+//! Windows also moves the handle's file pointer to the end of the
+//! transferred range, and there's no way to opt out of that. Code which
+//! shares a handle with an array, such as a `File` from `try_clone`, and
+//! uses it as a stream, must seek explicitly before each stream operation.
+//!
 //! [`ReadAt`]: crate::ReadAt
 //! [`WriteAt`]: crate::WriteAt
 
-use crate::Metadata;
+use crate::arrays::DEFAULT_BLKSIZE;
+use crate::{ArrayType, LockKind, Metadata, ReadAt};
 use io_lifetimes::AsFilelike;
 use std::fs::File;
 use std::io::{self, IoSlice, IoSliceMut};
-use std::os::windows::fs::FileExt;
-use std::slice;
+use std::os::windows::fs::{FileExt, OpenOptionsExt};
+use std::path::Path;
 #[cfg(feature = "io-streams")]
 use {
-    crate::owned_streamer::OwnedStreamer,
+    crate::streamer::Streamer,
     cap_fs_ext::{OpenOptions, Reopen},
     io_streams::StreamReader,
     std::io::SeekFrom,
@@ -23,17 +33,191 @@ use {
 };
 
 /// Implement [`crate::Array::metadata`].
+///
+/// For disk and volume handles, which don't report a size through the usual
+/// file information, this queries the device for its size and sector size.
 #[inline]
 pub fn metadata<'a, Filelike: AsFilelike>(filelike: &Filelike) -> io::Result<Metadata> {
-    filelike.as_filelike_view::<File>().metadata().map(|meta| {
-        Metadata {
-            len: meta.len(),
+    let file = filelike.as_filelike_view::<File>();
+    match file.metadata() {
+        Ok(meta) if meta.len() != 0 => Ok(file_metadata(&file, &meta)),
+        result => match disk_size(&file) {
+            Some((len, blksize)) => Ok(Metadata {
+                len,
+                blksize,
+                allocated_len: len,
+                file_type: ArrayType::BlockDevice,
+                modified: None,
+            }),
+            None => result.map(|meta| file_metadata(&file, &meta)),
+        },
+    }
+}
+
+fn file_metadata(file: &File, meta: &std::fs::Metadata) -> Metadata {
+    Metadata {
+        len: meta.len(),
+
+        blksize: storage_block_size(file).unwrap_or(DEFAULT_BLKSIZE),
+
+        allocated_len: allocation_size(file).unwrap_or(meta.len()),
+        file_type: if meta.is_file() {
+            ArrayType::File
+        } else {
+            ArrayType::Other
+        },
+        modified: meta.modified().ok(),
+    }
+}
+
+/// Query the amount of storage allocated for `file`.
+fn allocation_size(file: &File) -> Option<u64> {
+    use std::mem::{size_of, zeroed};
+    use std::os::windows::io::AsRawHandle;
+    use windows_sys::Win32::Storage::FileSystem::{
+        FileStandardInfo, GetFileInformationByHandleEx, FILE_STANDARD_INFO,
+    };
+
+    // SAFETY: The output buffer is a plain-old-data struct of the size
+    // passed in, and the handle is valid for the duration of the call.
+    unsafe {
+        let mut info: FILE_STANDARD_INFO = zeroed();
+        if GetFileInformationByHandleEx(
+            file.as_raw_handle(),
+            FileStandardInfo,
+            (&mut info as *mut FILE_STANDARD_INFO).cast(),
+            size_of::<FILE_STANDARD_INFO>() as u32,
+        ) == 0
+        {
+            return None;
+        }
+        info.AllocationSize.try_into().ok()
+    }
+}
+
+/// Query the physical sector size of the storage backing `file`, which is
+/// the smallest unit the device can write without a read-modify-write
+/// cycle, and the alignment unbuffered I/O needs.
+fn storage_block_size(file: &File) -> Option<u64> {
+    use std::mem::{size_of, zeroed};
+    use std::os::windows::io::AsRawHandle;
+    use windows_sys::Win32::Storage::FileSystem::{
+        FileStorageInfo, GetFileInformationByHandleEx, FILE_STORAGE_INFO,
+    };
+
+    // SAFETY: The output buffer is a plain-old-data struct of the size
+    // passed in, and the handle is valid for the duration of the call.
+    unsafe {
+        let mut info: FILE_STORAGE_INFO = zeroed();
+        if GetFileInformationByHandleEx(
+            file.as_raw_handle(),
+            FileStorageInfo,
+            (&mut info as *mut FILE_STORAGE_INFO).cast(),
+            size_of::<FILE_STORAGE_INFO>() as u32,
+        ) == 0
+        {
+            return None;
+        }
+        let size = info
+            .PhysicalBytesPerSectorForPerformance
+            .max(info.LogicalBytesPerSector);
+        size.is_power_of_two().then_some(size.into())
+    }
+}
 
-            // Windows doesn't have a convenient way to query this, but
-            // it often uses this specific value.
-            blksize: 0x1000,
+/// Query the size and sector size of a disk or volume, returning `None` if
+/// `file` isn't one.
+fn disk_size(file: &File) -> Option<(u64, u64)> {
+    use std::mem::{size_of, zeroed};
+    use std::os::windows::io::AsRawHandle;
+    use std::ptr::null;
+    use windows_sys::Win32::System::Ioctl::{
+        DISK_GEOMETRY, GET_LENGTH_INFORMATION, IOCTL_DISK_GET_DRIVE_GEOMETRY,
+        IOCTL_DISK_GET_LENGTH_INFO,
+    };
+    use windows_sys::Win32::System::IO::DeviceIoControl;
+
+    // SAFETY: The output buffers are plain-old-data structs of the sizes
+    // passed in, and the handle is valid for the duration of the calls.
+    unsafe {
+        let mut length: GET_LENGTH_INFORMATION = zeroed();
+        let mut geometry: DISK_GEOMETRY = zeroed();
+        let mut returned = 0;
+        if DeviceIoControl(
+            file.as_raw_handle(),
+            IOCTL_DISK_GET_LENGTH_INFO,
+            null(),
+            0,
+            (&mut length as *mut GET_LENGTH_INFORMATION).cast(),
+            size_of::<GET_LENGTH_INFORMATION>() as u32,
+            &mut returned,
+            std::ptr::null_mut(),
+        ) == 0
+        {
+            return None;
+        }
+        let blksize = if DeviceIoControl(
+            file.as_raw_handle(),
+            IOCTL_DISK_GET_DRIVE_GEOMETRY,
+            null(),
+            0,
+            (&mut geometry as *mut DISK_GEOMETRY).cast(),
+            size_of::<DISK_GEOMETRY>() as u32,
+            &mut returned,
+            std::ptr::null_mut(),
+        ) != 0
+        {
+            geometry.BytesPerSector.into()
+        } else {
+            DEFAULT_BLKSIZE
+        };
+        Some((length.Length.try_into().ok()?, blksize))
+    }
+}
+
+/// Test whether `a` and `b` refer to the same underlying file, by comparing
+/// their volume serial numbers and file indices.
+///
+/// This is useful for detecting aliasing, such as before a
+/// [`crate::WriteAt::copy_from`] between two arrays.
+pub fn is_same_file<A: AsFilelike, B: AsFilelike>(a: &A, b: &B) -> io::Result<bool> {
+    let a = file_id(&a.as_filelike_view::<File>())?;
+    let b = file_id(&b.as_filelike_view::<File>())?;
+    Ok(a == b)
+}
+
+/// Open the file `file` refers to again, for reading and writing.
+///
+/// This finds the file by the path Windows reports for the handle. Opening
+/// it checks the file's permissions like any other open, so this can't gain
+/// access that opening the file by name wouldn't.
+pub(crate) fn reopen_read_write(file: &File) -> io::Result<File> {
+    File::options()
+        .read(true)
+        .write(true)
+        .open(winx::file::get_file_path(file)?)
+}
+
+fn file_id(file: &File) -> io::Result<(u32, u32, u32)> {
+    use std::mem::zeroed;
+    use std::os::windows::io::AsRawHandle;
+    use windows_sys::Win32::Storage::FileSystem::{
+        GetFileInformationByHandle, BY_HANDLE_FILE_INFORMATION,
+    };
+
+    // SAFETY: The output buffer is a plain-old-data struct, and the handle
+    // is valid for the duration of the call.
+    unsafe {
+        let mut info: BY_HANDLE_FILE_INFORMATION = zeroed();
+        if GetFileInformationByHandle(file.as_raw_handle(), &mut info) == 0 {
+            return Err(io::Error::last_os_error());
         }
-    })
+        Ok((
+            info.dwVolumeSerialNumber,
+            info.nFileIndexHigh,
+            info.nFileIndexLow,
+        ))
+    }
 }
 
 /// Implement [`crate::ReadAt::read_at`].
@@ -49,43 +233,49 @@ pub fn read_at<'a, Filelike: AsFilelike>(
 /// Implement [`crate::ReadAt::read_exact_at`].
 pub fn read_exact_at<Filelike: AsFilelike>(
     filelike: &Filelike,
-    mut buf: &mut [u8],
-    mut offset: u64,
+    buf: &mut [u8],
+    offset: u64,
 ) -> io::Result<()> {
-    loop {
-        match read_at(filelike, buf, offset) {
-            Ok(0) if !buf.is_empty() => {
-                return Err(io::Error::new(
-                    io::ErrorKind::UnexpectedEof,
-                    "failed to fill whole buffer",
-                ))
-            }
-            Ok(nread) => {
-                offset = offset
-                    .checked_add(nread.try_into().unwrap())
-                    .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "offset overflow"))?;
-                buf = &mut buf[nread..];
-                if buf.is_empty() {
-                    return Ok(());
-                }
-            }
-            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
-            Err(err) => return Err(err),
-        }
+    let file = filelike.as_filelike_view::<File>();
+    if ReadAt::read_full_at(&*file, buf, offset)? != buf.len() {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "failed to fill whole buffer",
+        ));
     }
+    Ok(())
 }
 
 /// Implement [`crate::ReadAt::read_vectored_at`].
+///
+/// Windows has no positional scatter read for ordinary handles;
+/// `ReadFileScatter` requires page-sized buffers and an unbuffered,
+/// overlapped handle. So this reads into each buffer in turn, stopping at
+/// the end of the file. If an error occurs after some data has been read,
+/// the amount read so far is returned, and the error is left to be reported
+/// by the next call.
 pub fn read_vectored_at<'a, Filelike: AsFilelike>(
     filelike: &Filelike,
     bufs: &mut [IoSliceMut],
     offset: u64,
 ) -> io::Result<usize> {
-    let buf = bufs
-        .iter_mut()
-        .find(|b| !b.is_empty())
-        .map_or(&mut [][..], |b| &mut **b);
-    read_at(filelike, buf, offset)
+    let mut total = 0;
+    for buf in bufs.iter_mut().filter(|b| !b.is_empty()) {
+        let pos = offset
+            .checked_add(total as u64)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "offset overflow"))?;
+        match read_at(filelike, buf, pos) {
+            Ok(nread) => {
+                total += nread;
+                if nread < buf.len() {
+                    break;
+                }
+            }
+            Err(_) if total != 0 => break,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(total)
 }
 
 /// Implement [`crate::ReadAt::read_exact_vectored_at`].
@@ -94,25 +284,40 @@ pub fn read_exact_vectored_at<'a, Filelike: AsFilelike>(
     mut bufs: &mut [IoSliceMut],
     mut offset: u64,
 ) -> io::Result<()> {
-    while !bufs.is_empty() {
+    loop {
+        // Skip over any empty buffers so that an all-empty list doesn't
+        // look like the end of the file.
+        while bufs.first().is_some_and(|b| b.is_empty()) {
+            bufs = &mut bufs[1..];
+        }
+        if bufs.is_empty() {
+            return Ok(());
+        }
         match read_vectored_at(filelike, bufs, offset) {
+            Ok(0) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "failed to fill whole buffer",
+                ))
+            }
             Ok(nread) => {
                 offset = offset
                     .checked_add(nread.try_into().unwrap())
-                    .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "offset overflow"))?;
-                bufs = advance_mut(bufs, nread);
+                    .ok_or_else(|| {
+                        io::Error::new(io::ErrorKind::InvalidInput, "offset overflow")
+                    })?;
+                bufs = crate::iovec::advance_mut(bufs, nread);
             }
             Err(ref e) if e.kind() == io::ErrorKind::Interrupted => (),
             Err(e) => return Err(e),
         }
     }
-    Ok(())
 }
 
 /// Implement [`crate::ReadAt::is_read_vectored_at`].
 #[inline]
 pub fn is_read_vectored_at<'a, Filelike: AsFilelike>(_filelike: &Filelike) -> bool {
-    false
+    true
 }
 
 /// Implement [`crate::ReadAt::read_via_stream_at`].
@@ -134,12 +339,226 @@ pub fn read_via_stream_at<'a, Filelike: AsFilelike>(
     }
 
     // Otherwise, manually stream the file.
-    StreamReader::piped_thread(Box::new(OwnedStreamer::new(
+    StreamReader::piped_thread(Box::new(Streamer::owned(
         filelike.as_filelike_view::<File>().try_clone()?,
         offset,
     )))
 }
 
+/// Allocate storage for the range of `len` bytes at `offset` in `filelike`,
+/// extending it if needed.
+///
+/// Files on Windows aren't sparse unless they're marked as such, so
+/// extending the file with `SetEndOfFile` allocates the new range, and the
+/// existing contents are already allocated.
+pub(crate) fn allocate<Filelike: AsFilelike>(
+    filelike: &Filelike,
+    offset: u64,
+    len: u64,
+) -> io::Result<()> {
+    let end = offset
+        .checked_add(len)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "offset overflow"))?;
+    let file = filelike.as_filelike_view::<File>();
+    if end > file.metadata()?.len() {
+        file.set_len(end)?;
+    }
+    Ok(())
+}
+
+/// Reserve storage for the range of `len` bytes at `offset` in `filelike`,
+/// without changing its length, by raising the file's allocation size.
+pub(crate) fn reserve_at<Filelike: AsFilelike>(
+    filelike: &Filelike,
+    offset: u64,
+    len: u64,
+) -> io::Result<()> {
+    use std::mem::size_of;
+    use std::os::windows::io::AsRawHandle;
+    use windows_sys::Win32::Storage::FileSystem::{
+        FileAllocationInfo, SetFileInformationByHandle, FILE_ALLOCATION_INFO,
+    };
+
+    let end = offset
+        .checked_add(len)
+        .and_then(|end| i64::try_from(end).ok())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "offset overflow"))?;
+    let file = filelike.as_filelike_view::<File>();
+
+    // Setting a smaller allocation size would truncate the file.
+    if allocation_size(&file).is_some_and(|allocated| allocated >= end as u64) {
+        return Ok(());
+    }
+
+    let info = FILE_ALLOCATION_INFO {
+        AllocationSize: end,
+    };
+    // SAFETY: The input buffer is a plain-old-data struct of the size passed
+    // in, and the handle is valid for the duration of the call.
+    if unsafe {
+        SetFileInformationByHandle(
+            file.as_raw_handle(),
+            FileAllocationInfo,
+            (&info as *const FILE_ALLOCATION_INFO).cast(),
+            size_of::<FILE_ALLOCATION_INFO>() as u32,
+        )
+    } == 0
+    {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Deallocate the storage for `len` bytes at `offset` in `filelike`, which
+/// must be within the file, with `FSCTL_SET_ZERO_DATA`, returning `false` if
+/// the filesystem doesn't support it.
+///
+/// This zeros the range in all cases, but only releases the storage if the
+/// file is marked sparse.
+pub(crate) fn punch_hole<Filelike: AsFilelike>(
+    filelike: &Filelike,
+    offset: u64,
+    len: u64,
+) -> io::Result<bool> {
+    use std::mem::size_of;
+    use std::os::windows::io::AsRawHandle;
+    use std::ptr::null_mut;
+    use windows_sys::Win32::Foundation::ERROR_INVALID_FUNCTION;
+    use windows_sys::Win32::System::Ioctl::{FILE_ZERO_DATA_INFORMATION, FSCTL_SET_ZERO_DATA};
+    use windows_sys::Win32::System::IO::DeviceIoControl;
+
+    let overflow = || io::Error::new(io::ErrorKind::InvalidInput, "offset overflow");
+    let info = FILE_ZERO_DATA_INFORMATION {
+        FileOffset: offset.try_into().map_err(|_| overflow())?,
+        BeyondFinalZero: (offset + len).try_into().map_err(|_| overflow())?,
+    };
+    let file = filelike.as_filelike_view::<File>();
+    let mut returned = 0;
+
+    // SAFETY: The input buffer is a plain-old-data struct of the size passed
+    // in, and the handle is valid for the duration of the call.
+    if unsafe {
+        DeviceIoControl(
+            file.as_raw_handle(),
+            FSCTL_SET_ZERO_DATA,
+            (&info as *const FILE_ZERO_DATA_INFORMATION).cast(),
+            size_of::<FILE_ZERO_DATA_INFORMATION>() as u32,
+            null_mut(),
+            0,
+            &mut returned,
+            null_mut(),
+        )
+    } == 0
+    {
+        let err = io::Error::last_os_error();
+        if err.raw_os_error() == Some(ERROR_INVALID_FUNCTION as i32) {
+            return Ok(false);
+        }
+        return Err(err);
+    }
+    Ok(true)
+}
+
+/// Lock `len` bytes at `offset` in `filelike` with `LockFileEx`, returning
+/// `false` if `wait` is false and the range is locked by someone else.
+pub(crate) fn lock_range<Filelike: AsFilelike>(
+    filelike: &Filelike,
+    offset: u64,
+    len: u64,
+    kind: LockKind,
+    wait: bool,
+) -> io::Result<bool> {
+    use std::os::windows::io::AsRawHandle;
+    use windows_sys::Win32::Foundation::ERROR_LOCK_VIOLATION;
+    use windows_sys::Win32::Storage::FileSystem::{
+        LockFileEx, LOCKFILE_EXCLUSIVE_LOCK, LOCKFILE_FAIL_IMMEDIATELY,
+    };
+
+    let mut flags = 0;
+    if kind == LockKind::Exclusive {
+        flags |= LOCKFILE_EXCLUSIVE_LOCK;
+    }
+    if !wait {
+        flags |= LOCKFILE_FAIL_IMMEDIATELY;
+    }
+
+    let file = filelike.as_filelike_view::<File>();
+    let mut overlapped = overlapped_at(offset);
+    // SAFETY: The `OVERLAPPED` lives for the duration of the call, and the
+    // handle is synchronous, so the call completes before returning.
+    if unsafe {
+        LockFileEx(
+            file.as_raw_handle(),
+            flags,
+            0,
+            len as u32,
+            (len >> 32) as u32,
+            &mut overlapped,
+        )
+    } == 0
+    {
+        let err = io::Error::last_os_error();
+        if !wait && err.raw_os_error() == Some(ERROR_LOCK_VIOLATION as i32) {
+            return Ok(false);
+        }
+        return Err(err);
+    }
+    Ok(true)
+}
+
+/// Unlock `len` bytes at `offset` in `filelike` with `UnlockFileEx`.
+pub(crate) fn unlock_range<Filelike: AsFilelike>(
+    filelike: &Filelike,
+    offset: u64,
+    len: u64,
+) -> io::Result<()> {
+    use std::os::windows::io::AsRawHandle;
+    use windows_sys::Win32::Storage::FileSystem::UnlockFileEx;
+
+    let file = filelike.as_filelike_view::<File>();
+    let mut overlapped = overlapped_at(offset);
+    // SAFETY: The `OVERLAPPED` lives for the duration of the call.
+    if unsafe {
+        UnlockFileEx(
+            file.as_raw_handle(),
+            0,
+            len as u32,
+            (len >> 32) as u32,
+            &mut overlapped,
+        )
+    } == 0
+    {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Create an `OVERLAPPED` which specifies `offset`, for the file-locking
+/// functions.
+fn overlapped_at(offset: u64) -> windows_sys::Win32::System::IO::OVERLAPPED {
+    // SAFETY: `OVERLAPPED` is a plain-old-data struct, and all zeros is a
+    // valid value.
+    let mut overlapped: windows_sys::Win32::System::IO::OVERLAPPED = unsafe { std::mem::zeroed() };
+    overlapped.Anonymous.Anonymous.Offset = offset as u32;
+    overlapped.Anonymous.Anonymous.OffsetHigh = (offset >> 32) as u32;
+    overlapped
+}
+
+/// Copy up to `len` bytes from the file `input` to `filelike`.
+///
+/// Windows has no positional range-copy API, so this copies through a
+/// large buffer with `seek_read` and `seek_write`.
+#[inline]
+pub(crate) fn copy_from_file<Filelike: AsFilelike, Input: AsFilelike>(
+    filelike: &Filelike,
+    offset: u64,
+    input: &Input,
+    input_offset: u64,
+    len: u64,
+) -> io::Result<u64> {
+    crate::files::copy_buffered(filelike, offset, input, input_offset, len)
+}
+
 /// Implement [`crate::WriteAt::write_at`].
 #[inline]
 pub fn write_at<'a, Filelike: AsFilelike>(
@@ -156,34 +575,55 @@ pub fn write_all_at<'a, Filelike: AsFilelike>(
     mut buf: &[u8],
     mut offset: u64,
 ) -> io::Result<()> {
-    loop {
+    while !buf.is_empty() {
         match write_at(filelike, buf, offset) {
+            Ok(0) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::WriteZero,
+                    "failed to write whole buffer",
+                ))
+            }
             Ok(nwritten) => {
                 offset = offset
                     .checked_add(nwritten.try_into().unwrap())
-                    .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "offset overflow"))?;
+                    .ok_or_else(|| {
+                        io::Error::new(io::ErrorKind::InvalidInput, "offset overflow")
+                    })?;
                 buf = &buf[nwritten..];
-                if buf.is_empty() {
-                    return Ok(());
-                }
             }
             Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
             Err(err) => return Err(err),
         }
     }
+    Ok(())
 }
 
 /// Implement [`crate::WriteAt::write_vectored_at`].
+///
+/// Like [`read_vectored_at`], this writes each buffer in turn, stopping at
+/// the first short write.
 pub fn write_vectored_at<'a, Filelike: AsFilelike>(
     filelike: &Filelike,
     bufs: &[IoSlice],
     offset: u64,
 ) -> io::Result<usize> {
-    let buf = bufs
-        .iter()
-        .find(|b| !b.is_empty())
-        .map_or(&[][..], |b| &**b);
-    write_at(filelike, buf, offset)
+    let mut total = 0;
+    for buf in bufs.iter().filter(|b| !b.is_empty()) {
+        let pos = offset
+            .checked_add(total as u64)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "offset overflow"))?;
+        match write_at(filelike, buf, pos) {
+            Ok(nwritten) => {
+                total += nwritten;
+                if nwritten < buf.len() {
+                    break;
+                }
+            }
+            Err(_) if total != 0 => break,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(total)
 }
 
 /// Implement [`crate::WriteAt::write_all_vectored_at`].
@@ -192,87 +632,66 @@ pub fn write_all_vectored_at<'a, Filelike: AsFilelike>(
     mut bufs: &mut [IoSlice],
     mut offset: u64,
 ) -> io::Result<()> {
-    while !bufs.is_empty() {
+    loop {
+        // Skip over any empty buffers so that an all-empty list doesn't
+        // look like a failure to make progress.
+        while bufs.first().is_some_and(|b| b.is_empty()) {
+            bufs = &mut bufs[1..];
+        }
+        if bufs.is_empty() {
+            return Ok(());
+        }
         match write_vectored_at(filelike, bufs, offset) {
+            Ok(0) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::WriteZero,
+                    "failed to write whole buffer",
+                ))
+            }
             Ok(nwritten) => {
                 offset = offset
                     .checked_add(nwritten.try_into().unwrap())
-                    .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "offset overflow"))?;
-                bufs = advance(bufs, nwritten);
+                    .ok_or_else(|| {
+                        io::Error::new(io::ErrorKind::InvalidInput, "offset overflow")
+                    })?;
+                bufs = crate::iovec::advance(bufs, nwritten);
             }
             Err(ref e) if e.kind() == io::ErrorKind::Interrupted => (),
             Err(e) => return Err(e),
         }
     }
-    Ok(())
 }
 
 /// Implement [`crate::WriteAt::is_write_vectored_at`].
 #[inline]
 pub fn is_write_vectored_at<'a, Filelike: AsFilelike>(_filelike: &Filelike) -> bool {
-    false
+    true
 }
 
-/// This will be obviated by [rust-lang/rust#62726].
-///
-/// [rust-lang/rust#62726]: https://github.com/rust-lang/rust/issues/62726.
-fn advance<'a, 'b>(bufs: &'b mut [IoSlice<'a>], n: usize) -> &'b mut [IoSlice<'a>] {
-    // Number of buffers to remove.
-    let mut remove = 0;
-    // Total length of all the to be removed buffers.
-    let mut accumulated_len = 0;
-    for buf in bufs.iter() {
-        if accumulated_len + buf.len() > n {
-            break;
-        } else {
-            accumulated_len += buf.len();
-            remove += 1;
-        }
-    }
-
-    #[allow(clippy::indexing_slicing)]
-    let bufs = &mut bufs[remove..];
-    if let Some(first) = bufs.first_mut() {
-        let advance_by = n - accumulated_len;
-        let mut ptr = first.as_ptr();
-        let mut len = first.len();
-        unsafe {
-            ptr = ptr.add(advance_by);
-            len -= advance_by;
-            *first = IoSlice::<'a>::new(slice::from_raw_parts::<'a>(ptr, len));
-        }
-    }
-    bufs
-}
+/// `FILE_ATTRIBUTE_TEMPORARY`, which tells the cache manager to avoid writing
+/// the file's data to storage if it can.
+const FILE_ATTRIBUTE_TEMPORARY: u32 = 0x100;
 
-/// This will be obviated by [rust-lang/rust#62726].
-///
-/// [rust-lang/rust#62726]: https://github.com/rust-lang/rust/issues/62726.
-fn advance_mut<'a, 'b>(bufs: &'b mut [IoSliceMut<'a>], n: usize) -> &'b mut [IoSliceMut<'a>] {
-    // Number of buffers to remove.
-    let mut remove = 0;
-    // Total length of all the to be removed buffers.
-    let mut accumulated_len = 0;
-    for buf in bufs.iter() {
-        if accumulated_len + buf.len() > n {
-            break;
-        } else {
-            accumulated_len += buf.len();
-            remove += 1;
-        }
-    }
+/// Create an anonymous file in `dir` which is deleted when all handles to it
+/// are closed.
+pub(crate) fn anonymous_in(dir: &Path) -> io::Result<File> {
+    use winx::file::{Flags, ShareMode};
 
-    #[allow(clippy::indexing_slicing)]
-    let bufs = &mut bufs[remove..];
-    if let Some(first) = bufs.first_mut() {
-        let advance_by = n - accumulated_len;
-        let mut ptr = first.as_mut_ptr();
-        let mut len = first.len();
-        unsafe {
-            ptr = ptr.add(advance_by);
-            len -= advance_by;
-            *first = IoSliceMut::<'a>::new(slice::from_raw_parts_mut::<'a>(ptr, len));
+    let share =
+        ShareMode::FILE_SHARE_READ | ShareMode::FILE_SHARE_WRITE | ShareMode::FILE_SHARE_DELETE;
+    loop {
+        match std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .share_mode(share.bits())
+            .attributes(FILE_ATTRIBUTE_TEMPORARY)
+            .custom_flags(Flags::FILE_FLAG_DELETE_ON_CLOSE.bits())
+            .open(dir.join(crate::anonymous::temp_name()))
+        {
+            Ok(file) => return Ok(file),
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(err),
         }
     }
-    bufs
 }
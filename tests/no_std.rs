@@ -0,0 +1,52 @@
+//! Tests for the parts of the API which don't depend on `std`. These run
+//! both with and without the `std` feature.
+
+use io_arrays::io::ErrorKind;
+use io_arrays::{Array, PatternArray, ReadAt, SparseMemArray, WriteAt, ZeroArray};
+
+#[test]
+fn test_no_std_slices() {
+    let mut buf = [0_u8; 8];
+    let data: &[u8] = b"Hello, world!";
+    assert_eq!(data.read_at(&mut buf[..5], 7).unwrap(), 5);
+    assert_eq!(&buf[..5], b"world");
+    assert_eq!(data.metadata().unwrap().len(), 13);
+
+    let mut array = [0_u8; 4];
+    array.write_all_at(b"ab", 1).unwrap();
+    assert_eq!(&array, b"\0ab\0");
+    assert_eq!(array.read_at(&mut buf, 4).unwrap(), 0);
+
+    let mut vec = vec![0_u8; 5];
+    vec.write_all_at(b"xyz", 2).unwrap();
+    assert_eq!(vec, b"\0\0xyz");
+}
+
+#[test]
+fn test_no_std_mem_arrays() {
+    let reader = b"abcdef".to_vec();
+    let mut buf = [0_u8; 3];
+    reader.read_exact_at(&mut buf, 2).unwrap();
+    assert_eq!(&buf, b"cde");
+    assert_eq!(
+        reader.read_exact_at(&mut buf, 4).unwrap_err().kind(),
+        ErrorKind::UnexpectedEof
+    );
+
+    let mut sparse = SparseMemArray::new();
+    sparse.write_all_at(b"hi", 1 << 20).unwrap();
+    assert_eq!(sparse.metadata().unwrap().len(), (1 << 20) + 2);
+    sparse.read_exact_at(&mut buf[..2], 0).unwrap();
+    assert_eq!(&buf[..2], b"\0\0");
+}
+
+#[test]
+fn test_no_std_pattern_arrays() {
+    let mut buf = [1_u8; 4];
+    ZeroArray::new(16).read_exact_at(&mut buf, 4).unwrap();
+    assert_eq!(buf, [0; 4]);
+
+    let pattern = PatternArray::new(b"ab".to_vec(), 5);
+    assert_eq!(pattern.read_at(&mut buf, 3).unwrap(), 2);
+    assert_eq!(&buf[..2], b"ba");
+}
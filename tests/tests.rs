@@ -1,16 +1,58 @@
+#![cfg(feature = "std")]
 #![cfg_attr(can_vector, feature(can_vector))]
 #![cfg_attr(write_all_vectored, feature(write_all_vectored))]
 
-use cap_std::fs::OpenOptions;
+use cap_std::fs::{Dir, OpenOptions};
 use cap_tempfile::{ambient_authority, tempdir, TempDir};
-use io_arrays::{Array, ArrayEditor, ArrayReader, ArrayWriter, ReadAt, WriteAt};
-use std::io::{Read, Write};
+use io_arrays::testing::{Fault, FaultInjectionArray, Op, Rule};
+use io_arrays::{
+    Advice, Array, ArrayEditor, ArrayReader, ArrayWriter, EditAt, MemReader, Metadata, ReadAt,
+    Resize, WriteAt,
+};
+use std::io::{self, IoSlice, IoSliceMut, Read, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 #[allow(unused)]
 fn tmpdir() -> TempDir {
     tempdir(ambient_authority()).expect("expected to be able to create a temporary directory")
 }
 
+/// Open `name` in `dir` for reading and writing, creating it if needed, like
+/// `ArrayEditor::create` does for a path.
+fn create_editor<P: AsRef<std::path::Path>>(dir: &Dir, name: P) -> io::Result<ArrayEditor> {
+    Ok(ArrayEditor::file(dir.open_with(
+        name,
+        OpenOptions::new().create(true).read(true).write(true),
+    )?))
+}
+
+/// Create a directory for testing the APIs which take paths, which `tmpdir`
+/// doesn't provide. It's removed when the returned `TmpPath` is dropped.
+fn tmppath() -> TmpPath {
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+    let path = std::env::temp_dir().join(format!(
+        "io-arrays-test-{}-{}",
+        std::process::id(),
+        COUNT.fetch_add(1, Ordering::Relaxed)
+    ));
+    std::fs::create_dir(&path).expect("expected to be able to create a temporary directory");
+    TmpPath(path)
+}
+
+struct TmpPath(std::path::PathBuf);
+
+impl TmpPath {
+    fn path(&self) -> &std::path::Path {
+        &self.0
+    }
+}
+
+impl Drop for TmpPath {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
 #[test]
 fn test_small_copy() -> anyhow::Result<()> {
     let dir = tmpdir();
@@ -63,12 +105,55 @@ fn test_streaming_read() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[cfg(feature = "io-streams")]
+#[test]
+fn test_read_range_via_stream() -> anyhow::Result<()> {
+    let data = (0..100_000_u32)
+        .map(|i| (i % 253) as u8)
+        .collect::<Vec<_>>();
+    let dir = tmpdir();
+    dir.write("pack", &data)?;
+
+    let file = ArrayReader::file(dir.open("pack")?);
+    let mem = MemReader::from(data.clone());
+    // `MinimalArray` uses the default implementation.
+    let minimal = MinimalArray(data.clone());
+    let arrays: [&dyn ReadAt; 4] = [&file, &data, &mem, &minimal];
+    for array in arrays {
+        let mut buf = Vec::new();
+        let n = array
+            .read_range_via_stream(1000, 50_000)?
+            .read_to_end(&mut buf)?;
+        assert_eq!(n, 50_000);
+        assert_eq!(buf, data[1000..51_000]);
+
+        let mut buf = Vec::new();
+        array
+            .read_range_via_stream(99_990, 10)?
+            .read_to_end(&mut buf)?;
+        assert_eq!(buf, data[99_990..]);
+
+        let err = array.read_range_via_stream(99_990, 11).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+    Ok(())
+}
+
 #[test]
 fn test_bytes() -> anyhow::Result<()> {
     let reader = ArrayReader::bytes(b"abcdefghij")?;
     let mut buf = vec![0_u8; 4];
     reader.read_exact_at(&mut buf, 3)?;
     assert_eq!(buf, b"defg");
+    assert_eq!(reader.metadata()?.len(), 10);
+    assert!(reader.borrowed_filelike().is_some());
+
+    let reader = ArrayReader::in_memory(b"abcdefghij");
+    reader.read_exact_at(&mut buf, 6)?;
+    assert_eq!(buf, b"ghij");
+    assert_eq!(reader.metadata()?.len(), 10);
+    assert!(reader.borrowed_filelike().is_none());
+    assert_eq!(reader.into_vec(), b"abcdefghij");
     Ok(())
 }
 
@@ -165,3 +250,4157 @@ fn test_various_edits() -> anyhow::Result<()> {
     );
     Ok(())
 }
+
+/// An array which implements only the required methods of `WriteAt`, to
+/// exercise the default method implementations.
+struct MinimalArray(Vec<u8>);
+
+impl Array for MinimalArray {
+    fn metadata(&self) -> io::Result<Metadata> {
+        self.0.metadata()
+    }
+
+    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        self.0.advise(offset, len, advice)
+    }
+}
+
+impl ReadAt for MinimalArray {
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        self.0.read_at(buf, offset)
+    }
+
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+        self.0.read_exact_at(buf, offset)
+    }
+
+    fn read_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<usize> {
+        self.0.read_vectored_at(bufs, offset)
+    }
+
+    fn read_exact_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<()> {
+        self.0.read_exact_vectored_at(bufs, offset)
+    }
+
+    fn is_read_vectored_at(&self) -> bool {
+        self.0.is_read_vectored_at()
+    }
+
+    #[cfg(feature = "io-streams")]
+    fn read_via_stream_at(&self, offset: u64) -> io::Result<io_streams::StreamReader> {
+        self.0.read_via_stream_at(offset)
+    }
+}
+
+impl WriteAt for MinimalArray {
+    fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        let offset = usize::try_from(offset).unwrap();
+        if self.0.len() < offset + buf.len() {
+            self.0.resize(offset + buf.len(), 0);
+        }
+        self.0[offset..offset + buf.len()].copy_from_slice(buf);
+        Ok(buf.len())
+    }
+}
+
+impl Resize for MinimalArray {
+    fn set_len(&mut self, size: u64) -> io::Result<()> {
+        self.0.resize(usize::try_from(size).unwrap(), 0);
+        Ok(())
+    }
+}
+
+fn exercise_write_defaults<W: WriteAt + ReadAt>(array: &mut W) -> anyhow::Result<Vec<u8>> {
+    array.write_all_at(b"hello, world", 5)?;
+    assert!(array.write_vectored_at(&[IoSlice::new(&[]), IoSlice::new(b"H")], 5)? > 0);
+    array.write_all_vectored_at(
+        &mut [
+            IoSlice::new(b"abc"),
+            IoSlice::new(&[]),
+            IoSlice::new(b"defgh"),
+            IoSlice::new(b"ij"),
+        ],
+        20,
+    )?;
+    array.write_all_vectored_at(&mut [IoSlice::new(&[]), IoSlice::new(&[])], 100)?;
+    let input = ArrayReader::bytes(b"0123456789")?;
+    assert_eq!(array.copy_from(40, &input, 2, 5)?, 5);
+    assert_eq!(array.copy_from(50, &input, 8, 5)?, 2);
+    array.zero_range(43, 1)?;
+    array.zero_range(51, 4)?;
+    let len = array.metadata()?.len();
+    let mut buf = vec![0_u8; len as usize];
+    array.read_exact_at(&mut buf, 0)?;
+    Ok(buf)
+}
+
+#[test]
+fn test_write_defaults() -> anyhow::Result<()> {
+    // Interrupt some of the writes and make the rest short, so that the
+    // defaults have to retry.
+    let mut minimal = FaultInjectionArray::new(MinimalArray(Vec::new()))
+        .inject(
+            Rule::new(Op::Write, Fault::Error(io::ErrorKind::Interrupted))
+                .after(1)
+                .times(2),
+        )
+        .inject(Rule::new(Op::Write, Fault::Short(3)));
+    assert!(!minimal.is_write_vectored_at());
+    let minimal = exercise_write_defaults(&mut minimal)?;
+
+    let mut editor = ArrayEditor::anonymous()?;
+    let editor = exercise_write_defaults(&mut editor)?;
+
+    assert_eq!(minimal, editor);
+    assert_eq!(&minimal[5..17], b"Hello, world");
+    assert_eq!(&minimal[20..30], b"abcdefghij");
+    assert_eq!(&minimal[40..45], b"234\x006");
+    assert_eq!(&minimal[50..], b"8\0\0\0\0");
+    Ok(())
+}
+
+#[test]
+fn test_fault_injection() -> anyhow::Result<()> {
+    // Fail the third write.
+    let mut array = FaultInjectionArray::new(vec![0_u8; 9]).inject(
+        Rule::new(Op::Write, Fault::Error(io::ErrorKind::StorageFull))
+            .after(2)
+            .times(1),
+    );
+    array.write_all_at(b"abc", 0)?;
+    array.write_all_at(b"def", 3)?;
+    let err = array.write_all_at(b"ghi", 6).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::StorageFull);
+    array.write_all_at(b"ghi", 6)?;
+    assert_eq!(array.into_inner(), b"abcdefghi");
+
+    // Interrupt two reads, and then make them short.
+    let data: Vec<u8> = (0..100).collect();
+    let array = FaultInjectionArray::new(data.clone())
+        .inject(Rule::new(Op::Read, Fault::Error(io::ErrorKind::Interrupted)).times(2))
+        .inject(Rule::new(Op::Read, Fault::Short(7)));
+    let mut buf = [0_u8; 10];
+    let err = array.read_at(&mut buf, 0).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::Interrupted);
+    let mut buf = [0_u8; 30];
+    array.read_exact_at(&mut buf, 10)?;
+    assert_eq!(buf, data[10..40]);
+    assert_eq!(array.read_at(&mut buf, 10)?, 7);
+    assert_eq!(array.read_up_to_vec_at(90, 20)?, data[90..]);
+    let mut a = [0_u8; 3];
+    let mut b = [0_u8; 20];
+    array.read_exact_vectored_at(&mut [IoSliceMut::new(&mut a), IoSliceMut::new(&mut b)], 50)?;
+    assert_eq!(a, data[50..53]);
+    assert_eq!(b, data[53..73]);
+    let err = array.read_exact_at(&mut buf, 80).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+
+    // Corrupt reads which touch a range of offsets.
+    let array = FaultInjectionArray::new(data.clone())
+        .inject(Rule::new(Op::Read, Fault::Corrupt).at(20..30));
+    assert_eq!(array.read_to_vec_at(0, 20)?, data[..20]);
+    assert_eq!(array.read_to_vec_at(30, 10)?, data[30..40]);
+    assert_eq!(array.read_to_vec_at(25, 1)?, [!data[25]]);
+    let mut buf = [0_u8; 4];
+    assert_eq!(
+        array.read_vectored_at(&mut [IoSliceMut::new(&mut buf)], 18)?,
+        4
+    );
+    assert_eq!(buf, [!18, !19, !20, !21]);
+
+    // Short and corrupted writes.
+    let mut array = FaultInjectionArray::new(vec![0_u8; 8])
+        .inject(Rule::new(Op::Write, Fault::Corrupt).at(4..8))
+        .inject(Rule::new(Op::Write, Fault::Short(2)));
+    assert_eq!(array.write_at(b"abcd", 0)?, 2);
+    assert_eq!(array.write_vectored_at(&[IoSlice::new(b"wxyz")], 4)?, 4);
+    assert_eq!(
+        array.into_inner(),
+        [b'a', b'b', 0, 0, !b'w', !b'x', !b'y', !b'z']
+    );
+    Ok(())
+}
+
+#[test]
+fn test_trait_objects() -> anyhow::Result<()> {
+    let mut editor: Box<dyn EditAt> = Box::new(ArrayEditor::anonymous()?);
+    let mut vec: Box<dyn EditAt> = Box::new(vec![0_u8; 16]);
+    vec.write_all_at(b"hello", 4)?;
+    assert_eq!(editor.copy_from(2, &vec, 4, 5)?, 5);
+    assert_eq!(editor.metadata()?.len(), 7);
+
+    let readers: Vec<Box<dyn ReadAt>> = vec![editor, Box::new(ArrayReader::bytes(b"\0\0hello")?)];
+    for reader in &readers {
+        let mut buf = vec![0_u8; 5];
+        reader.read_exact_at(&mut buf, 2)?;
+        assert_eq!(buf, b"hello");
+    }
+
+    let mut writer: Box<dyn WriteAt> = Box::new(vec![0_u8; 4]);
+    writer.write_all_at(b"abcd", 0)?;
+    assert_eq!(writer.metadata()?.len(), 4);
+    Ok(())
+}
+
+#[test]
+fn test_arc_reader_threads() -> anyhow::Result<()> {
+    let data: Vec<u8> = (0..=255).collect();
+    let reader = std::sync::Arc::new(ArrayReader::bytes(&data)?);
+    let threads = (0..8_u8)
+        .map(|i| {
+            let reader = reader.clone();
+            std::thread::spawn(move || -> io::Result<()> {
+                let offset = u64::from(i) * 32;
+                let mut buf = [0_u8; 32];
+                reader.read_exact_at(&mut buf, offset)?;
+                assert!((0..32).all(|j| buf[j] == i * 32 + j as u8));
+                assert_eq!(reader.metadata()?.len(), 256);
+                Ok(())
+            })
+        })
+        .collect::<Vec<_>>();
+    for thread in threads {
+        thread.join().unwrap()?;
+    }
+    Ok(())
+}
+
+fn read_four<R: ReadAt>(reader: R, offset: u64) -> io::Result<[u8; 4]> {
+    let mut buf = [0_u8; 4];
+    reader.read_exact_at(&mut buf, offset)?;
+    Ok(buf)
+}
+
+fn write_four<W: WriteAt>(mut writer: W, buf: [u8; 4], offset: u64) -> io::Result<()> {
+    writer.write_all_at(&buf, offset)
+}
+
+#[test]
+fn test_forwarding_impls() -> anyhow::Result<()> {
+    let mut vec = b"abcdefgh".to_vec();
+    assert_eq!(&read_four(&vec, 2)?, b"cdef");
+    write_four(&mut vec, *b"WXYZ", 4)?;
+    assert_eq!(vec, b"abcdWXYZ");
+
+    let mut boxed = Box::new(ArrayEditor::anonymous()?);
+    write_four(&mut boxed, *b"1234", 0)?;
+    write_four(&mut *boxed, *b"5678", 4)?;
+    assert_eq!(&read_four(&boxed, 2)?, b"3456");
+    assert_eq!(&read_four(boxed, 4)?, b"5678");
+    Ok(())
+}
+
+#[test]
+fn test_create() -> anyhow::Result<()> {
+    let dir = tmppath();
+    let path = dir.path().join("file.txt");
+
+    let mut editor = ArrayEditor::create_new(&path)?;
+    editor.write_all_at(b"hello, world", 0)?;
+    drop(editor);
+
+    assert_eq!(
+        ArrayEditor::create_new(&path).unwrap_err().kind(),
+        io::ErrorKind::AlreadyExists
+    );
+
+    // Reopening with `create` preserves the existing contents.
+    let mut editor = ArrayEditor::create(&path)?;
+    assert_eq!(editor.metadata()?.len(), 12);
+    editor.write_all_at(b"W", 7)?;
+    let mut buf = vec![0_u8; 12];
+    editor.read_exact_at(&mut buf, 0)?;
+    assert_eq!(buf, b"hello, World");
+
+    // `create` creates files that don't exist yet.
+    let editor = ArrayEditor::create(dir.path().join("new.txt"))?;
+    assert_eq!(editor.metadata()?.len(), 0);
+    Ok(())
+}
+
+#[test]
+fn test_into_file() -> anyhow::Result<()> {
+    use std::io::{Seek, SeekFrom};
+
+    let mut editor = ArrayEditor::anonymous()?;
+    editor.write_all_at(b"hello, world", 0)?;
+    let mut file = editor.into_file();
+    file.seek(SeekFrom::Start(7))?;
+    let mut s = String::new();
+    file.read_to_string(&mut s)?;
+    assert_eq!(s, "world");
+
+    let reader = ArrayReader::file(file);
+    let mut file = std::fs::File::from(reader);
+    file.seek(SeekFrom::Start(0))?;
+    let mut buf = [0_u8; 5];
+    file.read_exact(&mut buf)?;
+    assert_eq!(&buf, b"hello");
+    Ok(())
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+fn test_owned_fd_round_trip() -> anyhow::Result<()> {
+    use rustix::fd::OwnedFd;
+    use rustix::fs::{memfd_create, MemfdFlags};
+
+    let fd = memfd_create("test_owned_fd_round_trip", MemfdFlags::CLOEXEC)?;
+    let mut editor = ArrayEditor::from(fd);
+    editor.write_all_at(b"hello, world", 0)?;
+
+    let fd = OwnedFd::from(editor);
+    let reader = ArrayReader::from(fd);
+    let mut buf = [0_u8; 5];
+    reader.read_exact_at(&mut buf, 7)?;
+    assert_eq!(&buf, b"world");
+
+    let mut writer = ArrayWriter::try_from(OwnedFd::from(reader))?;
+    writer.write_all_at(b"W", 7)?;
+    let editor = ArrayEditor::from(OwnedFd::from(writer));
+    let mut buf = [0_u8; 12];
+    editor.read_exact_at(&mut buf, 0)?;
+    assert_eq!(&buf, b"hello, World");
+    Ok(())
+}
+
+#[cfg(not(windows))]
+#[test]
+fn test_writer_rejects_append() -> anyhow::Result<()> {
+    let dir = tmpdir();
+    let file = dir.open_with("log.txt", OpenOptions::new().create(true).append(true))?;
+    let err = ArrayWriter::try_from(rustix::fd::OwnedFd::from(file)).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    Ok(())
+}
+
+#[test]
+fn test_array_appender() -> anyhow::Result<()> {
+    let dir = tmppath();
+    let path = dir.path().join("log.txt");
+
+    // Files which aren't in append mode are rejected.
+    let file = std::fs::File::create(&path)?;
+    let err = io_arrays::ArrayAppender::file(file).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+    let mut appender = io_arrays::ArrayAppender::open(&path)?;
+    assert_eq!(appender.append(b"header\n")?, 0);
+    assert_eq!(appender.append(b"")?, 7);
+    assert_eq!(
+        appender.append_vectored(&[IoSlice::new(b"abc"), IoSlice::new(b"def\n")])?,
+        7
+    );
+    assert_eq!(appender.metadata()?.len(), 14);
+
+    // Records appended concurrently through separate appenders don't
+    // overlap, and each lands at the offset reported for it.
+    let records = std::thread::scope(|scope| {
+        let threads = (0..4)
+            .map(|thread| {
+                let path = &path;
+                scope.spawn(move || -> io::Result<Vec<(u64, String)>> {
+                    let mut appender = io_arrays::ArrayAppender::open(path)?;
+                    (0..100)
+                        .map(|i| {
+                            let record = format!("thread {} record {}\n", thread, i);
+                            Ok((appender.append(record.as_bytes())?, record))
+                        })
+                        .collect()
+                })
+            })
+            .collect::<Vec<_>>();
+        threads
+            .into_iter()
+            .map(|thread| thread.join().unwrap())
+            .collect::<io::Result<Vec<_>>>()
+    })?;
+    let mut records = records.into_iter().flatten().collect::<Vec<_>>();
+    records.sort();
+
+    let contents = std::fs::read(&path)?;
+    let mut end = 14;
+    for (offset, record) in &records {
+        assert_eq!(*offset, end);
+        end += record.len() as u64;
+        assert_eq!(&contents[*offset as usize..end as usize], record.as_bytes());
+    }
+    assert_eq!(end, contents.len() as u64);
+    Ok(())
+}
+
+#[test]
+fn test_filelike_traits() -> anyhow::Result<()> {
+    use io_lifetimes::{AsFilelike, FromFilelike, IntoFilelike};
+
+    let mut editor = ArrayEditor::anonymous()?;
+    editor.write_all_at(b"hello", 0)?;
+    assert_eq!(io_arrays::filelike::metadata(&editor)?.len(), 5);
+    assert_eq!(
+        editor.as_filelike_view::<std::fs::File>().metadata()?.len(),
+        5
+    );
+
+    let reader = ArrayReader::from_into_filelike(editor);
+    let mut buf = [0_u8; 5];
+    io_arrays::filelike::read_exact_at(&reader, &mut buf, 0)?;
+    assert_eq!(&buf, b"hello");
+
+    let editor = ArrayEditor::from_filelike(reader.into_filelike());
+    assert_eq!(editor.metadata()?.len(), 5);
+    Ok(())
+}
+
+#[test]
+fn test_editor_downgrade() -> anyhow::Result<()> {
+    let dir = tmpdir();
+    let editor = create_editor(&dir, "data.bin")?;
+    (&editor).write_all_at(b"hello, world", 0)?;
+    let view = editor.as_reader();
+    assert_eq!(view.read_to_vec_at(7, 5)?, b"world");
+    assert_eq!(view.metadata()?.len(), 12);
+
+    let reader = editor.into_reader();
+    assert_eq!(reader.read_to_vec_at(0, 5)?, b"hello");
+    assert_eq!(reader.read_up_to_vec_at(7, 100)?, b"world");
+
+    let mut writer = create_editor(&dir, "data.bin")?.into_writer()?;
+    writer.write_all_at(b"W", 7)?;
+    assert_eq!(reader.read_to_vec_at(0, 12)?, b"hello, World");
+
+    // Converting to a writer checks that the file is open for writing.
+    let editor = ArrayEditor::file(dir.open("data.bin")?);
+    let err = editor.into_writer().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+    Ok(())
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+fn test_reader_try_reopen_as_editor() -> anyhow::Result<()> {
+    use cap_std::fs::{Permissions, PermissionsExt};
+
+    let dir = tmpdir();
+    dir.write("data.bin", b"hello, world")?;
+
+    let reader = ArrayReader::file(dir.open("data.bin")?);
+    let mut editor = reader.try_reopen_as_editor()?;
+    editor.write_all_at(b"W", 7)?;
+    assert_eq!(reader.read_to_vec_at(0, 12)?, b"hello, World");
+    assert_eq!(editor.read_to_vec_at(0, 5)?, b"hello");
+
+    // The file's permissions are checked. Privileged users may be able to
+    // write to it regardless.
+    dir.set_permissions("data.bin", Permissions::from_mode(0o444))?;
+    let reader = ArrayReader::file(dir.open("data.bin")?);
+    if dir
+        .open_with("data.bin", OpenOptions::new().write(true))
+        .is_err()
+    {
+        let err = reader.try_reopen_as_editor().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+    }
+
+    // Files from `bytes` are sealed against writes.
+    let reader = ArrayReader::bytes(b"hello")?;
+    let err = reader.try_reopen_as_editor().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+    Ok(())
+}
+
+#[test]
+fn test_mem_reader() -> anyhow::Result<()> {
+    let bytes = b"abcdefghij".to_vec();
+    let ptr = bytes.as_ptr();
+    let reader = MemReader::from(bytes);
+    assert_eq!(reader.metadata()?.len(), 10);
+    let mut buf = vec![0_u8; 4];
+    reader.read_exact_at(&mut buf, 3)?;
+    assert_eq!(buf, b"defg");
+
+    // The buffer is used in place, rather than being copied.
+    let bytes = reader.into_vec();
+    assert_eq!(bytes.as_ptr(), ptr);
+    Ok(())
+}
+
+#[test]
+fn test_mem_editor() -> anyhow::Result<()> {
+    let mut editor = ArrayEditor::from(b"abc".to_vec());
+    assert!(editor.borrowed_filelike().is_none());
+
+    // As with a file, writes past the end extend the array.
+    editor.write_all_at(b"xyz", 5)?;
+    assert_eq!(editor.metadata()?.len(), 8);
+    let mut buf = vec![0_u8; 8];
+    editor.read_exact_at(&mut buf, 0)?;
+    assert_eq!(buf, b"abc\0\0xyz");
+
+    editor.set_len(4)?;
+    let mut writer = editor.into_writer();
+    writer.write_all_at(b"d", 3)?;
+    writer.grow_to(6)?;
+    assert_eq!(writer.into_vec(), b"abcd\0\0");
+
+    let reader = ArrayEditor::from(b"hello".to_vec()).into_reader();
+    assert_eq!(reader.into_vec(), b"hello");
+    Ok(())
+}
+
+#[test]
+fn test_anonymous_with_len() -> anyhow::Result<()> {
+    const LEN: u64 = 1024 * 1024;
+
+    let mut editor = ArrayEditor::anonymous_with_len(LEN)?;
+    assert_eq!(editor.metadata()?.len(), LEN);
+
+    editor.write_all_at(b"hello", 4096)?;
+    assert_eq!(editor.metadata()?.len(), LEN);
+
+    let mut buf = vec![0xff_u8; 4096];
+    editor.read_exact_at(&mut buf, 0)?;
+    assert!(buf.iter().all(|b| *b == 0));
+    editor.read_exact_at(&mut buf, LEN - 4096)?;
+    assert!(buf.iter().all(|b| *b == 0));
+    Ok(())
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+fn test_anonymous_hugetlb() -> anyhow::Result<()> {
+    use io_arrays::HugePageSize;
+
+    let page_size = HugePageSize::Size2MiB;
+    let mut editor = match ArrayEditor::anonymous_hugetlb(page_size) {
+        Ok(editor) => editor,
+        Err(err) if err.kind() == io::ErrorKind::Unsupported => return Ok(()),
+        Err(err) => return Err(err.into()),
+    };
+
+    // Sizing the array fails if no huge pages are configured.
+    if editor.set_len(page_size.bytes()).is_err() {
+        return Ok(());
+    }
+    assert_eq!(editor.metadata()?.len(), page_size.bytes());
+
+    let mut buf = vec![0xff_u8; 4096];
+    editor.read_exact_at(&mut buf, 0)?;
+    assert!(buf.iter().all(|b| *b == 0));
+    Ok(())
+}
+
+#[cfg(not(windows))]
+#[test]
+fn test_bytes_read_only() -> anyhow::Result<()> {
+    let reader = ArrayReader::bytes(b"read-only data")?;
+
+    assert!(rustix::fs::ftruncate(&reader, 0).is_err());
+    assert!(rustix::io::pwrite(&reader, b"scribble", 0).is_err());
+
+    let mut buf = vec![0_u8; 14];
+    reader.read_exact_at(&mut buf, 0)?;
+    assert_eq!(buf, b"read-only data");
+    assert_eq!(reader.metadata()?.len(), 14);
+    Ok(())
+}
+
+#[test]
+fn test_anonymous_in() -> anyhow::Result<()> {
+    let tmp = tmppath();
+    let dir = Dir::open_ambient_dir(tmp.path(), ambient_authority())?;
+
+    let mut editors = vec![
+        ArrayEditor::anonymous_in(tmp.path())?,
+        ArrayEditor::anonymous_in_dir(&dir)?,
+    ];
+    for editor in &mut editors {
+        editor.write_all_at(b"hello", 0)?;
+        let mut buf = vec![0_u8; 5];
+        editor.read_exact_at(&mut buf, 0)?;
+        assert_eq!(buf, b"hello");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            let file_dev = rustix::fs::fstat(&*editor)?.st_dev;
+            assert_eq!(file_dev as u64, std::fs::metadata(tmp.path())?.dev());
+        }
+    }
+
+    // On Unix, the files aren't visible by name even while they're open. On
+    // Windows, they're deleted on close.
+    #[cfg(unix)]
+    assert_eq!(std::fs::read_dir(tmp.path())?.count(), 0);
+
+    // Either way, nothing is left behind once they're closed.
+    drop(editors);
+    assert_eq!(std::fs::read_dir(tmp.path())?.count(), 0);
+    Ok(())
+}
+
+#[cfg(all(windows, feature = "io-streams"))]
+#[test]
+fn test_anonymous_read_via_stream() -> anyhow::Result<()> {
+    let mut editor = ArrayEditor::anonymous()?;
+    editor.write_all_at(b"delete on close", 0)?;
+
+    let mut stream = editor.read_via_stream_at(7)?;
+    let mut s = String::new();
+    stream.read_to_string(&mut s)?;
+    assert_eq!(s, "on close");
+    Ok(())
+}
+
+#[test]
+fn test_metadata_regular_file() -> anyhow::Result<()> {
+    let dir = tmpdir();
+    dir.write("file.txt", vec![0_u8; 12345])?;
+
+    let reader = ArrayReader::file(dir.open("file.txt")?);
+    let meta = reader.metadata()?;
+    assert_eq!(meta.len(), 12345);
+    #[cfg(unix)]
+    {
+        use cap_std::fs::MetadataExt;
+        assert_eq!(meta.blksize(), dir.metadata("file.txt")?.blksize());
+    }
+    assert!(meta.blksize().is_power_of_two());
+    assert!(meta.blksize() >= 512);
+    Ok(())
+}
+
+/// Set `IO_ARRAYS_TEST_BLOCK_DEVICE` to a readable block device, such as a
+/// loop device, to run this test.
+#[cfg(not(windows))]
+#[test]
+fn test_metadata_block_device() -> anyhow::Result<()> {
+    use std::io::{Seek, SeekFrom};
+
+    let path = match std::env::var_os("IO_ARRAYS_TEST_BLOCK_DEVICE") {
+        Some(path) => path,
+        None => return Ok(()),
+    };
+    let mut file = std::fs::File::open(path)?;
+    let len = file.seek(SeekFrom::End(0))?;
+
+    let meta = ArrayReader::file(file).metadata()?;
+    assert_eq!(meta.len(), len);
+    assert!(meta.blksize() >= 512);
+    Ok(())
+}
+
+#[test]
+fn test_allocated_len() -> anyhow::Result<()> {
+    const LEN: u64 = 1024 * 1024;
+
+    let dir = tmpdir();
+    let mut editor = create_editor(&dir, "sparse")?;
+    editor.set_len(LEN)?;
+    let meta = editor.metadata()?;
+    assert_eq!(meta.len(), LEN);
+    // Files on Windows aren't sparse unless they're marked as such, so
+    // extending one allocates the new range.
+    #[cfg(unix)]
+    assert!(meta.allocated_len() < LEN / 4);
+
+    let bytes = vec![0_u8; 1000];
+    let meta = bytes.metadata()?;
+    assert_eq!(meta.allocated_len(), meta.len());
+    Ok(())
+}
+
+#[test]
+fn test_allocate() -> anyhow::Result<()> {
+    const LEN: u64 = 1024 * 1024;
+
+    let dir = tmpdir();
+    let mut editor = create_editor(&dir, "allocated")?;
+    editor.write_all_at(b"hello", 0)?;
+    let before = editor.metadata()?.allocated_len();
+    match editor.allocate(4096, LEN) {
+        Err(err) if err.kind() == io::ErrorKind::Unsupported => {}
+        result => {
+            result?;
+            let meta = editor.metadata()?;
+            assert_eq!(meta.len(), 4096 + LEN);
+            #[cfg(not(windows))]
+            assert!(meta.allocated_len() >= before + LEN);
+
+            // Allocating within the file doesn't change its length.
+            editor.allocate(0, 10)?;
+            assert_eq!(editor.metadata()?.len(), 4096 + LEN);
+            let mut buf = [0_u8; 5];
+            editor.read_exact_at(&mut buf, 0)?;
+            assert_eq!(&buf, b"hello");
+        }
+    }
+
+    let mut bytes = vec![1_u8; 4];
+    bytes.allocate(2, 6)?;
+    assert_eq!(bytes, [1, 1, 1, 1, 0, 0, 0, 0]);
+    bytes.allocate(0, 2)?;
+    assert_eq!(bytes.len(), 8);
+
+    let err = bytes.as_mut_slice().allocate(0, 1).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+    Ok(())
+}
+
+#[test]
+fn test_punch_hole() -> anyhow::Result<()> {
+    const LEN: usize = 4 * 1024 * 1024;
+
+    let dir = tmpdir();
+    let mut editor = create_editor(&dir, "holes")?;
+    editor.write_all_at(&vec![0xa5_u8; LEN], 0)?;
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    let before = editor.metadata()?.allocated_len();
+
+    editor.punch_hole(1000, LEN as u64 - 2000)?;
+    editor.punch_hole(LEN as u64 - 10, 100)?;
+    let meta = editor.metadata()?;
+    assert_eq!(meta.len(), LEN as u64);
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    assert!(meta.allocated_len() < before);
+
+    let mut buf = vec![0_u8; LEN];
+    editor.read_exact_at(&mut buf, 0)?;
+    assert!(buf[..1000].iter().all(|b| *b == 0xa5));
+    assert!(buf[1000..LEN - 1000].iter().all(|b| *b == 0));
+    assert!(buf[LEN - 1000..LEN - 10].iter().all(|b| *b == 0xa5));
+    assert!(buf[LEN - 10..].iter().all(|b| *b == 0));
+
+    let mut bytes = vec![1_u8; 8];
+    bytes.punch_hole(2, 3)?;
+    bytes.punch_hole(7, 100)?;
+    assert_eq!(bytes, [1, 1, 0, 0, 0, 1, 1, 0]);
+    Ok(())
+}
+
+#[test]
+fn test_zero_range() -> anyhow::Result<()> {
+    const LEN: usize = 3 * 1024 * 1024 + 100;
+
+    let dir = tmpdir();
+    let mut editor = create_editor(&dir, "zeros")?;
+    editor.write_all_at(&vec![0x5a_u8; LEN], 0)?;
+    editor.zero_range(10, 1024 * 1024)?;
+
+    // Cross the end of the file.
+    editor.zero_range(LEN as u64 - 50, 150)?;
+    assert_eq!(editor.metadata()?.len(), LEN as u64 + 100);
+
+    let mut buf = vec![0_u8; LEN + 100];
+    editor.read_exact_at(&mut buf, 0)?;
+    assert!(buf[..10].iter().all(|b| *b == 0x5a));
+    assert!(buf[10..1024 * 1024 + 10].iter().all(|b| *b == 0));
+    assert!(buf[1024 * 1024 + 10..LEN - 50].iter().all(|b| *b == 0x5a));
+    assert!(buf[LEN - 50..].iter().all(|b| *b == 0));
+
+    let mut bytes = vec![1_u8; 6];
+    bytes.zero_range(2, 2)?;
+    assert_eq!(bytes, [1, 1, 0, 0, 1, 1]);
+    bytes.zero_range(4, 4)?;
+    assert_eq!(bytes, [1, 1, 0, 0, 0, 0, 0, 0]);
+
+    let mut slice = [1_u8; 4];
+    slice.as_mut_slice().zero_range(1, 2)?;
+    assert_eq!(slice, [1, 0, 0, 1]);
+    assert!(slice.as_mut_slice().zero_range(3, 2).is_err());
+    Ok(())
+}
+
+/// An array which discards writes, recording the total number of bytes and
+/// the largest buffer written.
+#[derive(Default)]
+struct DiscardArray {
+    len: u64,
+    total: u64,
+    largest: usize,
+}
+
+impl Array for DiscardArray {
+    fn metadata(&self) -> io::Result<Metadata> {
+        [].metadata()
+    }
+
+    fn advise(&self, _offset: u64, _len: u64, _advice: Advice) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl WriteAt for DiscardArray {
+    fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        self.len = self.len.max(offset + buf.len() as u64);
+        self.total += buf.len() as u64;
+        self.largest = self.largest.max(buf.len());
+        Ok(buf.len())
+    }
+}
+
+impl Resize for DiscardArray {
+    fn set_len(&mut self, size: u64) -> io::Result<()> {
+        self.len = size;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_fill_at() -> anyhow::Result<()> {
+    let dir = tmpdir();
+    let mut editor = create_editor(&dir, "fill")?;
+    let mut bytes = Vec::new();
+    for (byte, offset, len) in [
+        (0xff, 3, 5000),
+        (0xa5, 4095, 70895),
+        (0, 100, 9),
+        (7, 74990, 20),
+    ] {
+        editor.fill_at(byte, offset, len)?;
+        bytes.fill_at(byte, offset, len)?;
+    }
+    assert_eq!(bytes.len(), 75010);
+    assert_eq!(editor.metadata()?.len(), 75010);
+    let mut buf = vec![0_u8; bytes.len()];
+    editor.read_exact_at(&mut buf, 0)?;
+    assert!(buf == bytes);
+
+    assert_eq!(&bytes[..3], [0, 0, 0]);
+    assert!(bytes[3..100].iter().all(|b| *b == 0xff));
+    assert!(bytes[100..109].iter().all(|b| *b == 0));
+    assert!(bytes[109..4095].iter().all(|b| *b == 0xff));
+    assert!(bytes[4095..74990].iter().all(|b| *b == 0xa5));
+    assert!(bytes[74990..].iter().all(|b| *b == 7));
+
+    // Filling a huge range only uses a small buffer.
+    let mut discard = DiscardArray::default();
+    discard.fill_at(0xff, 1, 1 << 32)?;
+    assert_eq!(discard.len, (1 << 32) + 1);
+    assert_eq!(discard.total, 1 << 32);
+    assert!(discard.largest <= 1024 * 1024);
+    Ok(())
+}
+
+#[test]
+fn test_metadata_file_type() -> anyhow::Result<()> {
+    use io_arrays::ArrayType;
+    use std::time::{Duration, SystemTime};
+
+    let dir = tmpdir();
+    let editor = create_editor(&dir, "file")?;
+    let meta = editor.metadata()?;
+    assert_eq!(meta.file_type(), ArrayType::File);
+    let age = SystemTime::now().duration_since(meta.modified().unwrap());
+    assert!(age.unwrap_or_default() < Duration::from_secs(60));
+
+    let meta = Vec::<u8>::new().metadata()?;
+    assert_eq!(meta.file_type(), ArrayType::Memory);
+    assert!(meta.modified().is_none());
+    Ok(())
+}
+
+#[test]
+fn test_is_same_file() -> anyhow::Result<()> {
+    use io_arrays::filelike::is_same_file;
+
+    let dir = tmpdir();
+    let a = create_editor(&dir, "a")?;
+    let b = create_editor(&dir, "b")?;
+    dir.hard_link("a", &dir, "link")?;
+    let link = ArrayReader::file(dir.open("link")?);
+    let file = dir.open("a")?;
+    let clone = ArrayReader::file(file.try_clone()?);
+
+    assert!(is_same_file(&a, &a)?);
+    assert!(is_same_file(&file, &clone)?);
+    assert!(is_same_file(&a, &clone)?);
+    assert!(is_same_file(&a, &link)?);
+    assert!(!is_same_file(&a, &b)?);
+    Ok(())
+}
+
+#[test]
+fn test_copy_from_large() -> anyhow::Result<()> {
+    const LEN: usize = 100 * 1024 * 1024;
+
+    let dir = tmpdir();
+    let data: Vec<u8> = (0..LEN).map(|i| (i % 251) as u8).collect();
+    let mut input = create_editor(&dir, "input")?;
+    input.write_all_at(&data, 0)?;
+    let input = ArrayReader::file(input.into_file());
+
+    let mut output = create_editor(&dir, "output")?;
+    assert_eq!(output.copy_from(7, &input, 0, LEN as u64)?, LEN as u64);
+    assert_eq!(output.metadata()?.len(), LEN as u64 + 7);
+
+    let mut buf = vec![0_u8; LEN];
+    output.read_exact_at(&mut buf, 7)?;
+    assert!(buf == data);
+
+    // Copying past the end of the input stops at the end.
+    assert_eq!(output.copy_from(0, &input, LEN as u64 - 3, 10)?, 3);
+    Ok(())
+}
+
+fn exercise_clone_range_from(dir: &Dir) -> anyhow::Result<io_arrays::CloneOutcome> {
+    let data: Vec<u8> = (0..3 * 65536 + 100).map(|i| (i % 253) as u8).collect();
+    let mut input = create_editor(dir, "input")?;
+    input.write_all_at(&data, 0)?;
+    let mut output = create_editor(dir, "output")?;
+
+    // Clone everything after the first 64 KiB, through the end of the input.
+    let outcome = output.clone_range_from(65536, &input, 65536, u64::MAX / 2)?;
+    assert_eq!(outcome.len(), data.len() as u64 - 65536);
+
+    let mut buf = vec![0_u8; data.len() - 65536];
+    output.read_exact_at(&mut buf, 65536)?;
+    assert!(buf == data[65536..]);
+
+    // Unaligned ranges are rejected.
+    assert_eq!(
+        output
+            .clone_range_from(65536, &input, 1, 65536)
+            .unwrap_err()
+            .kind(),
+        io::ErrorKind::InvalidInput
+    );
+    Ok(outcome)
+}
+
+#[test]
+fn test_clone_range_from() -> anyhow::Result<()> {
+    let dir = tmpdir();
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    exercise_clone_range_from(&dir)?;
+
+    // Memory-backed arrays always copy.
+    let mut output = vec![0_u8; 3];
+    let outcome = output.clone_range_from(0, &ArrayReader::bytes(b"hello")?, 1, 3)?;
+    assert_eq!(outcome, io_arrays::CloneOutcome::Copied(3));
+    assert_eq!(output, b"ell");
+    Ok(())
+}
+
+/// Set `IO_ARRAYS_TEST_REFLINK_DIR` to a directory on a filesystem which
+/// supports reflinks, such as btrfs or XFS, to run this test.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+#[ignore]
+fn test_clone_range_from_reflink() -> anyhow::Result<()> {
+    let parent = std::env::var_os("IO_ARRAYS_TEST_REFLINK_DIR")
+        .expect("IO_ARRAYS_TEST_REFLINK_DIR must be set");
+    let parent = Dir::open_ambient_dir(parent, ambient_authority())?;
+    let dir = TempDir::new_in(&parent)?;
+    assert!(exercise_clone_range_from(&dir)?.is_cloned());
+    Ok(())
+}
+
+/// Deduplicate a range which is shared between two files in `dir`.
+fn exercise_dedupe_range_from(dir: &Dir) -> io::Result<u64> {
+    const LEN: usize = 256 * 1024;
+
+    let data: Vec<u8> = (0..LEN).map(|i| (i % 251) as u8).collect();
+    let mut input = create_editor(dir, "input")?;
+    input.write_all_at(&data, 0)?;
+    let mut output = create_editor(dir, "output")?;
+    output.write_all_at(&data, 0)?;
+    output.write_all_at(b"different", LEN as u64 - 4096)?;
+
+    let deduped = output.dedupe_range_from(0, &input, 0, LEN as u64)?;
+    let mut buf = vec![0_u8; LEN];
+    output.read_exact_at(&mut buf, 0)?;
+    assert!(buf[..LEN - 4096] == data[..LEN - 4096]);
+    assert_eq!(&buf[LEN - 4096..LEN - 4087], b"different");
+    Ok(deduped)
+}
+
+#[test]
+fn test_dedupe_range_from_unsupported() -> anyhow::Result<()> {
+    // tmpfs doesn't support deduplication.
+    let dir = match Dir::open_ambient_dir("/dev/shm", ambient_authority()) {
+        Ok(shm) => TempDir::new_in(&shm)?,
+        Err(_) => tmpdir(),
+    };
+    match exercise_dedupe_range_from(&dir) {
+        Err(err) => assert_eq!(err.kind(), io::ErrorKind::Unsupported),
+        Ok(deduped) => assert!(deduped <= 256 * 1024 - 4096),
+    }
+
+    let mut bytes = vec![0_u8; 16];
+    let err = bytes
+        .dedupe_range_from(0, &vec![0_u8; 16], 0, 16)
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+    Ok(())
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+#[ignore]
+fn test_dedupe_range_from_reflink() -> anyhow::Result<()> {
+    let parent = std::env::var_os("IO_ARRAYS_TEST_REFLINK_DIR")
+        .expect("IO_ARRAYS_TEST_REFLINK_DIR must be set");
+    let parent = Dir::open_ambient_dir(parent, ambient_authority())?;
+    let dir = TempDir::new_in(&parent)?;
+    assert_eq!(exercise_dedupe_range_from(&dir)?, 256 * 1024 - 4096);
+    Ok(())
+}
+
+#[test]
+fn test_copy_from_sizes() -> anyhow::Result<()> {
+    const FAR: u64 = 5 * 1024 * 1024 * 1024;
+
+    let dir = tmpdir();
+    for size in [0, 1, 65535, 1024 * 1024 + 3] {
+        let data: Vec<u8> = (0..size).map(|i| (i % 239) as u8).collect();
+        let mut file_input = create_editor(&dir, "input")?;
+        file_input.set_len(0)?;
+        file_input.write_all_at(&data, 0)?;
+        let mem_input = MemReader::from(data.clone());
+        let inputs: [&dyn ReadAt; 2] = [&file_input, &mem_input];
+
+        for input in inputs {
+            for offset in [0, 3, FAR] {
+                let mut output = create_editor(&dir, "output")?;
+                output.set_len(0)?;
+                let copied = output.copy_from(offset, input, 0, size as u64)?;
+                assert_eq!(copied, size as u64);
+                let expected_len = if size == 0 { 0 } else { offset + size as u64 };
+                assert_eq!(output.metadata()?.len(), expected_len);
+
+                let mut buf = vec![0_u8; size];
+                output.read_exact_at(&mut buf, offset)?;
+                assert!(buf == data);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_copy_from_overlapping() -> anyhow::Result<()> {
+    let data: Vec<u8> = (0..200_000).map(|i| (i % 241) as u8).collect();
+
+    // (offset, input_offset, len)
+    let cases = [
+        (10, 0, 100_000),
+        (0, 10, 100_000),
+        (50, 50, 1000),
+        (7, 0, 300_000),
+    ];
+
+    let dir = tmpdir();
+    for (offset, input_offset, len) in cases {
+        // The expected result is what a snapshot-then-write would produce.
+        let end = data.len().min(input_offset + len);
+        let snapshot = data[input_offset..end].to_vec();
+        let mut expected = data.clone();
+        expected.resize(expected.len().max(offset + snapshot.len()), 0);
+        expected[offset..offset + snapshot.len()].copy_from_slice(&snapshot);
+
+        dir.write("file", &data)?;
+        let mut editor = create_editor(&dir, "file")?;
+        let input = ArrayReader::file(dir.open("file")?);
+        let copied = editor.copy_from(offset as u64, &input, input_offset as u64, len as u64)?;
+        assert_eq!(copied, snapshot.len() as u64);
+        assert!(dir.read("file")? == expected);
+
+        // In-memory arrays can't alias, so they copy from a snapshot.
+        let mut vec = data.clone();
+        vec.resize(expected.len(), 0);
+        vec.copy_from(
+            offset as u64,
+            &MemReader::from(snapshot.clone()),
+            0,
+            snapshot.len() as u64,
+        )?;
+        assert!(vec == expected);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_copy_from_with_progress() -> anyhow::Result<()> {
+    const LEN: usize = 5 * 1024 * 1024 + 17;
+
+    let dir = tmpdir();
+    let data: Vec<u8> = (0..LEN).map(|i| (i % 233) as u8).collect();
+    dir.write("input", &data)?;
+    let input = ArrayReader::file(dir.open("input")?);
+
+    for offset in [0, 1024 * 1024 + 1] {
+        let mut output = create_editor(&dir, format!("output{}", offset))?;
+        let mut totals = Vec::new();
+        let copied =
+            output.copy_from_with_progress(offset, &input, 0, LEN as u64, &mut |total| {
+                totals.push(total)
+            })?;
+        assert_eq!(copied, LEN as u64);
+        assert_eq!(totals.len(), 6);
+        assert!(totals.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(*totals.last().unwrap(), LEN as u64);
+
+        let mut buf = vec![0_u8; LEN];
+        output.read_exact_at(&mut buf, offset)?;
+        assert!(buf == data);
+    }
+
+    // Ranges which extend past the end of the address space are rejected
+    // before anything is copied.
+    let mut output = vec![0_u8; 4];
+    for (offset, input_offset) in [(u64::MAX - 1, 0), (0, u64::MAX - 1)] {
+        let err = output
+            .copy_from_with_progress(offset, &&data[..4], input_offset, 4, &mut |_| {
+                panic!("unexpected progress")
+            })
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+    assert_eq!(output, [0; 4]);
+    Ok(())
+}
+
+#[test]
+fn test_copy_from_parallel() -> anyhow::Result<()> {
+    let dir = tmpdir();
+    let data: Vec<u8> = (0..3 * 1024 * 1024 + 5).map(|i| (i % 241) as u8).collect();
+    dir.write("input", &data)?;
+    let input = ArrayReader::file(dir.open("input")?);
+
+    for size in [0, 1, 4095, 65536, 1024 * 1024 + 3, data.len()] {
+        let mut serial = create_editor(&dir, format!("serial{}", size))?;
+        let expected_copied = serial.copy_from(7, &input, 3, size as u64)?;
+
+        for threads in [1, 2, 5] {
+            let parallel = create_editor(&dir, format!("parallel{}-{}", size, threads))?;
+            let copied = io_arrays::bulk::copy_from_parallel(
+                &parallel,
+                7,
+                &input,
+                3,
+                size as u64,
+                threads,
+                64 * 1024,
+            )?;
+            assert_eq!(copied, expected_copied);
+            assert_eq!(parallel.metadata()?.len(), serial.metadata()?.len());
+
+            let mut expected = vec![0_u8; serial.metadata()?.len() as usize];
+            let mut actual = vec![0_u8; expected.len()];
+            serial.read_exact_at(&mut expected, 0)?;
+            parallel.read_exact_at(&mut actual, 0)?;
+            assert!(actual == expected);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_sync() -> anyhow::Result<()> {
+    let dir = tmpdir();
+    let mut editor = create_editor(&dir, "sync")?;
+    editor.write_all_at(b"durable", 0)?;
+    editor.sync_data()?;
+    editor.sync_all()?;
+
+    let file = dir.open("sync")?;
+    io_arrays::filelike::sync_all(&file)?;
+
+    let mut bytes = Vec::new();
+    bytes.write_all_at(b"memory", 0)?;
+    bytes.sync_data()?;
+    bytes.sync_all()?;
+    Ok(())
+}
+
+#[test]
+fn test_sync_range() -> anyhow::Result<()> {
+    use io_arrays::SyncRangeMode;
+
+    let dir = tmpdir();
+    let mut editor = create_editor(&dir, "sync_range")?;
+    editor.write_all_at(&[7_u8; 10000], 0)?;
+    for mode in [
+        SyncRangeMode::InitiateWriteback,
+        SyncRangeMode::WaitForCompletion,
+        SyncRangeMode::Full,
+    ] {
+        editor.sync_range(0, 4096, mode)?;
+        editor.sync_range(100, 0, mode)?;
+        editor.sync_range(8192, 100000, mode)?;
+        editor.sync_range(u64::MAX - 10, 10, mode)?;
+        editor.sync_range(1, u64::MAX - 1, mode)?;
+        vec![0_u8; 10].sync_range(0, 100, mode)?;
+    }
+    Ok(())
+}
+
+#[cfg(any(target_os = "linux", windows))]
+#[test]
+fn test_lock_at() -> anyhow::Result<()> {
+    use io_arrays::LockKind;
+
+    let dir = tmpdir();
+    let first = create_editor(&dir, "locked")?;
+    let second =
+        ArrayEditor::file(dir.open_with("locked", OpenOptions::new().read(true).write(true))?);
+
+    // Exclusive locks exclude overlapping ranges through other handles.
+    let guard = first.lock_at(100, 100, LockKind::Exclusive)?;
+    assert_eq!((guard.offset(), guard.len()), (100, 100));
+    assert!(second.try_lock_at(150, 100, LockKind::Shared)?.is_none());
+    assert!(second.try_lock_at(0, 100, LockKind::Exclusive)?.is_some());
+
+    // Dropping the guard releases the lock.
+    drop(guard);
+    let shared = second.try_lock_at(150, 100, LockKind::Shared)?.unwrap();
+
+    // Shared locks only exclude exclusive locks.
+    assert!(first.try_lock_at(200, 10, LockKind::Shared)?.is_some());
+    assert!(first.try_lock_at(200, 10, LockKind::Exclusive)?.is_none());
+    shared.unlock()?;
+    assert!(first.try_lock_at(200, 10, LockKind::Exclusive)?.is_some());
+
+    let err = vec![0_u8; 10]
+        .try_lock_at(0, 1, LockKind::Shared)
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+    Ok(())
+}
+
+#[test]
+fn test_read_at_nowait() -> anyhow::Result<()> {
+    let dir = tmpdir();
+    let mut editor = create_editor(&dir, "cached")?;
+    editor.write_all_at(b"in the page cache", 0)?;
+
+    // The data was just written, so it's in the page cache.
+    let mut buf = [0_u8; 4];
+    match editor.read_at_nowait(&mut buf, 7) {
+        Ok(n) => assert_eq!(&buf[..n], &b"page"[..n]),
+        Err(err) => assert_eq!(err.kind(), io::ErrorKind::Unsupported),
+    }
+
+    // Memory never blocks.
+    let bytes = b"in memory".to_vec();
+    assert_eq!(bytes.read_at_nowait(&mut buf, 3)?, 4);
+    assert_eq!(&buf, b"memo");
+
+    // Arrays without support report it, so that callers can fall back.
+    let minimal = MinimalArray(bytes);
+    let err = minimal.read_at_nowait(&mut buf, 0).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+    Ok(())
+}
+
+#[test]
+fn test_write_at_with() -> anyhow::Result<()> {
+    use io_arrays::WriteFlags;
+
+    let dir = tmpdir();
+    let mut editor = create_editor(&dir, "flags")?;
+    let mut minimal = MinimalArray(Vec::new());
+    let mut bytes = vec![0_u8; 40];
+    for (i, flags) in [
+        WriteFlags::empty(),
+        WriteFlags::DSYNC,
+        WriteFlags::SYNC,
+        WriteFlags::HIPRI,
+        WriteFlags::DSYNC | WriteFlags::HIPRI,
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let offset = i as u64 * 8;
+        assert_eq!(editor.write_at_with(b"flagged!", offset, flags)?, 8);
+        assert!(minimal.write_at_with(b"flagged!", offset, flags)? > 0);
+        assert_eq!(bytes.write_at_with(b"flagged!", offset, flags)?, 8);
+    }
+    let mut buf = vec![0_u8; 40];
+    editor.read_exact_at(&mut buf, 0)?;
+    assert_eq!(buf, b"flagged!".repeat(5));
+    assert_eq!(bytes, buf);
+
+    // `NOWAIT` either writes, would block, or isn't supported.
+    match editor.write_at_with(b"nowait", 0, WriteFlags::NOWAIT) {
+        Ok(n) => assert_eq!(n, 6),
+        Err(err) => assert!(matches!(
+            err.kind(),
+            io::ErrorKind::WouldBlock | io::ErrorKind::Unsupported
+        )),
+    }
+    let err = minimal
+        .write_at_with(b"nowait", 0, WriteFlags::NOWAIT)
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+    assert_eq!(bytes.write_at_with(b"nowait", 0, WriteFlags::NOWAIT)?, 6);
+    Ok(())
+}
+
+#[test]
+fn test_aligned_direct_io() -> anyhow::Result<()> {
+    use io_arrays::{read_aligned_at, write_aligned_at, AlignedBuf};
+
+    let dir = tmppath();
+    let path = dir.path().join("direct");
+    ArrayEditor::create_new(&path)?;
+    let mut editor = match ArrayEditor::open_direct(&path) {
+        Ok(editor) => editor,
+        // Some filesystems don't support direct I/O.
+        Err(err) if err.kind() == io::ErrorKind::InvalidInput => return Ok(()),
+        Err(err) => return Err(err.into()),
+    };
+
+    let mut buf = AlignedBuf::for_array(&editor, 8192)?;
+    assert_eq!(buf.as_ptr() as usize % buf.align(), 0);
+    assert!(buf.iter().all(|b| *b == 0));
+    for (i, b) in buf.iter_mut().enumerate() {
+        *b = i as u8;
+    }
+    let align = buf.align() as u64;
+    assert_eq!(write_aligned_at(&mut editor, &buf, align)?, 8192);
+
+    let mut back = AlignedBuf::for_array(&editor, 8192)?;
+    assert_eq!(read_aligned_at(&editor, &mut back, align)?, 8192);
+    assert_eq!(&back[..], &buf[..]);
+    assert_eq!(editor.metadata()?.len(), align + 8192);
+    Ok(())
+}
+
+#[test]
+fn test_aligned_validation() -> anyhow::Result<()> {
+    use io_arrays::{read_aligned_at, write_aligned_at, AlignedBuf};
+
+    let err = AlignedBuf::new(16, 3).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    assert!(AlignedBuf::new(0, 4096)?.is_empty());
+
+    let dir = tmpdir();
+    let mut editor = create_editor(&dir, "aligned")?;
+    let mut buf = AlignedBuf::for_array(&editor, 8192)?;
+    let align = buf.align();
+    assert!(align > 1);
+
+    let err = read_aligned_at(&editor, &mut buf[1..=align], 0).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    assert!(err.to_string().starts_with("buffer address"), "{err}");
+
+    let err = write_aligned_at(&mut editor, &buf[..align - 1], 0).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "buffer length {} is not a multiple of the required alignment {align}",
+            align - 1
+        )
+    );
+
+    let err = write_aligned_at(&mut editor, &buf[..align], 100).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!("offset 100 is not a multiple of the required alignment {align}")
+    );
+
+    assert_eq!(
+        write_aligned_at(&mut editor, &buf[..align], align as u64)?,
+        align
+    );
+    Ok(())
+}
+
+/// Create a `UringArray` for `file`, or return `None` if the kernel doesn't
+/// support `io_uring`.
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+fn uring_array(file: std::fs::File) -> anyhow::Result<Option<io_arrays::UringArray>> {
+    match io_arrays::UringArray::new(file) {
+        Ok(array) => Ok(Some(array)),
+        Err(err) if err.kind() == io::ErrorKind::Unsupported => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+#[test]
+fn test_uring_past_end() -> anyhow::Result<()> {
+    let Some(mut array) = uring_array(io_arrays::anonymous()?)? else {
+        return Ok(());
+    };
+    array.write_all_at(b"", 8192)?;
+    assert_eq!(array.metadata()?.len(), 0);
+
+    let message = b"Greetings, world!";
+    array.write_all_at(message, 8192)?;
+    let mut buf = vec![0xa0_u8; 8192 + message.len()];
+    array.read_exact_at(&mut buf, 0)?;
+    let mut cmp = vec![0_u8; 8192];
+    cmp.extend_from_slice(message);
+    assert_eq!(buf, cmp);
+    assert_eq!(array.metadata()?.len(), 8192 + message.len() as u64);
+
+    let mut buf = vec![0xa0_u8; 32];
+    assert_eq!(array.read_at(&mut buf, 16384)?, 0);
+    assert_eq!(array.read_at(&mut buf, 8192)?, message.len());
+    assert_eq!(
+        array.read_exact_at(&mut buf, 8192).unwrap_err().kind(),
+        io::ErrorKind::UnexpectedEof
+    );
+    Ok(())
+}
+
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+#[test]
+fn test_uring_write_defaults() -> anyhow::Result<()> {
+    let Some(mut array) = uring_array(io_arrays::anonymous()?)? else {
+        return Ok(());
+    };
+    let uring = exercise_write_defaults(&mut array)?;
+    let editor = exercise_write_defaults(&mut ArrayEditor::anonymous()?)?;
+    assert_eq!(uring, editor);
+
+    let mut a = [0_u8; 5];
+    let mut b = [0_u8; 3];
+    array.read_exact_vectored_at(&mut [IoSliceMut::new(&mut a), IoSliceMut::new(&mut b)], 5)?;
+    assert_eq!(&a, b"Hello");
+    assert_eq!(&b, b", w");
+    assert!(array.read_vectored_at(&mut [IoSliceMut::new(&mut a)], 20)? > 0);
+    assert_eq!(&a, b"abcde");
+    Ok(())
+}
+
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+#[test]
+fn test_uring_copy_from() -> anyhow::Result<()> {
+    let dir = tmpdir();
+    let file = dir.open_with(
+        "out",
+        OpenOptions::new().create_new(true).read(true).write(true),
+    )?;
+    let Some(mut array) = uring_array(file.into_std())? else {
+        return Ok(());
+    };
+
+    // From memory, in batches.
+    let data = (0..1_000_000_u32).map(|i| i as u8).collect::<Vec<_>>();
+    assert_eq!(array.copy_from(10, &data, 5, 999_995)?, 999_995);
+    let mut buf = vec![0_u8; 999_995];
+    array.read_exact_at(&mut buf, 10)?;
+    assert_eq!(buf, data[5..]);
+
+    // From a file.
+    let input = ArrayReader::bytes(&data[..4096])?;
+    assert_eq!(array.copy_from(0, &input, 0, 4096)?, 4096);
+    array.read_exact_at(&mut buf[..4096], 0)?;
+    assert_eq!(buf[..4096], data[..4096]);
+    Ok(())
+}
+
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+#[test]
+fn test_uring_random_reads() -> anyhow::Result<()> {
+    let dir = tmpdir();
+    let data = (0..4 << 20_u32)
+        .map(|i| (i ^ (i >> 11)) as u8)
+        .collect::<Vec<_>>();
+    dir.write("random", &data)?;
+    let Some(array) = uring_array(dir.open("random")?.into_std())? else {
+        return Ok(());
+    };
+    let array = std::sync::Arc::new(array);
+
+    let threads = (0..4_u64)
+        .map(|t| {
+            let array = array.clone();
+            let data = data.clone();
+            std::thread::spawn(move || -> io::Result<()> {
+                // A simple LCG, so the test is reproducible.
+                let mut state = 0x2545_f491_4f6c_dd1d_u64 ^ t;
+                let mut next = || {
+                    state = state
+                        .wrapping_mul(6_364_136_223_846_793_005)
+                        .wrapping_add(1_442_695_040_888_963_407);
+                    state >> 33
+                };
+                for _ in 0..50 {
+                    let mut bufs = (0..200)
+                        .map(|_| {
+                            let len = next() as usize % 8192 + 1;
+                            let offset = next() % (data.len() - len) as u64;
+                            (vec![0_u8; len], offset)
+                        })
+                        .collect::<Vec<_>>();
+                    let mut ranges = bufs
+                        .iter_mut()
+                        .map(|(buf, offset)| (&mut buf[..], *offset))
+                        .collect::<Vec<_>>();
+                    array.read_exact_ranges_at(&mut ranges)?;
+                    for (buf, offset) in &bufs {
+                        let offset = *offset as usize;
+                        assert_eq!(buf[..], data[offset..offset + buf.len()]);
+                    }
+                }
+                Ok(())
+            })
+        })
+        .collect::<Vec<_>>();
+    for thread in threads {
+        thread.join().unwrap()?;
+    }
+    Ok(())
+}
+
+// Test that vectored I/O transfers all the buffers in one call, and stops at
+// the end of the file, on all platforms.
+#[test]
+fn test_vectored_unequal_buffers() -> anyhow::Result<()> {
+    let mut editor = ArrayEditor::anonymous()?;
+    assert!(editor.is_read_vectored_at());
+    assert!(editor.is_write_vectored_at());
+
+    let nwritten = editor.write_vectored_at(
+        &[
+            IoSlice::new(b"a"),
+            IoSlice::new(&[]),
+            IoSlice::new(b"bcdef"),
+            IoSlice::new(b"ghi"),
+        ],
+        3,
+    )?;
+    assert_eq!(nwritten, 9);
+    assert_eq!(editor.metadata()?.len(), 12);
+
+    let (mut a, mut b, mut c) = ([0_u8; 2], [0_u8; 7], [0_u8; 4]);
+    let nread = editor.read_vectored_at(
+        &mut [
+            IoSliceMut::new(&mut a),
+            IoSliceMut::new(&mut b),
+            IoSliceMut::new(&mut c),
+        ],
+        0,
+    )?;
+    assert_eq!(nread, 12);
+    assert_eq!(&a, b"\0\0");
+    assert_eq!(&b, b"\0abcdef");
+    assert_eq!(&c, b"ghi\0");
+
+    // Reading past the end transfers what's there, and then nothing.
+    let (mut a, mut b, mut c) = ([0_u8; 3], [0_u8; 5], [0_u8; 1]);
+    let mut bufs = [
+        IoSliceMut::new(&mut a),
+        IoSliceMut::new(&mut b),
+        IoSliceMut::new(&mut c),
+    ];
+    assert_eq!(editor.read_vectored_at(&mut bufs, 6)?, 6);
+    assert_eq!(editor.read_vectored_at(&mut bufs, 12)?, 0);
+    assert_eq!(
+        editor
+            .read_exact_vectored_at(&mut bufs, 6)
+            .unwrap_err()
+            .kind(),
+        io::ErrorKind::UnexpectedEof
+    );
+    assert_eq!(&a, b"def");
+    assert_eq!(&b, b"ghi\0\0");
+    Ok(())
+}
+
+// Test that a vectored exact read which runs into the end of the file fails
+// promptly, and that a list of only empty buffers reads nothing.
+#[test]
+fn test_read_exact_vectored_at_eof() -> anyhow::Result<()> {
+    let mut editor = ArrayEditor::anonymous()?;
+    editor.write_all_at(&[7_u8; 100], 0)?;
+
+    let (mut a, mut b, mut c) = ([0_u8; 4], [0_u8; 40], [0_u8; 20]);
+    let mut bufs = [
+        IoSliceMut::new(&mut a),
+        IoSliceMut::new(&mut b),
+        IoSliceMut::new(&mut c),
+    ];
+    let err = editor.read_exact_vectored_at(&mut bufs, 90).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+
+    let mut bufs = [IoSliceMut::new(&mut []), IoSliceMut::new(&mut [])];
+    editor.read_exact_vectored_at(&mut bufs, 90)?;
+    editor.read_exact_vectored_at(&mut bufs, 100)?;
+    Ok(())
+}
+
+// Test skipping over the start of a list of buffers.
+#[test]
+fn test_iovec_advance() {
+    use io_arrays::iovec::{advance, advance_mut};
+
+    let mut bufs = [
+        IoSlice::new(b"abc"),
+        IoSlice::new(b"defg"),
+        IoSlice::new(b"hi"),
+    ];
+    let contents = |bufs: &[IoSlice]| bufs.iter().map(|b| b.to_vec()).collect::<Vec<_>>();
+
+    // Advancing by zero leaves the buffers as they are.
+    let rest = advance(&mut bufs, 0);
+    assert_eq!(contents(rest), [&b"abc"[..], b"defg", b"hi"]);
+
+    // Advancing by exactly one buffer removes it.
+    let rest = advance(rest, 3);
+    assert_eq!(contents(rest), [&b"defg"[..], b"hi"]);
+
+    // Advancing into the middle of a buffer shortens it.
+    let rest = advance(rest, 1);
+    assert_eq!(contents(rest), [&b"efg"[..], b"hi"]);
+
+    // Advancing across several buffers.
+    let mut bufs = [
+        IoSlice::new(b"abc"),
+        IoSlice::new(b""),
+        IoSlice::new(b"defg"),
+        IoSlice::new(b"hi"),
+    ];
+    let rest = advance(&mut bufs, 8);
+    assert_eq!(contents(rest), [&b"i"[..]]);
+    let rest = advance(rest, 1);
+    assert!(rest.is_empty());
+
+    let (mut a, mut b, mut c) = (*b"abc", *b"defg", *b"hi");
+    let mut bufs = [
+        IoSliceMut::new(&mut a),
+        IoSliceMut::new(&mut b),
+        IoSliceMut::new(&mut c),
+    ];
+    let rest = advance_mut(&mut bufs, 0);
+    assert_eq!(rest.len(), 3);
+    let rest = advance_mut(rest, 3);
+    assert_eq!(&*rest[0], b"defg");
+    let rest = advance_mut(rest, 4);
+    assert_eq!(rest.len(), 1);
+    rest[0][0] = b'H';
+    assert_eq!(&c, b"Hi");
+}
+
+// Test concurrent positional reads of a real file through a shared handle.
+#[test]
+fn test_concurrent_read_at_file() -> anyhow::Result<()> {
+    let dir = tmpdir();
+    let data = (0..64 * 1024_u32)
+        .map(|i| (i % 251) as u8)
+        .collect::<Vec<_>>();
+    dir.write("shared", &data)?;
+    let reader = std::sync::Arc::new(ArrayReader::file(dir.open("shared")?));
+    let data = std::sync::Arc::new(data);
+
+    let threads = (0..8_u64)
+        .map(|i| {
+            let reader = reader.clone();
+            let data = data.clone();
+            std::thread::spawn(move || -> io::Result<()> {
+                let mut buf = [0_u8; 1000];
+                for round in 0..200_u64 {
+                    let offset = (i * 7919 + round * 104_729) % (data.len() - buf.len()) as u64;
+                    reader.read_exact_at(&mut buf, offset)?;
+                    let offset = offset as usize;
+                    assert_eq!(buf[..], data[offset..offset + buf.len()]);
+                }
+                Ok(())
+            })
+        })
+        .collect::<Vec<_>>();
+    for thread in threads {
+        thread.join().unwrap()?;
+    }
+    Ok(())
+}
+
+/// An array which counts the calls to its `write_vectored_at`.
+struct CountingArray {
+    inner: ArrayEditor,
+    calls: usize,
+}
+
+impl Array for CountingArray {
+    fn metadata(&self) -> io::Result<Metadata> {
+        self.inner.metadata()
+    }
+
+    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        self.inner.advise(offset, len, advice)
+    }
+}
+
+impl WriteAt for CountingArray {
+    fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        self.inner.write_at(buf, offset)
+    }
+
+    fn write_vectored_at(&mut self, bufs: &[IoSlice], offset: u64) -> io::Result<usize> {
+        self.calls += 1;
+        self.inner.write_vectored_at(bufs, offset)
+    }
+}
+
+// Test that a vectored write of many small buffers is a single call.
+#[test]
+fn test_write_all_vectored_at_call_count() -> anyhow::Result<()> {
+    let mut array = CountingArray {
+        inner: ArrayEditor::anonymous()?,
+        calls: 0,
+    };
+    let data = (0..500_u32).map(|i| format!("{i:03}")).collect::<Vec<_>>();
+    let mut bufs = data
+        .iter()
+        .map(|s| IoSlice::new(s.as_bytes()))
+        .collect::<Vec<_>>();
+    array.write_all_vectored_at(&mut bufs, 7)?;
+    assert_eq!(array.calls, 1);
+
+    let mut buf = vec![0_u8; 1500];
+    array.inner.read_exact_at(&mut buf, 7)?;
+    assert_eq!(buf, data.concat().into_bytes());
+    Ok(())
+}
+
+/// An array whose writes succeed without writing anything, as a full
+/// device or a broken driver might.
+struct StuckArray;
+
+impl Array for StuckArray {
+    fn metadata(&self) -> io::Result<Metadata> {
+        Vec::<u8>::new().metadata()
+    }
+
+    fn advise(&self, _offset: u64, _len: u64, _advice: Advice) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl WriteAt for StuckArray {
+    fn write_at(&mut self, _buf: &[u8], _offset: u64) -> io::Result<usize> {
+        Ok(0)
+    }
+}
+
+// Test that writes which make no progress fail with `WriteZero` instead of
+// retrying forever, and that writing nothing still succeeds.
+#[test]
+fn test_write_zero() -> anyhow::Result<()> {
+    let mut array = StuckArray;
+    let err = array.write_all_at(b"hello", 3).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::WriteZero);
+
+    let mut bufs = [IoSlice::new(b""), IoSlice::new(b"hello")];
+    let err = array.write_all_vectored_at(&mut bufs, 3).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::WriteZero);
+
+    array.write_all_at(b"", 3)?;
+    array.write_all_vectored_at(&mut [IoSlice::new(b"")], 3)?;
+    Ok(())
+}
+
+// Test basic operations on a file in a directory opened with a capability,
+// as under WASI, where the directory is preopened by the host. Advice is
+// accepted even where the host doesn't implement it.
+#[test]
+fn test_preopened_dir_basics() -> anyhow::Result<()> {
+    let dir = tmpdir();
+    let mut editor = ArrayEditor::file(dir.open_with(
+        "basics",
+        OpenOptions::new().create_new(true).read(true).write(true),
+    )?);
+    for advice in [
+        Advice::Normal,
+        Advice::Sequential,
+        Advice::Random,
+        Advice::WillNeed,
+        Advice::DontNeed,
+        Advice::NoReuse,
+    ] {
+        editor.advise(0, 0, advice)?;
+    }
+
+    editor.write_all_at(b"portable", 4)?;
+    editor.write_all_vectored_at(&mut [IoSlice::new(b"ve"), IoSlice::new(b"ctored")], 12)?;
+    let mut buf = [0_u8; 16];
+    editor.read_exact_vectored_at(&mut [IoSliceMut::new(&mut buf)], 4)?;
+    assert_eq!(&buf, b"portablevectored");
+
+    editor.set_len(8)?;
+    let meta = editor.metadata()?;
+    assert_eq!(meta.len(), 8);
+    assert!(meta.blksize() > 0);
+    let mut buf = [0xff_u8; 8];
+    editor.read_exact_at(&mut buf, 0)?;
+    assert_eq!(&buf, b"\0\0\0\0port");
+    Ok(())
+}
+
+// Test the block size reported where there's no natural one.
+#[test]
+fn test_default_blksize() -> anyhow::Result<()> {
+    assert_eq!(vec![0_u8; 10].metadata()?.blksize(), 4096);
+    assert_eq!(b"abc"[..].metadata()?.blksize(), 4096);
+    Ok(())
+}
+
+#[test]
+fn test_edit_via_stream_at() -> anyhow::Result<()> {
+    let dir = tmpdir();
+    let mut editor = create_editor(&dir, "edit")?;
+    editor.write_all_at(b"abcdefghijkl", 0)?;
+
+    {
+        let mut stream = editor.edit_via_stream_at(2)?;
+        let mut buf = [0_u8; 4];
+        stream.read_exact(&mut buf)?;
+        assert_eq!(&buf, b"cdef");
+        stream.write_all(b"WXYZ")?;
+        let mut rest = Vec::new();
+        stream.read_to_end(&mut rest)?;
+        assert_eq!(rest, b"kl");
+    }
+
+    let mut buf = [0_u8; 12];
+    editor.read_exact_at(&mut buf, 0)?;
+    assert_eq!(&buf, b"abcdefWXYZkl");
+
+    // Writes through the stream are visible to reads through it.
+    let mut vec = vec![0_u8; 8];
+    let mut stream = vec.edit_via_stream_at(2)?;
+    stream.write_all(b"1234")?;
+    let mut stream = vec.edit_via_stream_at(0)?;
+    stream.write_all(b"++")?;
+    let mut buf = [0_u8; 6];
+    stream.read_exact(&mut buf)?;
+    assert_eq!(&buf, b"1234\0\0");
+    assert_eq!(&vec, b"++1234\0\0");
+    Ok(())
+}
+
+/// Read length-prefixed records with `read_exact`, the way a parser would.
+fn read_records(mut input: impl Read, count: usize) -> io::Result<Vec<Vec<u8>>> {
+    let mut records = Vec::new();
+    for _ in 0..count {
+        let mut len = [0_u8; 1];
+        input.read_exact(&mut len)?;
+        let mut record = vec![0_u8; len[0].into()];
+        input.read_exact(&mut record)?;
+        records.push(record);
+    }
+    Ok(records)
+}
+
+#[test]
+fn test_streamer() -> anyhow::Result<()> {
+    use io_arrays::Streamer;
+    use std::io::{BufRead, BufReader, Seek, SeekFrom};
+
+    let data = b"\x05hello\x01,\x06 world";
+    let expected = vec![b"hello".to_vec(), b",".to_vec(), b" world".to_vec()];
+
+    let dir = tmpdir();
+    let mut editor = create_editor(&dir, "streamer")?;
+    editor.write_all_at(data, 0)?;
+
+    // `io::copy` from a borrowing streamer, to the end of the file.
+    let mut out = Vec::new();
+    assert_eq!(io::copy(&mut Streamer::new(&editor, 1), &mut out)?, 14);
+    assert_eq!(out, &data[1..]);
+
+    // A `read_exact` loop, directly and through a `BufReader`.
+    assert_eq!(read_records(Streamer::new(&editor, 0), 3)?, expected);
+    let mut reader = BufReader::new(Streamer::new(&editor, 0));
+    assert_eq!(read_records(&mut reader, 3)?, expected);
+    assert!(reader.fill_buf()?.is_empty());
+
+    // Writing and seeking through a borrowing streamer.
+    let mut stream = Streamer::new(&editor, 1);
+    stream.write_all(b"HELLO")?;
+    assert_eq!(stream.seek(SeekFrom::End(-5))?, 10);
+    let mut buf = [0_u8; 5];
+    stream.read_exact(&mut buf)?;
+    assert_eq!(&buf, b"world");
+    assert_eq!(stream.seek(SeekFrom::Current(-11))?, 4);
+    stream.read_exact(&mut buf[..2])?;
+    assert_eq!(&buf[..2], b"LO");
+    assert!(stream.seek(SeekFrom::Current(-7)).is_err());
+
+    // The same, over a `Vec<u8>`, which is owned by the streamer.
+    let vec = data.to_vec();
+    let mut out = Vec::new();
+    io::copy(&mut Streamer::new(&vec, 1).take(5), &mut out)?;
+    assert_eq!(out, b"hello");
+    assert_eq!(read_records(Streamer::new(&vec, 0), 3)?, expected);
+    assert_eq!(
+        read_records(BufReader::new(Streamer::owned(vec, 0)).take(15), 3)?,
+        expected
+    );
+
+    let mut stream = Streamer::owned(vec![0_u8; 4], 1);
+    stream.write_all(b"abc")?;
+    assert_eq!(stream.seek(SeekFrom::End(0))?, 4);
+    assert_eq!(stream.into_inner(), b"\0abc");
+    Ok(())
+}
+
+/// A small `Read + Seek` consumer, in the style of a zip reader: read a
+/// footer holding the offset of a directory of names, then the directory.
+fn read_directory<R: Read + io::Seek>(mut input: R) -> io::Result<Vec<String>> {
+    let mut footer = [0_u8; 8];
+    input.seek(io::SeekFrom::End(-8))?;
+    input.read_exact(&mut footer)?;
+    input.seek(io::SeekFrom::Start(u64::from_le_bytes(footer)))?;
+    let mut count = [0_u8; 1];
+    input.read_exact(&mut count)?;
+    let mut names = Vec::new();
+    for _ in 0..count[0] {
+        let mut len = [0_u8; 1];
+        input.read_exact(&mut len)?;
+        let mut name = vec![0_u8; len[0].into()];
+        input.read_exact(&mut name)?;
+        names.push(String::from_utf8(name).unwrap());
+        // Skip the two-byte padding after each entry.
+        input.seek(io::SeekFrom::Current(2))?;
+    }
+    Ok(names)
+}
+
+#[test]
+fn test_streamer_seek() -> anyhow::Result<()> {
+    use io_arrays::Streamer;
+    use std::io::{Seek, SeekFrom};
+
+    let dir = tmpdir();
+    let mut file = dir.create("archive")?;
+    file.write_all(b"file data here")?;
+    let directory_offset: u64 = 14;
+    file.write_all(b"\x02\x03one..\x03two..")?;
+    file.write_all(&directory_offset.to_le_bytes())?;
+    drop(file);
+
+    let reader = ArrayReader::file(dir.open("archive")?);
+    assert_eq!(read_directory(Streamer::new(&reader, 0))?, ["one", "two"]);
+    assert_eq!(read_directory(Streamer::owned(reader, 0))?, ["one", "two"]);
+
+    // Seeking past the end is permitted; reads there return 0 bytes, and
+    // writes extend the array.
+    let editor =
+        ArrayEditor::file(dir.open_with("archive", OpenOptions::new().read(true).write(true))?);
+    let mut stream = Streamer::new(&editor, 0);
+    let len = stream.seek(SeekFrom::End(0))?;
+    assert_eq!(len, 35);
+    assert_eq!(stream.seek(SeekFrom::Current(4))?, 39);
+    assert_eq!(stream.read(&mut [0_u8; 4])?, 0);
+    stream.write_all(b"tail")?;
+    assert_eq!(stream.stream_position()?, 43);
+    assert!(stream.seek(SeekFrom::Current(i64::MAX)).is_ok());
+    assert!(stream.seek(SeekFrom::Current(i64::MAX)).is_err());
+    assert!(stream.seek(SeekFrom::End(-44)).is_err());
+    let mut buf = [0xff_u8; 8];
+    editor.read_exact_at(&mut buf, 35)?;
+    assert_eq!(&buf, b"\0\0\0\0tail");
+    Ok(())
+}
+
+#[test]
+fn test_streamer_buf_read() -> anyhow::Result<()> {
+    use io_arrays::Streamer;
+    use std::io::{BufRead, BufReader, Seek, SeekFrom};
+    use system_interface::io::Peek;
+
+    let dir = tmpdir();
+    let mut file = dir.create("lines")?;
+    let blksize = ArrayReader::file(dir.open("lines")?).metadata()?.blksize() as usize;
+    // Write lines of varying lengths, so that some span buffer boundaries.
+    for i in 0..blksize / 4 {
+        writeln!(file, "line {i} {}", "x".repeat(i % 37))?;
+    }
+    write!(file, "no newline")?;
+    drop(file);
+
+    let reader = ArrayReader::file(dir.open("lines")?);
+    let expected = BufReader::new(dir.open("lines")?.into_std())
+        .lines()
+        .collect::<io::Result<Vec<_>>>()?;
+    let lines = Streamer::new(&reader, 0)
+        .lines()
+        .collect::<io::Result<Vec<_>>>()?;
+    assert!(lines.len() > 1);
+    assert_eq!(lines, expected);
+
+    // Mix `BufRead`, `Read`, `Peek`, and `Seek`.
+    let mut stream = Streamer::new(&reader, 0);
+    let mut line = String::new();
+    stream.read_line(&mut line)?;
+    assert_eq!(line, "line 0 \n");
+    let mut buf = [0_u8; 6];
+    stream.read_exact(&mut buf)?;
+    assert_eq!(&buf, b"line 1");
+    assert_eq!(stream.peek(&mut buf[..3])?, 3);
+    assert_eq!(&buf[..3], b" x\n");
+    assert_eq!(stream.seek(SeekFrom::Current(-4))?, 10);
+    assert_eq!(stream.fill_buf()?[..4], *b"ne 1");
+    stream.seek(SeekFrom::Start(blksize as u64 - 2))?;
+    let mut buf = vec![0_u8; 4];
+    stream.read_exact(&mut buf)?;
+    let mut expected_buf = vec![0_u8; 4];
+    reader.read_exact_at(&mut expected_buf, blksize as u64 - 2)?;
+    assert_eq!(buf, expected_buf);
+    stream.seek(SeekFrom::End(-3))?;
+    line.clear();
+    stream.read_line(&mut line)?;
+    assert_eq!(line, "ine");
+    assert!(stream.fill_buf()?.is_empty());
+
+    // Writes discard the buffer, so later reads see them.
+    let editor =
+        ArrayEditor::file(dir.open_with("lines", OpenOptions::new().read(true).write(true))?);
+    let mut stream = Streamer::new(&editor, 0);
+    line.clear();
+    stream.read_line(&mut line)?;
+    stream.write_all(b"LINE")?;
+    stream.seek(SeekFrom::Start(line.len() as u64))?;
+    line.clear();
+    stream.read_line(&mut line)?;
+    assert_eq!(line, "LINE 1 x\n");
+    Ok(())
+}
+
+fn check_read_at_ext(array: &(impl ReadAt + ?Sized)) -> io::Result<()> {
+    use io_arrays::ReadAtExt;
+
+    assert_eq!(array.read_u8_at(0)?, 0xfe);
+    assert_eq!(array.read_i8_at(0)?, -2);
+    assert_eq!(array.read_u16_le_at(1)?, 0x3412);
+    assert_eq!(array.read_u16_be_at(1)?, 0x1234);
+    assert_eq!(array.read_i16_be_at(3)?, -2);
+    assert_eq!(array.read_u32_le_at(5)?, 0x7856_3412);
+    assert_eq!(array.read_u32_be_at(5)?, 0x1234_5678);
+    assert_eq!(array.read_i32_le_at(9)?, -1);
+    assert_eq!(array.read_u64_be_at(13)?, 0x0102_0304_0506_0708);
+    assert_eq!(array.read_u64_le_at(13)?, 0x0807_0605_0403_0201);
+    assert_eq!(array.read_i64_be_at(21)?, i64::MIN);
+    assert_eq!(array.read_f32_le_at(29)?, 1.5);
+    assert_eq!(array.read_f64_be_at(33)?, -0.25);
+    assert_eq!(array.read_array_at::<3>(2)?, [0x34, 0xff, 0xfe]);
+    Ok(())
+}
+
+#[test]
+fn test_read_at_ext() -> anyhow::Result<()> {
+    use io_arrays::ReadAtExt;
+
+    let mut bytes = vec![0xfe, 0x12, 0x34, 0xff, 0xfe, 0x12, 0x34, 0x56, 0x78];
+    bytes.extend_from_slice(&[0xff; 4]);
+    bytes.extend_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]);
+    bytes.extend_from_slice(&[0x80, 0, 0, 0, 0, 0, 0, 0]);
+    bytes.extend_from_slice(&1.5_f32.to_le_bytes());
+    bytes.extend_from_slice(&(-0.25_f64).to_be_bytes());
+
+    check_read_at_ext(bytes.as_slice())?;
+
+    let dir = tmpdir();
+    let mut file = dir.create("ext")?;
+    file.write_all(&bytes)?;
+    let reader = ArrayReader::file(dir.open("ext")?);
+    check_read_at_ext(&reader)?;
+    assert_eq!(
+        reader
+            .read_u64_le_at(bytes.len() as u64 - 4)
+            .unwrap_err()
+            .kind(),
+        io::ErrorKind::UnexpectedEof
+    );
+    Ok(())
+}
+
+#[test]
+fn test_write_at_ext() -> anyhow::Result<()> {
+    use io_arrays::WriteAtExt;
+
+    // A header with a magic number, a version, a flags byte, an entry
+    // count, a signed adjustment, a scale, and a trailing checksum which
+    // lands past the end of the array.
+    let mut editor = ArrayEditor::anonymous()?;
+    editor.write_array_at(*b"HDR\0", 0)?;
+    editor.write_u16_be_at(0x0102, 4)?;
+    editor.write_u8_at(0xa5, 6)?;
+    editor.write_i8_at(-1, 7)?;
+    editor.write_u32_le_at(0x1122_3344, 8)?;
+    editor.write_i64_be_at(-2, 12)?;
+    editor.write_f64_le_at(2.5, 20)?;
+    editor.write_f32_be_at(-1.0, 28)?;
+    editor.write_u64_le_at(u64::MAX - 1, 40)?;
+    assert_eq!(editor.metadata()?.len(), 48);
+
+    let mut buf = [0_u8; 48];
+    editor.read_exact_at(&mut buf, 0)?;
+    let mut expected = Vec::new();
+    expected.extend_from_slice(b"HDR\0\x01\x02\xa5\xff");
+    expected.extend_from_slice(&[0x44, 0x33, 0x22, 0x11]);
+    expected.extend_from_slice(&[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe]);
+    expected.extend_from_slice(&2.5_f64.to_le_bytes());
+    expected.extend_from_slice(&(-1.0_f32).to_be_bytes());
+    expected.extend_from_slice(&[0; 8]);
+    expected.extend_from_slice(&[0xfe, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]);
+    assert_eq!(buf[..], expected[..]);
+    Ok(())
+}
+
+fn check_write_fmt_at(array: &mut (impl EditAt + ?Sized)) -> io::Result<()> {
+    array.write_all_at(b"[..........]", 0)?;
+    assert_eq!(array.write_fmt_at(format_args!("{}-{:03}", 7, 42), 1)?, 5);
+    array.write_str_at("ok", 7)?;
+    assert_eq!(array.write_fmt_at(format_args!("plain"), 12)?, 5);
+    assert_eq!(array.write_fmt_at(format_args!("{:x}!", 255_u32), 17)?, 3);
+    let mut buf = [0_u8; 20];
+    array.read_exact_at(&mut buf, 0)?;
+    assert_eq!(&buf, b"[7-042.ok..]plainff!");
+    Ok(())
+}
+
+#[test]
+fn test_write_fmt_at() -> anyhow::Result<()> {
+    let mut vec = vec![0_u8; 20];
+    check_write_fmt_at(&mut vec)?;
+
+    let dir = tmpdir();
+    let mut editor = ArrayEditor::file(dir.open_with(
+        "fmt",
+        OpenOptions::new().read(true).write(true).create(true),
+    )?);
+    check_write_fmt_at(&mut editor)?;
+    assert_eq!(editor.metadata()?.len(), 20);
+
+    // An error from the array is reported, rather than a formatter error.
+    let mut read_only = ArrayEditor::file(dir.open("fmt")?);
+    let err = read_only
+        .write_fmt_at(format_args!("{}", 1), 0)
+        .unwrap_err();
+    assert!(err.raw_os_error().is_some(), "{err}");
+    Ok(())
+}
+
+fn check_read_to_vec_at(array: &(impl ReadAt + ?Sized)) -> io::Result<()> {
+    assert_eq!(array.read_to_vec_at(2, 4)?, b"cdef");
+    assert_eq!(array.read_to_vec_at(10, 0)?, b"");
+    assert_eq!(array.read_to_vec_at(0, 10)?, b"abcdefghij");
+    for (offset, len) in [(8, 4), (10, 1), (20, 3)] {
+        assert_eq!(
+            array.read_to_vec_at(offset, len).unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+    }
+
+    assert_eq!(array.read_up_to_vec_at(2, 4)?, b"cdef");
+    assert_eq!(array.read_up_to_vec_at(8, 4)?, b"ij");
+    assert_eq!(array.read_up_to_vec_at(10, 1)?, b"");
+    assert_eq!(array.read_up_to_vec_at(20, 3)?, b"");
+    Ok(())
+}
+
+#[test]
+fn test_read_to_vec_at() -> anyhow::Result<()> {
+    check_read_to_vec_at(&b"abcdefghij"[..])?;
+    check_read_to_vec_at(&b"abcdefghij".to_vec())?;
+
+    let dir = tmpdir();
+    let mut file = dir.create("vec")?;
+    file.write_all(b"abcdefghij")?;
+    check_read_to_vec_at(&ArrayReader::file(dir.open("vec")?))?;
+    Ok(())
+}
+
+#[test]
+fn test_bytes_at() -> anyhow::Result<()> {
+    let dir = tmpdir();
+    let mut file = dir.create("bytes")?;
+    let data = (0..10000_u32).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+    file.write_all(&data)?;
+    let reader = ArrayReader::file(dir.open("bytes")?);
+
+    let len = data.len() - 3;
+    let bytes = reader.bytes_at(3).collect::<io::Result<Vec<_>>>()?;
+    assert_eq!(bytes, reader.read_to_vec_at(3, len)?);
+    assert_eq!(reader.bytes_at(data.len() as u64).count(), 0);
+    assert_eq!(reader.bytes_at(data.len() as u64 + 10).count(), 0);
+
+    // Two iterators over one array, at different offsets.
+    let mut front = reader.bytes_at(0);
+    let mut back = reader.bytes_at(5000);
+    for i in 0..5000 {
+        assert_eq!(front.next().unwrap()?, data[i]);
+        assert_eq!(back.next().unwrap()?, data[5000 + i]);
+    }
+    assert!(front.next().is_some());
+    assert!(back.next().is_none());
+
+    // Scan for a delimiter in memory.
+    let vec = b"key=value".to_vec();
+    let pos = vec.bytes_at(0).position(|b| matches!(b, Ok(b'=')));
+    assert_eq!(pos, Some(3));
+    Ok(())
+}
+
+#[test]
+fn test_chunks_at() -> anyhow::Result<()> {
+    let dir = tmpdir();
+    let mut file = dir.create("chunks")?;
+    file.write_all(b"0123456789ab")?;
+    let reader = ArrayReader::file(dir.open("chunks")?);
+
+    // An exact multiple of the chunk size.
+    let chunks = reader.chunks_at(0, 4).collect::<io::Result<Vec<_>>>()?;
+    assert_eq!(chunks, [&b"0123"[..], b"4567", b"89ab"]);
+
+    // A short final chunk.
+    let chunks = reader.chunks_at(3, 4).collect::<io::Result<Vec<_>>>()?;
+    assert_eq!(chunks, [&b"3456"[..], b"789a", b"b"]);
+
+    // Reusing a buffer, while the file grows.
+    let mut chunks = reader.chunks_at(6, 5);
+    let mut buf = Vec::new();
+    assert_eq!(chunks.next_into(&mut buf).unwrap()?, 5);
+    assert_eq!(buf, b"6789a");
+    file.write_all(b"cdefghi")?;
+    assert_eq!(chunks.next_into(&mut buf).unwrap()?, 5);
+    assert_eq!(buf, b"bcdef");
+    assert_eq!(chunks.next_into(&mut buf).unwrap()?, 3);
+    assert_eq!(buf, b"ghi");
+    assert!(chunks.next_into(&mut buf).is_none());
+    assert!(buf.is_empty());
+
+    // An empty array.
+    let empty = ArrayEditor::anonymous()?;
+    assert_eq!(empty.chunks_at(0, 4).count(), 0);
+    assert_eq!(Vec::new().chunks_at(0, 1).count(), 0);
+    Ok(())
+}
+
+#[test]
+fn test_lines_at() -> anyhow::Result<()> {
+    let dir = tmpdir();
+    let mut file = dir.create("lines")?;
+    // A line longer than the internal buffer, and a line which isn't UTF-8.
+    let long = "x".repeat(10000);
+    write!(file, "one\r\ntwo\n\n{long}\n")?;
+    file.write_all(b"bad \xff\nlast")?;
+    let reader = ArrayReader::file(dir.open("lines")?);
+
+    let mut lines = reader.lines_at(0);
+    assert_eq!(lines.next().unwrap()?, "one");
+    assert_eq!(lines.next().unwrap()?, "two");
+    assert_eq!(lines.next().unwrap()?, "");
+    assert_eq!(lines.next().unwrap()?, long);
+    assert_eq!(
+        lines.next().unwrap().unwrap_err().kind(),
+        io::ErrorKind::InvalidData
+    );
+    assert_eq!(lines.next().unwrap()?, "last");
+    assert!(lines.next().is_none());
+
+    let lines = reader
+        .lines_at(2)
+        .with_offsets()
+        .take(4)
+        .collect::<io::Result<Vec<_>>>()?;
+    assert_eq!(
+        lines,
+        [
+            (2, "e".to_owned()),
+            (5, "two".to_owned()),
+            (9, String::new()),
+            (10, long.clone())
+        ]
+    );
+    let mut lines = reader.lines_at(10011).with_offsets();
+    assert!(lines.next().unwrap().is_err());
+    assert_eq!(lines.next().unwrap()?, (10017, "last".to_owned()));
+    assert!(lines.next().is_none());
+    Ok(())
+}
+
+#[test]
+fn test_find_at() -> anyhow::Result<()> {
+    // Searches read in 64 KiB chunks; put needles across the boundaries.
+    let chunk = 64 * 1024;
+    let mut data = vec![b'.'; 3 * chunk];
+    data[chunk - 2..chunk + 2].copy_from_slice(b"PK56");
+    data[2 * chunk - 1..2 * chunk + 3].copy_from_slice(b"PK56");
+    data[10..14].copy_from_slice(b"PK56");
+
+    let dir = tmpdir();
+    let mut file = dir.create("find")?;
+    file.write_all(&data)?;
+    let reader = ArrayReader::file(dir.open("find")?);
+    let end = data.len() as u64;
+
+    for array in [&reader as &dyn ReadAt, &data] {
+        let chunk = chunk as u64;
+        assert_eq!(array.find_at(b"PK56", 0)?, Some(10));
+        assert_eq!(array.find_at(b"PK56", 11)?, Some(chunk - 2));
+        assert_eq!(array.find_at(b"PK56", chunk - 1)?, Some(2 * chunk - 1));
+        assert_eq!(array.find_at(b"PK56", 2 * chunk)?, None);
+        assert_eq!(array.find_at(b"PK57", 0)?, None);
+        assert_eq!(array.find_at(b"PK56", end + 5)?, None);
+
+        assert_eq!(array.rfind_at(b"PK56", u64::MAX)?, Some(2 * chunk - 1));
+        assert_eq!(array.rfind_at(b"PK56", end)?, Some(2 * chunk - 1));
+        assert_eq!(array.rfind_at(b"PK56", 2 * chunk + 2)?, Some(chunk - 2));
+        assert_eq!(array.rfind_at(b"PK56", chunk + 2)?, Some(chunk - 2));
+        assert_eq!(array.rfind_at(b"PK56", chunk + 1)?, Some(10));
+        assert_eq!(array.rfind_at(b"PK56", 13)?, None);
+
+        // Empty needles, and needles longer than the array.
+        assert_eq!(array.find_at(b"", 7)?, Some(7));
+        assert_eq!(array.rfind_at(b"", u64::MAX)?, Some(end));
+        let long = vec![b'.'; data.len() + 1];
+        assert_eq!(array.find_at(&long, 0)?, None);
+        assert_eq!(array.rfind_at(&long, u64::MAX)?, None);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_eq_range() -> anyhow::Result<()> {
+    use io_arrays::bulk::{eq_range, mismatch_range};
+
+    let data = (0..200_000_u32)
+        .map(|i| (i % 253) as u8)
+        .collect::<Vec<_>>();
+    let dir = tmpdir();
+    let mut file = dir.create("a")?;
+    file.write_all(&data)?;
+    let a = ArrayReader::file(dir.open("a")?);
+    let mut changed = data.clone();
+    changed[150_000] ^= 1;
+    let mut file = dir.create("b")?;
+    file.write_all(&changed)?;
+    let b = ArrayReader::file(dir.open("b")?);
+    let len = data.len() as u64;
+
+    // File and file.
+    assert!(eq_range(&a, 0, &b, 0, 150_000)?);
+    assert!(!eq_range(&a, 0, &b, 0, len)?);
+    assert_eq!(mismatch_range(&a, 0, &b, 0, len)?, Some(150_000));
+    assert_eq!(mismatch_range(&a, 100, &b, 100, len - 100)?, Some(149_900));
+    assert_eq!(mismatch_range(&a, 253, &b, 0, 1000)?, None);
+
+    // File and slice.
+    assert_eq!(mismatch_range(&a, 0, &data[..], 0, len)?, None);
+    assert_eq!(mismatch_range(&a, 0, &changed[..], 0, len)?, Some(150_000));
+    assert_eq!(mismatch_range(&data[..], 10, &a, 11, 5)?, Some(0));
+
+    // The same file, aliased.
+    assert!(eq_range(&a, 0, &a, 0, len)?);
+    assert!(eq_range(&a, 0, &a, 253 * 100, 100_000)?);
+
+    // Running out of data is an error, not inequality.
+    for (a_offset, b_offset) in [(253, 0), (0, 253)] {
+        assert_eq!(
+            mismatch_range(&a, a_offset, &data[..], b_offset, len - 252)
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+    }
+    assert!(eq_range(&a, len, &b, len, 0)?);
+    Ok(())
+}
+
+#[test]
+fn test_hash_range() -> anyhow::Result<()> {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::Hasher;
+
+    let dir = tmpdir();
+    let mut file = dir.create("hash")?;
+    let data = (0..100_000_u32)
+        .map(|i| (i % 241) as u8)
+        .collect::<Vec<_>>();
+    file.write_all(&data)?;
+    let reader = ArrayReader::file(dir.open("hash")?);
+
+    for (offset, len) in [(0, 100_000), (7, 50_000), (99_999, 1), (5, 0)] {
+        let mut expected = DefaultHasher::new();
+        expected.write(&reader.read_to_vec_at(offset, len)?);
+        let mut hasher = DefaultHasher::new();
+        assert_eq!(
+            reader.hash_range(offset, len as u64, &mut hasher)?,
+            len as u64
+        );
+        assert_eq!(hasher.finish(), expected.finish());
+    }
+
+    // The range is truncated at the end of the array.
+    let mut expected = DefaultHasher::new();
+    expected.write(&data[90_000..]);
+    let mut hasher = DefaultHasher::new();
+    assert_eq!(reader.hash_range(90_000, 20_000, &mut hasher)?, 10_000);
+    assert_eq!(hasher.finish(), expected.finish());
+    assert_eq!(reader.hash_range(200_000, 10, &mut hasher)?, 0);
+
+    #[cfg(feature = "digest")]
+    {
+        use sha2::{Digest, Sha256};
+
+        let mut sha = Sha256::new();
+        assert_eq!(reader.hash_range_digest(10, 80_000, &mut sha)?, 80_000);
+        assert_eq!(sha.finalize(), Sha256::digest(&data[10..80_010]));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_array_slice() -> anyhow::Result<()> {
+    let editor = ArrayEditor::anonymous()?;
+    (&editor).write_all_at(b"0123456789abcdefghij", 0)?;
+
+    // Reads are confined to the window.
+    let view = editor.slice(5..15);
+    assert_eq!(view.metadata()?.len(), 10);
+    assert_eq!(view.read_to_vec_at(0, 10)?, b"56789abcde");
+    assert_eq!(view.read_up_to_vec_at(8, 10)?, b"de");
+    assert_eq!(view.read_at(&mut [0; 4], 10)?, 0);
+    assert_eq!(
+        view.read_exact_at(&mut [0; 3], 8).unwrap_err().kind(),
+        io::ErrorKind::UnexpectedEof
+    );
+
+    // Writes are translated, and don't extend past the window.
+    let mut view = editor.slice(5..15);
+    view.write_all_at(b"WXYZ", 2)?;
+    assert_eq!(view.write_at(b"!!!", 9)?, 1);
+    assert_eq!(view.write_at(b"!", 10)?, 0);
+    assert_eq!(
+        view.write_all_at(b"??", 9).unwrap_err().kind(),
+        io::ErrorKind::InvalidInput
+    );
+    assert_eq!(
+        view.fill_at(b'?', 5, 6).unwrap_err().kind(),
+        io::ErrorKind::InvalidInput
+    );
+    assert_eq!(editor.read_to_vec_at(0, 20)?, b"0123456WXYZbcd!fghij");
+    assert_eq!(editor.metadata()?.len(), 20);
+
+    // Nested slices compose.
+    let inner = view.slice(2..6);
+    assert_eq!(inner.read_to_vec_at(0, 4)?, b"WXYZ");
+    assert_eq!(inner.read_at(&mut [0; 2], 4)?, 0);
+    let mut inner = (&mut view).into_slice(4, 1);
+    inner.write_all_at(b"-", 0)?;
+    assert_eq!(editor.read_to_vec_at(9, 1)?, b"-");
+
+    // An owned view, extending past the end of the array.
+    let mut vec = b"abcdef".to_vec();
+    let mut tail = (&mut vec).into_slice(4, 10);
+    assert_eq!(tail.metadata()?.len(), 2);
+    assert_eq!(tail.read_up_to_vec_at(0, 10)?, b"ef");
+    tail.write_all_at(b"EF", 0)?;
+    assert_eq!(vec, b"abcdEF");
+    Ok(())
+}
+
+#[test]
+fn test_split_at() -> anyhow::Result<()> {
+    let editor = ArrayEditor::anonymous()?;
+    let (mut front, mut back) = editor.split_at(4096);
+    assert_eq!(front.offset(), 0);
+    assert_eq!(back.offset(), 4096);
+
+    std::thread::scope(|scope| -> io::Result<()> {
+        let front = scope.spawn(move || -> io::Result<()> {
+            for i in 0..64_u64 {
+                front.write_all_at(&[b'a' + (i % 26) as u8; 64], i * 64)?;
+            }
+            Ok(())
+        });
+        let back = scope.spawn(move || -> io::Result<()> {
+            for i in (0..64_u64).rev() {
+                back.write_all_at(&[b'A' + (i % 26) as u8; 64], i * 64)?;
+            }
+            Ok(())
+        });
+        front.join().unwrap()?;
+        back.join().unwrap()
+    })?;
+
+    assert_eq!(editor.metadata()?.len(), 8192);
+    let contents = editor.read_to_vec_at(0, 8192)?;
+    for (i, chunk) in contents.chunks(64).enumerate() {
+        let expected = match i {
+            0..=63 => b'a' + (i % 26) as u8,
+            _ => b'A' + ((i - 64) % 26) as u8,
+        };
+        assert!(chunk.iter().all(|&b| b == expected), "chunk {i}");
+    }
+
+    // The front half can't write past the split point.
+    let (mut front, _) = editor.split_at(4096);
+    assert_eq!(
+        front.write_all_at(b"xx", 4095).unwrap_err().kind(),
+        io::ErrorKind::InvalidInput
+    );
+    assert_eq!(front.write_at(b"xx", 4095)?, 1);
+    assert_eq!(editor.read_to_vec_at(4095, 2)?, b"xA");
+
+    // Views can be split again.
+    let (left, right) = editor.slice(10..20).split_at(4);
+    assert_eq!((left.offset(), right.offset()), (10, 14));
+    assert_eq!(right.metadata()?.len(), 6);
+    Ok(())
+}
+
+#[test]
+fn test_chain_array() -> anyhow::Result<()> {
+    use io_arrays::ChainArray;
+
+    let dir = tmpdir();
+    let mut file = dir.create("segment")?;
+    file.write_all(b"ghijkl")?;
+    let file =
+        ArrayEditor::file(dir.open_with("segment", OpenOptions::new().read(true).write(true))?);
+
+    // A slice, an empty segment, a Vec, and a file.
+    let mut slice = *b"abc";
+    let segments: Vec<Box<dyn EditAt + '_>> = vec![
+        Box::new(&mut slice[..]),
+        Box::new(Vec::new()),
+        Box::new(b"def".to_vec()),
+        Box::new(file),
+    ];
+    let mut chain = ChainArray::new(segments)?;
+    assert_eq!(chain.metadata()?.len(), 12);
+
+    // Reads straddling and exactly at boundaries.
+    assert_eq!(chain.read_to_vec_at(0, 12)?, b"abcdefghijkl");
+    assert_eq!(chain.read_to_vec_at(2, 5)?, b"cdefg");
+    assert_eq!(chain.read_to_vec_at(3, 3)?, b"def");
+    assert_eq!(chain.read_up_to_vec_at(1, 20)?, b"bcdefghijkl");
+    assert_eq!(chain.read_at(&mut [0; 8], 1)?, 2);
+    assert_eq!(chain.read_at(&mut [0; 8], 12)?, 0);
+    assert_eq!(
+        chain.read_exact_at(&mut [0; 3], 10).unwrap_err().kind(),
+        io::ErrorKind::UnexpectedEof
+    );
+
+    // A vectored read spanning three segments.
+    let (mut a, mut b, mut c) = ([0; 2], [0; 5], [0; 3]);
+    chain.read_exact_vectored_at(
+        &mut [
+            IoSliceMut::new(&mut a),
+            IoSliceMut::new(&mut b),
+            IoSliceMut::new(&mut c),
+        ],
+        1,
+    )?;
+    assert_eq!((&a, &b, &c), (b"bc", b"defgh", b"ijk"));
+
+    // Writes straddling boundaries, which don't extend the chain.
+    chain.write_all_at(b"XYZW", 1)?;
+    chain.write_all_at(b"1234", 5)?;
+    assert_eq!(
+        chain.write_all_at(b"!!", 11).unwrap_err().kind(),
+        io::ErrorKind::InvalidInput
+    );
+    assert_eq!(chain.read_to_vec_at(0, 12)?, b"aXYZW1234jkl");
+
+    let segments = chain.into_segments();
+    assert_eq!(segments[3].read_to_vec_at(0, 6)?, b"234jkl");
+    drop(segments);
+    assert_eq!(&slice, b"aXY");
+    Ok(())
+}
+
+#[test]
+fn test_sparse_mem_array() -> anyhow::Result<()> {
+    use io_arrays::SparseMemArray;
+
+    let tib = 1 << 40;
+    let mut array = SparseMemArray::new();
+    array.set_len(tib)?;
+    let metadata = array.metadata()?;
+    assert_eq!(metadata.len(), tib);
+    assert_eq!(metadata.allocated_len(), 0);
+    let page = metadata.blksize();
+
+    // Holes read as zeros.
+    assert_eq!(array.read_to_vec_at(tib / 2, 16)?, [0; 16]);
+    assert_eq!(array.read_at(&mut [0; 16], tib - 4)?, 4);
+    assert_eq!(array.read_at(&mut [0; 16], tib)?, 0);
+
+    // A write straddling a page boundary allocates two pages.
+    array.write_all_at(b"straddle", tib / 2 - 3)?;
+    assert_eq!(array.metadata()?.allocated_len(), 2 * page);
+    assert_eq!(array.read_to_vec_at(tib / 2 - 5, 12)?, b"\0\0straddle\0\0");
+
+    // Writing zeros into a hole doesn't allocate.
+    array.write_all_at(&[0; 100], 1000 * page)?;
+    assert_eq!(array.metadata()?.allocated_len(), 2 * page);
+
+    // Memory usage stays proportional to the data written.
+    for i in 0..10 {
+        array.write_all_at(b"x", i * (tib / 10))?;
+    }
+    assert_eq!(array.metadata()?.allocated_len(), 11 * page);
+
+    // Punching a hole deallocates whole pages and zeros partial ones.
+    array.punch_hole(tib / 2 - page, page + 1)?;
+    assert_eq!(array.metadata()?.allocated_len(), 10 * page);
+    assert_eq!(array.read_to_vec_at(tib / 2 - 3, 8)?, b"\0\0\0\0ddle");
+    array.punch_hole(tib / 2, page)?;
+    assert_eq!(array.metadata()?.allocated_len(), 9 * page);
+    assert_eq!(array.read_to_vec_at(tib / 2 - 3, 8)?, [0; 8]);
+
+    // Writes past the end extend the array.
+    array.write_all_at(b"end", tib)?;
+    assert_eq!(array.metadata()?.len(), tib + 3);
+
+    // Truncating and regrowing reads zeros in the truncated range.
+    let mut small = SparseMemArray::new();
+    small.write_all_at(&[1; 10000], 0)?;
+    assert_eq!(small.metadata()?.allocated_len(), 3 * page);
+    small.set_len(5000)?;
+    assert_eq!(small.metadata()?.allocated_len(), 2 * page);
+    small.set_len(10000)?;
+    assert_eq!(small.read_to_vec_at(4998, 4)?, [1, 1, 0, 0]);
+    assert_eq!(small.read_to_vec_at(9000, 1000)?, [0; 1000]);
+    assert_eq!(
+        small.read_exact_at(&mut [0; 2], 9999).unwrap_err().kind(),
+        io::ErrorKind::UnexpectedEof
+    );
+
+    // Vectored reads cross pages and holes.
+    let (mut a, mut b) = ([0; 3], [0; 3]);
+    assert_eq!(
+        small.read_vectored_at(
+            &mut [IoSliceMut::new(&mut a), IoSliceMut::new(&mut b)],
+            4997
+        )?,
+        6
+    );
+    assert_eq!((a, b), ([1, 1, 1], [0, 0, 0]));
+    Ok(())
+}
+
+/// An array which counts the calls to its `read_at`.
+#[derive(Default)]
+struct CountingReader {
+    inner: io_arrays::SparseMemArray,
+    reads: AtomicUsize,
+}
+
+impl Array for CountingReader {
+    fn metadata(&self) -> io::Result<Metadata> {
+        self.inner.metadata()
+    }
+
+    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        self.inner.advise(offset, len, advice)
+    }
+}
+
+impl ReadAt for CountingReader {
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        self.reads.fetch_add(1, Ordering::Relaxed);
+        self.inner.read_at(buf, offset)
+    }
+
+    fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+        self.inner.read_exact_at(buf, offset)
+    }
+
+    fn read_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<usize> {
+        self.inner.read_vectored_at(bufs, offset)
+    }
+
+    fn read_exact_vectored_at(&self, bufs: &mut [IoSliceMut], offset: u64) -> io::Result<()> {
+        self.inner.read_exact_vectored_at(bufs, offset)
+    }
+
+    fn is_read_vectored_at(&self) -> bool {
+        true
+    }
+
+    #[cfg(feature = "io-streams")]
+    fn read_via_stream_at(&self, offset: u64) -> io::Result<io_streams::StreamReader> {
+        self.inner.read_via_stream_at(offset)
+    }
+}
+
+impl WriteAt for CountingReader {
+    fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        self.inner.write_at(buf, offset)
+    }
+}
+
+impl Resize for CountingReader {
+    fn set_len(&mut self, size: u64) -> io::Result<()> {
+        self.inner.set_len(size)
+    }
+}
+
+#[test]
+fn test_cached_array() -> anyhow::Result<()> {
+    use io_arrays::CachedArray;
+
+    let mut inner = CountingReader::default();
+    inner.write_all_at(&(0..=255).cycle().take(1000).collect::<Vec<u8>>(), 0)?;
+    let cached = CachedArray::new(inner, 64, 4);
+
+    // The first read misses, and repeated reads hit the cache.
+    assert_eq!(cached.read_to_vec_at(10, 4)?, [10, 11, 12, 13]);
+    assert_eq!((cached.hits(), cached.misses()), (0, 1));
+    for _ in 0..10 {
+        assert_eq!(cached.read_to_vec_at(20, 4)?, [20, 21, 22, 23]);
+    }
+    assert_eq!((cached.hits(), cached.misses()), (10, 1));
+    let inner = cached.into_inner();
+    assert_eq!(inner.reads.load(Ordering::Relaxed), 1);
+    let mut cached = CachedArray::with_capacity_bytes(inner, 64, 256);
+
+    // A read straddling blocks reads each block once.
+    for _ in 0..2 {
+        assert_eq!(
+            cached.read_to_vec_at(60, 8)?,
+            [60, 61, 62, 63, 64, 65, 66, 67]
+        );
+    }
+    assert_eq!((cached.hits(), cached.misses()), (2, 2));
+
+    // Reads stop at the end of the array, in a short block.
+    assert_eq!(cached.read_up_to_vec_at(990, 100)?.len(), 10);
+    assert_eq!(cached.read_at(&mut [0; 8], 1000)?, 0);
+    assert_eq!(
+        cached.read_exact_at(&mut [0; 8], 996).unwrap_err().kind(),
+        io::ErrorKind::UnexpectedEof
+    );
+
+    // The least recently used blocks are evicted.
+    for block in 0..4 {
+        cached.read_exact_at(&mut [0; 1], block * 64)?;
+    }
+    let misses = cached.misses();
+    cached.read_exact_at(&mut [0; 1], 3 * 64)?;
+    assert_eq!(cached.misses(), misses);
+    cached.read_exact_at(&mut [0; 1], 4 * 64)?;
+    cached.read_exact_at(&mut [0; 1], 0)?;
+    assert_eq!(cached.misses(), misses + 2);
+
+    // Writes are write-through, and keep the cache coherent.
+    cached.read_exact_at(&mut [0; 1], 0)?;
+    cached.write_all_at(b"abcdef", 62)?;
+    assert_eq!(cached.read_to_vec_at(60, 10)?, b"\x3c\x3dabcdef\x44\x45");
+    cached.write_all_at(b"tail", 998)?;
+    assert_eq!(cached.metadata()?.len(), 1002);
+    assert_eq!(cached.read_up_to_vec_at(996, 100)?, b"\xe4\xe5tail");
+    cached.set_len(997)?;
+    assert_eq!(cached.read_up_to_vec_at(996, 100)?, b"\xe4");
+
+    // Invalidation discards blocks, so they're read again.
+    cached.read_exact_at(&mut [0; 1], 64)?;
+    let misses = cached.misses();
+    cached.read_exact_at(&mut [0; 1], 64)?;
+    assert_eq!(cached.misses(), misses);
+    cached.invalidate(100, 1);
+    cached.read_exact_at(&mut [0; 1], 64)?;
+    assert_eq!(cached.misses(), misses + 1);
+
+    // Concurrent reads from multiple threads are correct.
+    let inner = cached.into_inner();
+    inner.reads.store(0, Ordering::Relaxed);
+    let cached = CachedArray::new(inner, 16, 8);
+    std::thread::scope(|scope| {
+        for t in 0..4_u64 {
+            let cached = &cached;
+            scope.spawn(move || {
+                for i in 0..200 {
+                    let offset = (i * 7 + t * 13) % 900;
+                    let mut buf = [0; 20];
+                    cached.read_exact_at(&mut buf, offset).unwrap();
+                    let expected = (offset..offset + 20).map(|x| x as u8).collect::<Vec<_>>();
+                    if offset + 20 <= 62 || offset >= 68 {
+                        assert_eq!(buf[..], expected[..]);
+                    }
+                }
+            });
+        }
+    });
+    assert!(cached.into_inner().reads.load(Ordering::Relaxed) < 4 * 200);
+    Ok(())
+}
+
+#[test]
+fn test_buf_reader_at() -> anyhow::Result<()> {
+    use io_arrays::BufReaderAt;
+
+    let len = 100_000_usize;
+    let data = (0..len).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+    let mut inner = CountingReader::default();
+    inner.write_all_at(&data, 0)?;
+
+    // A forward scan with small reads reads each window once, plus one read
+    // to find the end of the array.
+    let reader = BufReaderAt::with_window_blocks(inner, 2);
+    let window = 2 * reader.metadata()?.blksize() as usize;
+    let mut scanned = Vec::new();
+    let mut buf = [0; 10];
+    for offset in (0..len).step_by(buf.len()) {
+        reader.read_exact_at(&mut buf, offset as u64)?;
+        scanned.extend_from_slice(&buf);
+    }
+    assert_eq!(scanned, data);
+    assert_eq!(reader.read_at(&mut buf, len as u64)?, 0);
+    assert_eq!(
+        reader
+            .read_exact_at(&mut buf, len as u64 - 5)
+            .unwrap_err()
+            .kind(),
+        io::ErrorKind::UnexpectedEof
+    );
+    let inner = reader.into_inner();
+    assert_eq!(
+        inner.reads.swap(0, Ordering::Relaxed),
+        len.div_ceil(window) + 1
+    );
+
+    // Reads as large as a window go directly to the inner array.
+    let reader = BufReaderAt::with_window_blocks(inner, 2);
+    let mut big = vec![0; window];
+    reader.read_exact_at(&mut big, 3)?;
+    assert_eq!(big, data[3..3 + window]);
+    reader.read_exact_at(&mut big, 5)?;
+    let inner = reader.into_inner();
+    assert_eq!(inner.reads.load(Ordering::Relaxed), 2);
+
+    // Concurrent scans of different parts of the array are correct.
+    let reader = BufReaderAt::new(inner);
+    std::thread::scope(|scope| {
+        for t in 0..4 {
+            let (reader, data) = (&reader, &data);
+            scope.spawn(move || {
+                let mut buf = [0; 7];
+                for offset in (t * 20_000..t * 20_000 + 20_000).step_by(7) {
+                    reader.read_exact_at(&mut buf, offset as u64).unwrap();
+                    assert_eq!(buf, data[offset..offset + 7]);
+                }
+            });
+        }
+    });
+    Ok(())
+}
+
+#[test]
+fn test_stats_array() -> anyhow::Result<()> {
+    use io_arrays::StatsArray;
+
+    let mut array = StatsArray::new(ArrayEditor::anonymous()?);
+    let stats = array.stats();
+    assert_eq!((stats.reads().count(), stats.writes().count()), (0, 0));
+    assert_eq!(stats.reads().min_len(), 0);
+
+    // Writes, including a copy from another array.
+    array.write_all_at(b"Hello, world!", 0)?;
+    array.write_all_vectored_at(&mut [IoSlice::new(b"abc"), IoSlice::new(b"de")], 13)?;
+    assert_eq!(array.copy_from(100, &b"0123456789".to_vec(), 2, 8)?, 8);
+    let writes = array.stats().writes();
+    assert_eq!(writes.count(), 3);
+    assert_eq!(writes.bytes(), 13 + 5 + 8);
+    assert_eq!((writes.min_len(), writes.max_len()), (5, 13));
+    assert_eq!(writes.mean_len(), (13 + 5 + 8) / 3);
+    assert_eq!(array.stats().max_end(), 108);
+
+    // Reads, including a short read at the end, and a failed read.
+    let mut buf = [0; 5];
+    array.read_exact_at(&mut buf, 7)?;
+    assert_eq!(&buf, b"world");
+    assert_eq!(array.read_at(&mut [0; 16], 104)?, 4);
+    assert_eq!(array.read_to_vec_at(13, 5)?, b"abcde");
+    assert!(array.read_exact_at(&mut [0; 8], 104).is_err());
+    let reads = array.stats().reads();
+    assert_eq!(reads.count(), 4);
+    assert_eq!(reads.bytes(), 5 + 4 + 5);
+    assert_eq!((reads.min_len(), reads.max_len()), (5, 16));
+
+    // Reads through `&self` from multiple threads are all counted.
+    array.reset();
+    assert_eq!(array.stats().reads().count(), 0);
+    assert_eq!(array.stats().max_end(), 0);
+    std::thread::scope(|scope| {
+        for _ in 0..4 {
+            let array = &array;
+            scope.spawn(move || {
+                for i in 0..100 {
+                    array.read_exact_at(&mut [0; 2], i).unwrap();
+                }
+            });
+        }
+    });
+    let stats = array.stats();
+    assert_eq!(stats.reads().count(), 400);
+    assert_eq!(stats.reads().bytes(), 800);
+    assert_eq!(stats.max_end(), 101);
+    assert_eq!(stats.writes().count(), 0);
+    Ok(())
+}
+
+#[test]
+fn test_tracing_array_hook() -> anyhow::Result<()> {
+    use io_arrays::TracingArray;
+    use std::sync::{Arc, Mutex};
+
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let hook_log = Arc::clone(&log);
+    let mut array = TracingArray::new(b"hello".to_vec()).with_hook(move |event| {
+        hook_log.lock().unwrap().push((
+            event.op(),
+            event.offset(),
+            event.len(),
+            event.result().map_err(|err| err.kind()),
+        ));
+    });
+
+    // Results, including short reads and errors, are passed through.
+    assert_eq!(array.read_at(&mut [0; 3], 1)?, 3);
+    assert_eq!(array.read_at(&mut [0; 8], 2)?, 3);
+    assert_eq!(
+        array.read_exact_at(&mut [0; 8], 2).unwrap_err().kind(),
+        io::ErrorKind::UnexpectedEof
+    );
+    array.write_all_at(b"J", 0)?;
+    assert_eq!(array.read_to_vec_at(0, 5)?, b"Jello");
+
+    assert_eq!(
+        *log.lock().unwrap(),
+        [
+            ("read_at", 1, 3, Ok(3)),
+            ("read_at", 2, 8, Ok(3)),
+            ("read_exact_at", 2, 8, Err(io::ErrorKind::UnexpectedEof)),
+            ("write_all_at", 0, 1, Ok(1)),
+            ("read_exact_at", 0, 5, Ok(5)),
+        ]
+    );
+    Ok(())
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn test_tracing_array_events() -> anyhow::Result<()> {
+    use io_arrays::TracingArray;
+    use std::collections::BTreeMap;
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Level, Metadata, Subscriber};
+
+    type Fields = BTreeMap<&'static str, String>;
+
+    /// A subscriber which records the fields of each event, along with the
+    /// fields of the span it's in.
+    #[derive(Default)]
+    struct Recorder {
+        spans: Mutex<Vec<Fields>>,
+        current: Mutex<Vec<u64>>,
+        events: Arc<Mutex<Vec<(Level, Fields)>>>,
+    }
+
+    struct Visitor<'a>(&'a mut Fields);
+
+    impl Visit for Visitor<'_> {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            self.0.insert(field.name(), format!("{value:?}"));
+        }
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            let mut fields = Fields::new();
+            span.record(&mut Visitor(&mut fields));
+            let mut spans = self.spans.lock().unwrap();
+            spans.push(fields);
+            Id::from_u64(spans.len() as u64)
+        }
+
+        fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+        fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let mut fields = Fields::new();
+            event.record(&mut Visitor(&mut fields));
+            if let Some(&id) = self.current.lock().unwrap().last() {
+                fields.extend(self.spans.lock().unwrap()[id as usize - 1].clone());
+            }
+            fields
+                .remove("elapsed_us")
+                .expect("expected an elapsed time");
+            let level = *event.metadata().level();
+            self.events.lock().unwrap().push((level, fields));
+        }
+
+        fn enter(&self, span: &Id) {
+            self.current.lock().unwrap().push(span.into_u64());
+        }
+
+        fn exit(&self, _span: &Id) {
+            self.current.lock().unwrap().pop();
+        }
+    }
+
+    let recorder = Recorder::default();
+    let events = Arc::clone(&recorder.events);
+    tracing::subscriber::with_default(recorder, || -> anyhow::Result<()> {
+        let data = *b"hello";
+        let array = TracingArray::new(&data[..]).with_level(Level::DEBUG);
+        assert_eq!(array.read_at(&mut [0; 3], 1)?, 3);
+        assert_eq!(array.read_at(&mut [0; 8], 2)?, 3);
+        assert!(array.read_exact_at(&mut [0; 8], 2).is_err());
+        Ok(())
+    })?;
+
+    let expected = |fields: &[(&'static str, &str)]| {
+        fields
+            .iter()
+            .map(|&(name, value)| (name, value.to_owned()))
+            .collect::<Fields>()
+    };
+    assert_eq!(
+        *events.lock().unwrap(),
+        [
+            (
+                Level::DEBUG,
+                expected(&[
+                    ("op", "\"read_at\""),
+                    ("offset", "1"),
+                    ("len", "3"),
+                    ("ret", "3")
+                ])
+            ),
+            (
+                Level::DEBUG,
+                expected(&[
+                    ("op", "\"read_at\""),
+                    ("offset", "2"),
+                    ("len", "8"),
+                    ("ret", "3")
+                ])
+            ),
+            (
+                Level::DEBUG,
+                expected(&[
+                    ("op", "\"read_exact_at\""),
+                    ("offset", "2"),
+                    ("len", "8"),
+                    ("error", "failed to fill whole buffer")
+                ])
+            ),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_zero_and_pattern_arrays() -> anyhow::Result<()> {
+    use io_arrays::{PatternArray, ZeroArray};
+
+    // Reads start at the right phase of the pattern for any offset.
+    let tib = 1 << 40;
+    let pattern = PatternArray::new(b"abcdefg".to_vec(), tib).with_blksize(512);
+    assert_eq!(pattern.metadata()?.len(), tib);
+    assert_eq!(pattern.metadata()?.blksize(), 512);
+    assert_eq!(pattern.read_to_vec_at(0, 10)?, b"abcdefgabc");
+    assert_eq!(pattern.read_to_vec_at(3, 10)?, b"defgabcdef");
+    assert_eq!(pattern.read_to_vec_at(7 * 1001 + 6, 3)?, b"gab");
+    let offset = tib - 5;
+    let expected = (offset..tib)
+        .map(|i| b"abcdefg"[(i % 7) as usize])
+        .collect::<Vec<_>>();
+    let mut bufs = [0; 2].map(|_| vec![0; 2]);
+    let mut slices = bufs.each_mut().map(|b| IoSliceMut::new(b));
+    pattern.read_exact_vectored_at(&mut slices, offset)?;
+    assert_eq!([&bufs[0][..], &bufs[1][..]].concat(), expected[..4]);
+
+    // Reads are clamped at the end.
+    let mut buf = [0xa0; 16];
+    assert_eq!(pattern.read_at(&mut buf, offset)?, 5);
+    assert_eq!(buf[..5], expected);
+    assert_eq!(buf[5..], [0xa0; 11]);
+    assert_eq!(pattern.read_at(&mut buf, tib)?, 0);
+    assert_eq!(
+        pattern.read_exact_at(&mut buf, offset).unwrap_err().kind(),
+        io::ErrorKind::UnexpectedEof
+    );
+    let zeros = ZeroArray::new(10);
+    let mut buf = [0xa0; 16];
+    assert_eq!(zeros.read_at(&mut buf, 4)?, 6);
+    assert_eq!(buf[..7], [0, 0, 0, 0, 0, 0, 0xa0]);
+
+    // Writes are rejected, except by a sink, which just tracks the length.
+    let mut pattern = pattern;
+    assert_eq!(
+        pattern.write_all_at(b"x", 0).unwrap_err().kind(),
+        io::ErrorKind::PermissionDenied
+    );
+    assert_eq!(
+        Resize::set_len(&mut ZeroArray::new(10), 0)
+            .unwrap_err()
+            .kind(),
+        io::ErrorKind::PermissionDenied
+    );
+    let mut sink = ZeroArray::sink();
+    assert_eq!(sink.copy_from(1000, &pattern, 0, 1 << 20)?, 1 << 20);
+    sink.write_all_at(b"x", 10)?;
+    assert_eq!(sink.metadata()?.len(), 1000 + (1 << 20));
+    assert_eq!(sink.read_to_vec_at(0, 4)?, [0; 4]);
+
+    // Copy zeros into a sparse file.
+    let dir = tmpdir();
+    let mut file = ArrayEditor::file(dir.open_with(
+        "sparse",
+        OpenOptions::new().create_new(true).read(true).write(true),
+    )?);
+    file.set_len(1 << 30)?;
+    file.write_all_at(b"data", 1 << 20)?;
+    let len = 4 << 20;
+    assert_eq!(file.copy_from(1 << 19, &ZeroArray::new(len), 0, len)?, len);
+    assert_eq!(file.metadata()?.len(), 1 << 30);
+    assert_eq!(file.read_to_vec_at((1 << 20) - 2, 8)?, [0; 8]);
+    assert_eq!(file.copy_from(0, &ZeroArray::new(10), 5, 20)?, 5);
+    Ok(())
+}
+
+#[test]
+fn test_cursor_read_at() -> anyhow::Result<()> {
+    let mut cursor = io::Cursor::new(b"hello, world".to_vec());
+    let mut buf = [0; 5];
+    cursor.read_exact(&mut buf)?;
+    assert_eq!(&buf, b"hello");
+
+    // Positional reads are relative to the start of the bytes, and leave the
+    // position alone.
+    assert_eq!(cursor.read_to_vec_at(7, 5)?, b"world");
+    assert_eq!(cursor.read_at(&mut [0; 8], 10)?, 2);
+    assert_eq!(cursor.position(), 5);
+    assert_eq!(cursor.metadata()?.len(), 12);
+
+    let mut rest = String::new();
+    cursor.read_to_string(&mut rest)?;
+    assert_eq!(rest, ", world");
+    assert_eq!(cursor.read_to_vec_at(0, 5)?, b"hello");
+    assert_eq!(cursor.position(), 12);
+
+    let cursor = io::Cursor::new(&b"borrowed"[..]);
+    assert_eq!(cursor.read_to_vec_at(2, 3)?, b"rro");
+    assert_eq!(cursor.position(), 0);
+    Ok(())
+}
+
+#[test]
+fn test_cursor_write_at() -> anyhow::Result<()> {
+    let mut cursor = io::Cursor::new(Vec::new());
+    cursor.write_all(b"hello")?;
+
+    // Positional writes past the end grow the vector, and leave the
+    // position alone.
+    cursor.write_all_at(b"world", 7)?;
+    assert_eq!(cursor.position(), 5);
+    cursor.write_all(b", ")?;
+    assert_eq!(cursor.get_ref(), b"hello, world");
+    cursor.write_all_at(b"W", 7)?;
+    cursor.write_all(b"!")?;
+    assert_eq!(cursor.get_ref(), b"hello, !orld");
+    assert_eq!(cursor.copy_from(14, &b"copied".to_vec(), 0, 6)?, 6);
+    assert_eq!(cursor.get_ref(), b"hello, !orld\0\0copied");
+    cursor.set_len(5)?;
+    assert_eq!(cursor.position(), 8);
+    assert_eq!(cursor.get_ref(), b"hello");
+
+    let mut buf = *b"..........";
+    let mut cursor = io::Cursor::new(&mut buf[..]);
+    cursor.write_all(b"ab")?;
+    cursor.write_all_at(b"xyz", 4)?;
+    cursor.write_all(b"cd")?;
+    assert_eq!(cursor.position(), 4);
+    // Writes past the end of a slice are cut short.
+    cursor.write_all_at(b"long", 8)?;
+    assert_eq!(cursor.position(), 4);
+    assert_eq!(&buf, b"abcdxyz.lo");
+    Ok(())
+}
+
+#[test]
+fn test_shared_buffers() -> anyhow::Result<()> {
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    let data = (0..=255).cycle().take(1 << 16).collect::<Vec<u8>>();
+    let shared: Arc<[u8]> = data.clone().into();
+    let threads = [0, 1 << 15].map(|start| {
+        let shared = Arc::clone(&shared);
+        std::thread::spawn(move || shared.read_to_vec_at(start, 1 << 15))
+    });
+    for (thread, start) in threads.into_iter().zip([0, 1 << 15]) {
+        assert_eq!(thread.join().unwrap()?, data[start..][..1 << 15]);
+    }
+
+    let rc: Rc<[u8]> = data[..100].into();
+    assert_eq!(rc.metadata()?.len(), 100);
+    assert_eq!(rc.read_at(&mut [0; 8], 96)?, 4);
+
+    let source = Arc::new(data.clone());
+    let mut copy = vec![0; 10];
+    assert_eq!(copy.copy_from(0, &source, 300, 10)?, 10);
+    assert_eq!(copy, data[300..310]);
+    Ok(())
+}
+
+#[test]
+fn test_fixed_size_arrays() -> anyhow::Result<()> {
+    fn fill_sector<W: WriteAt>(mut sector: W) -> io::Result<W> {
+        sector.write_all_at(b"boot", 0)?;
+        sector.fill_at(0xaa, 30, 2)?;
+        Ok(sector)
+    }
+
+    let sector = fill_sector([0_u8; 32])?;
+    assert_eq!(sector.metadata()?.len(), 32);
+    assert_eq!(sector.read_to_vec_at(0, 5)?, b"boot\0");
+    assert_eq!(sector.read_to_vec_at(28, 4)?, [0, 0, 0xaa, 0xaa]);
+
+    // Reads and writes are clamped to the array's fixed bounds.
+    let mut sector = sector;
+    sector.write_all_at(b"tail", 30)?;
+    assert_eq!(sector.read_to_vec_at(28, 4)?, b"\0\0ta");
+    assert_eq!(sector.read_at(&mut [0; 8], 30)?, 2);
+    assert_eq!(sector.read_at(&mut [0; 8], 40)?, 0);
+    assert_eq!(
+        sector.read_exact_at(&mut [0; 8], 30).unwrap_err().kind(),
+        io::ErrorKind::UnexpectedEof
+    );
+    Ok(())
+}
+
+#[cfg(feature = "bytes")]
+#[test]
+fn test_bytes_buffers() -> anyhow::Result<()> {
+    use bytes::{Bytes, BytesMut};
+
+    let bytes = Bytes::from_static(b"the quick brown fox");
+    let inner = bytes.slice(4..).slice(6..);
+    assert_eq!(inner.metadata()?.len(), 9);
+    assert_eq!(inner.read_to_vec_at(0, 5)?, b"brown");
+    assert_eq!(inner.read_at(&mut [0; 8], 6)?, 3);
+    #[cfg(feature = "io-streams")]
+    {
+        let mut stream = inner.read_range_via_stream(6, 3)?;
+        let mut out = String::new();
+        stream.read_to_string(&mut out)?;
+        assert_eq!(out, "fox");
+    }
+
+    // Writes past the end grow the buffer.
+    let mut buf = BytesMut::from(&b"hello"[..]);
+    buf.write_all_at(b"world", 7)?;
+    assert_eq!(&buf[..], b"hello\0\0world");
+    buf.write_all_at(b", ", 5)?;
+    assert_eq!(buf.read_to_vec_at(0, 12)?, b"hello, world");
+    Resize::set_len(&mut buf, 4)?;
+    assert_eq!(&buf.freeze()[..], b"hell");
+    Ok(())
+}
+
+#[cfg(feature = "bytes")]
+#[test]
+fn test_read_bytes_at() -> anyhow::Result<()> {
+    use io_arrays::{ReadAtExt, SparseMemArray};
+
+    let mut array = SparseMemArray::new();
+    array.write_all_at(b"header:payload", 0)?;
+
+    let bytes = array.read_bytes_at(7, 7)?;
+    assert_eq!(&bytes[..], b"payload");
+    assert_eq!(
+        array.read_bytes_at(7, 8).unwrap_err().kind(),
+        io::ErrorKind::UnexpectedEof
+    );
+    assert_eq!(&array.read_up_to_bytes_at(7, 100)?[..], b"payload");
+    assert!(array.read_up_to_bytes_at(100, 10)?.is_empty());
+
+    // Slices and clones share the buffer.
+    let load = bytes.slice(3..);
+    assert_eq!(&load[..], b"load");
+    assert_eq!(load.as_ptr(), bytes[3..].as_ptr());
+    assert_eq!(bytes.clone().as_ptr(), bytes.as_ptr());
+    Ok(())
+}
+
+#[cfg(feature = "mmap")]
+#[test]
+fn test_mmap() -> anyhow::Result<()> {
+    use memmap2::{Mmap, MmapMut};
+
+    let dir = tmpdir();
+    let mut file = dir.open_with(
+        "mapped",
+        OpenOptions::new().create_new(true).read(true).write(true),
+    )?;
+    file.write_all(b"mapped file contents")?;
+    let file = file.into_std();
+
+    let mut map = unsafe { MmapMut::map_mut(&file)? };
+    assert_eq!(map.metadata()?.len(), 20);
+    assert_eq!(map.read_to_vec_at(7, 4)?, b"file");
+    map.write_all_at(b"FILE", 7)?;
+    map.write_at_with(b"!", 19, io_arrays::WriteFlags::DSYNC)?;
+    map.sync_data()?;
+    Array::advise(&map, 0, 0, Advice::WillNeed)?;
+    Array::advise(&map, 4, 100, Advice::Sequential)?;
+    Array::advise(&map, 100, 4, Advice::Random)?;
+    drop(map);
+
+    let map = unsafe { Mmap::map(&file)? };
+    assert_eq!(map.read_to_vec_at(0, 20)?, b"mapped FILE content!");
+    assert_eq!(map.read_at(&mut [0; 8], 16)?, 4);
+    Array::advise(&map, 0, 0, Advice::WillNeed)?;
+    Array::advise(&map, 0, 0, Advice::DontNeed)?;
+    Ok(())
+}
+
+#[cfg(feature = "mmap")]
+#[test]
+fn test_mapped_array() -> anyhow::Result<()> {
+    use io_arrays::MappedArray;
+
+    /// Perform a sequence of operations on `array`, returning what it reads.
+    fn exercise<A: EditAt + Resize>(array: &mut A) -> io::Result<Vec<Vec<u8>>> {
+        let mut reads = Vec::new();
+        array.write_all_at(b"hello", 0)?;
+        array.write_all_at(b"world", 8)?;
+        reads.push(array.read_to_vec_at(0, 13)?);
+        reads.push(array.read_up_to_vec_at(6, 100)?);
+        array.set_len(4)?;
+        reads.push(array.read_up_to_vec_at(0, 100)?);
+        array.set_len(10)?;
+        array.write_all_at(b"straddle", 6)?;
+        reads.push(array.read_up_to_vec_at(0, 100)?);
+        let mut buf = [0; 8];
+        let n = array.read_at(&mut buf, 12)?;
+        reads.push(buf[..n].to_vec());
+        array.zero_range(2, 4)?;
+        array.fill_at(b'z', 20, 3)?;
+        reads.push(array.read_up_to_vec_at(0, 100)?);
+        array.set_len(0)?;
+        reads.push(array.read_up_to_vec_at(0, 100)?);
+        array.write_all_at(b"again", 2)?;
+        reads.push(array.read_up_to_vec_at(0, 100)?);
+        reads.push(array.metadata()?.len().to_le_bytes().to_vec());
+        Ok(reads)
+    }
+
+    let dir = tmpdir();
+    let open = |name| dir.open_with(name, OpenOptions::new().create(true).read(true).write(true));
+    let expected = exercise(&mut ArrayEditor::file(open("editor")?))?;
+    assert_eq!(expected[0], b"hello\0\0\0world");
+    // SAFETY: Nothing else modifies the files this test maps.
+    let mut mapped = unsafe { MappedArray::file(open("mapped")?)? };
+    assert_eq!(exercise(&mut mapped)?, expected);
+    assert_eq!(exercise(&mut MappedArray::anonymous(0)?)?, expected);
+
+    // Data written through the map persists.
+    let mut array = unsafe { MappedArray::file(open("persist")?)? };
+    array.set_len(4096)?;
+    array.write_all_at(b"persistent", 100)?;
+    array.flush_range(100, 10)?;
+    array.flush()?;
+    Array::advise(&array, 0, 0, Advice::WillNeed)?;
+    drop(array);
+    let array = unsafe { MappedArray::file(open("persist")?)? };
+    assert_eq!(array.read_to_vec_at(100, 10)?, b"persistent");
+    assert_eq!(
+        ArrayReader::file(open("persist")?).read_to_vec_at(98, 12)?,
+        b"\0\0persistent"
+    );
+
+    // Writes past the end of the map go to the file, until it's remapped.
+    let mut array = MappedArray::anonymous(8)?;
+    array.write_all_at(b"mapped", 4)?;
+    assert_eq!(array.metadata()?.len(), 10);
+    assert_eq!(array.read_to_vec_at(0, 10)?, b"\0\0\0\0mapped");
+    unsafe { array.remap()? };
+    assert_eq!(array.read_to_vec_at(0, 10)?, b"\0\0\0\0mapped");
+    Ok(())
+}
+
+#[cfg(feature = "positioned-io")]
+#[test]
+fn test_positioned_io() -> anyhow::Result<()> {
+    use io_arrays::{AsPositionedIo, FromPositionedIo};
+    use positioned_io::Size;
+
+    /// A consumer of `positioned_io`'s traits, which reads a length-prefixed
+    /// record.
+    fn read_record<R: positioned_io::ReadAt>(r: &R, pos: u64) -> io::Result<Vec<u8>> {
+        let mut len = [0; 1];
+        r.read_exact_at(pos, &mut len)?;
+        let mut record = vec![0; usize::from(len[0])];
+        r.read_exact_at(pos + 1, &mut record)?;
+        Ok(record)
+    }
+
+    let reader = AsPositionedIo::new(ArrayReader::bytes(b"\x05hello\x05world")?);
+    assert_eq!(read_record(&reader, 0)?, b"hello");
+    assert_eq!(read_record(&reader, 6)?, b"world");
+    assert_eq!(reader.size()?, Some(12));
+    assert_eq!(
+        read_record(&reader, 7).unwrap_err().kind(),
+        io::ErrorKind::UnexpectedEof
+    );
+
+    let mut writer = AsPositionedIo::new(io::Cursor::new(Vec::new()));
+    positioned_io::WriteAt::write_all_at(&mut writer, 0, b"\x03abc")?;
+    assert_eq!(read_record(&writer, 0)?, b"abc");
+    assert_eq!(writer.into_inner().into_inner(), b"\x03abc");
+
+    // And the other way around.
+    let data = b"\x05hello\x05world".to_vec();
+    let array = FromPositionedIo::sized(data.clone());
+    assert_eq!(array.metadata()?.len(), 12);
+    assert_eq!(array.read_to_vec_at(7, 5)?, b"world");
+    assert_eq!(array.read_up_to_vec_at(9, 100)?, b"rld");
+    let unsized_array = FromPositionedIo::new(&data[..]);
+    assert_eq!(
+        unsized_array
+            .metadata()
+            .map(|m| m.len())
+            .unwrap_err()
+            .kind(),
+        io::ErrorKind::Unsupported
+    );
+    assert_eq!(unsized_array.read_to_vec_at(1, 5)?, b"hello");
+
+    let mut array = FromPositionedIo::sized(Vec::new());
+    array.write_all_at(b"grown", 3)?;
+    assert_eq!(array.into_inner(), b"\0\0\0grown");
+    Ok(())
+}
+
+#[cfg(feature = "tokio")]
+#[test]
+fn test_tokio_file() -> anyhow::Result<()> {
+    use tokio::io::AsyncWriteExt;
+
+    fn edit<E: EditAt + Resize>(array: &mut E) -> io::Result<Vec<u8>> {
+        array.write_all_at(b"Hello, world!", 3)?;
+        array.set_len(8)?;
+        array.read_to_vec_at(0, 8)
+    }
+
+    let dir = tmpdir();
+    let runtime = tokio::runtime::Builder::new_current_thread().build()?;
+    runtime.block_on(async {
+        let file = dir.open_with(
+            "file.txt",
+            OpenOptions::new().create_new(true).read(true).write(true),
+        )?;
+        let mut file = tokio::fs::File::from_std(file.into_std());
+
+        // Writes through tokio must be flushed before positioned I/O sees them.
+        file.write_all(b"XYZ").await?;
+        file.flush().await?;
+        assert_eq!(file.metadata().await?.len(), 3);
+        assert_eq!(Array::metadata(&file)?.len(), 3);
+
+        assert_eq!(edit(&mut file)?, b"XYZHello");
+        assert_eq!(file.read_up_to_vec_at(6, 100)?, b"lo");
+        assert_eq!(file.write_at(b"!", 8)?, 1);
+        assert_eq!(
+            file.read_to_vec_at(7, 5).unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+        let std_file = file.into_std().await;
+        assert_eq!(std_file.read_to_vec_at(0, 9)?, b"XYZHello!");
+        Ok(())
+    })
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn test_async_blocking() -> anyhow::Result<()> {
+    use io_arrays::{AsyncArray, AsyncReadAt, AsyncResize, AsyncWriteAt, Blocking};
+
+    async fn copy_between(
+        mut input: Blocking<ArrayEditor>,
+        mut output: Blocking<ArrayEditor>,
+    ) -> io::Result<Vec<u8>> {
+        input.write_all_at(b"XYZHello, world!XYZ", 0).await?;
+        assert_eq!(AsyncArray::metadata(&input).await?.len(), 19);
+        assert_eq!(output.copy_from(2, &input, 3, 100).await?, 16);
+        output.set_len(15).await?;
+        output.sync_data().await?;
+
+        let mut buf = [0_u8; 20];
+        let nread = AsyncReadAt::read_at(&output, &mut buf, 10).await?;
+        assert_eq!(&buf[..nread], b"orld!");
+        assert_eq!(
+            AsyncReadAt::read_exact_at(&output, &mut buf, 10)
+                .await
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+        let mut buf = vec![0_u8; 15];
+        AsyncReadAt::read_exact_at(&output, &mut buf, 0).await?;
+        Ok(buf)
+    }
+
+    let expected = b"\0\0Hello, world!";
+
+    let runtime = tokio::runtime::Builder::new_multi_thread().build()?;
+    let copied = runtime.block_on(copy_between(
+        Blocking::with_spawner(ArrayEditor::anonymous()?, |task| {
+            tokio::task::spawn_blocking(task);
+        }),
+        Blocking::with_spawner(ArrayEditor::anonymous()?, |task| {
+            tokio::task::spawn_blocking(task);
+        }),
+    ))?;
+    assert_eq!(copied, expected);
+
+    let copied = async_std::task::block_on(copy_between(
+        Blocking::with_spawner(ArrayEditor::anonymous()?, |task| {
+            async_std::task::spawn_blocking(task);
+        }),
+        Blocking::new(ArrayEditor::anonymous()?),
+    ))?;
+    assert_eq!(copied, expected);
+
+    let output = Blocking::new(ArrayEditor::anonymous()?);
+    let dropped = Blocking::with_spawner(ArrayEditor::anonymous()?, drop);
+    let mut buf = [0_u8; 4];
+    let err = async_std::task::block_on(AsyncReadAt::read_at(&dropped, &mut buf, 0)).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::Other);
+    assert!(output.into_inner().is_ok());
+    Ok(())
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn test_async_streamer_at() -> anyhow::Result<()> {
+    use futures::io::{AsyncBufReadExt, AsyncReadExt, AsyncSeekExt};
+    use io_arrays::{AsyncStreamerAt, Blocking};
+    use std::io::SeekFrom;
+
+    let data = (0..1000_u32).map(|i| i as u8).collect::<Vec<u8>>();
+    let mut editor = ArrayEditor::anonymous()?;
+    editor.write_all_at(&data, 0)?;
+    let reader = ArrayReader::file(editor.into_file());
+
+    futures::executor::block_on(async {
+        let mut streamer = AsyncStreamerAt::new(Blocking::new(reader), 10);
+        let mut copied = Vec::new();
+        assert_eq!(futures::io::copy(&mut streamer, &mut copied).await?, 990);
+        assert_eq!(copied, &data[10..]);
+        assert_eq!(streamer.position(), 1000);
+
+        // Copy again with a buffer smaller than the data, and seek around.
+        let mut streamer = AsyncStreamerAt::with_capacity(64, streamer.into_inner(), 0);
+        let mut copied = Vec::new();
+        assert_eq!(futures::io::copy(&mut streamer, &mut copied).await?, 1000);
+        assert_eq!(copied, data);
+        assert_eq!(streamer.seek(SeekFrom::End(-4)).await?, 996);
+        let mut buf = [0_u8; 8];
+        assert_eq!(streamer.read(&mut buf).await?, 4);
+        assert_eq!(&buf[..4], &data[996..]);
+        assert_eq!(streamer.seek(SeekFrom::Start(100)).await?, 100);
+        assert_eq!(streamer.fill_buf().await?, &data[100..164]);
+        streamer.consume_unpin(60);
+        assert_eq!(streamer.seek(SeekFrom::Current(-2)).await?, 158);
+        streamer.read_exact(&mut buf).await?;
+        assert_eq!(buf, data[158..166]);
+        assert_eq!(
+            streamer
+                .seek(SeekFrom::Current(-1000))
+                .await
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::InvalidInput
+        );
+        assert_eq!(streamer.seek(SeekFrom::Start(2000)).await?, 2000);
+        assert_eq!(streamer.read(&mut buf).await?, 0);
+        Ok(())
+    })
+}
+
+#[cfg(feature = "async-std")]
+#[test]
+fn test_async_std_file() -> anyhow::Result<()> {
+    use io_arrays::{AsyncArray, AsyncReadAt, AsyncResize, AsyncWriteAt};
+    use std::time::{Duration, Instant};
+
+    let dir = tmpdir();
+    let file = dir.open_with(
+        "file.txt",
+        OpenOptions::new().create_new(true).read(true).write(true),
+    )?;
+    let mut file = async_std::fs::File::from(file.into_std());
+    let data = (0..1 << 20_u32).map(|i| i as u8).collect::<Vec<u8>>();
+
+    async_std::task::block_on(async {
+        AsyncWriteAt::write_all_at(&mut file, &data, 0).await?;
+        AsyncResize::set_len(&mut file, data.len() as u64 + 1).await?;
+        AsyncWriteAt::sync_data(&file).await?;
+        assert_eq!(
+            AsyncArray::metadata(&file).await?.len(),
+            data.len() as u64 + 1
+        );
+
+        // Run many reads at once, and check that a timer still fires
+        // promptly while they're in flight.
+        let reads = (0..64)
+            .map(|i| {
+                let file = file.clone();
+                async_std::task::spawn(async move {
+                    let mut buf = vec![0_u8; 1 << 16];
+                    let offset = i << 13;
+                    AsyncReadAt::read_exact_at(&file, &mut buf, offset).await?;
+                    io::Result::Ok((offset as usize, buf))
+                })
+            })
+            .collect::<Vec<_>>();
+        let start = Instant::now();
+        async_std::task::sleep(Duration::from_millis(1)).await;
+        assert!(start.elapsed() < Duration::from_secs(1));
+        for read in reads {
+            let (offset, buf) = read.await?;
+            assert_eq!(buf, data[offset..][..buf.len()]);
+        }
+
+        let mut buf = [0_u8; 4];
+        let len = data.len() as u64;
+        assert_eq!(AsyncReadAt::read_at(&file, &mut buf, len - 1).await?, 2);
+        assert_eq!(buf[..2], [data[data.len() - 1], 0]);
+        assert_eq!(
+            AsyncReadAt::read_exact_at(&file, &mut buf, len - 1)
+                .await
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+        Ok(())
+    })
+}
+
+#[cfg(feature = "cap-std-utf8")]
+#[test]
+fn test_small_copy_utf8() -> anyhow::Result<()> {
+    let tmp = tmpdir();
+    let dir = cap_std::fs_utf8::Dir::from_cap_std(tmp.try_clone()?);
+    let in_txt = "in.txt";
+    let out_txt = "out.txt";
+
+    let mut in_file = dir.create(in_txt)?;
+    write!(in_file, "XYZHello, world!XYZ")?;
+
+    {
+        let input = dir.open(in_txt)?;
+        let mut output = dir.create(out_txt)?;
+        let meta = Array::metadata(&input)?;
+        let len = meta.len();
+        assert_eq!(len, 19);
+        let mut buf = vec![0_u8; 13];
+        output.set_len(13)?;
+        assert_eq!(Array::metadata(&output)?.len(), 13);
+        input.read_exact_at(&mut buf, 3)?;
+        output.write_all_at(&buf, 3)?;
+        let mut s = String::new();
+        dir.open(out_txt)?.read_to_string(&mut s)?;
+        assert_eq!(s, "\0\0\0Hello, world!");
+
+        let mut output = &output;
+        assert_eq!(output.copy_from(16, &input, 0, 3)?, 3);
+        assert_eq!(Array::metadata(output)?.len(), 19);
+        let mut s = String::new();
+        dir.open(out_txt)?.read_to_string(&mut s)?;
+        assert_eq!(s, "\0\0\0Hello, world!XYZ");
+        dir.remove_file(out_txt)?;
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_shared_file_writes() -> anyhow::Result<()> {
+    let file = ArrayEditor::anonymous()?.into_file();
+    let halves = [[b'a'; 4096], [b'b'; 4096]];
+
+    std::thread::scope(|scope| {
+        let writers = halves
+            .iter()
+            .enumerate()
+            .map(|(i, half)| {
+                let mut file = &file;
+                scope.spawn(move || {
+                    for chunk in 0..8 {
+                        file.write_all_at(half, (chunk * 2 + i as u64) * 4096)?;
+                    }
+                    io::Result::Ok(())
+                })
+            })
+            .collect::<Vec<_>>();
+        writers
+            .into_iter()
+            .try_for_each(|writer| writer.join().unwrap())
+    })?;
+
+    assert_eq!(Array::metadata(&&file)?.len(), 16 * 4096);
+    let data = file.read_to_vec_at(0, 16 * 4096)?;
+    for (i, chunk) in data.chunks(4096).enumerate() {
+        assert_eq!(chunk, halves[i % 2]);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_locked_arrays() -> anyhow::Result<()> {
+    use io_arrays::Streamer;
+    use std::sync::{Arc, Mutex, RwLock};
+
+    // One writer fills each block of the array with its index, while
+    // readers stream through it.
+    let array = Arc::new(RwLock::new(vec![0_u8; 64 * 256]));
+    let readers = (0..4)
+        .map(|_| {
+            let array = Arc::clone(&array);
+            std::thread::spawn(move || {
+                for _ in 0..16 {
+                    let mut data = Vec::new();
+                    Streamer::new(&*array, 0).read_to_end(&mut data)?;
+                    for (i, block) in data.chunks(256).enumerate() {
+                        assert!(block.iter().all(|&b| b == 0 || b == i as u8));
+                    }
+                }
+                io::Result::Ok(())
+            })
+        })
+        .collect::<Vec<_>>();
+    let mut writer = &*array;
+    for i in 0..64_u8 {
+        writer.write_all_at(&[i; 256], u64::from(i) * 256)?;
+    }
+    for reader in readers {
+        reader.join().unwrap()?;
+    }
+
+    // Copying from the same lock locks it for each chunk.
+    writer.set_len(66 * 256)?;
+    assert_eq!(writer.copy_from(64 * 256, &*array, 0, 512)?, 512);
+    assert_eq!(array.read_to_vec_at(65 * 256, 4)?, [1; 4]);
+
+    // Owned locks are written to without locking.
+    let mut array = Mutex::new(Vec::new());
+    array.set_len(4)?;
+    array.write_all_at(b"ab", 1)?;
+    assert_eq!(array.read_to_vec_at(0, 4)?, b"\0ab\0");
+
+    // A poisoned lock is reported as an error.
+    let array = Arc::new(Mutex::new(vec![0_u8; 4]));
+    let poisoner = Arc::clone(&array);
+    std::thread::spawn(move || {
+        let _guard = poisoner.lock().unwrap();
+        panic!("poisoning the lock");
+    })
+    .join()
+    .unwrap_err();
+    assert_eq!(
+        array.read_to_vec_at(0, 4).unwrap_err().kind(),
+        io::ErrorKind::Other
+    );
+    assert!((&*array).write_all_at(b"x", 0).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_resize() -> anyhow::Result<()> {
+    use io_arrays::{SparseMemArray, ZeroArray};
+    use std::sync::Mutex;
+
+    // Code which resizes an array requires `Resize` as well as `WriteAt`.
+    fn truncate_and_write<W: WriteAt + Resize>(array: &mut W) -> io::Result<u64> {
+        array.set_len(0)?;
+        array.grow_to(8)?;
+        array.grow_to(4)?;
+        array.write_all_at(b"ab", 2)?;
+        Ok(array.metadata()?.len())
+    }
+
+    let dir = tmpdir();
+    let file = dir
+        .open_with(
+            "file",
+            OpenOptions::new().create_new(true).read(true).write(true),
+        )?
+        .into_std();
+    file.set_len(100)?;
+    assert_eq!(truncate_and_write(&mut &file)?, 8);
+    assert_eq!(truncate_and_write(&mut ArrayEditor::anonymous()?)?, 8);
+    assert_eq!(truncate_and_write(&mut Box::new(SparseMemArray::new()))?, 8);
+    assert_eq!(truncate_and_write(&mut Mutex::new(Vec::new()))?, 8);
+    assert_eq!(truncate_and_write(&mut &Mutex::new(Vec::new()))?, 8);
+    assert_eq!(truncate_and_write(&mut ZeroArray::sink())?, 8);
+    let mut vec = b"0123456789".to_vec();
+    assert_eq!(truncate_and_write(&mut vec)?, 8);
+    assert_eq!(vec, b"\0\0ab\0\0\0\0");
+    assert_eq!(file.read_to_vec_at(0, 8)?, vec);
+    Ok(())
+}
+
+#[test]
+fn test_read_full_at() -> anyhow::Result<()> {
+    fn check(array: &dyn ReadAt) -> io::Result<()> {
+        // A range straddling the end yields the bytes before it.
+        let mut buf = [b'.'; 8];
+        assert_eq!(array.read_full_at(&mut buf, 6)?, 4);
+        assert_eq!(&buf, b"6789....");
+
+        let mut buf = [b'.'; 4];
+        assert_eq!(array.read_full_at(&mut buf, 2)?, 4);
+        assert_eq!(&buf, b"2345");
+        assert_eq!(array.read_full_at(&mut buf, 10)?, 0);
+        assert_eq!(array.read_full_at(&mut buf, 100)?, 0);
+        assert_eq!(array.read_full_at(&mut [], 2)?, 0);
+        Ok(())
+    }
+
+    let data = b"0123456789";
+    let mut file = ArrayEditor::anonymous()?;
+    file.write_all_at(data, 0)?;
+    check(&file)?;
+    check(&&data[..])?;
+    check(&data.to_vec())?;
+    check(data)?;
+    check(&ArrayReader::bytes(data)?)?;
+    Ok(())
+}
+
+#[test]
+fn test_read_full_at_retries() -> anyhow::Result<()> {
+    // Interrupt the first two reads, and make the rest short.
+    let array = FaultInjectionArray::new(b"0123456789".to_vec())
+        .inject(Rule::new(Op::Read, Fault::Error(io::ErrorKind::Interrupted)).times(2))
+        .inject(Rule::new(Op::Read, Fault::Short(3)));
+
+    // Short and interrupted reads are retried until the buffer is full.
+    let mut buf = [0; 8];
+    assert_eq!(array.read_full_at(&mut buf, 1)?, 8);
+    assert_eq!(&buf, b"12345678");
+
+    // At the end of the array, the total read so far is returned.
+    let mut buf = [b'.'; 8];
+    assert_eq!(array.read_full_at(&mut buf, 5)?, 5);
+    assert_eq!(&buf, b"56789...");
+    assert_eq!(array.read_full_at(&mut buf, 10)?, 0);
+    assert_eq!(array.read_full_at(&mut [], 0)?, 0);
+
+    // Methods which are implemented with it see the whole range.
+    assert_eq!(array.read_up_to_vec_at(4, 100)?, b"456789");
+    let chunks = array.chunks_at(0, 4).collect::<io::Result<Vec<_>>>()?;
+    assert_eq!(chunks, [&b"0123"[..], b"4567", b"89"]);
+    assert_eq!(array.find_at(b"789", 0)?, Some(7));
+
+    // Other errors are returned, even after a partial read.
+    let array = FaultInjectionArray::new(b"0123456789".to_vec())
+        .inject(
+            Rule::new(Op::Read, Fault::Error(io::ErrorKind::BrokenPipe))
+                .after(1)
+                .times(1),
+        )
+        .inject(Rule::new(Op::Read, Fault::Short(3)));
+    let mut buf = [0; 8];
+    let err = array.read_full_at(&mut buf, 0).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+    Ok(())
+}
+
+/// An array in memory which counts calls to `copy_from`.
+#[derive(Default)]
+struct CopyCounter {
+    data: Vec<u8>,
+    copies: usize,
+}
+
+impl Array for CopyCounter {
+    fn metadata(&self) -> io::Result<Metadata> {
+        self.data.metadata()
+    }
+
+    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        self.data.advise(offset, len, advice)
+    }
+}
+
+impl WriteAt for CopyCounter {
+    fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        self.data.write_at(buf, offset)
+    }
+
+    fn copy_from(
+        &mut self,
+        offset: u64,
+        input: &dyn ReadAt,
+        input_offset: u64,
+        len: u64,
+    ) -> io::Result<u64> {
+        self.copies += 1;
+        self.data.copy_from(offset, input, input_offset, len)
+    }
+}
+
+#[test]
+fn test_copy_to() -> anyhow::Result<()> {
+    let data = b"0123456789".to_vec();
+    let mut file = ArrayEditor::anonymous()?;
+    file.write_all_at(&data, 0)?;
+
+    for (offset, len) in [(0, 10), (3, 4), (6, 100), (10, 5), (20, 5)] {
+        let mut expected = CopyCounter::default();
+        let copied = expected.copy_from(2, &data, offset, len)?;
+
+        // Sources in memory write directly, without calling `copy_from`.
+        let mut output = CopyCounter::default();
+        assert_eq!(data.copy_to(offset, &mut output, 2, len)?, copied);
+        assert_eq!(output.data, expected.data);
+        assert_eq!(output.copies, 0);
+        let mut output = CopyCounter::default();
+        assert_eq!(
+            MemReader::from(data.clone()).copy_to(offset, &mut output, 2, len)?,
+            copied
+        );
+        assert_eq!(output.data, expected.data);
+        assert_eq!(output.copies, 0);
+
+        // Other sources use `copy_from`.
+        let mut output = CopyCounter::default();
+        assert_eq!(file.copy_to(offset, &mut output, 2, len)?, copied);
+        assert_eq!(output.data, expected.data);
+        assert_eq!(output.copies, 1);
+    }
+
+    // A generic source can be copied to a file.
+    let mut output = ArrayEditor::anonymous()?;
+    assert_eq!(data.copy_to(4, &mut output, 1, 3)?, 3);
+    assert_eq!(output.read_to_vec_at(0, 4)?, &[0, b'4', b'5', b'6']);
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn test_char_device() -> anyhow::Result<()> {
+    let urandom = ArrayReader::file(std::fs::File::open("/dev/urandom")?);
+    let metadata = urandom.metadata()?;
+    assert_eq!(metadata.file_type(), io_arrays::ArrayType::CharDevice);
+    assert!(!metadata.is_bounded());
+    let data = urandom.read_to_vec_at(0, 4096)?;
+    assert_ne!(data, vec![0; 4096]);
+
+    let zero = ArrayReader::file(std::fs::File::open("/dev/zero")?);
+    let mut chunks = zero.chunks_at(0, 1000);
+    assert_eq!(chunks.next().unwrap()?, vec![0; 1000]);
+    #[cfg(feature = "io-streams")]
+    {
+        let mut data = Vec::new();
+        zero.read_range_via_stream(10, 5000)?
+            .read_to_end(&mut data)?;
+        assert_eq!(data, vec![0; 5000]);
+    }
+
+    // An unbounded input doesn't limit the length of a parallel copy, so
+    // the input range is checked before `output` is extended.
+    let output = ArrayEditor::anonymous()?;
+    let err =
+        io_arrays::bulk::copy_from_parallel(&output, 0, &zero, u64::MAX - 1, 4, 2, 1).unwrap_err();
+    assert_eq!(err.error().kind(), io::ErrorKind::InvalidInput);
+    assert_eq!(output.metadata()?.len(), 0);
+
+    let file = ArrayEditor::anonymous()?;
+    assert!(file.metadata()?.is_bounded());
+    assert!(b"abc".metadata()?.is_bounded());
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn test_unseekable_read_at() -> anyhow::Result<()> {
+    // Sockets don't support positional reads, so they're read from their
+    // current position.
+    let (mut a, b) = std::os::unix::net::UnixStream::pair()?;
+    a.write_all(b"hello, world")?;
+    let reader = ArrayReader::from(rustix::fd::OwnedFd::from(b));
+    let mut buf = [0_u8; 5];
+    reader.read_exact_at(&mut buf, 100)?;
+    assert_eq!(&buf, b"hello");
+    assert_eq!(reader.read_at(&mut buf, 0)?, 5);
+    assert_eq!(&buf, b", wor");
+    Ok(())
+}
+
+#[test]
+fn test_readahead() -> anyhow::Result<()> {
+    let mut file = ArrayEditor::anonymous()?;
+    file.write_all_at(&[1; 100_000], 0)?;
+    file.readahead(0, 100_000)?;
+    file.readahead(50_000, 0)?;
+    file.readahead(200_000, 10)?;
+    file.readahead(u64::MAX, 10)?;
+    io_arrays::filelike::readahead(&file, 0, 100)?;
+    assert_eq!(file.read_to_vec_at(99_999, 1)?, [1]);
+
+    let slice = io_arrays::ArraySlice::new(&file, 10, 100);
+    slice.readahead(0, 1000)?;
+
+    let data = vec![1_u8; 100];
+    data.readahead(0, 100)?;
+    assert_eq!(data, [1; 100]);
+    Ok(())
+}
+
+#[test]
+fn test_reserve_at() -> anyhow::Result<()> {
+    const MIB: u64 = 1024 * 1024;
+
+    let mut file = ArrayEditor::anonymous()?;
+    let supported = match file.reserve_at(0, MIB) {
+        Ok(()) => {
+            let metadata = file.metadata()?;
+            assert_eq!(metadata.len(), 0);
+            assert!(metadata.allocated_len() >= MIB);
+            true
+        }
+        // Not all filesystems support reserving.
+        Err(err) if err.kind() == io::ErrorKind::Unsupported => false,
+        Err(err) => return Err(err.into()),
+    };
+    file.write_all_at(&[1; 1000], MIB - 1000)?;
+    assert_eq!(file.metadata()?.len(), MIB);
+
+    // Reserving within the array doesn't shrink it.
+    if supported {
+        file.reserve_at(0, 10)?;
+        assert_eq!(file.metadata()?.len(), MIB);
+    }
+
+    let mut data = b"hello".to_vec();
+    data.reserve_at(0, 1000)?;
+    assert_eq!(data, b"hello");
+    assert!(data.capacity() >= 1000);
+    let err = data.reserve_at(u64::MAX, 1).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    Ok(())
+}
+
+/// An array which accepts writes at any offset, standing in for a device
+/// with an address space as large as `u64`.
+struct BoundlessArray;
+
+impl Array for BoundlessArray {
+    fn metadata(&self) -> io::Result<Metadata> {
+        [].metadata()
+    }
+
+    fn advise(&self, _offset: u64, _len: u64, _advice: Advice) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl WriteAt for BoundlessArray {
+    fn write_at(&mut self, buf: &[u8], _offset: u64) -> io::Result<usize> {
+        Ok(buf.len())
+    }
+}
+
+// Test that offsets past the end of the address space are reported as
+// `InvalidInput`, and not as some other I/O failure.
+#[test]
+fn test_offset_overflow() -> anyhow::Result<()> {
+    use io_arrays::{SparseMemArray, Streamer, ZeroArray};
+
+    fn check<T>(result: io::Result<T>, message: &str) {
+        let err = result.map(drop).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(err.to_string(), message);
+    }
+
+    let near_end = u64::MAX - 1;
+
+    // Arrays in memory.
+    let mut data = vec![0_u8; 4];
+    check(data.allocate(near_end, 4), "offset overflow");
+    check(data.reserve_at(near_end, 4), "offset overflow");
+    check(data.fill_at(b'x', near_end, 4), "offset overflow");
+    check(
+        data.as_mut_slice().fill_at(b'x', near_end, 4),
+        "offset overflow",
+    );
+    let mut sparse = SparseMemArray::new();
+    check(sparse.write_all_at(b"abcd", near_end), "offset overflow");
+    check(sparse.allocate(near_end, 4), "offset overflow");
+    assert_eq!(data, [0; 4]);
+
+    // The default methods, given an array which accepts any offset.
+    let mut array = BoundlessArray;
+    check(array.write_all_at(b"abcd", near_end), "offset overflow");
+    check(
+        array.write_all_vectored_at(&mut [IoSlice::new(b"ab"), IoSlice::new(b"cd")], near_end),
+        "offset overflow",
+    );
+
+    // Streamers.
+    let mut buf = [0_u8; 4];
+    let zeros = ZeroArray::new(u64::MAX);
+    let mut stream = Streamer::new(&zeros, near_end);
+    check(stream.read(&mut buf), "position overflow");
+    check(stream.read_exact(&mut buf), "position overflow");
+    check(
+        stream.read_vectored(&mut [IoSliceMut::new(&mut buf)]),
+        "position overflow",
+    );
+    let mut stream = Streamer::owned(BoundlessArray, near_end);
+    check(stream.write(b"abcd"), "position overflow");
+    check(stream.write_all(b"abcd"), "position overflow");
+    check(
+        stream.write_vectored(&[IoSlice::new(b"abcd")]),
+        "position overflow",
+    );
+    // Nothing was transferred, so the position is unchanged.
+    assert_eq!(io::Seek::stream_position(&mut stream)?, near_end);
+    Ok(())
+}
+
+// Test that offsets which don't fit in a `usize` are reported as
+// `InvalidInput` by arrays in memory, rather than wrapping or reading
+// nothing.
+#[cfg(target_pointer_width = "32")]
+#[test]
+fn test_offset_overflow_32() -> anyhow::Result<()> {
+    fn check<T>(result: io::Result<T>) {
+        let err = result.map(drop).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(err.to_string(), "offset overflow");
+    }
+
+    let offset = u64::from(u32::MAX) + 1;
+
+    let mut data = vec![0_u8; 4];
+    let mut buf = [0_u8; 4];
+    check(data.read_at(&mut buf, offset));
+    check(data.read_exact_at(&mut buf, offset));
+    check(data.read_vectored_at(&mut [IoSliceMut::new(&mut buf)], offset));
+    check(data.write_at(b"abcd", offset));
+    check(data.write_all_at(b"abcd", offset));
+    check(data.write_vectored_at(&[IoSlice::new(b"abcd")], offset));
+    check(data.allocate(offset, 4));
+    check(data.reserve_at(offset, 4));
+    check(data.set_len(offset));
+    check(MemReader::from(data.clone()).read_at(&mut buf, offset));
+    assert_eq!(data, [0; 4]);
+    Ok(())
+}